@@ -0,0 +1,30 @@
+CREATE TABLE api_keys (
+    id VARCHAR PRIMARY KEY,
+    user_id VARCHAR NOT NULL REFERENCES users(id),
+    name TEXT NOT NULL,
+    key_hash TEXT NOT NULL UNIQUE,
+    key_prefix TEXT NOT NULL,
+    drips_per_day INTEGER NOT NULL,
+    revoked_at TIMESTAMPTZ NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+
+-- max amount (in base units) a key may drip per token in a rolling 24h window
+CREATE TABLE api_key_quotas (
+    api_key_id VARCHAR NOT NULL REFERENCES api_keys(id),
+    token_address TEXT NOT NULL,
+    max_amount_per_day TEXT NOT NULL,
+    PRIMARY KEY (api_key_id, token_address)
+);
+
+CREATE TABLE api_key_usage (
+    id BIGSERIAL PRIMARY KEY,
+    api_key_id VARCHAR NOT NULL REFERENCES api_keys(id),
+    token_address TEXT NOT NULL,
+    tx_hash TEXT NOT NULL,
+    amount TEXT NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE INDEX api_key_usage_key_created_at ON api_key_usage (api_key_id, created_at);
@@ -0,0 +1,16 @@
+-- a sign in, kept alive by rotating its refresh token. Access tokens name the
+-- session in their `sid` claim and are refused once it is revoked
+CREATE TABLE auth_sessions (
+    id VARCHAR PRIMARY KEY,
+    user_id VARCHAR NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    -- SHA-256 of the current refresh token, the token itself is never stored
+    refresh_token_hash TEXT NOT NULL UNIQUE,
+    -- the token it replaced, presenting it again means it leaked
+    previous_refresh_token_hash TEXT NULL UNIQUE,
+    expires_at TIMESTAMPTZ NOT NULL,
+    revoked_at TIMESTAMPTZ NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE INDEX auth_sessions_user_id_idx ON auth_sessions (user_id);
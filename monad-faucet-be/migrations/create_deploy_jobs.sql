@@ -0,0 +1,18 @@
+-- progress of token deployments, so clients can follow a deploy after the upload request returned
+CREATE TYPE deploy_step AS ENUM ('QUEUED', 'UPLOADING_LOGO', 'DEPLOYING', 'ALLOCATING_SUPPLY', 'REGISTERED', 'FAILED');
+
+CREATE TABLE deploy_jobs (
+    id VARCHAR PRIMARY KEY,
+    user_id VARCHAR NOT NULL REFERENCES users(id),
+    symbol TEXT NOT NULL,
+    step deploy_step NOT NULL DEFAULT 'QUEUED',
+    deploy_tx_hash TEXT NULL,
+    contract_address TEXT NULL,
+    allocation_tx_hashes TEXT[] NOT NULL DEFAULT '{}',
+    error TEXT NULL,
+    error_code TEXT NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE INDEX deploy_jobs_user_id_idx ON deploy_jobs (user_id);
@@ -0,0 +1,2 @@
+-- anonymous callers can deploy too, their jobs belong to no user
+ALTER TABLE deploy_jobs ALTER COLUMN user_id DROP NOT NULL;
@@ -0,0 +1,17 @@
+-- `Idempotency-Key`s of withdraw and deploy requests, so retries return the first outcome
+CREATE TABLE idempotency_keys (
+    -- endpoint and caller the key belongs to, e.g. `withdraw:<user id>`
+    scope TEXT NOT NULL,
+    key TEXT NOT NULL,
+    -- SHA-256 of the request, a key reused for another request is rejected
+    request_hash TEXT NOT NULL,
+    -- JSON outcome of a withdraw, NULL while the job is in flight
+    response TEXT NULL,
+    -- claimed right before the job is created, so not a foreign key
+    deploy_job_id VARCHAR NULL,
+    -- a withdraw holds its key until this lease runs out, so a key left in flight by an
+    -- instance that died is freed without touching the keys of other instances
+    locked_until TIMESTAMPTZ NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    PRIMARY KEY (scope, key)
+);
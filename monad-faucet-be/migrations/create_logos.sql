@@ -0,0 +1,7 @@
+-- uploaded logos keyed by content hash so identical files are stored once
+CREATE TABLE logos (
+    hash TEXT PRIMARY KEY,
+    logo_url TEXT NOT NULL,
+    thumbnail_url TEXT NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
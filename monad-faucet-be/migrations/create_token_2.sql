@@ -0,0 +1,2 @@
+-- optional "top-up to target" drip mode, NULL keeps the fixed withdraw_limit drip
+ALTER TABLE tokens ADD COLUMN top_up_target TEXT NULL;
@@ -0,0 +1,2 @@
+-- square thumbnail generated from the uploaded logo, NULL for older tokens
+ALTER TABLE tokens ADD COLUMN logo_thumbnail_url TEXT NULL;
@@ -0,0 +1,2 @@
+-- user who deployed the token through the faucet, allowed to manage its webhooks
+ALTER TABLE tokens ADD COLUMN created_by_user_id VARCHAR NULL REFERENCES users(id);
@@ -0,0 +1,13 @@
+-- circuit breaker of each token, drips of a paused token are refused until an admin resumes it
+CREATE TABLE token_pauses (
+    token_address TEXT PRIMARY KEY,
+    paused BOOLEAN NOT NULL,
+    reason TEXT NULL,
+    paused_at TIMESTAMPTZ NULL,
+    -- outflow sent before the last resume no longer counts towards the caps
+    resumed_at TIMESTAMPTZ NULL,
+    resumed_by VARCHAR NULL
+);
+
+-- outflow of a token over the hourly and daily cap windows
+CREATE INDEX token_transfers_token_address_created_at_idx ON token_transfers (token_address, created_at);
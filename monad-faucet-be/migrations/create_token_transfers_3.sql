@@ -0,0 +1,4 @@
+-- what triggered a transfer, so scheduled and API key drips show up in claim history
+CREATE TYPE drip_kind AS ENUM ('CLAIM', 'SCHEDULED', 'API_KEY', 'DEPLOY');
+
+ALTER TABLE token_transfers ADD COLUMN kind drip_kind NOT NULL DEFAULT 'CLAIM';
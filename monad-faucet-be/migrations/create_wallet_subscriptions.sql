@@ -0,0 +1,14 @@
+CREATE TABLE wallet_subscriptions (
+    id VARCHAR PRIMARY KEY,
+    user_id VARCHAR NOT NULL REFERENCES users(id),
+    wallet_address TEXT NOT NULL,
+    token_address TEXT NOT NULL,
+    min_balance TEXT NOT NULL,
+    refill_amount TEXT NOT NULL,
+    enabled BOOLEAN NOT NULL DEFAULT TRUE,
+    last_checked_at TIMESTAMPTZ NULL,
+    last_refill_at TIMESTAMPTZ NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    UNIQUE (wallet_address, token_address)
+);
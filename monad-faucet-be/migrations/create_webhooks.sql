@@ -0,0 +1,34 @@
+-- per-token outbound webhooks, managed by the user who deployed the token or an admin,
+-- and per-user ones without a token, notified about the user's own deploys
+CREATE TABLE webhooks (
+    id VARCHAR PRIMARY KEY,
+    token_address TEXT NULL REFERENCES tokens(address) ON DELETE CASCADE,
+    url TEXT NOT NULL,
+    secret TEXT NOT NULL,
+    events TEXT[] NOT NULL,
+    created_by VARCHAR NOT NULL REFERENCES users(id),
+    created_at TIMESTAMPTZ NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE INDEX webhooks_token_address_idx ON webhooks (token_address);
+CREATE INDEX webhooks_created_by_idx ON webhooks (created_by) WHERE token_address IS NULL;
+
+CREATE TYPE webhook_delivery_status AS ENUM ('PENDING', 'DELIVERED', 'FAILED');
+
+-- outbox of webhook calls, kept afterwards as the delivery log
+CREATE TABLE webhook_deliveries (
+    id VARCHAR PRIMARY KEY,
+    webhook_id VARCHAR NOT NULL REFERENCES webhooks(id) ON DELETE CASCADE,
+    event_type TEXT NOT NULL,
+    payload TEXT NOT NULL,
+    status webhook_delivery_status NOT NULL DEFAULT 'PENDING',
+    attempts INTEGER NOT NULL DEFAULT 0,
+    next_attempt_at TIMESTAMPTZ NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    last_status_code INTEGER NULL,
+    last_error TEXT NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE INDEX webhook_deliveries_due_idx ON webhook_deliveries (next_attempt_at) WHERE status = 'PENDING';
+CREATE INDEX webhook_deliveries_webhook_id_idx ON webhook_deliveries (webhook_id, created_at);
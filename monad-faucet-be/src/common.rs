@@ -1,20 +1,96 @@
-use std::{collections::HashMap, sync::Arc, time::Duration};
+use std::{
+    collections::{HashMap, VecDeque},
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc, Mutex,
+    },
+    time::{Duration, Instant},
+};
 
 use eyre::Result;
-use tokio::runtime::Handle;
+use serde::Deserialize;
+use tokio::sync::Notify;
 use tracing::{
     field::{Field, Visit},
     Event, Subscriber,
 };
 use tracing_subscriber::{layer::Context, Layer};
 
+use crate::{
+    config::{AlertSinkConfig, LogFormat, OtlpConfig},
+    constants::alerts,
+    telemetry,
+};
+
+/// Tracing layer that posts events to a Discord, Slack, Telegram or generic
+/// JSON webhook, see [`AlertFormat`].
+///
+/// Events are queued and sent by a single background task, which batches
+/// everything seen within `BATCH_WINDOW_SECS` into one message, stays under
+/// `MAX_MESSAGES_PER_MINUTE` and waits out `Retry-After` on 429 responses.
 #[derive(Clone)]
 pub struct WebhookLayer {
-    webhook_url: String,
-    http_client: reqwest::Client,
+    queue: Arc<AlertQueue>,
     name: String,
     level: tracing::Level,
-    formatter: Arc<dyn Fn(&Event, &FieldVisitor, &str) -> serde_json::Value + Send + Sync>,
+    /// Only events whose target starts with one of these are sent, all of
+    /// them when empty
+    targets: Vec<String>,
+    formatter: Formatter,
+}
+
+/// Turns an event into the JSON body posted to a sink
+type Formatter = Arc<dyn Fn(&Event, &FieldVisitor, &str) -> serde_json::Value + Send + Sync>;
+
+/// A formatted event waiting to be sent
+struct PendingAlert {
+    /// Events with the same key are sent once with a repeat count
+    key: String,
+    message: serde_json::Value,
+}
+
+/// Bounded queue between the layer and the sender task. When it is full the
+/// oldest event is dropped and counted, so the latest state always gets out.
+struct AlertQueue {
+    alerts: Mutex<VecDeque<PendingAlert>>,
+    notify: Notify,
+    dropped: AtomicU64,
+    capacity: usize,
+}
+
+impl AlertQueue {
+    fn new(capacity: usize) -> Self {
+        Self {
+            alerts: Mutex::new(VecDeque::with_capacity(capacity)),
+            notify: Notify::new(),
+            dropped: AtomicU64::new(0),
+            capacity,
+        }
+    }
+
+    fn push(&self, alert: PendingAlert) {
+        {
+            let mut alerts = self.alerts.lock().unwrap_or_else(|e| e.into_inner());
+            if alerts.len() >= self.capacity {
+                alerts.pop_front();
+                self.dropped.fetch_add(1, Ordering::Relaxed);
+            }
+            alerts.push_back(alert);
+        }
+        self.notify.notify_one();
+    }
+
+    /// Takes every queued event, along with how many were dropped since the
+    /// last drain.
+    fn drain(&self) -> (Vec<PendingAlert>, u64) {
+        let alerts = self
+            .alerts
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .drain(..)
+            .collect();
+        (alerts, self.dropped.swap(0, Ordering::Relaxed))
+    }
 }
 
 /// Field visitor that collects field values from tracing events.
@@ -73,48 +149,225 @@ impl<S: Subscriber> Layer<S> for WebhookLayer {
         if *event.metadata().level() > self.level {
             return;
         }
+        if !self.targets.is_empty()
+            && !self
+                .targets
+                .iter()
+                .any(|target| event.metadata().target().starts_with(target.as_str()))
+        {
+            return;
+        }
 
         // Collect all fields from the event
         let mut visitor = FieldVisitor::new();
         event.record(&mut visitor);
 
-        let message = (self.formatter)(event, &visitor, &self.name);
-
-        let webhook_url = self.webhook_url.clone();
-        let client = self.http_client.clone();
-
-        // Spawning the webhook sending as a non-blocking task
-        let rt = Handle::current();
-        rt.spawn(async move {
-            // Use a timeout to prevent hanging if the webhook is slow to respond
-            let result = tokio::time::timeout(
-                Duration::from_secs(5),
-                client.post(webhook_url.as_str()).json(&message).send(),
-            )
-            .await;
-
-            match result {
-                Ok(Ok(response)) => {
-                    if !response.status().is_success() {
-                        eprintln!("Webhook error: HTTP {}", response.status());
-                    }
-                }
-                Ok(Err(e)) => eprintln!("Failed to send to webhook: {}", e),
-                Err(_) => eprintln!("Webhook request timed out"),
-            }
+        self.queue.push(PendingAlert {
+            key: alert_key(event, &visitor),
+            message: (self.formatter)(event, &visitor, &self.name),
         });
     }
 }
 
+/// Identifies repeats of an event: same level, target and field values.
+fn alert_key(event: &Event, visitor: &FieldVisitor) -> String {
+    let mut fields: Vec<_> = visitor.fields.iter().collect();
+    fields.sort();
+    format!(
+        "{}|{}|{:?}",
+        event.metadata().level(),
+        event.metadata().target(),
+        fields
+    )
+}
+
+/// Groups repeated events, keeping the first message of each group and the
+/// order they were first seen in.
+fn coalesce(alerts: Vec<PendingAlert>) -> Vec<(serde_json::Value, usize)> {
+    let mut groups: Vec<(serde_json::Value, usize)> = Vec::new();
+    let mut index: HashMap<String, usize> = HashMap::new();
+    for alert in alerts {
+        match index.get(&alert.key) {
+            Some(&i) => groups[i].1 += 1,
+            None => {
+                index.insert(alert.key, groups.len());
+                groups.push((alert.message, 1));
+            }
+        }
+    }
+    groups
+}
+
+/// Builds the messages for one batch window. For formats that allow it the
+/// events are merged into as few messages as the service accepts, the others
+/// get one message per distinct event.
+fn batch_messages(
+    groups: Vec<(serde_json::Value, usize)>,
+    dropped: u64,
+    name: &str,
+    format: &AlertFormat,
+) -> Vec<serde_json::Value> {
+    let mut messages: Vec<_> = groups
+        .into_iter()
+        .map(|(message, count)| format.with_repeat_count(message, count))
+        .collect();
+    if dropped > 0 {
+        messages.push(format.render(
+            name,
+            tracing::Level::WARN,
+            &format!("{} events dropped", dropped),
+            "The alert queue was full, the oldest events were not sent",
+        ));
+    }
+
+    let Some((field, max_items)) = format.merge_field() else {
+        return messages;
+    };
+    let mut merged = Vec::new();
+    let mut base: Option<serde_json::Value> = None;
+    let mut items = Vec::new();
+    for mut message in messages {
+        match message.get_mut(field).map(serde_json::Value::take) {
+            Some(serde_json::Value::Array(message_items)) => {
+                items.extend(message_items);
+                base.get_or_insert(message);
+            }
+            _ => merged.push(message),
+        }
+    }
+    if let Some(base) = base {
+        for chunk in items.chunks(max_items) {
+            let mut message = base.clone();
+            message[field] = serde_json::Value::from(chunk.to_vec());
+            merged.push(message);
+        }
+    }
+    merged
+}
+
+/// Single task sending everything queued by a [`WebhookLayer`].
+///
+/// Errors are written to stderr, logging them through tracing would feed
+/// them back into the queue.
+async fn run_sender(
+    queue: Arc<AlertQueue>,
+    http_client: reqwest::Client,
+    webhook_url: String,
+    name: String,
+    format: AlertFormat,
+) {
+    let mut sent_at: VecDeque<Instant> = VecDeque::new();
+    loop {
+        queue.notify.notified().await;
+        // let the rest of a burst arrive so it goes out as one message
+        tokio::time::sleep(Duration::from_secs(alerts::BATCH_WINDOW_SECS)).await;
+
+        let (pending, dropped) = queue.drain();
+        for message in batch_messages(coalesce(pending), dropped, &name, &format) {
+            wait_for_send_slot(&mut sent_at).await;
+            send_message(&http_client, &webhook_url, &message).await;
+        }
+    }
+}
+
+/// Waits until fewer than `MAX_MESSAGES_PER_MINUTE` messages were sent in the
+/// last minute, then records a send.
+async fn wait_for_send_slot(sent_at: &mut VecDeque<Instant>) {
+    let minute = Duration::from_secs(60);
+    loop {
+        while sent_at.front().is_some_and(|at| at.elapsed() >= minute) {
+            sent_at.pop_front();
+        }
+        match sent_at.front() {
+            Some(oldest) if sent_at.len() >= alerts::MAX_MESSAGES_PER_MINUTE => {
+                tokio::time::sleep(minute.saturating_sub(oldest.elapsed())).await;
+            }
+            _ => break,
+        }
+    }
+    sent_at.push_back(Instant::now());
+}
+
+async fn send_message(
+    http_client: &reqwest::Client,
+    webhook_url: &str,
+    message: &serde_json::Value,
+) {
+    for _ in 0..=alerts::MAX_RETRIES {
+        // Use a timeout to prevent hanging if the webhook is slow to respond
+        let result = tokio::time::timeout(
+            Duration::from_secs(alerts::REQUEST_TIMEOUT_SECS),
+            http_client.post(webhook_url).json(message).send(),
+        )
+        .await;
+
+        let response = match result {
+            Ok(Ok(response)) => response,
+            Ok(Err(e)) => {
+                eprintln!("Failed to send to webhook: {}", e);
+                return;
+            }
+            Err(_) => {
+                eprintln!("Webhook request timed out");
+                return;
+            }
+        };
+
+        if response.status() == reqwest::StatusCode::TOO_MANY_REQUESTS {
+            tokio::time::sleep(retry_after(response).await).await;
+            continue;
+        }
+        if !response.status().is_success() {
+            eprintln!("Webhook error: HTTP {}", response.status());
+        }
+        return;
+    }
+    eprintln!(
+        "Webhook still rate limited after {} retries, dropping message",
+        alerts::MAX_RETRIES
+    );
+}
+
+/// How long a 429 response asks to wait, from the `Retry-After` header or
+/// the `retry_after` field Discord puts in the body.
+async fn retry_after(response: reqwest::Response) -> Duration {
+    let header = response
+        .headers()
+        .get(reqwest::header::RETRY_AFTER)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.parse::<f64>().ok());
+    let seconds = match header {
+        Some(seconds) => Some(seconds),
+        None => response
+            .json::<serde_json::Value>()
+            .await
+            .ok()
+            .and_then(|body| body.get("retry_after").and_then(|v| v.as_f64())),
+    };
+    Duration::try_from_secs_f64(seconds.unwrap_or(1.0)).unwrap_or(Duration::from_secs(1))
+}
+
 impl WebhookLayer {
-    /// Creates a new webhook layer with optional rate limiting.
+    /// Creates a layer for a configured alert sink
+    pub fn from_sink(sink: &AlertSinkConfig, name: &str) -> Result<Self> {
+        let mut layer = Self::new(&sink.url, name, sink.level, sink.format.clone())?;
+        layer.targets = sink.targets.clone();
+        Ok(layer)
+    }
+
+    /// Creates a new webhook layer and spawns the task sending its messages,
+    /// so it must be called from within a Tokio runtime. Events are formatted
+    /// with the preset of `format`, see [`WebhookLayer::with_formatter`] to
+    /// replace it.
     ///
     /// # Arguments
     ///
-    /// * `webhook_url` - The webhook URL to send events to
+    /// * `webhook_url` - The webhook URL to send events to, https unless it
+    ///   is a loopback address
     /// * `name` - Name to display for the webhook message
     /// * `level` - The tracing level threshold for sending events
-    /// * `formatter` - Custom formatter function for webhook messages
+    /// * `format` - Service the messages are for, deciding how they are
+    ///   formatted and batched
     ///
     /// # Returns
     ///
@@ -124,42 +377,22 @@ impl WebhookLayer {
     ///
     /// ```rust
     /// use tracing::Level;
-    /// use utils::WebhookLayer;
-    /// use utils::default_message_formatter;
-    ///
-    /// // Without rate limiting
-    /// let discord_layer = WebhookLayer::new(
-    ///     "https://discord.com/api/webhooks/...",
-    ///     "MyApp",
-    ///     Level::ERROR,
-    ///     |event, visitor, name| {
-    ///         // Custom formatting logic
-    ///         serde_json::json!({
-    ///             "content": format!("Error in {}", event.metadata().target())
-    ///         })
-    ///     }
-    /// ).unwrap();
+    /// use utils::{AlertFormat, WebhookLayer};
     ///
-    /// let discord_layer = WebhookLayer::new(
-    ///     "https://discord.com/api/webhooks/...",
+    /// let slack_layer = WebhookLayer::new(
+    ///     "https://hooks.slack.com/services/...",
     ///     "MyApp",
     ///     Level::ERROR,
-    ///     default_message_formatter
+    ///     AlertFormat::Slack,
     /// ).unwrap();
     /// ```
-    pub fn new<F>(
+    pub fn new(
         webhook_url: &str,
         name: &str,
         level: tracing::Level,
-        formatter: F,
-    ) -> Result<Self>
-    where
-        F: Fn(&Event, &FieldVisitor, &str) -> serde_json::Value + Send + Sync + 'static,
-    {
-        // Validate the webhook URL format
-        if !webhook_url.starts_with("https://") {
-            return Err(eyre::eyre!("Invalid webhook URL format"));
-        }
+        format: AlertFormat,
+    ) -> Result<Self> {
+        validate_webhook_url(webhook_url)?;
 
         // HTTP client with timeout and user agent
         let client = reqwest::Client::builder()
@@ -168,25 +401,263 @@ impl WebhookLayer {
             .build()
             .map_err(|e| eyre::eyre!("Failed to create HTTP client: {}", e))?;
 
+        let runtime = tokio::runtime::Handle::try_current()
+            .map_err(|e| eyre::eyre!("WebhookLayer needs a Tokio runtime: {}", e))?;
+        let queue = Arc::new(AlertQueue::new(alerts::QUEUE_CAPACITY));
+        runtime.spawn(run_sender(
+            queue.clone(),
+            client,
+            webhook_url.to_string(),
+            name.to_string(),
+            format.clone(),
+        ));
+
         Ok(Self {
-            webhook_url: webhook_url.to_string(),
-            http_client: client,
+            queue,
             name: name.to_string(),
             level,
-            formatter: Arc::new(formatter),
+            targets: Vec::new(),
+            formatter: Arc::new(move |event, visitor, name| format.format(event, visitor, name)),
         })
     }
+
+    /// Replaces the preset formatter of the layer's format. Messages are
+    /// still batched as that format's, so a custom Discord formatter should
+    /// use embeds or `content`.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use tracing::Level;
+    /// use utils::{AlertFormat, WebhookLayer};
+    ///
+    /// let discord_layer = WebhookLayer::new(
+    ///     "https://discord.com/api/webhooks/...",
+    ///     "MyApp",
+    ///     Level::ERROR,
+    ///     AlertFormat::Discord,
+    /// )
+    /// .unwrap()
+    /// .with_formatter(|event, visitor, name| {
+    ///     // Custom formatting logic
+    ///     serde_json::json!({
+    ///         "content": format!("Error in {}", event.metadata().target())
+    ///     })
+    /// });
+    /// ```
+    pub fn with_formatter<F>(mut self, formatter: F) -> Self
+    where
+        F: Fn(&Event, &FieldVisitor, &str) -> serde_json::Value + Send + Sync + 'static,
+    {
+        self.formatter = Arc::new(formatter);
+        self
+    }
 }
 
 /// Default message formatter
 ///
 /// Creates a standardized Discord-friendly error message
+#[deprecated(note = "use `AlertFormat::Discord.format`")]
+#[allow(dead_code)]
 pub fn default_message_formatter(
     event: &Event,
     visitor: &FieldVisitor,
     name: &str,
 ) -> serde_json::Value {
-    let timestamp = chrono::Utc::now().to_rfc3339();
+    AlertFormat::Discord.format(event, visitor, name)
+}
+
+/// Webhooks must use https, except on loopback addresses so a local receiver
+/// can be used for testing.
+pub fn validate_webhook_url(webhook_url: &str) -> Result<()> {
+    let url = reqwest::Url::parse(webhook_url)
+        .map_err(|e| eyre::eyre!("Invalid webhook URL format: {}", e))?;
+    let is_loopback = url.host_str().is_some_and(|host| {
+        host == "localhost"
+            || host
+                .trim_start_matches('[')
+                .trim_end_matches(']')
+                .parse::<std::net::IpAddr>()
+                .is_ok_and(|ip| ip.is_loopback())
+    });
+    match url.scheme() {
+        "https" if url.host().is_some() => Ok(()),
+        "http" if is_loopback => Ok(()),
+        _ => Err(eyre::eyre!(
+            "Webhook URL must use https, or http on a loopback address"
+        )),
+    }
+}
+
+/// Service an alert sink posts to, deciding the shape of its messages and
+/// how a batch of events is combined.
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+#[serde(tag = "kind", rename_all = "lowercase")]
+pub enum AlertFormat {
+    /// Discord embeds, up to 10 events per message
+    Discord,
+    /// Slack Block Kit, up to 25 events per message
+    Slack,
+    /// Telegram `sendMessage`, the URL being `https://api.telegram.org/bot<token>/sendMessage`
+    Telegram { chat_id: String },
+    /// One structured JSON object per event
+    Json,
+}
+
+impl AlertFormat {
+    /// Preset formatter of the format, usable as a `WebhookLayer` formatter
+    pub fn format(&self, event: &Event, visitor: &FieldVisitor, name: &str) -> serde_json::Value {
+        let level = *event.metadata().level();
+        match self {
+            AlertFormat::Json => {
+                let fields: serde_json::Map<_, _> = visitor
+                    .fields
+                    .iter()
+                    .map(|(key, value)| (key.clone(), serde_json::Value::String(value.clone())))
+                    .collect();
+                serde_json::json!({
+                    "source": name,
+                    "level": level.to_string(),
+                    "target": event.metadata().target(),
+                    "file": event.metadata().file(),
+                    "line": event.metadata().line(),
+                    "fields": fields,
+                    "timestamp": chrono::Utc::now().to_rfc3339(),
+                })
+            }
+            _ => self.render(
+                name,
+                level,
+                &format!("{} event from {}", level, name),
+                &describe_event(event, visitor),
+            ),
+        }
+    }
+
+    /// Message with a title and a plain text description
+    fn render(
+        &self,
+        name: &str,
+        level: tracing::Level,
+        title: &str,
+        description: &str,
+    ) -> serde_json::Value {
+        let timestamp = chrono::Utc::now().to_rfc3339();
+        match self {
+            AlertFormat::Discord => {
+                let color = match level {
+                    tracing::Level::ERROR => 0xFF0000, // Red
+                    tracing::Level::WARN => 0xFFA500,  // Orange
+                    tracing::Level::INFO => 0x00FF00,  // Green
+                    tracing::Level::DEBUG => 0x808080, // Gray
+                    tracing::Level::TRACE => 0x0000FF, // Blue
+                };
+                serde_json::json!({
+                    "username": name,
+                    "embeds": [{
+                        "title": title,
+                        "description": truncate(description, alerts::DISCORD_DESCRIPTION_LIMIT),
+                        "color": color,
+                        "timestamp": timestamp
+                    }]
+                })
+            }
+            AlertFormat::Slack => serde_json::json!({
+                "username": name,
+                "text": title,
+                "blocks": [
+                    {
+                        "type": "header",
+                        "text": { "type": "plain_text", "text": title }
+                    },
+                    {
+                        "type": "section",
+                        "text": {
+                            "type": "mrkdwn",
+                            "text": format!(
+                                "```{}```",
+                                truncate(description, alerts::SLACK_SECTION_LIMIT)
+                            )
+                        }
+                    }
+                ]
+            }),
+            AlertFormat::Telegram { chat_id } => serde_json::json!({
+                "chat_id": chat_id,
+                "text": truncate(
+                    &format!("{}\n\n{}", title, description),
+                    alerts::TELEGRAM_TEXT_LIMIT
+                ),
+                "disable_web_page_preview": true
+            }),
+            AlertFormat::Json => serde_json::json!({
+                "source": name,
+                "level": level.to_string(),
+                "title": title,
+                "message": description,
+                "timestamp": timestamp,
+            }),
+        }
+    }
+
+    /// Field holding the parts of a message that can be merged across events,
+    /// with the most a single message can hold
+    fn merge_field(&self) -> Option<(&'static str, usize)> {
+        match self {
+            AlertFormat::Discord => Some(("embeds", alerts::MAX_EMBEDS_PER_MESSAGE)),
+            // every event is a header and a section
+            AlertFormat::Slack => Some(("blocks", alerts::MAX_SLACK_BLOCKS_PER_MESSAGE)),
+            AlertFormat::Telegram { .. } | AlertFormat::Json => None,
+        }
+    }
+
+    /// Marks a message as standing for `count` identical events
+    fn with_repeat_count(&self, mut message: serde_json::Value, count: usize) -> serde_json::Value {
+        if count < 2 {
+            return message;
+        }
+        let suffix = format!(" (x{})", count);
+        let append = |value: &mut serde_json::Value| {
+            if let Some(text) = value.as_str() {
+                *value = serde_json::Value::from(format!("{}{}", text, suffix));
+            }
+        };
+        match self {
+            AlertFormat::Discord => {
+                if let Some(embeds) = message.get_mut("embeds").and_then(|e| e.as_array_mut()) {
+                    embeds
+                        .iter_mut()
+                        .filter_map(|embed| embed.get_mut("title"))
+                        .for_each(append);
+                } else if message.get("content").is_some() {
+                    // custom formatters may not use embeds
+                    append(&mut message["content"]);
+                }
+            }
+            AlertFormat::Slack => {
+                if let Some(blocks) = message.get_mut("blocks").and_then(|b| b.as_array_mut()) {
+                    blocks
+                        .iter_mut()
+                        .filter(|block| block["type"] == "header")
+                        .for_each(|block| append(&mut block["text"]["text"]));
+                }
+            }
+            AlertFormat::Telegram { .. } => {
+                if let Some(text) = message.get("text").and_then(|t| t.as_str()) {
+                    message["text"] = serde_json::Value::from(truncate(
+                        &format!("Repeated {} times\n{}", count, text),
+                        alerts::TELEGRAM_TEXT_LIMIT,
+                    ));
+                }
+            }
+            AlertFormat::Json => message["count"] = serde_json::Value::from(count),
+        }
+        message
+    }
+}
+
+/// Plain text description of an event: target, location and fields
+fn describe_event(event: &Event, visitor: &FieldVisitor) -> String {
     let target = event.metadata().target();
     let target_parts: Vec<&str> = target.split(':').collect();
     let short_target = target_parts.last().unwrap_or(&target);
@@ -204,88 +675,217 @@ pub fn default_message_formatter(
     for (key, value) in &visitor.fields {
         content.push_str(&format!("{}: {}\n", key, value));
     }
+    content
+}
+
+/// Cuts `text` down to at most `max_chars` characters
+fn truncate(text: &str, max_chars: usize) -> String {
+    if text.chars().count() <= max_chars {
+        return text.to_string();
+    }
+    let kept: String = text.chars().take(max_chars.saturating_sub(1)).collect();
+    format!("{}…", kept)
+}
+
+/// Sets up tracing with any number of alert sinks, each with its own level,
+/// target filter and message format. Without sinks only the console output
+/// is set up. Spans are also exported over OTLP when `otlp` is set.
+pub fn setup_tracing_with_sinks(
+    sinks: &[AlertSinkConfig],
+    app_name: &str,
+    log_format: LogFormat,
+    otlp: Option<&OtlpConfig>,
+) -> Result<()> {
+    use opentelemetry::trace::TracerProvider as _;
+    use tracing_subscriber::prelude::*;
 
-    let color = match *event.metadata().level() {
-        tracing::Level::ERROR => 0xFF0000, // Red
-        tracing::Level::WARN => 0xFFA500,  // Orange
-        tracing::Level::INFO => 0x00FF00,  // Green
-        tracing::Level::DEBUG => 0x808080, // Gray
-        tracing::Level::TRACE => 0x0000FF, // Blue
+    let otel_layer = match otlp {
+        Some(otlp) => {
+            let provider = telemetry::tracer_provider(otlp)
+                .map_err(|e| eyre::eyre!("Failed to create OTLP exporter: {}", e))?;
+            let tracer = provider.tracer("monad-faucet");
+            opentelemetry::global::set_tracer_provider(provider);
+            Some(tracing_opentelemetry::layer().with_tracer(tracer))
+        }
+        None => None,
     };
 
-    serde_json::json!({
-        "username": name,
-        "embeds": [{
-            "title": format!("{} event from {}", event.metadata().level().to_string(), name),
-            "description": content,
-            "color": color,
-            "timestamp": timestamp
-        }]
-    })
+    let fmt_layer = match log_format {
+        LogFormat::Pretty => tracing_subscriber::fmt::layer().pretty().boxed(),
+        // spans are included, so job logs carry the request ID they were queued with
+        LogFormat::Json => tracing_subscriber::fmt::layer().json().boxed(),
+    }
+    .with_filter(tracing_subscriber::filter::LevelFilter::INFO);
+
+    let sink_layers = sinks
+        .iter()
+        .map(|sink| WebhookLayer::from_sink(sink, app_name))
+        .collect::<Result<Vec<_>>>()?;
+
+    tracing_subscriber::registry()
+        .with(otel_layer)
+        .with(fmt_layer)
+        .with(sink_layers)
+        .try_init()
+        .map_err(|e| eyre::eyre!("Failed to initialize tracing: {}", e))
 }
 
-/// Sets up tracing with a webhook for notifications.
+/// Sets up tracing with a single Discord webhook for notifications.
 ///
 /// This function configures a tracing subscriber with:
 /// 1. A standard formatting layer for console output (INFO level and above)
-/// 2. A webhook layer for sending events at the specified level
+/// 2. A webhook layer for sending events at the specified level, batched and
+///    rate limited as described on [`WebhookLayer`]
 ///
 /// # Arguments
 ///
 /// * `webhook_url` - The webhook URL to send events to
 /// * `app_name` - Name to display for the webhook messages
 /// * `level` - The level of events to send to the webhook (e.g., ERROR)
-/// * `rate_limit_per_minute` - Optional rate limit for webhook messages
 /// * `formatter` - Optional custom formatter function for webhook messages
 ///
 /// # Returns
 ///
 /// `Result<()>` - Ok if initialization succeeded, or an error
-///
-/// # Examples
-///
-/// ```no_run
-/// use tracing::Level;
-/// let webhook_url = std::env::var("WEBHOOK_URL")
-///     .unwrap_or_else(|_| "https://discord.com/api/webhooks/your/url".to_string());
-///
-/// utils::setup_tracing_with_webhook(
-///     &webhook_url,
-///     "MyApp",
-///     Level::ERROR,
-///     None
-/// ).unwrap();
-///
-/// // Now events at ERROR level will be sent to the webhook
-/// tracing::error!("This will appear in the webhook");
-/// ```
+#[deprecated(note = "configure `alerts.sinks` and use `setup_tracing_with_sinks`")]
+#[allow(dead_code, clippy::type_complexity)]
 pub fn setup_tracing_with_webhook(
     webhook_url: &str,
     app_name: &str,
     level: tracing::Level,
     formatter: Option<Box<dyn Fn(&Event, &FieldVisitor, &str) -> serde_json::Value + Send + Sync>>,
 ) -> Result<()> {
+    use tracing_subscriber::prelude::*;
+
     let fmt_layer = tracing_subscriber::fmt::layer()
         .pretty()
         .with_filter(tracing_subscriber::filter::LevelFilter::INFO);
 
-    use tracing_subscriber::prelude::*;
+    let mut webhook_layer = WebhookLayer::new(webhook_url, app_name, level, AlertFormat::Discord)?;
+    if let Some(formatter) = formatter {
+        webhook_layer = webhook_layer.with_formatter(formatter);
+    }
 
-    let webhook_layer = WebhookLayer::new(
-        webhook_url,
-        app_name,
-        level,
-        match formatter {
-            Some(f) => f,
-            None => Box::new(default_message_formatter),
-        },
-    )?;
-
-    let subscriber = tracing_subscriber::registry()
+    tracing_subscriber::registry()
         .with(fmt_layer)
-        .with(webhook_layer);
-
-    subscriber
+        .with(webhook_layer)
         .try_init()
         .map_err(|e| eyre::eyre!("Failed to initialize tracing: {}", e))
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn alert(key: &str, title: &str) -> PendingAlert {
+        PendingAlert {
+            key: key.to_string(),
+            message: serde_json::json!({
+                "username": "Faucet",
+                "embeds": [{ "title": title }]
+            }),
+        }
+    }
+
+    #[test]
+    fn test_queue_drops_oldest() {
+        let queue = AlertQueue::new(2);
+        queue.push(alert("a", "A"));
+        queue.push(alert("b", "B"));
+        queue.push(alert("c", "C"));
+
+        let (pending, dropped) = queue.drain();
+        assert_eq!(dropped, 1);
+        let keys: Vec<_> = pending.iter().map(|a| a.key.as_str()).collect();
+        assert_eq!(keys, ["b", "c"]);
+        assert_eq!(queue.drain().1, 0);
+    }
+
+    #[test]
+    fn test_batch_messages() {
+        let groups = coalesce(vec![
+            alert("rpc", "RPC down"),
+            alert("db", "DB slow"),
+            alert("rpc", "RPC down"),
+            alert("rpc", "RPC down"),
+        ]);
+        assert_eq!(groups.len(), 2);
+
+        let messages = batch_messages(groups, 3, "Faucet", &AlertFormat::Discord);
+        assert_eq!(messages.len(), 1);
+        let embeds = messages[0]["embeds"].as_array().unwrap();
+        assert_eq!(embeds[0]["title"], "RPC down (x3)");
+        assert_eq!(embeds[1]["title"], "DB slow");
+        assert_eq!(embeds[2]["title"], "3 events dropped");
+        assert_eq!(messages[0]["username"], "Faucet");
+
+        // Discord caps embeds per message
+        let many = (0..alerts::MAX_EMBEDS_PER_MESSAGE + 1)
+            .map(|i| alert(&i.to_string(), "event"))
+            .collect();
+        assert_eq!(
+            batch_messages(coalesce(many), 0, "Faucet", &AlertFormat::Discord).len(),
+            2
+        );
+    }
+
+    #[test]
+    fn test_batch_messages_by_format() {
+        let slack = AlertFormat::Slack;
+        let groups = vec![
+            (
+                slack.render("Faucet", tracing::Level::WARN, "RPC down", "timeout"),
+                2,
+            ),
+            (
+                slack.render("Faucet", tracing::Level::ERROR, "DB down", "refused"),
+                1,
+            ),
+        ];
+        let messages = batch_messages(groups, 0, "Faucet", &slack);
+        assert_eq!(messages.len(), 1);
+        let blocks = messages[0]["blocks"].as_array().unwrap();
+        assert_eq!(blocks.len(), 4);
+        assert_eq!(blocks[0]["text"]["text"], "RPC down (x2)");
+
+        let telegram = AlertFormat::Telegram {
+            chat_id: "-100".to_string(),
+        };
+        let groups = vec![(
+            telegram.render("Faucet", tracing::Level::WARN, "RPC down", "timeout"),
+            3,
+        )];
+        let messages = batch_messages(groups, 1, "Faucet", &telegram);
+        assert_eq!(messages.len(), 2);
+        assert_eq!(messages[0]["chat_id"], "-100");
+        assert!(messages[0]["text"]
+            .as_str()
+            .unwrap()
+            .starts_with("Repeated 3 times"));
+
+        let groups = vec![(
+            AlertFormat::Json.render("Faucet", tracing::Level::WARN, "RPC down", "timeout"),
+            2,
+        )];
+        let messages = batch_messages(groups, 0, "Faucet", &AlertFormat::Json);
+        assert_eq!(messages[0]["count"], 2);
+        assert_eq!(messages[0]["level"], "WARN");
+    }
+
+    #[test]
+    fn test_validate_webhook_url() {
+        assert!(validate_webhook_url("https://hooks.slack.com/services/x").is_ok());
+        assert!(validate_webhook_url("http://localhost:8080/hook").is_ok());
+        assert!(validate_webhook_url("http://127.0.0.1:8080/hook").is_ok());
+        assert!(validate_webhook_url("http://[::1]:8080/hook").is_ok());
+        assert!(validate_webhook_url("http://example.com/hook").is_err());
+        assert!(validate_webhook_url("ftp://localhost/hook").is_err());
+        assert!(validate_webhook_url("not a url").is_err());
+    }
+
+    #[test]
+    fn test_truncate() {
+        assert_eq!(truncate("short", 10), "short");
+        assert_eq!(truncate("ééééé", 3), "éé…");
+    }
+}
@@ -1,58 +1,1417 @@
-use dotenv::var;
-use serde::Deserialize;
+use std::{fmt, sync::Arc};
 
-#[derive(Deserialize, Debug, Clone)]
+use alloy::{
+    hex::FromHex,
+    primitives::{Address, FixedBytes},
+};
+use arc_swap::ArcSwap;
+use axum::http::HeaderValue;
+use serde::{Deserialize, Deserializer, Serialize};
+use tracing::{error, info};
+use utoipa::ToSchema;
+
+use crate::{
+    common::{validate_webhook_url, AlertFormat},
+    constants,
+};
+
+/// Config file read when `CONFIG_FILE` is not set, it may be absent
+const DEFAULT_CONFIG_FILE: &str = "config.toml";
+
+/// Settings read from a TOML file, with the environment variables named in
+/// the README taking precedence over it.
+///
+/// Sections that are `Option`al turn their feature off when left out.
+#[derive(Debug, Clone)]
 pub struct Config {
-    pub db_url: String,
-    pub port: u16,
+    pub server: ServerConfig,
+    pub db: DbConfig,
+    pub chain: ChainConfig,
+    pub signers: SignersConfig,
+    pub auth: AuthConfig,
+    /// Token deploys are rejected without somewhere to store logos
+    pub storage: Option<StorageConfig>,
+    pub alerts: AlertsConfig,
+    pub logging: LoggingConfig,
+    pub cors: CorsConfig,
+    pub policies: PoliciesConfig,
+    /// Values at startup, the live ones are read through [`Tuning`]
+    pub tuning: TuningConfig,
+}
+
+#[derive(Debug, Clone)]
+pub struct ServerConfig {
     pub host: String,
-    pub captcha_secret_key: String,
-    pub github_client_id: String,
-    pub github_client_secret: String,
-    pub github_redirect_uri: String,
-    pub jwt_secret: String,
-    pub private_key: String,
+    pub port: u16,
+}
+
+#[derive(Debug, Clone)]
+pub struct DbConfig {
+    pub url: String,
+}
+
+#[derive(Debug, Clone)]
+pub struct ChainConfig {
     pub rpc_url: String,
-    pub deploy_key: String,
-    pub imgix_key: String,
-    pub orderbook_url: String,
-    pub discord_webhook: Option<String>,
 }
 
+/// Accounts the faucet sends from, see [`crate::signer::Signers`]
+#[derive(Debug, Clone)]
+pub struct SignersConfig {
+    pub faucet: SignerConfig,
+    pub deployer: SignerConfig,
+}
+
+/// Where a signer's key is held, selected with `kind` in its `[signers.*]` table
+#[derive(Debug, Clone, PartialEq)]
+pub enum SignerConfig {
+    /// Raw hex private key, meant for development
+    Key { key: String },
+    /// Web3 secret storage JSON file, decrypted with the passphrase read
+    /// from `passphrase_file`
+    Keystore {
+        path: String,
+        passphrase_file: String,
+    },
+    /// Signs over HTTP, see [`crate::signer::RemoteSigner`]
+    Remote {
+        url: String,
+        address: String,
+        auth_token: Option<String>,
+    },
+}
+
+#[derive(Debug, Clone)]
+pub struct AuthConfig {
+    /// Keys access tokens are verified with, picked by their `kid`. The first
+    /// one signs new tokens, the others keep tokens signed before a rotation
+    /// valid until they expire.
+    pub jwt_keys: Vec<JwtKey>,
+    pub jwt_issuer: String,
+    pub jwt_audience: String,
+    pub access_token_ttl_secs: i64,
+    /// Sessions end this long after sign in, however often they are refreshed
+    pub refresh_token_ttl_days: i64,
+    pub admin_user_ids: Vec<String>,
+    /// GitHub login is disabled without an OAuth app
+    pub github: Option<GithubConfig>,
+}
+
+/// An HMAC key access tokens are signed with, named in their `kid` header
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct JwtKey {
+    pub id: String,
+    pub secret: String,
+}
+
+#[derive(Debug, Clone)]
+pub struct GithubConfig {
+    pub client_id: String,
+    pub client_secret: String,
+    pub redirect_uri: String,
+    /// Where users are sent to sign in
+    pub authorize_url: String,
+    /// Where the code is exchanged for an access token
+    pub token_url: String,
+    /// Base of the REST API the user is fetched from
+    pub api_url: String,
+}
+
+#[derive(Debug, Clone)]
+pub struct AlertsConfig {
+    pub sinks: Vec<AlertSinkConfig>,
+}
+
+#[derive(Debug, Clone)]
+pub struct LoggingConfig {
+    pub format: LogFormat,
+    /// Spans are only exported when a collector is configured
+    pub otlp: Option<OtlpConfig>,
+}
+
+#[derive(Debug, Clone)]
+pub struct CorsConfig {
+    pub allowed_origins: Vec<String>,
+}
+
+#[derive(Debug, Clone)]
+pub struct PoliciesConfig {
+    /// Turnstile secret, every captcha is refused without it unless
+    /// `captcha_disabled` is set
+    pub captcha_secret_key: Option<String>,
+    /// Lets every captcha pass unchecked, for local setups without Turnstile
+    pub captcha_disabled: bool,
+    /// Garden orderbook queried for the drip magnification
+    pub orderbook_url: Option<String>,
+}
+
+/// Drip parameters that can be changed at runtime, from the `[tuning]`
+/// section of the config file
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq, ToSchema)]
+#[serde(default, deny_unknown_fields)]
+pub struct TuningConfig {
+    /// Either GitHub authenticated or Garden user
+    pub magnification_github_auth: u8,
+    pub magnification_garden_user: u8,
+    /// Neither authenticated nor Garden user
+    pub magnification_no_auth: u8,
+    /// Share of a deployed token's supply dripped at once
+    pub withdraw_limit_denominator: f64,
+    /// Tokens claimable in one `/withdraw/batch` request
+    pub max_batch_tokens: usize,
+    /// Per owner, across all their wallets
+    pub max_scheduled_refills_per_day: i64,
+    /// Warn once fewer max size drips than this are left
+    pub low_balance_drips: u64,
+    /// Pause a token once more max size drips than this went out in an
+    /// hour, 0 disables the cap
+    pub hourly_outflow_cap_drips: u64,
+    /// Same over a day
+    pub daily_outflow_cap_drips: u64,
+}
+
+impl Default for TuningConfig {
+    fn default() -> Self {
+        Self {
+            magnification_github_auth: 10,
+            magnification_garden_user: 10,
+            magnification_no_auth: 1,
+            withdraw_limit_denominator: 1_000_000_000.0,
+            max_batch_tokens: 5,
+            max_scheduled_refills_per_day: 10,
+            low_balance_drips: 100,
+            hourly_outflow_cap_drips: 500,
+            daily_outflow_cap_drips: 5_000,
+        }
+    }
+}
+
+/// Live [`TuningConfig`], replaced as a whole when the config is reloaded
+#[derive(Debug, Clone)]
+pub struct Tuning(Arc<ArcSwap<TuningConfig>>);
+
+impl Tuning {
+    pub fn new(tuning: TuningConfig) -> Self {
+        Self(Arc::new(ArcSwap::from_pointee(tuning)))
+    }
+
+    pub fn load(&self) -> Arc<TuningConfig> {
+        self.0.load_full()
+    }
+
+    /// A handle pinned to the current values, for work that should not see
+    /// a reload halfway through
+    pub fn snapshot(&self) -> Self {
+        Self(Arc::new(ArcSwap::new(self.load())))
+    }
+
+    /// Reloads the config file and environment, only swapping the tuning in
+    /// once the whole config is valid. Other sections need a restart.
+    pub fn reload(&self) -> Result<Arc<TuningConfig>, ConfigErrors> {
+        let tuning = Arc::new(Config::load()?.tuning);
+        self.0.store(tuning.clone());
+        Ok(tuning)
+    }
+
+    /// Reloads whenever the process receives SIGHUP.
+    pub async fn reload_on_sighup(self) {
+        use tokio::signal::unix::{signal, SignalKind};
+
+        let mut hangups = match signal(SignalKind::hangup()) {
+            Ok(hangups) => hangups,
+            Err(e) => {
+                error!(
+                    "Failed to listen for SIGHUP, config reloads are disabled {}",
+                    e
+                );
+                return;
+            }
+        };
+        while hangups.recv().await.is_some() {
+            match self.reload() {
+                Ok(tuning) => info!(?tuning, "Reloaded config"),
+                Err(e) => error!("Keeping the current config, {}", e),
+            }
+        }
+    }
+}
+
+/// Console log output, selected with `LOG_FORMAT`
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum LogFormat {
+    /// Human readable multi-line output
+    Pretty,
+    /// One JSON object per line, with the fields of the current spans
+    Json,
+}
+
+/// OTLP/HTTP collector spans are exported to, enabled by setting
+/// `OTEL_EXPORTER_OTLP_ENDPOINT`
+#[derive(Debug, Clone)]
+pub struct OtlpConfig {
+    /// Base URL of the collector, e.g. `http://localhost:4318`
+    pub endpoint: String,
+    pub service_name: String,
+}
+
+/// Where token logos are stored, selected with `STORAGE_BACKEND`
+#[derive(Debug, Clone)]
+pub enum StorageConfig {
+    Imgix {
+        api_key: String,
+        source_id: String,
+        public_url: String,
+    },
+    S3 {
+        endpoint: String,
+        bucket: String,
+        region: String,
+        access_key: String,
+        secret_key: String,
+        public_url: String,
+    },
+    /// Files written to `dir` and served by the backend under `/assets`
+    Local { dir: String, public_url: String },
+}
+
+/// A webhook that tracing events are posted to, see `WebhookLayer`
+#[derive(Deserialize, Debug, Clone)]
+pub struct AlertSinkConfig {
+    #[serde(flatten)]
+    pub format: AlertFormat,
+    pub url: String,
+    /// Least severe level sent to the sink
+    #[serde(
+        default = "default_alert_level",
+        deserialize_with = "deserialize_level"
+    )]
+    pub level: tracing::Level,
+    /// Target prefixes to send events from, e.g. `monad_faucet::executor`,
+    /// everything when empty
+    #[serde(default)]
+    pub targets: Vec<String>,
+}
+
+fn default_alert_level() -> tracing::Level {
+    tracing::Level::WARN
+}
+
+fn deserialize_level<'de, D: Deserializer<'de>>(
+    deserializer: D,
+) -> Result<tracing::Level, D::Error> {
+    String::deserialize(deserializer)?
+        .parse()
+        .map_err(serde::de::Error::custom)
+}
+
+/// Every problem found while loading the config
+#[derive(Debug)]
+pub struct ConfigErrors(pub Vec<String>);
+
+impl fmt::Display for ConfigErrors {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "Invalid configuration:")?;
+        for error in &self.0 {
+            writeln!(f, "  - {}", error)?;
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for ConfigErrors {}
+
 impl Config {
-    pub fn from_env() -> Self {
-        let db_url = var("DATABASE_URL").expect("DATABASE_URL must be set");
-        let port = var("PORT").unwrap_or("6969".to_string()).parse().unwrap();
-        let host = var("HOST").unwrap_or("0.0.0.0".to_string());
-        let captcha_secret_key = var("CAPTCHA_SECRET_KEY").expect("CAPTCHA_SECRET_KEY must be set");
-        let github_client_id = var("GITHUB_CLIENT_ID").expect("GITHUB_CLIENT_ID must be set");
-        let github_client_secret =
-            var("GITHUB_CLIENT_SECRET").expect("GITHUB_CLIENT_SECRET must be set");
-        let github_redirect_uri =
-            var("GITHUB_REDIRECT_URI").expect("GITHUB_REDIRECT_URI must be set");
-        let jwt_secret = var("JWT_SECRET_KEY").expect("JWT_SECRET_KEY must be set");
-        let private_key = var("PRIVATE_KEY").expect("PRIVATE_KEY must be set");
-        let rpc_url = var("RPC_URL").expect("RPC_URL must be set");
-        let deploy_key = var("DEPLOY_KEY").expect("DEPLOY_KEY must be set");
-        let imgix_key = var("IMGIX_KEY").expect("IMGIX_KEY must be set");
-        let orderbook_url = var("ORDERBOOK_URL").expect("ORDERBOOK_URL must be set");
-        let discord_webhook = var("DISCORD_WEBHOOK").ok();
+    /// Reads `CONFIG_FILE` (or `config.toml` when present) and the environment
+    pub fn load() -> Result<Self, ConfigErrors> {
+        let mut errors = Vec::new();
+        let path = dotenv::var("CONFIG_FILE").ok();
+        let file = match std::fs::read_to_string(path.as_deref().unwrap_or(DEFAULT_CONFIG_FILE)) {
+            Ok(file) => Some(file),
+            Err(e) if path.is_none() && e.kind() == std::io::ErrorKind::NotFound => None,
+            Err(e) => {
+                errors.push(format!(
+                    "Failed to read {}: {}",
+                    path.as_deref().unwrap_or(DEFAULT_CONFIG_FILE),
+                    e
+                ));
+                None
+            }
+        };
+
+        Self::parse(file.as_deref(), |name| dotenv::var(name).ok(), errors)
+    }
+
+    /// Layers `env` over the TOML `file` and validates the result, collecting
+    /// every problem instead of stopping at the first.
+    fn parse(
+        file: Option<&str>,
+        env: impl Fn(&str) -> Option<String>,
+        mut errors: Vec<String>,
+    ) -> Result<Self, ConfigErrors> {
+        let mut file = match file.map(toml::from_str::<ConfigFile>) {
+            Some(Ok(file)) => file,
+            Some(Err(e)) => {
+                errors.push(format!("Invalid config file: {}", e));
+                ConfigFile::default()
+            }
+            None => ConfigFile::default(),
+        };
+        file.apply_env(&env, &mut errors);
+        let config = file.validate(&mut errors);
+
+        if errors.is_empty() {
+            Ok(config)
+        } else {
+            Err(ConfigErrors(errors))
+        }
+    }
+}
+
+/// The config file as written, everything optional until validated
+#[derive(Deserialize, Debug, Default)]
+#[serde(default, deny_unknown_fields)]
+struct ConfigFile {
+    server: ServerFile,
+    db: DbFile,
+    chain: ChainFile,
+    signers: SignersFile,
+    auth: AuthFile,
+    storage: Option<StorageFile>,
+    alerts: AlertsFile,
+    logging: LoggingFile,
+    cors: CorsFile,
+    policies: PoliciesFile,
+    tuning: TuningConfig,
+}
+
+#[derive(Deserialize, Debug, Default)]
+#[serde(default, deny_unknown_fields)]
+struct ServerFile {
+    host: Option<String>,
+    port: Option<u16>,
+}
+
+#[derive(Deserialize, Debug, Default)]
+#[serde(default, deny_unknown_fields)]
+struct DbFile {
+    url: Option<String>,
+}
+
+#[derive(Deserialize, Debug, Default)]
+#[serde(default, deny_unknown_fields)]
+struct ChainFile {
+    rpc_url: Option<String>,
+}
 
+#[derive(Deserialize, Debug, Default)]
+#[serde(default, deny_unknown_fields)]
+struct SignersFile {
+    faucet: Option<SignerFile>,
+    deployer: Option<SignerFile>,
+}
+
+/// Holds the fields of every kind of signer, which ones are needed depends on `kind`
+#[derive(Deserialize, Debug, Default)]
+#[serde(default, deny_unknown_fields)]
+struct SignerFile {
+    kind: Option<String>,
+    key: Option<String>,
+    path: Option<String>,
+    passphrase_file: Option<String>,
+    url: Option<String>,
+    address: Option<String>,
+    auth_token: Option<String>,
+}
+
+impl SignerFile {
+    fn key(key: String) -> Self {
         Self {
-            db_url,
-            port,
-            host,
-            captcha_secret_key,
-            github_client_id,
-            github_client_secret,
-            github_redirect_uri,
-            jwt_secret,
-            private_key,
-            rpc_url,
-            deploy_key,
-            imgix_key,
-            orderbook_url,
-            discord_webhook,
+            kind: Some("key".to_string()),
+            key: Some(key),
+            ..Default::default()
+        }
+    }
+}
+
+#[derive(Deserialize, Debug, Default)]
+#[serde(default, deny_unknown_fields)]
+struct AuthFile {
+    /// Key with the id `default`, accepted after the `jwt_keys`
+    jwt_secret: Option<String>,
+    jwt_keys: Vec<JwtKey>,
+    jwt_issuer: Option<String>,
+    jwt_audience: Option<String>,
+    access_token_ttl_secs: Option<i64>,
+    refresh_token_ttl_days: Option<i64>,
+    admin_user_ids: Vec<String>,
+    github: Option<GithubFile>,
+}
+
+#[derive(Deserialize, Debug, Default)]
+#[serde(default, deny_unknown_fields)]
+struct GithubFile {
+    client_id: Option<String>,
+    client_secret: Option<String>,
+    redirect_uri: Option<String>,
+    authorize_url: Option<String>,
+    token_url: Option<String>,
+    api_url: Option<String>,
+}
+
+/// Holds the fields of every backend, which ones are needed depends on `backend`
+#[derive(Deserialize, Debug, Default)]
+#[serde(default, deny_unknown_fields)]
+struct StorageFile {
+    backend: Option<String>,
+    api_key: Option<String>,
+    source_id: Option<String>,
+    endpoint: Option<String>,
+    bucket: Option<String>,
+    region: Option<String>,
+    access_key: Option<String>,
+    secret_key: Option<String>,
+    dir: Option<String>,
+    public_url: Option<String>,
+}
+
+#[derive(Deserialize, Debug, Default)]
+#[serde(default, deny_unknown_fields)]
+struct AlertsFile {
+    sinks: Vec<AlertSinkConfig>,
+}
+
+#[derive(Deserialize, Debug, Default)]
+#[serde(default, deny_unknown_fields)]
+struct LoggingFile {
+    format: Option<LogFormat>,
+    otlp_endpoint: Option<String>,
+    service_name: Option<String>,
+}
+
+#[derive(Deserialize, Debug, Default)]
+#[serde(default, deny_unknown_fields)]
+struct CorsFile {
+    allowed_origins: Option<Vec<String>>,
+}
+
+#[derive(Deserialize, Debug, Default)]
+#[serde(default, deny_unknown_fields)]
+struct PoliciesFile {
+    captcha_secret_key: Option<String>,
+    captcha_disabled: Option<bool>,
+    orderbook_url: Option<String>,
+}
+
+/// Replaces `value` with the environment variable `name` when it is set
+fn set(value: &mut Option<String>, env: &impl Fn(&str) -> Option<String>, name: &str) {
+    if let Some(var) = env(name) {
+        *value = Some(var);
+    }
+}
+
+fn comma_separated(list: &str) -> Vec<String> {
+    list.split(',')
+        .map(|item| item.trim().to_string())
+        .filter(|item| !item.is_empty())
+        .collect()
+}
+
+impl ConfigFile {
+    fn apply_env(&mut self, env: &impl Fn(&str) -> Option<String>, errors: &mut Vec<String>) {
+        set(&mut self.server.host, env, "HOST");
+        if let Some(port) = env("PORT") {
+            match port.parse() {
+                Ok(port) => self.server.port = Some(port),
+                Err(_) => errors.push(format!("PORT must be a port number, got `{}`", port)),
+            }
+        }
+        set(&mut self.db.url, env, "DATABASE_URL");
+        set(&mut self.chain.rpc_url, env, "RPC_URL");
+        // raw keys from the environment replace whatever signer the file has
+        if let Some(key) = env("PRIVATE_KEY") {
+            self.signers.faucet = Some(SignerFile::key(key));
         }
+        if let Some(key) = env("DEPLOY_KEY") {
+            self.signers.deployer = Some(SignerFile::key(key));
+        }
+
+        set(&mut self.auth.jwt_secret, env, "JWT_SECRET_KEY");
+        if let Some(keys) = env("JWT_KEYS") {
+            self.auth.jwt_keys = Vec::new();
+            for key in comma_separated(&keys) {
+                match key.split_once('=') {
+                    Some((id, secret)) => self.auth.jwt_keys.push(JwtKey {
+                        id: id.trim().to_string(),
+                        secret: secret.trim().to_string(),
+                    }),
+                    None => errors.push(format!(
+                        "JWT_KEYS must be comma separated `kid=secret` pairs, got `{}`",
+                        key
+                    )),
+                }
+            }
+        }
+        if let Some(ids) = env("ADMIN_USER_IDS") {
+            self.auth.admin_user_ids = comma_separated(&ids);
+        }
+        let github_vars = [
+            "GITHUB_CLIENT_ID",
+            "GITHUB_CLIENT_SECRET",
+            "GITHUB_REDIRECT_URI",
+            "GITHUB_AUTHORIZE_URL",
+            "GITHUB_TOKEN_URL",
+            "GITHUB_API_URL",
+        ];
+        if github_vars.iter().any(|name| env(name).is_some()) {
+            let github = self.auth.github.get_or_insert_with(GithubFile::default);
+            set(&mut github.client_id, env, "GITHUB_CLIENT_ID");
+            set(&mut github.client_secret, env, "GITHUB_CLIENT_SECRET");
+            set(&mut github.redirect_uri, env, "GITHUB_REDIRECT_URI");
+            set(&mut github.authorize_url, env, "GITHUB_AUTHORIZE_URL");
+            set(&mut github.token_url, env, "GITHUB_TOKEN_URL");
+            set(&mut github.api_url, env, "GITHUB_API_URL");
+        }
+
+        // an `IMGIX_KEY` on its own keeps selecting imgix, as it did before
+        // `STORAGE_BACKEND` existed
+        let backend =
+            env("STORAGE_BACKEND").or_else(|| env("IMGIX_KEY").map(|_| "imgix".to_string()));
+        if let Some(backend) = backend {
+            self.storage
+                .get_or_insert_with(StorageFile::default)
+                .backend = Some(backend);
+        }
+        if let Some(storage) = &mut self.storage {
+            match storage.backend.as_deref() {
+                Some("imgix") => {
+                    set(&mut storage.api_key, env, "IMGIX_KEY");
+                    set(&mut storage.source_id, env, "IMGIX_SOURCE_ID");
+                    set(&mut storage.public_url, env, "IMGIX_PUBLIC_URL");
+                }
+                Some("s3") => {
+                    set(&mut storage.endpoint, env, "S3_ENDPOINT");
+                    set(&mut storage.bucket, env, "S3_BUCKET");
+                    set(&mut storage.region, env, "S3_REGION");
+                    set(&mut storage.access_key, env, "S3_ACCESS_KEY");
+                    set(&mut storage.secret_key, env, "S3_SECRET_KEY");
+                    set(&mut storage.public_url, env, "S3_PUBLIC_URL");
+                }
+                Some("local") => {
+                    set(&mut storage.dir, env, "LOCAL_STORAGE_DIR");
+                    set(&mut storage.public_url, env, "PUBLIC_URL");
+                }
+                _ => {}
+            }
+        }
+
+        if let Some(sinks) = env("ALERT_SINKS") {
+            match serde_json::from_str(&sinks) {
+                Ok(sinks) => self.alerts.sinks = sinks,
+                Err(e) => errors.push(format!("ALERT_SINKS must be a JSON array of sinks: {}", e)),
+            }
+        }
+        if let Some(url) = env("DISCORD_WEBHOOK") {
+            self.alerts.sinks.push(AlertSinkConfig {
+                format: AlertFormat::Discord,
+                url,
+                level: default_alert_level(),
+                targets: Vec::new(),
+            });
+        }
+
+        if let Some(format) = env("LOG_FORMAT") {
+            match format.as_str() {
+                "json" => self.logging.format = Some(LogFormat::Json),
+                "pretty" => self.logging.format = Some(LogFormat::Pretty),
+                _ => errors.push(format!(
+                    "LOG_FORMAT must be `pretty` or `json`, got `{}`",
+                    format
+                )),
+            }
+        }
+        set(
+            &mut self.logging.otlp_endpoint,
+            env,
+            "OTEL_EXPORTER_OTLP_ENDPOINT",
+        );
+        set(&mut self.logging.service_name, env, "OTEL_SERVICE_NAME");
+
+        if let Some(origins) = env("CORS_ALLOWED_ORIGINS") {
+            self.cors.allowed_origins = Some(comma_separated(&origins));
+        }
+
+        set(
+            &mut self.policies.captcha_secret_key,
+            env,
+            "CAPTCHA_SECRET_KEY",
+        );
+        if let Some(disabled) = env("CAPTCHA_DISABLED") {
+            match disabled.parse() {
+                Ok(disabled) => self.policies.captcha_disabled = Some(disabled),
+                Err(_) => errors.push(format!(
+                    "CAPTCHA_DISABLED must be true or false, got `{}`",
+                    disabled
+                )),
+            }
+        }
+        set(&mut self.policies.orderbook_url, env, "ORDERBOOK_URL");
+    }
+
+    fn validate(self, errors: &mut Vec<String>) -> Config {
+        let mut check = Validator { errors };
+
+        let server = ServerConfig {
+            host: self.server.host.unwrap_or("0.0.0.0".to_string()),
+            port: self.server.port.unwrap_or(6969),
+        };
+        let db = DbConfig {
+            url: check.url(
+                self.db.url,
+                "db.url",
+                "DATABASE_URL",
+                &["postgres", "postgresql"],
+            ),
+        };
+        let chain = ChainConfig {
+            rpc_url: check.url(
+                self.chain.rpc_url,
+                "chain.rpc_url",
+                "RPC_URL",
+                &["http", "https", "ws", "wss"],
+            ),
+        };
+        let signers = SignersConfig {
+            faucet: check.signer(self.signers.faucet, "signers.faucet", "PRIVATE_KEY"),
+            deployer: check.signer(self.signers.deployer, "signers.deployer", "DEPLOY_KEY"),
+        };
+
+        let github = self.auth.github.map(|github| GithubConfig {
+            client_id: check.required(
+                github.client_id,
+                "auth.github.client_id",
+                "GITHUB_CLIENT_ID",
+            ),
+            client_secret: check.required(
+                github.client_secret,
+                "auth.github.client_secret",
+                "GITHUB_CLIENT_SECRET",
+            ),
+            redirect_uri: check.url(
+                github.redirect_uri,
+                "auth.github.redirect_uri",
+                "GITHUB_REDIRECT_URI",
+                &["http", "https"],
+            ),
+            authorize_url: check.url(
+                github
+                    .authorize_url
+                    .or(Some(constants::github::AUTHORIZE_URL.to_string())),
+                "auth.github.authorize_url",
+                "GITHUB_AUTHORIZE_URL",
+                &["http", "https"],
+            ),
+            token_url: check.url(
+                github
+                    .token_url
+                    .or(Some(constants::github::TOKEN_URL.to_string())),
+                "auth.github.token_url",
+                "GITHUB_TOKEN_URL",
+                &["http", "https"],
+            ),
+            api_url: check.url(
+                github
+                    .api_url
+                    .or(Some(constants::github::API_URL.to_string())),
+                "auth.github.api_url",
+                "GITHUB_API_URL",
+                &["http", "https"],
+            ),
+        });
+        let mut jwt_keys = self.auth.jwt_keys;
+        if let Some(secret) = self.auth.jwt_secret {
+            jwt_keys.push(JwtKey {
+                id: "default".to_string(),
+                secret,
+            });
+        }
+        if jwt_keys.is_empty() {
+            check.errors.push(
+                "auth.jwt_secret (JWT_SECRET_KEY) is required, or auth.jwt_keys (JWT_KEYS)"
+                    .to_string(),
+            );
+        }
+        for (i, key) in jwt_keys.iter().enumerate() {
+            if key.id.trim().is_empty() || key.secret.trim().is_empty() {
+                check
+                    .errors
+                    .push(format!("auth.jwt_keys[{}] needs an id and a secret", i));
+            } else if jwt_keys[..i].iter().any(|other| other.id == key.id) {
+                check
+                    .errors
+                    .push(format!("auth.jwt_keys has the id `{}` twice", key.id));
+            }
+        }
+        let access_token_ttl_secs = self.auth.access_token_ttl_secs.unwrap_or(15 * 60);
+        let refresh_token_ttl_days = self.auth.refresh_token_ttl_days.unwrap_or(30);
+        if access_token_ttl_secs <= 0 || refresh_token_ttl_days <= 0 {
+            check.errors.push(
+                "auth.access_token_ttl_secs and auth.refresh_token_ttl_days must be positive"
+                    .to_string(),
+            );
+        }
+        let auth = AuthConfig {
+            jwt_keys,
+            jwt_issuer: self.auth.jwt_issuer.unwrap_or("monad-faucet".to_string()),
+            jwt_audience: self.auth.jwt_audience.unwrap_or("monad-faucet".to_string()),
+            access_token_ttl_secs,
+            refresh_token_ttl_days,
+            admin_user_ids: self.auth.admin_user_ids,
+            github,
+        };
+
+        let storage = self.storage.and_then(|storage| check.storage(storage));
+
+        for (i, sink) in self.alerts.sinks.iter().enumerate() {
+            if let Err(e) = validate_webhook_url(&sink.url) {
+                check.errors.push(format!("alerts.sinks[{}].url: {}", i, e));
+            }
+        }
+        let alerts = AlertsConfig {
+            sinks: self.alerts.sinks,
+        };
+
+        let otlp = self.logging.otlp_endpoint.map(|endpoint| OtlpConfig {
+            endpoint: check.url(
+                Some(endpoint),
+                "logging.otlp_endpoint",
+                "OTEL_EXPORTER_OTLP_ENDPOINT",
+                &["http", "https"],
+            ),
+            service_name: self
+                .logging
+                .service_name
+                .unwrap_or("monad-faucet".to_string()),
+        });
+        let logging = LoggingConfig {
+            format: self.logging.format.unwrap_or(LogFormat::Pretty),
+            otlp,
+        };
+
+        let allowed_origins = self.cors.allowed_origins.unwrap_or(vec![
+            "https://faucet.wtf".to_string(),
+            "http://localhost:5173".to_string(),
+        ]);
+        for origin in &allowed_origins {
+            let is_origin = reqwest::Url::parse(origin)
+                .is_ok_and(|url| matches!(url.scheme(), "http" | "https") && url.path() == "/");
+            if !is_origin || HeaderValue::from_str(origin).is_err() || origin.ends_with('/') {
+                check.errors.push(format!(
+                    "cors.allowed_origins (CORS_ALLOWED_ORIGINS): `{}` is not an origin like `https://faucet.wtf`",
+                    origin
+                ));
+            }
+        }
+        let cors = CorsConfig { allowed_origins };
+
+        let policies = PoliciesConfig {
+            captcha_secret_key: self.policies.captcha_secret_key,
+            captcha_disabled: self.policies.captcha_disabled.unwrap_or(false),
+            orderbook_url: self.policies.orderbook_url.map(|url| {
+                check.url(
+                    Some(url),
+                    "policies.orderbook_url",
+                    "ORDERBOOK_URL",
+                    &["http", "https"],
+                )
+            }),
+        };
+
+        let tuning = self.tuning;
+        for (value, key) in [
+            (
+                tuning.magnification_github_auth,
+                "tuning.magnification_github_auth",
+            ),
+            (
+                tuning.magnification_garden_user,
+                "tuning.magnification_garden_user",
+            ),
+            (tuning.magnification_no_auth, "tuning.magnification_no_auth"),
+        ] {
+            if value == 0 {
+                check.errors.push(format!("{} must be at least 1", key));
+            }
+        }
+        if !(tuning.withdraw_limit_denominator.is_finite()
+            && tuning.withdraw_limit_denominator >= 1.0)
+        {
+            check
+                .errors
+                .push("tuning.withdraw_limit_denominator must be at least 1".to_string());
+        }
+        if tuning.max_batch_tokens == 0 {
+            check
+                .errors
+                .push("tuning.max_batch_tokens must be at least 1".to_string());
+        }
+        if tuning.max_scheduled_refills_per_day < 0 {
+            check
+                .errors
+                .push("tuning.max_scheduled_refills_per_day must not be negative".to_string());
+        }
+        if tuning.hourly_outflow_cap_drips > 0
+            && tuning.daily_outflow_cap_drips > 0
+            && tuning.daily_outflow_cap_drips < tuning.hourly_outflow_cap_drips
+        {
+            check.errors.push(
+                "tuning.daily_outflow_cap_drips must not be below tuning.hourly_outflow_cap_drips"
+                    .to_string(),
+            );
+        }
+
+        Config {
+            server,
+            db,
+            chain,
+            signers,
+            auth,
+            storage,
+            alerts,
+            logging,
+            cors,
+            policies,
+            tuning,
+        }
+    }
+}
+
+/// Names a setting in errors, with the environment variable it can also be
+/// set with when there is one
+fn source(key: &str, env: &str) -> String {
+    if env.is_empty() {
+        key.to_string()
+    } else {
+        format!("{} ({})", key, env)
+    }
+}
+
+/// Records a problem for every missing or malformed value, handing back a
+/// placeholder so validation can carry on
+struct Validator<'a> {
+    errors: &'a mut Vec<String>,
+}
+
+impl Validator<'_> {
+    fn required(&mut self, value: Option<String>, key: &str, env: &str) -> String {
+        match value.filter(|value| !value.trim().is_empty()) {
+            Some(value) => value,
+            None => {
+                self.errors
+                    .push(format!("{} is required", source(key, env)));
+                String::new()
+            }
+        }
+    }
+
+    fn url(&mut self, value: Option<String>, key: &str, env: &str, schemes: &[&str]) -> String {
+        let value = self.required(value, key, env);
+        if value.is_empty() {
+            return value;
+        }
+        match reqwest::Url::parse(&value) {
+            Ok(url) if schemes.contains(&url.scheme()) => {}
+            Ok(url) => self.errors.push(format!(
+                "{} must be a {} URL, got a {} URL",
+                source(key, env),
+                schemes.join("/"),
+                url.scheme()
+            )),
+            Err(e) => self
+                .errors
+                .push(format!("{} is not a valid URL: {}", source(key, env), e)),
+        }
+        value
+    }
+
+    fn private_key(&mut self, value: Option<String>, key: &str, env: &str) -> String {
+        let value = self.required(value, key, env);
+        if !value.is_empty() && FixedBytes::<32>::from_hex(&value).is_err() {
+            self.errors.push(format!(
+                "{} must be a 32 byte hex private key",
+                source(key, env)
+            ));
+        }
+        value
+    }
+
+    fn signer(&mut self, signer: Option<SignerFile>, key: &str, env: &str) -> SignerConfig {
+        let Some(signer) = signer else {
+            self.errors
+                .push(format!("{} is required", source(key, env)));
+            return SignerConfig::Key { key: String::new() };
+        };
+        let field = |name: &str| format!("{}.{}", key, name);
+
+        match signer.kind.as_deref() {
+            Some("key") | None => SignerConfig::Key {
+                key: self.private_key(signer.key, &field("key"), env),
+            },
+            Some("keystore") => SignerConfig::Keystore {
+                path: self.required(signer.path, &field("path"), ""),
+                passphrase_file: self.required(
+                    signer.passphrase_file,
+                    &field("passphrase_file"),
+                    "",
+                ),
+            },
+            Some("remote") => {
+                let address = self.required(signer.address, &field("address"), "");
+                if !address.is_empty() && address.parse::<Address>().is_err() {
+                    self.errors
+                        .push(format!("{} is not a valid address", field("address")));
+                }
+                SignerConfig::Remote {
+                    url: self.url(signer.url, &field("url"), "", &["http", "https"]),
+                    address,
+                    auth_token: signer.auth_token,
+                }
+            }
+            Some(kind) => {
+                self.errors.push(format!(
+                    "{} must be `key`, `keystore` or `remote`, got {:?}",
+                    field("kind"),
+                    kind
+                ));
+                SignerConfig::Key { key: String::new() }
+            }
+        }
+    }
+
+    fn storage(&mut self, storage: StorageFile) -> Option<StorageConfig> {
+        let storage = match storage.backend.as_deref() {
+            Some("imgix") => StorageConfig::Imgix {
+                api_key: self.required(storage.api_key, "storage.api_key", "IMGIX_KEY"),
+                source_id: storage
+                    .source_id
+                    .unwrap_or("66d6dfc6847423eb9bbc7d49".to_string()),
+                public_url: self.url(
+                    Some(
+                        storage
+                            .public_url
+                            .unwrap_or("https://garden-finance.imgix.net".to_string()),
+                    ),
+                    "storage.public_url",
+                    "IMGIX_PUBLIC_URL",
+                    &["http", "https"],
+                ),
+            },
+            Some("s3") => StorageConfig::S3 {
+                endpoint: self.url(
+                    storage.endpoint,
+                    "storage.endpoint",
+                    "S3_ENDPOINT",
+                    &["http", "https"],
+                ),
+                bucket: self.required(storage.bucket, "storage.bucket", "S3_BUCKET"),
+                region: storage.region.unwrap_or("us-east-1".to_string()),
+                access_key: self.required(
+                    storage.access_key,
+                    "storage.access_key",
+                    "S3_ACCESS_KEY",
+                ),
+                secret_key: self.required(
+                    storage.secret_key,
+                    "storage.secret_key",
+                    "S3_SECRET_KEY",
+                ),
+                public_url: self.url(
+                    storage.public_url,
+                    "storage.public_url",
+                    "S3_PUBLIC_URL",
+                    &["http", "https"],
+                ),
+            },
+            Some("local") => StorageConfig::Local {
+                dir: storage.dir.unwrap_or("./assets".to_string()),
+                public_url: self.url(
+                    Some(
+                        storage
+                            .public_url
+                            .unwrap_or("http://localhost:6969".to_string()),
+                    ),
+                    "storage.public_url",
+                    "PUBLIC_URL",
+                    &["http", "https"],
+                ),
+            },
+            backend => {
+                self.errors.push(format!(
+                    "storage.backend (STORAGE_BACKEND) must be `imgix`, `s3` or `local`, got {:?}",
+                    backend.unwrap_or_default()
+                ));
+                return None;
+            }
+        };
+        Some(storage)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use super::*;
+
+    const KEY: &str = "0x59c6995e998f97a5a0044966f0945389dc9e86dae88c7a8412f4603b6b78690d";
+
+    fn parse(file: Option<&str>, env: &[(&str, &str)]) -> Result<Config, ConfigErrors> {
+        let env: HashMap<String, String> = env
+            .iter()
+            .map(|(name, value)| (name.to_string(), value.to_string()))
+            .collect();
+        Config::parse(file, |name| env.get(name).cloned(), Vec::new())
+    }
+
+    #[test]
+    fn test_config_file() {
+        let file = r#"
+            [server]
+            port = 8080
+
+            [db]
+            url = "postgres://faucet@localhost/faucet"
+
+            [chain]
+            rpc_url = "https://testnet-rpc.monad.xyz"
+
+            [signers.faucet]
+            kind = "keystore"
+            path = "/run/secrets/faucet.json"
+            passphrase_file = "/run/secrets/faucet.pass"
+
+            [signers.deployer]
+            kind = "remote"
+            url = "http://localhost:9000/sign"
+            address = "0x000000000000000000000000000000000000dEaD"
+
+            [auth]
+            jwt_secret = "secret"
+            admin_user_ids = ["1"]
+
+            [storage]
+            backend = "local"
+            dir = "/var/lib/faucet"
+
+            [[alerts.sinks]]
+            kind = "slack"
+            url = "https://hooks.slack.com/services/x"
+            level = "error"
+
+            [cors]
+            allowed_origins = ["https://faucet.example"]
+            "#;
+
+        let config = parse(Some(file), &[]).unwrap();
+        assert_eq!(config.server.port, 8080);
+        assert_eq!(config.server.host, "0.0.0.0");
+        assert_eq!(config.auth.admin_user_ids, ["1"]);
+        assert!(matches!(
+            config.signers.faucet,
+            SignerConfig::Keystore { ref path, .. } if path == "/run/secrets/faucet.json"
+        ));
+        assert!(matches!(
+            config.signers.deployer,
+            SignerConfig::Remote {
+                auth_token: None,
+                ..
+            }
+        ));
+        assert!(matches!(
+            config.storage,
+            Some(StorageConfig::Local { ref dir, .. }) if dir == "/var/lib/faucet"
+        ));
+        assert_eq!(config.alerts.sinks[0].format, AlertFormat::Slack);
+        assert_eq!(config.alerts.sinks[0].level, tracing::Level::ERROR);
+        assert_eq!(config.cors.allowed_origins, ["https://faucet.example"]);
+        // sections that were left out disable their feature
+        assert!(config.auth.github.is_none());
+        assert!(config.logging.otlp.is_none());
+        assert!(config.policies.captcha_secret_key.is_none());
+        assert!(!config.policies.captcha_disabled);
+        assert!(config.policies.orderbook_url.is_none());
+
+        // the environment wins over the file
+        let config = parse(
+            Some(file),
+            &[
+                ("PORT", "9090"),
+                ("PRIVATE_KEY", KEY),
+                ("STORAGE_BACKEND", "s3"),
+                ("S3_ENDPOINT", "http://localhost:9000"),
+                ("S3_BUCKET", "logos"),
+                ("S3_ACCESS_KEY", "minio"),
+                ("S3_SECRET_KEY", "minio123"),
+                ("S3_PUBLIC_URL", "http://localhost:9000/logos"),
+                ("DISCORD_WEBHOOK", "https://discord.com/api/webhooks/x"),
+            ],
+        )
+        .unwrap();
+        assert_eq!(config.server.port, 9090);
+        assert_eq!(
+            config.signers.faucet,
+            SignerConfig::Key {
+                key: KEY.to_string()
+            }
+        );
+        assert!(matches!(
+            config.storage,
+            Some(StorageConfig::S3 { ref region, .. }) if region == "us-east-1"
+        ));
+        assert_eq!(config.alerts.sinks.len(), 2);
+        assert_eq!(config.alerts.sinks[1].format, AlertFormat::Discord);
+    }
+
+    #[test]
+    fn test_env_only() {
+        let config = parse(
+            None,
+            &[
+                ("DATABASE_URL", "postgres://localhost/faucet"),
+                ("RPC_URL", "https://testnet-rpc.monad.xyz"),
+                ("PRIVATE_KEY", KEY),
+                ("DEPLOY_KEY", &KEY[2..]),
+                ("JWT_SECRET_KEY", "secret"),
+                ("ADMIN_USER_IDS", "1, 2,"),
+                ("IMGIX_KEY", "imgix"),
+                ("GITHUB_CLIENT_ID", "id"),
+                ("GITHUB_CLIENT_SECRET", "secret"),
+                ("GITHUB_REDIRECT_URI", "https://faucet.wtf/auth"),
+                ("GITHUB_API_URL", "http://localhost:8080/api/v3"),
+                ("LOG_FORMAT", "json"),
+                ("CAPTCHA_DISABLED", "true"),
+            ],
+        )
+        .unwrap();
+
+        assert_eq!(config.auth.admin_user_ids, ["1", "2"]);
+        assert_eq!(config.auth.jwt_keys[0].id, "default");
+        assert_eq!(config.auth.access_token_ttl_secs, 900);
+        assert!(matches!(config.storage, Some(StorageConfig::Imgix { .. })));
+        let github = config.auth.github.unwrap();
+        assert_eq!(github.token_url, constants::github::TOKEN_URL);
+        assert_eq!(github.api_url, "http://localhost:8080/api/v3");
+        assert_eq!(config.logging.format, LogFormat::Json);
+        assert_eq!(
+            config.cors.allowed_origins,
+            ["https://faucet.wtf", "http://localhost:5173"]
+        );
+        assert!(config.policies.captcha_disabled);
+    }
+
+    #[test]
+    fn test_reports_every_error() {
+        let file = r#"
+            [db]
+            url = "mysql://localhost/faucet"
+
+            [signers.faucet]
+            key = "0x1234"
+
+            [storage]
+            backend = "s3"
+            bucket = "logos"
+
+            [[alerts.sinks]]
+            kind = "json"
+            url = "http://alerts.example"
+
+            [cors]
+            allowed_origins = ["https://faucet.wtf/"]
+        "#;
+
+        let errors = parse(
+            Some(file),
+            &[
+                ("PORT", "http"),
+                ("GITHUB_CLIENT_ID", "id"),
+                ("CAPTCHA_DISABLED", "yes"),
+            ],
+        )
+        .unwrap_err()
+        .0;
+
+        for expected in [
+            "PORT must be a port number",
+            "CAPTCHA_DISABLED must be true or false",
+            "db.url (DATABASE_URL) must be a postgres/postgresql URL",
+            "chain.rpc_url (RPC_URL) is required",
+            "signers.faucet.key (PRIVATE_KEY) must be a 32 byte hex private key",
+            "signers.deployer (DEPLOY_KEY) is required",
+            "auth.jwt_secret (JWT_SECRET_KEY) is required",
+            "auth.github.client_secret (GITHUB_CLIENT_SECRET) is required",
+            "auth.github.redirect_uri (GITHUB_REDIRECT_URI) is required",
+            "storage.endpoint (S3_ENDPOINT) is required",
+            "storage.access_key (S3_ACCESS_KEY) is required",
+            "alerts.sinks[0].url",
+            "cors.allowed_origins",
+        ] {
+            assert!(
+                errors.iter().any(|error| error.starts_with(expected)),
+                "missing `{}` in {:?}",
+                expected,
+                errors
+            );
+        }
+        assert_eq!(errors.len(), 15);
+    }
+
+    #[test]
+    fn test_jwt_keys() {
+        let env = [
+            ("DATABASE_URL", "postgres://localhost/faucet"),
+            ("RPC_URL", "https://testnet-rpc.monad.xyz"),
+            ("PRIVATE_KEY", KEY),
+            ("DEPLOY_KEY", KEY),
+        ];
+        let file = r#"
+            [auth]
+            jwt_secret = "old"
+            jwt_issuer = "faucet.example"
+
+            [[auth.jwt_keys]]
+            id = "2026-10"
+            secret = "new"
+        "#;
+
+        // the first key signs, `jwt_secret` is only accepted after the others
+        let config = parse(Some(file), &env).unwrap();
+        let ids: Vec<&str> = config
+            .auth
+            .jwt_keys
+            .iter()
+            .map(|key| key.id.as_str())
+            .collect();
+        assert_eq!(ids, ["2026-10", "default"]);
+        assert_eq!(config.auth.jwt_issuer, "faucet.example");
+        assert_eq!(config.auth.jwt_audience, "monad-faucet");
+        assert_eq!(config.auth.refresh_token_ttl_days, 30);
+
+        let mut with_keys = env.to_vec();
+        with_keys.push(("JWT_KEYS", "b=second, a=first=="));
+        let config = parse(Some(file), &with_keys).unwrap();
+        assert_eq!(
+            config.auth.jwt_keys[0],
+            JwtKey {
+                id: "b".to_string(),
+                secret: "second".to_string()
+            }
+        );
+        assert_eq!(config.auth.jwt_keys[1].secret, "first==");
+        assert_eq!(config.auth.jwt_keys.len(), 3);
+
+        let errors = parse(
+            Some(
+                r#"
+                [auth]
+                jwt_keys = [{ id = "a", secret = "x" }, { id = "a", secret = "y" }]
+                access_token_ttl_secs = 0
+                "#,
+            ),
+            &env,
+        )
+        .unwrap_err()
+        .0;
+        assert_eq!(errors.len(), 2);
+    }
+
+    #[test]
+    fn test_tuning() {
+        let env = [
+            ("DATABASE_URL", "postgres://localhost/faucet"),
+            ("RPC_URL", "https://testnet-rpc.monad.xyz"),
+            ("PRIVATE_KEY", KEY),
+            ("DEPLOY_KEY", KEY),
+            ("JWT_SECRET_KEY", "secret"),
+        ];
+
+        let config = parse(None, &env).unwrap();
+        assert_eq!(config.tuning, TuningConfig::default());
+
+        let config = parse(
+            Some("[tuning]\nmagnification_github_auth = 20\nlow_balance_drips = 5\n"),
+            &env,
+        )
+        .unwrap();
+        assert_eq!(config.tuning.magnification_github_auth, 20);
+        assert_eq!(config.tuning.low_balance_drips, 5);
+        assert_eq!(config.tuning.max_batch_tokens, 5);
+
+        let errors = parse(
+            Some("[tuning]\nmagnification_no_auth = 0\nwithdraw_limit_denominator = 0.0\n"),
+            &env,
+        )
+        .unwrap_err()
+        .0;
+        assert_eq!(errors.len(), 2);
+
+        let errors = parse(
+            Some("[tuning]\nhourly_outflow_cap_drips = 10\ndaily_outflow_cap_drips = 5\n"),
+            &env,
+        )
+        .unwrap_err()
+        .0;
+        assert_eq!(errors.len(), 1);
+
+        // 0 disables either cap
+        let disabled = parse(
+            Some("[tuning]\nhourly_outflow_cap_drips = 10\ndaily_outflow_cap_drips = 0\n"),
+            &env,
+        )
+        .unwrap();
+        assert_eq!(disabled.tuning.daily_outflow_cap_drips, 0);
+
+        // jobs holding a snapshot keep their values across a swap
+        let tuning = Tuning::new(TuningConfig::default());
+        let snapshot = tuning.snapshot();
+        tuning.0.store(Arc::new(config.tuning.clone()));
+        assert_eq!(tuning.load().magnification_github_auth, 20);
+        assert_eq!(snapshot.load().magnification_github_auth, 10);
+    }
+
+    #[test]
+    fn test_unknown_fields() {
+        let errors = parse(Some("[server]\nprot = 80\n"), &[]).unwrap_err().0;
+        assert!(errors[0].starts_with("Invalid config file"));
+    }
+
+    #[test]
+    fn test_alert_sinks() {
+        let sinks: Vec<AlertSinkConfig> = serde_json::from_str(
+            r#"[
+                {"kind": "slack", "url": "https://hooks.slack.com/services/x", "level": "error"},
+                {"kind": "telegram", "chat_id": "-100", "url": "https://api.telegram.org/botx/sendMessage", "targets": ["monad_faucet::executor"]},
+                {"kind": "json", "url": "http://localhost:8080/alerts", "level": "INFO"}
+            ]"#,
+        )
+        .unwrap();
+
+        assert_eq!(sinks[0].format, AlertFormat::Slack);
+        assert_eq!(sinks[0].level, tracing::Level::ERROR);
+        assert_eq!(
+            sinks[1].format,
+            AlertFormat::Telegram {
+                chat_id: "-100".to_string()
+            }
+        );
+        assert_eq!(sinks[1].level, tracing::Level::WARN);
+        assert_eq!(sinks[1].targets, ["monad_faucet::executor"]);
+        assert_eq!(sinks[2].level, tracing::Level::INFO);
+
+        assert!(serde_json::from_str::<AlertSinkConfig>(
+            r#"{"kind": "json", "url": "http://localhost", "level": "loud"}"#
+        )
+        .is_err());
     }
 }
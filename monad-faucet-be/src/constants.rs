@@ -1,6 +1,68 @@
 pub mod faucet {
-    pub const MAGNIFICATION_GITHUB_AUTH: u8 = 10; // Either GitHub authenticated or Garden user
-    pub const MAGNIFICATION_GARDEN_USER: u8 = 10;
-    pub const MAGNIFICATION_NO_AUTH: u8 = 1; // Neither authenticated nor Garden user
-    pub const WITHDRAW_LIMIT_DENOMINATOR: f64 = 1_000_000_000.0;
+    // drip sizes and limits are runtime tunable, see `config::TuningConfig`
+    pub const SUBSCRIPTION_CHECK_INTERVAL_SECS: u64 = 300;
+    pub const RECEIPT_POLL_INTERVAL_SECS: u64 = 2;
+    pub const RECEIPT_TIMEOUT_SECS: u64 = 120;
+    /// Multicall3 is deployed at this address on every chain that has it
+    pub const MULTICALL3_ADDRESS: &str = "0xcA11bde05977b3631167028862bE2a173976CA11";
+    pub const MAX_TOKEN_DECIMALS: u8 = 18; // Of deployed tokens
+}
+
+pub mod logo {
+    pub const MAX_BYTES: usize = 512 * 1024;
+    pub const MAX_DIMENSION: u32 = 1024; // Width and height, in pixels
+    pub const MAX_SVG_NODES: usize = 5_000;
+    pub const FULL_SIZE: u32 = 512; // Longest side of the stored full size image
+    pub const THUMBNAIL_SIZE: u32 = 256;
+}
+
+pub mod events {
+    pub const EVENT_BUS_CAPACITY: usize = 1024;
+    pub const REDACTED_PREFIX_LEN: usize = 6; // "0x" and 4 hex characters
+}
+
+pub mod webhooks {
+    pub const MAX_WEBHOOKS_PER_TOKEN: usize = 5;
+    pub const MAX_WEBHOOKS_PER_USER: usize = 5; // Webhooks without a token
+    pub const POLL_INTERVAL_SECS: u64 = 5;
+    pub const DELIVERY_BATCH_SIZE: i64 = 50;
+    pub const CLAIM_LEASE_SECS: i64 = 15 * 60; // Longer than a batch takes to send
+    pub const REQUEST_TIMEOUT_SECS: u64 = 10;
+    pub const MAX_ATTEMPTS: i32 = 8; // Roughly a day and a half of retries
+    pub const BACKOFF_BASE_SECS: u64 = 30;
+    pub const MAX_BACKOFF_SECS: u64 = 6 * 60 * 60;
+}
+
+pub mod idempotency {
+    pub const MAX_KEY_LEN: usize = 255;
+    pub const KEY_TTL_HOURS: i64 = 24; // Older keys can be used again
+    pub const IN_FLIGHT_LEASE_SECS: i64 = 30 * 60; // Longer than a withdraw waits in the queue
+}
+
+pub mod alerts {
+    pub const QUEUE_CAPACITY: usize = 256; // Oldest events are dropped past this
+    pub const BATCH_WINDOW_SECS: u64 = 2; // Events within the window share a message
+    pub const MAX_MESSAGES_PER_MINUTE: usize = 20; // Per sink, Discord allows 30 per webhook
+    pub const MAX_EMBEDS_PER_MESSAGE: usize = 10; // Discord limit
+    pub const MAX_SLACK_BLOCKS_PER_MESSAGE: usize = 50; // Slack limit
+    pub const DISCORD_DESCRIPTION_LIMIT: usize = 4096; // Characters
+    pub const SLACK_SECTION_LIMIT: usize = 2990; // 3000 with the code fences
+    pub const TELEGRAM_TEXT_LIMIT: usize = 4096; // Characters
+    pub const REQUEST_TIMEOUT_SECS: u64 = 5;
+    pub const MAX_RETRIES: u32 = 3; // On 429 responses
+}
+
+pub mod api {
+    pub const LEGACY_DEPRECATION: &str = "@1792281600"; // 2026-10-18, unversioned routes
+    pub const LEGACY_SUNSET: &str = "Fri, 30 Apr 2027 00:00:00 GMT"; // Unversioned routes go away
+    pub const MAX_REQUEST_ID_LEN: usize = 64;
+}
+
+pub mod github {
+    pub const AUTHORIZE_URL: &str = "https://github.com/login/oauth/authorize";
+    pub const TOKEN_URL: &str = "https://github.com/login/oauth/access_token";
+    pub const API_URL: &str = "https://api.github.com";
+    pub const SCOPE: &str = "read:user user:email";
+    pub const LOGIN_STATE_TTL_SECS: i64 = 600; // Time to finish signing in on GitHub
+    pub const REQUEST_TIMEOUT_SECS: u64 = 10;
 }
@@ -0,0 +1,199 @@
+use serde::Serialize;
+use tokio::sync::broadcast;
+
+use crate::constants::events;
+use crate::store::{DeployJob, DripKind};
+
+/// Something that happened in the faucet, published on the [`EventBus`]
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "type", rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum FaucetEvent {
+    DripQueued {
+        token_address: String,
+        to: String,
+    },
+    DripSent {
+        token_address: String,
+        to: String,
+        amount: String,
+        tx_hash: String,
+        kind: DripKind,
+    },
+    DripConfirmed {
+        token_address: String,
+        to: String,
+        tx_hash: String,
+        block_number: u64,
+        success: bool,
+    },
+    TokenDeployed {
+        token_address: String,
+        symbol: String,
+        name: String,
+    },
+    LowBalance {
+        token_address: String,
+        balance: String,
+        threshold: String,
+    },
+    /// Drips of the token are refused until an admin resumes it
+    TokenPaused {
+        token_address: String,
+        reason: String,
+    },
+    TokenResumed {
+        token_address: String,
+    },
+    /// Only streamed to followers of the job, see `deploy_job_events`
+    DeployProgress(DeployJob),
+}
+
+impl FaucetEvent {
+    /// The token the event is about, used to filter feeds
+    pub fn token_address(&self) -> Option<&str> {
+        match self {
+            FaucetEvent::DripQueued { token_address, .. }
+            | FaucetEvent::DripSent { token_address, .. }
+            | FaucetEvent::DripConfirmed { token_address, .. }
+            | FaucetEvent::TokenDeployed { token_address, .. }
+            | FaucetEvent::LowBalance { token_address, .. }
+            | FaucetEvent::TokenPaused { token_address, .. }
+            | FaucetEvent::TokenResumed { token_address } => Some(token_address),
+            FaucetEvent::DeployProgress(job) => job.contract_address.as_deref(),
+        }
+    }
+
+    /// Name of the event, as found in the `type` field of its JSON
+    pub fn event_type(&self) -> &'static str {
+        match self {
+            FaucetEvent::DripQueued { .. } => "DRIP_QUEUED",
+            FaucetEvent::DripSent { .. } => "DRIP_SENT",
+            FaucetEvent::DripConfirmed { .. } => "DRIP_CONFIRMED",
+            FaucetEvent::TokenDeployed { .. } => "TOKEN_DEPLOYED",
+            FaucetEvent::LowBalance { .. } => "LOW_BALANCE",
+            FaucetEvent::TokenPaused { .. } => "TOKEN_PAUSED",
+            FaucetEvent::TokenResumed { .. } => "TOKEN_RESUMED",
+            FaucetEvent::DeployProgress(_) => "DEPLOY_PROGRESS",
+        }
+    }
+
+    /// Whether the event belongs on the public activity feed
+    pub fn is_public(&self) -> bool {
+        !matches!(self, FaucetEvent::DeployProgress(_))
+    }
+
+    /// Copy of the event that is safe to show to anyone, with recipient
+    /// addresses and transaction hashes cut down to a short prefix. A full
+    /// hash would give the recipient away in any block explorer.
+    pub fn redacted(&self) -> FaucetEvent {
+        let mut event = self.clone();
+        match &mut event {
+            FaucetEvent::DripQueued { to, .. } => *to = redact(to),
+            FaucetEvent::DripSent { to, tx_hash, .. }
+            | FaucetEvent::DripConfirmed { to, tx_hash, .. } => {
+                *to = redact(to);
+                *tx_hash = redact(tx_hash);
+            }
+            _ => {}
+        }
+        event
+    }
+}
+
+/// Cuts an address or hash down to its first `REDACTED_PREFIX_LEN` characters
+pub fn redact(value: &str) -> String {
+    match value.get(..events::REDACTED_PREFIX_LEN) {
+        Some(prefix) if value.len() > events::REDACTED_PREFIX_LEN => format!("{}…", prefix),
+        _ => value.to_string(),
+    }
+}
+
+/// Fan-out of [`FaucetEvent`]s to every live subscriber. Subscribers that fall
+/// more than `EVENT_BUS_CAPACITY` events behind skip the ones they missed.
+#[derive(Debug, Clone)]
+pub struct EventBus {
+    sender: broadcast::Sender<FaucetEvent>,
+}
+
+impl Default for EventBus {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl EventBus {
+    pub fn new() -> Self {
+        let (sender, _) = broadcast::channel(events::EVENT_BUS_CAPACITY);
+        Self { sender }
+    }
+
+    pub fn publish(&self, event: FaucetEvent) {
+        // an error only means nobody is listening right now
+        let _ = self.sender.send(event);
+    }
+
+    pub fn subscribe(&self) -> broadcast::Receiver<FaucetEvent> {
+        self.sender.subscribe()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_redacted() {
+        let to = "0x1234567890abcdef1234567890abcdef12345678";
+        let tx_hash = "0xabcdef0123456789abcdef0123456789abcdef0123456789abcdef0123456789";
+        let event = FaucetEvent::DripSent {
+            token_address: "0xtoken".to_string(),
+            to: to.to_string(),
+            amount: "1".to_string(),
+            tx_hash: tx_hash.to_string(),
+            kind: DripKind::CLAIM,
+        };
+
+        let json = serde_json::to_value(event.redacted()).unwrap();
+        assert_eq!(json["type"], "DRIP_SENT");
+        assert_eq!(event.event_type(), "DRIP_SENT");
+        assert_eq!(json["to"], "0x1234…");
+        assert_eq!(json["tx_hash"], "0xabcd…");
+        assert_eq!(json["token_address"], "0xtoken");
+        assert_eq!(redact("0x12"), "0x12");
+
+        // neither the recipient nor the transaction can be looked up from
+        // the public payload
+        let confirmed = FaucetEvent::DripConfirmed {
+            token_address: "0xtoken".to_string(),
+            to: to.to_string(),
+            tx_hash: tx_hash.to_string(),
+            block_number: 1,
+            success: true,
+        };
+        for event in [event, confirmed] {
+            let text = serde_json::to_string(&event.redacted()).unwrap();
+            assert!(!text.contains(tx_hash), "{}", text);
+            assert!(!text.contains(to), "{}", text);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_event_bus() {
+        let bus = EventBus::new();
+        // publishing without subscribers is not an error
+        bus.publish(FaucetEvent::DripQueued {
+            token_address: "0xtoken".to_string(),
+            to: "0xto".to_string(),
+        });
+
+        let mut receiver = bus.subscribe();
+        bus.publish(FaucetEvent::TokenDeployed {
+            token_address: "0xtoken".to_string(),
+            symbol: "TKN".to_string(),
+            name: "Token".to_string(),
+        });
+        let event = receiver.recv().await.unwrap();
+        assert_eq!(event.token_address(), Some("0xtoken"));
+        assert!(event.is_public());
+    }
+}
@@ -1,40 +1,95 @@
 use std::{
-    collections::VecDeque,
+    collections::{HashMap, HashSet, VecDeque},
     sync::{Arc, Mutex},
     time::Duration,
 };
 
-use alloy::{hex::FromHex, primitives::FixedBytes, signers::local::PrivateKeySigner};
+use alloy::primitives::{Address, U256};
 use chrono::{DateTime, Utc};
 use ipnetwork::IpNetwork;
 use serde::{Deserialize, Serialize};
 use tokio::sync::oneshot;
+use tokio_util::sync::CancellationToken;
+use tracing::{error, info_span, warn, Instrument};
+use utoipa::ToSchema;
 
 use crate::{
-    config::Config,
-    faucet::{self, DripResponse},
+    config::{Config, Tuning, TuningConfig},
+    constants,
+    events::{EventBus, FaucetEvent},
+    faucet::{self, DripResponse, FaucetError},
     handlers::response::ResponseStatus,
-    store::{FieldType, PgStore, Store, TokenType},
+    logo::ProcessedLogo,
+    object_store::{self, ObjectStore},
+    signer::Signers,
+    store::{
+        DeployJob, DeployStep, DripKind, Logo, PgStore, Store, Token, TokenType, WalletSubscription,
+    },
+    telemetry::QueueTrace,
+    webhooks::WebhookDispatcher,
 };
 
-#[derive(Debug, serde::Deserialize, Clone)]
+#[derive(Debug, serde::Deserialize, Clone, ToSchema)]
 pub struct TokenTransferRequest {
     pub token_address: String,
     pub to: String,
     pub token_type: TokenType,
+    /// Ignored, the faucet decides the magnification
     #[serde(skip_serializing_if = "Option::is_none")]
     pub magnification: Option<u8>,
+    #[schema(value_type = String, example = "203.0.113.7")]
     pub ip: IpNetwork,
+    /// `x-request-id` of the HTTP request, set by the handler so executor
+    /// logs for the job can be matched with it
+    #[serde(default, skip_deserializing)]
+    #[schema(ignore)]
+    pub request_id: Option<String>,
 }
 
-#[derive(Debug, Deserialize, Serialize, Clone)]
+#[derive(Debug, Deserialize, Serialize, Clone, ToSchema)]
 pub struct ErrorResponse {
     pub message: String,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub next_access: Option<DateTime<Utc>>,
+    /// Stable machine-readable error code, e.g. `INSUFFICIENT_FAUCET_BALANCE`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub code: Option<String>,
+}
+
+impl ErrorResponse {
+    /// Answer to jobs refused or left queued once a shutdown started
+    pub fn shutting_down() -> Self {
+        Self {
+            message: "The faucet is restarting, please try again shortly".to_string(),
+            next_access: None,
+            code: Some("SHUTTING_DOWN".to_string()),
+        }
+    }
+
+    /// Answer to a claim made before `next_access`
+    pub fn cooldown_active(next_access: DateTime<Utc>) -> Self {
+        Self {
+            message: format!(
+                "You have already claimed this token, you can claim again after {}",
+                next_access
+            ),
+            next_access: Some(next_access),
+            code: Some("COOLDOWN_ACTIVE".to_string()),
+        }
+    }
+}
+
+impl From<FaucetError> for ErrorResponse {
+    fn from(e: FaucetError) -> Self {
+        Self {
+            message: e.to_string(),
+            next_access: None,
+            code: Some(e.code().to_string()),
+        }
+    }
 }
 
-#[derive(Debug, serde::Serialize, Clone)]
+#[derive(Debug, serde::Serialize, Clone, ToSchema)]
 pub struct ExecutorResponse {
     pub status: ResponseStatus,
     pub error: Option<ErrorResponse>,
@@ -43,87 +98,453 @@ pub struct ExecutorResponse {
 
 #[derive(Debug, serde::Deserialize, Clone)]
 pub struct TokenDeployRequest {
+    pub job: DeployJob,
     pub name: String,
     pub symbol: String,
     pub total_supply: u128,
     pub decimals: u8,
     pub deployer_address: String,
-    pub file_name: String,
-    pub file_data: Vec<u8>,
+    pub logo: ProcessedLogo,
+    pub ip: IpNetwork,
+    /// `x-request-id` of the HTTP request that queued the deploy
+    #[serde(default)]
+    pub request_id: Option<String>,
+}
+
+/// Claims several tokens for one recipient in a single executor job
+#[derive(Debug, serde::Deserialize, Clone, ToSchema)]
+pub struct BatchTransferRequest {
+    pub to: String,
+    pub token_addresses: Vec<String>,
+    /// Ignored, the faucet decides the magnification
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub magnification: Option<u8>,
+    #[schema(value_type = String, example = "203.0.113.7")]
+    pub ip: IpNetwork,
+    /// `x-request-id` of the HTTP request, set by the handler
+    #[serde(default, skip_deserializing)]
+    #[schema(ignore)]
+    pub request_id: Option<String>,
+}
+
+#[derive(Debug, serde::Serialize, Clone, ToSchema)]
+pub struct BatchTransferResult {
+    pub token_address: String,
+    #[serde(flatten)]
+    pub response: ExecutorResponse,
+}
+
+#[derive(Debug, serde::Deserialize, Clone, ToSchema)]
+pub struct BulkDrip {
+    pub to: String,
+    pub token_address: String,
+    /// Amount in the token's base units
+    pub amount: String,
+}
+
+/// Funds a list of addresses on behalf of an API key, bounded by its quotas
+#[derive(Debug, Clone)]
+pub struct BulkDripRequest {
+    pub api_key_id: String,
+    pub drips: Vec<BulkDrip>,
     pub ip: IpNetwork,
+    /// `x-request-id` of the HTTP request that queued the job
+    pub request_id: Option<String>,
 }
 
+#[derive(Debug, serde::Serialize, Clone, ToSchema)]
+pub struct BulkDripResult {
+    pub to: String,
+    pub token_address: String,
+    #[serde(flatten)]
+    pub response: ExecutorResponse,
+}
+
+/// Why a job was not queued
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EnqueueError {
+    /// A shutdown started, the workers would never pick the job up
+    ShuttingDown,
+    /// A thread panicked while holding the queue
+    Poisoned,
+}
+
+/// An executor queue, as named in `/v1/admin/queues/:queue/...`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum QueueName {
+    Withdraw,
+    WithdrawBatch,
+    BulkDrip,
+    Scheduled,
+    Deploy,
+}
+
+impl QueueName {
+    pub const ALL: [QueueName; 5] = [
+        QueueName::Withdraw,
+        QueueName::WithdrawBatch,
+        QueueName::BulkDrip,
+        QueueName::Scheduled,
+        QueueName::Deploy,
+    ];
+}
+
+#[derive(Debug, Serialize, Clone, ToSchema)]
+pub struct QueueState {
+    pub queue: QueueName,
+    pub paused: bool,
+    /// Jobs waiting to be picked up
+    pub length: usize,
+}
+
+/// A job queue drained by the executor, pairing each request with the
+/// channel its response is sent back on and the trace it was queued from
+pub type JobQueue<T, R> = Arc<Mutex<VecDeque<(T, oneshot::Sender<R>, QueueTrace)>>>;
+
 #[derive(Debug, Clone)]
 pub struct Executor {
-    pub withdraw_queue:
-        Arc<Mutex<VecDeque<(TokenTransferRequest, oneshot::Sender<ExecutorResponse>)>>>,
-    pub deploy_queue: Arc<Mutex<VecDeque<(TokenDeployRequest, oneshot::Sender<ExecutorResponse>)>>>,
+    pub withdraw_queue: JobQueue<TokenTransferRequest, ExecutorResponse>,
+    /// Deploys report progress on `events` rather than through a response channel
+    pub deploy_queue: Arc<Mutex<VecDeque<(TokenDeployRequest, QueueTrace)>>>,
+    pub events: EventBus,
+    /// Tokens a `LowBalance` event was published for and which did not recover since
+    low_balance_tokens: Arc<Mutex<HashSet<String>>>,
+    /// Per token, held from the outflow check of a drip until it was sent so
+    /// that two drips cannot both pass a cap only one of them fits under
+    outflow_locks: Arc<Mutex<HashMap<String, Arc<tokio::sync::Mutex<()>>>>>,
+    pub batch_queue: JobQueue<BatchTransferRequest, Vec<BatchTransferResult>>,
+    pub bulk_queue: JobQueue<BulkDripRequest, Vec<BulkDripResult>>,
+    pub scheduled_queue: JobQueue<(WalletSubscription, TokenType), ExecutorResponse>,
+    /// Queues an admin paused, their jobs wait until they are resumed. Kept
+    /// in memory, every queue runs again after a restart
+    paused_queues: Arc<Mutex<HashSet<QueueName>>>,
+    /// Cancelled once a shutdown starts
+    shutdown: CancellationToken,
+    /// Only set when storage is configured, deploys are rejected otherwise
+    object_store: Option<Arc<dyn ObjectStore>>,
     config: Config,
+    tuning: Tuning,
+    signers: Signers,
     store: PgStore,
 }
 
 impl Executor {
-    pub fn new(store: PgStore) -> Self {
+    pub fn new(store: PgStore, config: Config, tuning: Tuning, signers: Signers) -> Self {
         Self {
             withdraw_queue: Arc::new(Mutex::new(VecDeque::new())),
             deploy_queue: Arc::new(Mutex::new(VecDeque::new())),
-            config: Config::from_env(),
+            events: EventBus::new(),
+            low_balance_tokens: Arc::new(Mutex::new(HashSet::new())),
+            outflow_locks: Arc::new(Mutex::new(HashMap::new())),
+            batch_queue: Arc::new(Mutex::new(VecDeque::new())),
+            bulk_queue: Arc::new(Mutex::new(VecDeque::new())),
+            scheduled_queue: Arc::new(Mutex::new(VecDeque::new())),
+            paused_queues: Arc::new(Mutex::new(HashSet::new())),
+            shutdown: CancellationToken::new(),
+            object_store: config.storage.as_ref().map(object_store::from_config),
+            config,
+            tuning,
+            signers,
             store,
         }
     }
 
+    /// Copy of the executor running a single job, which keeps the tuning it
+    /// started with even if the config is reloaded meanwhile.
+    fn for_job(&self) -> Self {
+        Self {
+            tuning: self.tuning.snapshot(),
+            ..self.clone()
+        }
+    }
+
+    /// Runs the workers until a shutdown completes. Jobs being processed are
+    /// finished, the ones still queued are refused with a `SHUTTING_DOWN` error.
     pub async fn process_queue(&self) {
-        let withdraw_queue = self.withdraw_queue.clone();
-        let deploy_queue = self.deploy_queue.clone();
-        let config1 = self.config.clone();
-        let config2 = self.config.clone();
-        let store1 = self.store.clone();
-        let store2 = self.store.clone();
+        let withdraw_executor = self.clone();
+        let deploy_executor = self.clone();
+        let scheduler_executor = self.clone();
         let withdraw_task = tokio::spawn(async move {
-            let executor = Executor {
-                withdraw_queue,
-                deploy_queue: Arc::new(Mutex::new(VecDeque::new())),
-                config: config1,
-                store: store1,
-            };
-            executor.process_withdraw_queue().await;
+            withdraw_executor.process_withdraw_queue().await;
         });
 
         let deploy_task = tokio::spawn(async move {
-            let executor = Executor {
-                withdraw_queue: Arc::new(Mutex::new(VecDeque::new())),
-                deploy_queue,
-                config: config2,
-                store: store2,
-            };
-            executor.process_deploy_queue().await;
+            deploy_executor.process_deploy_queue().await;
+        });
+
+        let scheduler_task = tokio::spawn(async move {
+            scheduler_executor.process_subscriptions().await;
+        });
+
+        let webhook_dispatcher = WebhookDispatcher::new(self.store.clone(), self.events.clone());
+        let webhook_task = tokio::spawn(async move {
+            webhook_dispatcher.run().await;
         });
 
-        let _ = tokio::try_join!(withdraw_task, deploy_task);
+        let _ = tokio::join!(withdraw_task, deploy_task, scheduler_task);
+        // undelivered webhooks stay in the outbox and go out after the restart
+        webhook_task.abort();
+    }
+
+    /// Starts a shutdown: handlers stop queuing jobs and the workers stop once
+    /// their current job is done.
+    pub fn shutdown(&self) {
+        self.shutdown.cancel();
+    }
+
+    pub fn is_shutting_down(&self) -> bool {
+        self.shutdown.is_cancelled()
+    }
+
+    /// Queues a job unless a shutdown started. The check is made under the
+    /// queue's lock, which the workers take to refuse what is left once they
+    /// stopped, so every queued job gets answered.
+    pub fn enqueue<T>(&self, queue: &Mutex<VecDeque<T>>, job: T) -> Result<(), EnqueueError> {
+        let mut queue = queue.lock().map_err(|e| {
+            error!("Failed to lock queue {}", e);
+            EnqueueError::Poisoned
+        })?;
+        if self.is_shutting_down() {
+            return Err(EnqueueError::ShuttingDown);
+        }
+        queue.push_back(job);
+        Ok(())
+    }
+
+    /// Resolves once a shutdown started
+    pub async fn shutdown_started(&self) {
+        self.shutdown.cancelled().await
+    }
+
+    /// Returns false if the queue was already paused
+    pub fn pause_queue(&self, queue: QueueName) -> bool {
+        self.paused_queues
+            .lock()
+            .expect("Failed to lock paused queues")
+            .insert(queue)
+    }
+
+    /// Returns false if the queue was not paused
+    pub fn resume_queue(&self, queue: QueueName) -> bool {
+        self.paused_queues
+            .lock()
+            .expect("Failed to lock paused queues")
+            .remove(&queue)
+    }
+
+    pub fn queue_states(&self) -> Vec<QueueState> {
+        let paused = self
+            .paused_queues
+            .lock()
+            .expect("Failed to lock paused queues")
+            .clone();
+        QueueName::ALL
+            .into_iter()
+            .map(|queue| QueueState {
+                queue,
+                paused: paused.contains(&queue),
+                length: match queue {
+                    QueueName::Withdraw => queue_length(&self.withdraw_queue),
+                    QueueName::WithdrawBatch => queue_length(&self.batch_queue),
+                    QueueName::BulkDrip => queue_length(&self.bulk_queue),
+                    QueueName::Scheduled => queue_length(&self.scheduled_queue),
+                    QueueName::Deploy => queue_length(&self.deploy_queue),
+                },
+            })
+            .collect()
+    }
+
+    /// Pops the next job of a queue, unless an admin paused it
+    fn next_job<T>(&self, name: QueueName, queue: &Mutex<VecDeque<T>>) -> Option<T> {
+        if self
+            .paused_queues
+            .lock()
+            .expect("Failed to lock paused queues")
+            .contains(&name)
+        {
+            return None;
+        }
+        queue.lock().expect("Failed to lock queue").pop_front()
+    }
+
+    /// Waits between polls of the queues, cut short by a shutdown
+    async fn idle(&self) {
+        tokio::select! {
+            _ = tokio::time::sleep(Duration::from_secs(1)) => {}
+            _ = self.shutdown.cancelled() => {}
+        }
     }
 
     pub async fn process_withdraw_queue(&self) {
-        loop {
-            let task = {
-                let mut queue = self.withdraw_queue.lock().expect("Failed to lock queue");
-                queue.pop_front()
-            };
+        // the queue lives in memory, keys of withdraws lost with the queue of
+        // a stopped instance are dropped once their lease ran out. Keys other
+        // instances hold are left alone.
+        match self.store.release_unfinished_idempotency_keys().await {
+            Ok(0) => {}
+            Ok(count) => warn!(
+                "Released {} idempotency keys of interrupted withdraws",
+                count
+            ),
+            Err(e) => error!("Failed to release interrupted idempotency keys: {}", e),
+        }
+
+        while !self.is_shutting_down() {
+            if let Some((request, responder, trace)) =
+                self.next_job(QueueName::Withdraw, &self.withdraw_queue)
+            {
+                let span = info_span!(
+                    parent: &trace.dequeue(),
+                    "withdraw",
+                    request_id = request.request_id.as_deref()
+                );
+                let response = self
+                    .for_job()
+                    .process_transfer(request)
+                    .instrument(span)
+                    .await;
+
+                respond(responder, response, QueueName::Withdraw);
+            }
+
+            // batches share the withdraw worker so that drips signed by the
+            // same key never race each other for nonces
+            if let Some((request, responder, trace)) =
+                self.next_job(QueueName::WithdrawBatch, &self.batch_queue)
+            {
+                let span = info_span!(
+                    parent: &trace.dequeue(),
+                    "withdraw_batch",
+                    request_id = request.request_id.as_deref()
+                );
+                let response = self
+                    .for_job()
+                    .process_batch_transfer(request)
+                    .instrument(span)
+                    .await;
+
+                respond(responder, response, QueueName::WithdrawBatch);
+            }
+
+            if let Some((request, responder, trace)) =
+                self.next_job(QueueName::BulkDrip, &self.bulk_queue)
+            {
+                let span = info_span!(
+                    parent: &trace.dequeue(),
+                    "bulk_drip",
+                    request_id = request.request_id.as_deref()
+                );
+                let response = self
+                    .for_job()
+                    .process_bulk_drip(request)
+                    .instrument(span)
+                    .await;
+
+                respond(responder, response, QueueName::BulkDrip);
+            }
 
-            if let Some((request, responder)) = task {
-                let response = self.process_transfer(request).await;
+            if let Some(((subscription, token_type), responder, trace)) =
+                self.next_job(QueueName::Scheduled, &self.scheduled_queue)
+            {
+                let span = info_span!(
+                    parent: &trace.dequeue(),
+                    "scheduled_top_up",
+                    subscription_id = %subscription.id
+                );
+                let response = self
+                    .for_job()
+                    .process_scheduled_top_up(subscription, token_type)
+                    .instrument(span)
+                    .await;
 
-                responder
-                    .send(response)
-                    .expect("Failed to send response: receiver dropped");
+                respond(responder, response, QueueName::Scheduled);
             }
 
-            tokio::time::sleep(Duration::from_secs(1)).await;
+            self.idle().await;
+        }
+
+        self.refuse_queued_withdraws();
+    }
+
+    /// Answers every job left in the withdraw worker's queues with a
+    /// `SHUTTING_DOWN` error, including those of paused queues
+    fn refuse_queued_withdraws(&self) {
+        let refused = || ExecutorResponse {
+            status: ResponseStatus::Error,
+            error: Some(ErrorResponse::shutting_down()),
+            data: None,
+        };
+        let mut count = 0;
+
+        let jobs: Vec<_> = self
+            .withdraw_queue
+            .lock()
+            .expect("Failed to lock queue")
+            .drain(..)
+            .collect();
+        for (_, responder, _) in jobs {
+            respond(responder, refused(), QueueName::Withdraw);
+            count += 1;
+        }
+
+        let jobs: Vec<_> = self
+            .batch_queue
+            .lock()
+            .expect("Failed to lock queue")
+            .drain(..)
+            .collect();
+        for (request, responder, _) in jobs {
+            let results = request
+                .token_addresses
+                .into_iter()
+                .map(|token_address| BatchTransferResult {
+                    token_address,
+                    response: refused(),
+                })
+                .collect();
+            respond(responder, results, QueueName::WithdrawBatch);
+            count += 1;
+        }
+
+        let jobs: Vec<_> = self
+            .bulk_queue
+            .lock()
+            .expect("Failed to lock queue")
+            .drain(..)
+            .collect();
+        for (request, responder, _) in jobs {
+            let results = request
+                .drips
+                .into_iter()
+                .map(|drip| BulkDripResult {
+                    to: drip.to,
+                    token_address: drip.token_address,
+                    response: refused(),
+                })
+                .collect();
+            respond(responder, results, QueueName::BulkDrip);
+            count += 1;
+        }
+
+        let jobs: Vec<_> = self
+            .scheduled_queue
+            .lock()
+            .expect("Failed to lock queue")
+            .drain(..)
+            .collect();
+        for (_, responder, _) in jobs {
+            respond(responder, refused(), QueueName::Scheduled);
+            count += 1;
+        }
+
+        if count > 0 {
+            warn!("Refused {} queued withdraw jobs on shutdown", count);
         }
     }
 
     async fn check_eligibility(
         &self,
-        request: &TokenTransferRequest,
+        _request: &TokenTransferRequest,
     ) -> Result<(), ExecutorResponse> {
         // Check if IP has claimed within 24 hours
         // let next_access_by_ip = self
@@ -137,12 +558,7 @@ impl Executor {
         // if next_access_by_ip > Utc::now() {
         //     return Err(ExecutorResponse {
         //         status: ResponseStatus::Error,
-        //         error: Some(ErrorResponse {
-        //             message: format!(
-        //                 "You have already claimed this in the last 24hrs. You can claim again after"
-        //             ),
-        //             next_access: Some(next_access_by_ip),
-        //         }),
+        //         error: Some(ErrorResponse::cooldown_active(next_access_by_ip)),
         //         data: None,
         //     });
         // }
@@ -159,12 +575,7 @@ impl Executor {
         // if next_access_by_wallet > Utc::now() {
         //     return Err(ExecutorResponse {
         //         status: ResponseStatus::Error,
-        //         error: Some(ErrorResponse {
-        //             message: format!(
-        //                 "You have already claimed this in the last 24hrs. You can claim again after"
-        //             ),
-        //             next_access: Some(next_access_by_wallet),
-        //         }),
+        //         error: Some(ErrorResponse::cooldown_active(next_access_by_wallet)),
         //         data: None,
         //     });
         // }
@@ -185,12 +596,48 @@ impl Executor {
                 error: Some(ErrorResponse {
                     message: "Token not found".to_string(),
                     next_access: None,
+                    code: Some("TOKEN_NOT_FOUND".to_string()),
+                }),
+                data: None,
+            })?;
+
+        // drips are sent and charged by the token's stored type, a client
+        // claiming another one would send native MON under an ERC-20's cap
+        if token.token_type != request.token_type {
+            return Err(ExecutorResponse {
+                status: ResponseStatus::Error,
+                error: Some(ErrorResponse {
+                    message: "Token type does not match the token".to_string(),
+                    next_access: None,
+                    code: Some("INVALID_REQUEST".to_string()),
                 }),
                 data: None,
+            });
+        }
+
+        if let Some(target) = token.top_up_target {
+            let target = target.parse::<u128>().map_err(|e| {
+                error!(
+                    "Invalid top up target {} for token {} {}",
+                    target, token.address, e
+                );
+                ExecutorResponse {
+                    status: ResponseStatus::Error,
+                    error: Some(ErrorResponse {
+                        message: "Token top up target is invalid".to_string(),
+                        next_access: None,
+                        code: Some("TOKEN_NOT_CLAIMABLE".to_string()),
+                    }),
+                    data: None,
+                }
             })?;
+            return self
+                .get_top_up_amount(request, &token.token_type, target)
+                .await;
+        }
 
         let mut withdraw_limit = token.withdraw_limit.parse::<u128>().unwrap_or_default();
-        withdraw_limit = withdraw_limit * request.magnification.unwrap_or(1 as u8) as u128;
+        withdraw_limit *= request.magnification.unwrap_or(1) as u128;
 
         if withdraw_limit == 0 {
             return Err(ExecutorResponse {
@@ -198,6 +645,7 @@ impl Executor {
                 error: Some(ErrorResponse {
                     message: "Token withdraw limit is 0".to_string(),
                     next_access: None,
+                    code: Some("TOKEN_NOT_CLAIMABLE".to_string()),
                 }),
                 data: None,
             });
@@ -206,14 +654,54 @@ impl Executor {
         Ok(withdraw_limit)
     }
 
+    /// Computes the amount needed to bring the recipient's balance up to the
+    /// magnified `target`, declining when the wallet already holds `target`.
+    /// The balance is read by the token's stored type, not the client's.
+    async fn get_top_up_amount(
+        &self,
+        request: &TokenTransferRequest,
+        token_type: &TokenType,
+        target: u128,
+    ) -> Result<u128, ExecutorResponse> {
+        let faucet = faucet::Faucet::new(
+            &self.signers.faucet,
+            &self.config.chain.rpc_url,
+            self.store.clone(),
+        );
+
+        let balance = faucet
+            .balance_of(token_type, &request.token_address, &request.to)
+            .await
+            .map_err(|e| ExecutorResponse {
+                status: ResponseStatus::Error,
+                error: Some(e.into()),
+                data: None,
+            })?;
+
+        top_up_amount(balance, target, request.magnification.unwrap_or(1)).ok_or_else(|| {
+            ExecutorResponse {
+                status: ResponseStatus::Error,
+                error: Some(ErrorResponse {
+                    message: format!(
+                        "Your wallet already holds {} which meets the faucet target of {}",
+                        balance, target
+                    ),
+                    next_access: None,
+                    code: Some("ABOVE_TOP_UP_TARGET".to_string()),
+                }),
+                data: None,
+            }
+        })
+    }
+
     async fn execute_transfer(
         &self,
         request: &TokenTransferRequest,
         withdraw_limit: u128,
     ) -> ExecutorResponse {
         let faucet = faucet::Faucet::new(
-            &self.config.private_key,
-            &self.config.rpc_url,
+            &self.signers.faucet,
+            &self.config.chain.rpc_url,
             self.store.clone(),
         );
 
@@ -224,8 +712,9 @@ impl Executor {
                         &request.token_address,
                         &request.to,
                         withdraw_limit,
-                        request.ip,
+                        Some(request.ip),
                         request.magnification.unwrap_or(1),
+                        DripKind::CLAIM,
                     )
                     .await
             }
@@ -234,39 +723,228 @@ impl Executor {
                     .send_native_token(
                         &request.to,
                         withdraw_limit,
-                        request.ip,
+                        Some(request.ip),
                         request.magnification.unwrap_or(1),
+                        DripKind::CLAIM,
                     )
                     .await
             }
         };
 
         match result {
-            Ok(data) => ExecutorResponse {
-                status: ResponseStatus::Success,
-                error: None,
-                data: Some(data),
-            },
+            Ok(data) => {
+                self.publish_drip_sent(&request.token_address, &request.to, &data, DripKind::CLAIM);
+                ExecutorResponse {
+                    status: ResponseStatus::Success,
+                    error: None,
+                    data: Some(data),
+                }
+            }
             Err(e) => ExecutorResponse {
                 status: ResponseStatus::Error,
-                error: Some(ErrorResponse {
-                    message: e.to_string(),
-                    next_access: None,
-                }),
+                error: Some(e.into()),
                 data: None,
             },
         }
     }
 
-    pub async fn process_transfer(&self, request: TokenTransferRequest) -> ExecutorResponse {
-        // Check eligibility
-        if let Err(response) = self.check_eligibility(&request).await {
-            return response;
+    /// Publishes a sent drip and follows it in the background until it is
+    /// mined, then checks whether the faucet is running low on the token.
+    fn publish_drip_sent(
+        &self,
+        token_address: &str,
+        to: &str,
+        drip: &DripResponse,
+        kind: DripKind,
+    ) {
+        self.events.publish(FaucetEvent::DripSent {
+            token_address: token_address.to_string(),
+            to: to.to_string(),
+            amount: drip.amount.clone(),
+            tx_hash: drip.tx_hash.clone(),
+            kind,
+        });
+
+        let executor = self.clone();
+        let token_address = token_address.to_string();
+        let to = to.to_string();
+        let tx_hash = drip.tx_hash.clone();
+        // keeps the request ID of the job on the confirmation logs
+        tokio::spawn(
+            async move {
+                executor.confirm_drip(token_address, to, tx_hash).await;
+            }
+            .in_current_span(),
+        );
+    }
+
+    async fn confirm_drip(&self, token_address: String, to: String, tx_hash: String) {
+        let faucet = faucet::Faucet::new(
+            &self.signers.faucet,
+            &self.config.chain.rpc_url,
+            self.store.clone(),
+        );
+        let Ok((block_number, success)) = faucet.wait_for_receipt(&tx_hash).await else {
+            return;
+        };
+        self.events.publish(FaucetEvent::DripConfirmed {
+            token_address: token_address.clone(),
+            to,
+            tx_hash,
+            block_number,
+            success,
+        });
+
+        let Ok(token) = self.store.get_token_by_address(token_address.clone()).await else {
+            return;
+        };
+        let Ok(balance) = faucet
+            .balance_of(&token.token_type, &token_address, &faucet.address())
+            .await
+        else {
+            return;
+        };
+        let tuning = self.tuning.load();
+        let max_drip = token.withdraw_limit.parse::<u128>().unwrap_or_default()
+            * tuning.magnification_github_auth as u128;
+        let threshold = U256::from(max_drip.saturating_mul(tuning.low_balance_drips as u128));
+
+        let mut low_balance_tokens = self
+            .low_balance_tokens
+            .lock()
+            .expect("Failed to lock low balance tokens");
+        if balance >= threshold {
+            low_balance_tokens.remove(&token_address);
+        } else if low_balance_tokens.insert(token_address.clone()) {
+            warn!("Faucet balance of {} is low: {}", token_address, balance);
+            self.events.publish(FaucetEvent::LowBalance {
+                token_address,
+                balance: balance.to_string(),
+                threshold: threshold.to_string(),
+            });
         }
+    }
 
-        // Validate token and get withdraw limit
-        let withdraw_limit = match self.validate_and_get_withdraw_limit(&request).await {
-            Ok(limit) => limit,
+    /// Circuit breaker in front of every drip: refuses drips of a paused
+    /// token, and pauses the token when sending `amount` would take its
+    /// outflow over the hourly or daily cap. Outflow is summed from the
+    /// stored transfers, so the caps hold across restarts.
+    ///
+    /// The returned permit must be held until the drip was sent and stored.
+    async fn check_outflow(
+        &self,
+        token_address: &str,
+        amount: u128,
+    ) -> Result<OutflowPermit, ExecutorResponse> {
+        let error = |message: &str, code: &str| ExecutorResponse {
+            status: ResponseStatus::Error,
+            error: Some(ErrorResponse {
+                message: message.to_string(),
+                next_access: None,
+                code: Some(code.to_string()),
+            }),
+            data: None,
+        };
+        let paused = || {
+            error(
+                "Faucet paused: drips of this token are on hold, please try again later",
+                "FAUCET_PAUSED",
+            )
+        };
+
+        // pauses and transfers are stored under the checksummed address
+        let Ok(token_address) = token_address.parse::<Address>() else {
+            return Err(error("Token not found", "TOKEN_NOT_FOUND"));
+        };
+        let token_address = token_address.to_checksum(None);
+        let lock = self
+            .outflow_locks
+            .lock()
+            .expect("Failed to lock outflow locks")
+            .entry(token_address.clone())
+            .or_default()
+            .clone();
+        let permit = lock.lock_owned().await;
+
+        let pause = match self.store.get_token_pause(token_address.clone()).await {
+            Ok(pause) => pause,
+            Err(e) => {
+                // fail closed, the breaker cannot tell whether the token is paused
+                error!("Failed to fetch pause state of {} {}", token_address, e);
+                return Err(error("Something went wrong", "INTERNAL_ERROR"));
+            }
+        };
+        if pause.as_ref().is_some_and(|pause| pause.paused) {
+            return Err(paused());
+        }
+
+        let token = match self.store.get_token_by_address(token_address.clone()).await {
+            Ok(token) => token,
+            Err(_) => return Err(error("Token not found", "TOKEN_NOT_FOUND")),
+        };
+        let tuning = self.tuning.load();
+        let resumed_at = pause.and_then(|pause| pause.resumed_at);
+        let now = Utc::now();
+
+        for (window, drips, name) in [
+            (
+                chrono::Duration::hours(1),
+                tuning.hourly_outflow_cap_drips,
+                "hourly",
+            ),
+            (
+                chrono::Duration::days(1),
+                tuning.daily_outflow_cap_drips,
+                "daily",
+            ),
+        ] {
+            let Some(cap) = outflow_cap(&token, drips, &tuning) else {
+                continue;
+            };
+            let since = resumed_at.map_or(now - window, |resumed_at| resumed_at.max(now - window));
+            let outflow = match self
+                .store
+                .get_token_outflow_since(token_address.clone(), since)
+                .await
+            {
+                Ok(outflow) => outflow,
+                Err(e) => {
+                    error!("Failed to fetch outflow of {} {}", token_address, e);
+                    return Err(error("Something went wrong", "INTERNAL_ERROR"));
+                }
+            };
+            if outflow.saturating_add(amount) <= cap {
+                continue;
+            }
+
+            let reason = format!(
+                "{} outflow cap of {} reached, {} sent and {} requested",
+                name, cap, outflow, amount
+            );
+            match self
+                .store
+                .pause_token(token_address.clone(), reason.clone())
+                .await
+            {
+                Ok(true) => {
+                    error!("Paused drips of {}: {}", token_address, reason);
+                    self.events.publish(FaucetEvent::TokenPaused {
+                        token_address: token_address.clone(),
+                        reason,
+                    });
+                }
+                Ok(false) => {}
+                Err(e) => error!("Failed to pause drips of {} {}", token_address, e),
+            }
+            return Err(paused());
+        }
+
+        Ok(permit)
+    }
+
+    pub async fn process_transfer(&self, request: TokenTransferRequest) -> ExecutorResponse {
+        let (withdraw_limit, _permit) = match self.prepare_transfer(&request).await {
+            Ok(prepared) => prepared,
             Err(response) => return response,
         };
 
@@ -274,164 +952,1229 @@ impl Executor {
         self.execute_transfer(&request, withdraw_limit).await
     }
 
-    pub async fn process_deploy_queue(&self) {
-        loop {
-            let task = {
-                let mut queue = self.deploy_queue.lock().expect("Failed to lock queue");
-                queue.pop_front()
-            };
-
-            if let Some((request, responder)) = task {
-                let response = self.process_deploy(request).await;
+    /// Runs every check a claim goes through before it is sent, returning
+    /// the amount to drip and the token's outflow permit
+    async fn prepare_transfer(
+        &self,
+        request: &TokenTransferRequest,
+    ) -> Result<(u128, OutflowPermit), ExecutorResponse> {
+        // Check eligibility
+        self.check_eligibility(request).await?;
 
-                responder
-                    .send(response)
-                    .expect("Failed to send response: receiver dropped");
-            }
+        // Validate token and get withdraw limit
+        let withdraw_limit = self.validate_and_get_withdraw_limit(request).await?;
 
-            tokio::time::sleep(Duration::from_secs(1)).await;
-        }
+        let permit = self
+            .check_outflow(&request.token_address, withdraw_limit)
+            .await?;
+        Ok((withdraw_limit, permit))
     }
 
-    async fn process_deploy(&self, request: TokenDeployRequest) -> ExecutorResponse {
-        if request.file_data.is_empty()
-            || request.file_name.is_empty()
-            || request.name.is_empty()
-            || request.symbol.is_empty()
-            || request.deployer_address.is_empty()
-            || request.total_supply == 0
-            || request.decimals == 0
-        {
-            return ExecutorResponse {
-                status: ResponseStatus::Error,
-                error: Some(ErrorResponse {
-                    message: "Invalid request".to_string(),
-                    next_access: None,
-                }),
-                data: None,
-            };
-        }
-        let token = self
-            .store
-            .get_token_from_symbol(request.symbol.clone())
-            .await;
+    /// Evaluates eligibility and drips every token of a batch for one recipient.
+    ///
+    /// The faucet's balances of every eligible token are read together with
+    /// one Multicall3 call, so tokens it is out of are answered before any of
+    /// the drips is simulated. The transfers themselves are broadcast back to
+    /// back with consecutive nonces: inside a multicall `msg.sender` would be
+    /// the Multicall3 contract, so ERC-20 transfers could not spend the
+    /// faucet's own balance, and any allowance given to Multicall3 could be
+    /// spent by anyone calling it.
+    pub async fn process_batch_transfer(
+        &self,
+        request: BatchTransferRequest,
+    ) -> Vec<BatchTransferResult> {
+        let token_addresses = unique_tokens(request.token_addresses);
+        let mut responses: Vec<Option<ExecutorResponse>> = vec![None; token_addresses.len()];
+        let mut drips = Vec::new();
 
-        if token.is_ok() {
-            return ExecutorResponse {
-                status: ResponseStatus::Error,
-                error: Some(ErrorResponse {
-                    message: "Token with same symbol already exists".to_string(),
-                    next_access: None,
-                }),
-                data: None,
+        for (index, token_address) in token_addresses.iter().enumerate() {
+            let token = match self.store.get_token_by_address(token_address.clone()).await {
+                Ok(token) => token,
+                Err(_) => {
+                    responses[index] = Some(ExecutorResponse {
+                        status: ResponseStatus::Error,
+                        error: Some(ErrorResponse {
+                            message: "Token not found".to_string(),
+                            next_access: None,
+                            code: Some("TOKEN_NOT_FOUND".to_string()),
+                        }),
+                        data: None,
+                    });
+                    continue;
+                }
             };
-        }
-
-        let imgix_url = self
-            .upload_to_imgix(request.file_name.clone(), request.file_data.clone())
-            .await;
-        if imgix_url.is_err() {
-            return ExecutorResponse {
-                status: ResponseStatus::Error,
-                error: Some(ErrorResponse {
-                    message: imgix_url.err().unwrap_or_default(),
-                    next_access: None,
-                }),
-                data: None,
+            let transfer = TokenTransferRequest {
+                token_address: token_address.clone(),
+                to: request.to.clone(),
+                token_type: token.token_type,
+                magnification: request.magnification,
+                ip: request.ip,
+                request_id: request.request_id.clone(),
             };
+            match self.prepare_transfer(&transfer).await {
+                Ok((withdraw_limit, permit)) => {
+                    drips.push((index, transfer, withdraw_limit, permit))
+                }
+                Err(response) => responses[index] = Some(response),
+            }
         }
 
         let faucet = faucet::Faucet::new(
-            &self.config.deploy_key,
-            &self.config.rpc_url,
+            &self.signers.faucet,
+            &self.config.chain.rpc_url,
             self.store.clone(),
         );
-        match faucet
-            .deploy_erc_20(
-                request.name,
-                request.symbol,
-                request.total_supply,
-                request.decimals,
-                imgix_url.unwrap_or_default(),
-                request.deployer_address.clone(),
-            )
-            .await
-        {
-            Ok(result) => {
-                //send 20% to deployer
-                let _ = faucet
-                    .send_erc_20(
-                        &result,
-                        &request.deployer_address,
-                        request.total_supply * 20 / 100 * 10u128.pow(request.decimals as u32),
-                        request.ip,
-                        1,
-                    )
-                    .await;
+        let tokens: Vec<_> = drips
+            .iter()
+            .map(|(_, transfer, _, _)| {
+                (transfer.token_type.clone(), transfer.token_address.clone())
+            })
+            .collect();
+        // each transfer checks its balance again, this only saves simulating
+        // drips that cannot be covered
+        let balances = faucet.own_balances(&tokens).await.unwrap_or_else(|e| {
+            warn!(
+                "Sending the batch without its balances, they could not be read: {}",
+                e
+            );
+            vec![None; tokens.len()]
+        });
 
-                //send 80% to faucet
-                let withdraw_privkey = FixedBytes::from_hex(self.config.private_key.clone())
-                    .expect("Invalid private key");
-                let signer =
-                    PrivateKeySigner::from_bytes(&withdraw_privkey).expect("Invalid private key");
-                let withdraw_address = signer.address().to_string();
-                let faucet_drip =
-                    request.total_supply * 80 / 100 * 10u128.pow(request.decimals as u32);
-                let _ = faucet
-                    .send_erc_20(&result, &withdraw_address, faucet_drip, request.ip, 1)
-                    .await;
-                // on successful deployment, send the contract address in tx_hash
-                // this tx_hash is sent as {contract_address: 0x123} in deploy_erc20.rs response
+        for ((index, transfer, withdraw_limit, _permit), balance) in drips.into_iter().zip(balances)
+        {
+            let response = if balance.is_some_and(|balance| balance < U256::from(withdraw_limit)) {
+                ExecutorResponse {
+                    status: ResponseStatus::Error,
+                    error: Some(FaucetError::InsufficientBalance.into()),
+                    data: None,
+                }
+            } else {
+                self.execute_transfer(&transfer, withdraw_limit).await
+            };
+            responses[index] = Some(response);
+        }
+
+        token_addresses
+            .into_iter()
+            .zip(responses)
+            .filter_map(|(token_address, response)| {
+                Some(BatchTransferResult {
+                    token_address,
+                    response: response?,
+                })
+            })
+            .collect()
+    }
+
+    /// Sends every drip of an API key bulk job, skipping drips that would
+    /// exceed the key's daily drip count or per-token amount quota.
+    pub async fn process_bulk_drip(&self, request: BulkDripRequest) -> Vec<BulkDripResult> {
+        let error = |message: &str, code: &str| ExecutorResponse {
+            status: ResponseStatus::Error,
+            error: Some(ErrorResponse {
+                message: message.to_string(),
+                next_access: None,
+                code: Some(code.to_string()),
+            }),
+            data: None,
+        };
+
+        let quota_state = async {
+            let api_key = self
+                .store
+                .get_api_key_by_id(request.api_key_id.clone())
+                .await?;
+            let quotas = self
+                .store
+                .get_api_key_quotas(request.api_key_id.clone())
+                .await?;
+            let usage = self
+                .store
+                .get_api_key_usage_since(
+                    request.api_key_id.clone(),
+                    Utc::now() - chrono::Duration::days(1),
+                )
+                .await?;
+            Ok::<_, sqlx::Error>((api_key, quotas, usage))
+        }
+        .await;
+
+        let (api_key, quotas, mut usage) = match quota_state {
+            Ok(state) => state,
+            Err(e) => {
+                error!(
+                    "Failed to load quotas for api key {} {}",
+                    request.api_key_id, e
+                );
+                return request
+                    .drips
+                    .into_iter()
+                    .map(|drip| BulkDripResult {
+                        to: drip.to,
+                        token_address: drip.token_address,
+                        response: error("Failed to load API key quotas", "INTERNAL_ERROR"),
+                    })
+                    .collect();
+            }
+        };
+
+        let faucet = faucet::Faucet::new(
+            &self.signers.faucet,
+            &self.config.chain.rpc_url,
+            self.store.clone(),
+        );
+        let mut results = Vec::with_capacity(request.drips.len());
+
+        for drip in request.drips {
+            let response = 'drip: {
+                if api_key.revoked {
+                    break 'drip error("API key has been revoked", "UNAUTHORIZED");
+                }
+                if usage.drips >= api_key.drips_per_day as i64 {
+                    break 'drip error("Daily drip quota exhausted", "QUOTA_EXCEEDED");
+                }
+
+                let amount = match drip.amount.parse::<u128>() {
+                    Ok(amount) if amount > 0 => amount,
+                    _ => break 'drip error("Invalid amount", "INVALID_AMOUNT"),
+                };
+                let Some(quota) = quotas
+                    .iter()
+                    .find(|q| q.token_address.eq_ignore_ascii_case(&drip.token_address))
+                else {
+                    break 'drip error("API key has no quota for this token", "TOKEN_NOT_ALLOWED");
+                };
+                let max_amount = quota.max_amount_per_day.parse::<u128>().unwrap_or_default();
+                let used = usage
+                    .amounts
+                    .get(&drip.token_address)
+                    .copied()
+                    .unwrap_or_default();
+                if used.saturating_add(amount) > max_amount {
+                    break 'drip error(
+                        "Daily amount quota exhausted for this token",
+                        "QUOTA_EXCEEDED",
+                    );
+                }
+
+                let token = match self
+                    .store
+                    .get_token_by_address(drip.token_address.clone())
+                    .await
+                {
+                    Ok(token) => token,
+                    Err(_) => break 'drip error("Token not found", "TOKEN_NOT_FOUND"),
+                };
+                let _permit = match self.check_outflow(&drip.token_address, amount).await {
+                    Ok(permit) => permit,
+                    Err(response) => break 'drip response,
+                };
+
+                let result = match token.token_type {
+                    TokenType::ERC20 => {
+                        faucet
+                            .send_erc_20(
+                                &drip.token_address,
+                                &drip.to,
+                                amount,
+                                Some(request.ip),
+                                1,
+                                DripKind::API_KEY,
+                            )
+                            .await
+                    }
+                    TokenType::NATIVE => {
+                        faucet
+                            .send_native_token(
+                                &drip.to,
+                                amount,
+                                Some(request.ip),
+                                1,
+                                DripKind::API_KEY,
+                            )
+                            .await
+                    }
+                };
+
+                match result {
+                    Ok(data) => {
+                        self.publish_drip_sent(
+                            &drip.token_address,
+                            &drip.to,
+                            &data,
+                            DripKind::API_KEY,
+                        );
+                        usage.drips += 1;
+                        *usage.amounts.entry(drip.token_address.clone()).or_default() += amount;
+                        if let Err(e) = self
+                            .store
+                            .create_api_key_usage(
+                                api_key.id.clone(),
+                                drip.token_address.clone(),
+                                data.tx_hash.clone(),
+                                amount.to_string(),
+                            )
+                            .await
+                        {
+                            error!("Failed to record api key usage {} {}", data.tx_hash, e);
+                        }
+                        ExecutorResponse {
+                            status: ResponseStatus::Success,
+                            error: None,
+                            data: Some(data),
+                        }
+                    }
+                    Err(e) => ExecutorResponse {
+                        status: ResponseStatus::Error,
+                        error: Some(e.into()),
+                        data: None,
+                    },
+                }
+            };
+
+            results.push(BulkDripResult {
+                to: drip.to,
+                token_address: drip.token_address,
+                response,
+            });
+        }
+
+        results
+    }
+
+    /// Periodically checks registered wallets and queues a top-up on the
+    /// withdraw worker for every wallet whose balance dropped below its minimum.
+    pub async fn process_subscriptions(&self) {
+        let interval = constants::faucet::SUBSCRIPTION_CHECK_INTERVAL_SECS;
+
+        loop {
+            tokio::select! {
+                _ = tokio::time::sleep(Duration::from_secs(interval)) => {}
+                _ = self.shutdown.cancelled() => return,
+            }
+
+            let due = match self
+                .store
+                .get_due_subscriptions(Utc::now() - chrono::Duration::seconds(interval as i64))
+                .await
+            {
+                Ok(due) => due,
+                Err(e) => {
+                    error!("Failed to fetch due subscriptions {}", e);
+                    continue;
+                }
+            };
+
+            for subscription in due {
+                self.check_subscription(subscription).await;
+            }
+        }
+    }
+
+    async fn check_subscription(&self, subscription: WalletSubscription) {
+        let token = match self
+            .store
+            .get_token_by_address(subscription.token_address.clone())
+            .await
+        {
+            Ok(token) => token,
+            Err(e) => {
+                error!(
+                    "Token {} of subscription {} not found {}",
+                    subscription.token_address, subscription.id, e
+                );
+                return;
+            }
+        };
+
+        let faucet = faucet::Faucet::new(
+            &self.signers.faucet,
+            &self.config.chain.rpc_url,
+            self.store.clone(),
+        );
+        let balance = match faucet
+            .balance_of(
+                &token.token_type,
+                &subscription.token_address,
+                &subscription.wallet_address,
+            )
+            .await
+        {
+            Ok(balance) => balance,
+            Err(e) => {
+                error!(
+                    "Failed to fetch balance for subscription {} {}",
+                    subscription.id, e
+                );
+                return;
+            }
+        };
+
+        let min_balance = subscription.min_balance.parse::<u128>().unwrap_or_default();
+        let mut refilled = false;
+
+        if balance < U256::from(min_balance) {
+            match self
+                .refill_quota_exhausted(subscription.user_id.clone())
+                .await
+            {
+                Ok(true) => {
+                    warn!(
+                        "User {} exhausted their scheduled refill quota, skipping subscription {}",
+                        subscription.user_id, subscription.id
+                    );
+                }
+                Ok(false) => {
+                    let (tx, rx) = oneshot::channel();
+                    let job = (
+                        (subscription.clone(), token.token_type),
+                        tx,
+                        QueueTrace::new("scheduled"),
+                    );
+                    // left unchecked, so the next run tries the refill again
+                    if let Err(e) = self.enqueue(&self.scheduled_queue, job) {
+                        warn!(
+                            "Scheduled top-up for subscription {} not queued {:?}",
+                            subscription.id, e
+                        );
+                        return;
+                    }
+                    self.events.publish(FaucetEvent::DripQueued {
+                        token_address: subscription.token_address.clone(),
+                        to: subscription.wallet_address.clone(),
+                    });
+                    // the withdraw worker may be gone already once a shutdown started
+                    let response = tokio::select! {
+                        response = rx => response,
+                        _ = self.shutdown.cancelled() => return,
+                    };
+                    match response {
+                        Ok(response) => match response.status {
+                            ResponseStatus::Success => refilled = true,
+                            ResponseStatus::Error => error!(
+                                "Scheduled top-up for subscription {} failed {:?}",
+                                subscription.id, response.error
+                            ),
+                        },
+                        Err(e) => error!(
+                            "Scheduled top-up for subscription {} dropped {}",
+                            subscription.id, e
+                        ),
+                    }
+                }
+                Err(e) => error!("Failed to count scheduled refills {}", e),
+            }
+        }
+
+        if let Err(e) = self
+            .store
+            .mark_subscription_checked(subscription.id.clone(), refilled)
+            .await
+        {
+            error!(
+                "Failed to mark subscription {} checked {}",
+                subscription.id, e
+            );
+        }
+    }
+
+    /// Whether the user already got their daily share of scheduled refills
+    async fn refill_quota_exhausted(&self, user_id: String) -> Result<bool, sqlx::Error> {
+        let since = Utc::now() - chrono::Duration::days(1);
+        let count = self
+            .store
+            .count_scheduled_refills_since(user_id, since)
+            .await?;
+        Ok(count >= self.tuning.load().max_scheduled_refills_per_day)
+    }
+
+    async fn process_scheduled_top_up(
+        &self,
+        subscription: WalletSubscription,
+        token_type: TokenType,
+    ) -> ExecutorResponse {
+        let faucet = faucet::Faucet::new(
+            &self.signers.faucet,
+            &self.config.chain.rpc_url,
+            self.store.clone(),
+        );
+        let amount = subscription
+            .refill_amount
+            .parse::<u128>()
+            .unwrap_or_default();
+
+        let _permit = match self
+            .check_outflow(&subscription.token_address, amount)
+            .await
+        {
+            Ok(permit) => permit,
+            Err(response) => return response,
+        };
+
+        // no client asked for a scheduled top-up, so there is no ip to record
+        let result = match token_type {
+            TokenType::ERC20 => {
+                faucet
+                    .send_erc_20(
+                        &subscription.token_address,
+                        &subscription.wallet_address,
+                        amount,
+                        None,
+                        1,
+                        DripKind::SCHEDULED,
+                    )
+                    .await
+            }
+            TokenType::NATIVE => {
+                faucet
+                    .send_native_token(
+                        &subscription.wallet_address,
+                        amount,
+                        None,
+                        1,
+                        DripKind::SCHEDULED,
+                    )
+                    .await
+            }
+        };
+
+        match result {
+            Ok(data) => {
+                self.publish_drip_sent(
+                    &subscription.token_address,
+                    &subscription.wallet_address,
+                    &data,
+                    DripKind::SCHEDULED,
+                );
                 ExecutorResponse {
                     status: ResponseStatus::Success,
                     error: None,
-                    data: Some(DripResponse {
-                        tx_hash: result,
-                        amount: faucet_drip.to_string(),
-                        magnification: 1,
-                    }),
+                    data: Some(data),
                 }
             }
             Err(e) => ExecutorResponse {
                 status: ResponseStatus::Error,
-                error: Some(ErrorResponse {
-                    message: e.to_string(),
-                    next_access: None,
-                }),
+                error: Some(e.into()),
                 data: None,
             },
         }
     }
 
-    async fn upload_to_imgix(
+    pub async fn process_deploy_queue(&self) {
+        // the queue lives in memory, jobs it held before a restart are lost
+        match self
+            .store
+            .fail_unfinished_deploy_jobs(
+                "Interrupted by a restart, please deploy again".to_string(),
+            )
+            .await
+        {
+            Ok(0) => {}
+            Ok(count) => warn!("Failed {} deploy jobs interrupted by a restart", count),
+            Err(e) => error!("Failed to clean up interrupted deploy jobs: {}", e),
+        }
+
+        while !self.is_shutting_down() {
+            if let Some((request, trace)) = self.next_job(QueueName::Deploy, &self.deploy_queue) {
+                let span = info_span!(
+                    parent: &trace.dequeue(),
+                    "deploy",
+                    request_id = request.request_id.as_deref(),
+                    job_id = %request.job.id
+                );
+                let mut job = request.job.clone();
+                async {
+                    if let Err(e) = self.for_job().process_deploy(request, &mut job).await {
+                        error!("Deploy job {} failed: {}", job.id, e.message);
+                        self.update_deploy_job(&mut job, |job| {
+                            job.step = DeployStep::FAILED;
+                            job.error = Some(e.message);
+                            job.error_code = e.code;
+                        })
+                        .await;
+                    }
+                }
+                .instrument(span)
+                .await;
+            }
+
+            self.idle().await;
+        }
+
+        // jobs are stored, fail the queued ones so their followers hear about it
+        let jobs: Vec<_> = self
+            .deploy_queue
+            .lock()
+            .expect("Failed to lock queue")
+            .drain(..)
+            .collect();
+        if !jobs.is_empty() {
+            warn!("Failing {} queued deploy jobs on shutdown", jobs.len());
+        }
+        for (request, _) in jobs {
+            let mut job = request.job;
+            let error = ErrorResponse::shutting_down();
+            self.update_deploy_job(&mut job, |job| {
+                job.step = DeployStep::FAILED;
+                job.error = Some("Interrupted by a restart, please deploy again".to_string());
+                job.error_code = error.code;
+            })
+            .await;
+        }
+    }
+
+    /// Applies `update` to a job, publishes it to its followers and persists it.
+    async fn update_deploy_job(&self, job: &mut DeployJob, update: impl FnOnce(&mut DeployJob)) {
+        update(job);
+        job.updated_at = Utc::now();
+        self.events
+            .publish(FaucetEvent::DeployProgress(job.clone()));
+
+        if let Err(e) = self.store.update_deploy_job(job.clone()).await {
+            error!("Failed to persist deploy job {}: {}", job.id, e);
+        }
+    }
+
+    /// Uploads a processed logo and its thumbnail, reusing the stored URLs when
+    /// the same file was uploaded before.
+    async fn store_logo(&self, logo: &ProcessedLogo) -> Result<Logo, String> {
+        if let Ok(existing) = self.store.get_logo_by_hash(logo.hash.clone()).await {
+            return Ok(existing);
+        }
+        let Some(object_store) = &self.object_store else {
+            return Err("Storage is not configured".to_string());
+        };
+
+        let logo_url = object_store
+            .put(
+                &format!(
+                    "{}/{}.{}",
+                    object_store::LOGO_PREFIX,
+                    logo.hash,
+                    logo.full_extension
+                ),
+                logo.full.clone(),
+                &logo.full_content_type,
+            )
+            .await?;
+        let thumbnail_url = object_store
+            .put(
+                &format!("{}/{}_thumb.png", object_store::LOGO_PREFIX, logo.hash),
+                logo.thumbnail.clone(),
+                "image/png",
+            )
+            .await?;
+
+        let stored = Logo {
+            hash: logo.hash.clone(),
+            logo_url,
+            thumbnail_url,
+        };
+        // the upload succeeded, so a failed insert only costs a re-upload next time
+        if let Err(e) = self.store.create_logo(stored.clone()).await {
+            warn!("Failed to record logo {}: {}", logo.hash, e);
+        }
+        Ok(stored)
+    }
+
+    async fn process_deploy(
         &self,
-        file_name: String,
-        file_data: Vec<u8>,
-    ) -> Result<String, String> {
-        let url = format!(
-            "https://api.imgix.com/api/v1/sources/66d6dfc6847423eb9bbc7d49/upload/monad-faucet/{}",
-            file_name
-        );
-        let client = reqwest::Client::new();
-        let response = client
-            .post(url)
-            .header("Authorization", format!("Bearer {}", self.config.imgix_key))
-            .body(file_data.to_vec())
-            .send()
+        request: TokenDeployRequest,
+        job: &mut DeployJob,
+    ) -> Result<(), ErrorResponse> {
+        if request.logo.full.is_empty()
+            || request.name.is_empty()
+            || request.symbol.is_empty()
+            || request.deployer_address.is_empty()
+            || request.total_supply == 0
+            || request.decimals == 0
+        {
+            return Err(ErrorResponse {
+                message: "Invalid request".to_string(),
+                next_access: None,
+                code: Some("INVALID_REQUEST".to_string()),
+            });
+        }
+        // checked before deploying, so an impossible supply costs no gas
+        let Some((deployer_allocation, faucet_allocation)) =
+            supply_allocations(request.total_supply, request.decimals)
+        else {
+            return Err(ErrorResponse {
+                message: "Total supply is too large for the decimals".to_string(),
+                next_access: None,
+                code: Some("INVALID_AMOUNT".to_string()),
+            });
+        };
+        let token = self
+            .store
+            .get_token_from_symbol(request.symbol.clone())
             .await;
 
-        match response {
-            Ok(response) => {
-                if response.status().is_success() {
-                    Ok(format!(
-                        "https://garden-finance.imgix.net/monad-faucet/{}",
-                        file_name
-                    ))
-                } else {
-                    Err(response.text().await.unwrap())
-                }
+        if token.is_ok() {
+            return Err(ErrorResponse {
+                message: "Token with same symbol already exists".to_string(),
+                next_access: None,
+                code: Some("CONFLICT".to_string()),
+            });
+        }
+
+        self.update_deploy_job(job, |job| job.step = DeployStep::UPLOADING_LOGO)
+            .await;
+        let logo = self.store_logo(&request.logo).await.map_err(|e| {
+            error!("Failed to upload logo {}: {}", request.logo.hash, e);
+            ErrorResponse {
+                message: "Failed to upload logo".to_string(),
+                next_access: None,
+                code: Some("INTERNAL_ERROR".to_string()),
             }
-            Err(e) => Err(e.to_string()),
+        })?;
+
+        self.update_deploy_job(job, |job| job.step = DeployStep::DEPLOYING)
+            .await;
+        let faucet = faucet::Faucet::new(
+            &self.signers.deployer,
+            &self.config.chain.rpc_url,
+            self.store.clone(),
+        );
+        let contract_address = faucet
+            .deploy_erc_20(
+                request.name.clone(),
+                request.symbol.clone(),
+                request.total_supply,
+                request.decimals,
+                |tx_hash| {
+                    // published right away, persisted with the next step
+                    job.deploy_tx_hash = Some(tx_hash);
+                    job.updated_at = Utc::now();
+                    self.events
+                        .publish(FaucetEvent::DeployProgress(job.clone()));
+                },
+            )
+            .await?;
+
+        self.update_deploy_job(job, |job| {
+            job.step = DeployStep::ALLOCATING_SUPPLY;
+            job.contract_address = Some(contract_address.clone());
+        })
+        .await;
+
+        let withdraw_address = self.signers.faucet.address().to_string();
+
+        for (to, amount) in [
+            (&request.deployer_address, deployer_allocation),
+            (&withdraw_address, faucet_allocation),
+        ] {
+            let drip = faucet
+                .send_erc_20(
+                    &contract_address,
+                    to,
+                    amount,
+                    Some(request.ip),
+                    1,
+                    DripKind::DEPLOY,
+                )
+                .await?;
+            self.update_deploy_job(job, |job| job.allocation_tx_hashes.push(drip.tx_hash))
+                .await;
         }
+
+        faucet
+            .register_erc_20(
+                contract_address.clone(),
+                request.name.clone(),
+                request.symbol.clone(),
+                request.total_supply,
+                request.decimals,
+                logo.logo_url,
+                logo.thumbnail_url,
+                request.deployer_address,
+                job.user_id.clone(),
+                self.tuning.load().withdraw_limit_denominator,
+            )
+            .await?;
+        self.events.publish(FaucetEvent::TokenDeployed {
+            token_address: contract_address,
+            symbol: request.symbol,
+            name: request.name,
+        });
+        self.update_deploy_job(job, |job| job.step = DeployStep::REGISTERED)
+            .await;
+
+        Ok(())
+    }
+}
+
+/// Hands a job's response back to its handler, which may have given up on
+/// it already, e.g. after a timeout or because its client went away
+fn respond<R>(responder: oneshot::Sender<R>, response: R, queue: QueueName) {
+    if responder.send(response).is_err() {
+        warn!(
+            "Dropped the response of a {:?} job, its request is gone",
+            queue
+        );
+    }
+}
+
+/// Tokens of a batch in the order they were asked for, each only once however
+/// its address was cased
+fn unique_tokens(token_addresses: Vec<String>) -> Vec<String> {
+    let mut unique: Vec<String> = Vec::with_capacity(token_addresses.len());
+    for token_address in token_addresses {
+        if !unique
+            .iter()
+            .any(|seen| seen.eq_ignore_ascii_case(&token_address))
+        {
+            unique.push(token_address);
+        }
+    }
+    unique
+}
+
+/// Amount that brings `balance` up to `target * magnification`, or `None`
+/// when the wallet already holds the unmagnified `target`, so magnified
+/// callers get a bigger top-up but not one they'd otherwise be refused
+fn top_up_amount(balance: U256, target: u128, magnification: u8) -> Option<u128> {
+    if balance >= U256::from(target) {
+        return None;
+    }
+    let magnified = U256::from(target.saturating_mul(magnification.max(1) as u128));
+    Some(magnified.saturating_sub(balance).to::<u128>())
+}
+
+/// Base units of a deployed supply sent to the deployer (20%) and to the
+/// faucet (80%), or `None` when the decimals are above `MAX_TOKEN_DECIMALS`
+/// or the supply in base units does not fit in a `u128`
+pub fn supply_allocations(total_supply: u128, decimals: u8) -> Option<(u128, u128)> {
+    if decimals > constants::faucet::MAX_TOKEN_DECIMALS {
+        return None;
+    }
+    let unit = 10u128.checked_pow(decimals as u32)?;
+    total_supply.checked_mul(unit)?;
+    let share = |percent: u128| {
+        total_supply
+            .checked_mul(percent)?
+            .checked_div(100)?
+            .checked_mul(unit)
+    };
+    Some((share(20)?, share(80)?))
+}
+
+/// Lets one drip of a token at a time past the outflow caps
+type OutflowPermit = tokio::sync::OwnedMutexGuard<()>;
+
+fn queue_length<T>(queue: &Mutex<VecDeque<T>>) -> usize {
+    queue.lock().expect("Failed to lock queue").len()
+}
+
+/// Most of `token` allowed out over a cap window, `drips` max size drips, or
+/// `None` when the cap is disabled
+fn outflow_cap(token: &Token, drips: u64, tuning: &TuningConfig) -> Option<u128> {
+    if drips == 0 {
+        return None;
+    }
+    let drip = token
+        .top_up_target
+        .as_deref()
+        .unwrap_or(&token.withdraw_limit)
+        .parse::<u128>()
+        .unwrap_or_default();
+    Some(
+        drip.saturating_mul(tuning.magnification_github_auth as u128)
+            .saturating_mul(drips as u128),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_unique_tokens() {
+        let tokens = unique_tokens(vec![
+            "0xDda173bd23b07007394611D789EF789a9Aae5CF5".to_string(),
+            "0x0000000000000000000000000000000000000000".to_string(),
+            "0xdda173bd23b07007394611d789ef789a9aae5cf5".to_string(),
+        ]);
+        assert_eq!(
+            tokens,
+            [
+                "0xDda173bd23b07007394611D789EF789a9Aae5CF5",
+                "0x0000000000000000000000000000000000000000"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_top_up_amount() {
+        assert_eq!(top_up_amount(U256::ZERO, 100, 1), Some(100));
+        assert_eq!(top_up_amount(U256::from(40), 100, 1), Some(60));
+        assert_eq!(top_up_amount(U256::from(100), 100, 1), None);
+        assert_eq!(top_up_amount(U256::from(250), 100, 1), None);
+
+        // magnified callers are topped up further, but a wallet above the
+        // plain target is still refused
+        assert_eq!(top_up_amount(U256::from(40), 100, 10), Some(960));
+        assert_eq!(top_up_amount(U256::from(100), 100, 10), None);
+        assert_eq!(top_up_amount(U256::from(40), 100, 0), Some(60));
+        assert_eq!(top_up_amount(U256::ZERO, u128::MAX, 10), Some(u128::MAX));
+    }
+
+    #[test]
+    fn test_supply_allocations() {
+        assert_eq!(
+            supply_allocations(1_000, 18),
+            Some((200 * 10u128.pow(18), 800 * 10u128.pow(18)))
+        );
+        assert_eq!(supply_allocations(7, 1), Some((10, 50)));
+        assert_eq!(supply_allocations(1, 19), None);
+        assert_eq!(supply_allocations(1, u8::MAX), None);
+        // the whole supply has to fit in base units, not only the shares
+        assert_eq!(supply_allocations(u128::MAX / 10u128.pow(18) + 1, 18), None);
+        assert_eq!(supply_allocations(u128::MAX, 1), None);
+    }
+
+    #[test]
+    fn test_outflow_cap() {
+        let mut token = Token {
+            created_by: "0xdeployer".to_string(),
+            token_type: TokenType::ERC20,
+            address: "0xtoken".to_string(),
+            logo_url: String::new(),
+            chain_id: 10143,
+            symbol: "TKN".to_string(),
+            name: "Token".to_string(),
+            decimals: 18,
+            withdraw_limit: "1000".to_string(),
+            top_up_target: None,
+            logo_thumbnail_url: None,
+            created_by_user_id: None,
+        };
+        let tuning = TuningConfig::default();
+
+        assert_eq!(outflow_cap(&token, 500, &tuning), Some(1000 * 10 * 500));
+        assert_eq!(outflow_cap(&token, 0, &tuning), None);
+
+        // top-up tokens drip at most their target
+        token.top_up_target = Some("50".to_string());
+        assert_eq!(outflow_cap(&token, 2, &tuning), Some(50 * 10 * 2));
+
+        token.withdraw_limit = u128::MAX.to_string();
+        token.top_up_target = None;
+        assert_eq!(outflow_cap(&token, 2, &tuning), Some(u128::MAX));
+    }
+
+    #[tokio::test]
+    async fn test_shutdown_refuses_queued_jobs() {
+        let executor = crate::utils::setup().await.unwrap().executor;
+        let request = TokenTransferRequest {
+            token_address: "0xb1baC9E12095043045d19F3E7a988D0C63dC2523".to_string(),
+            to: "0xDda173bd23b07007394611D789EF789a9Aae5CF5".to_string(),
+            token_type: TokenType::ERC20,
+            magnification: None,
+            ip: IpNetwork::V4("127.0.0.1".parse().unwrap()),
+            request_id: None,
+        };
+
+        // a paused queue keeps its jobs
+        assert!(executor.pause_queue(QueueName::Withdraw));
+        let (tx, rx) = oneshot::channel();
+        let (gone_tx, gone_rx) = oneshot::channel();
+        {
+            let mut queue = executor.withdraw_queue.lock().unwrap();
+            queue.push_back((request.clone(), tx, QueueTrace::new("withdraw")));
+            queue.push_back((request.clone(), gone_tx, QueueTrace::new("withdraw")));
+        }
+        assert!(executor
+            .next_job(QueueName::Withdraw, &executor.withdraw_queue)
+            .is_none());
+        let state = &executor.queue_states()[0];
+        assert_eq!(state.queue, QueueName::Withdraw);
+        assert!(state.paused);
+        assert_eq!(state.length, 2);
+
+        // the client of the second job went away, which must not stop the worker
+        drop(gone_rx);
+        executor.shutdown();
+        // jobs queued once the shutdown started are refused right away
+        let (late_tx, _late_rx) = oneshot::channel();
+        assert_eq!(
+            executor.enqueue(
+                &executor.withdraw_queue,
+                (request, late_tx, QueueTrace::new("withdraw"))
+            ),
+            Err(EnqueueError::ShuttingDown)
+        );
+        executor.process_withdraw_queue().await;
+
+        let response = rx.await.unwrap();
+        assert_eq!(
+            response.error.unwrap().code.as_deref(),
+            Some("SHUTTING_DOWN")
+        );
+        assert_eq!(executor.queue_states()[0].length, 0);
+    }
+
+    #[tokio::test]
+    async fn test_invalid_top_up_target() {
+        let state = crate::utils::setup().await.unwrap();
+        let executor = state.executor;
+        let address = format!("0x{}", &uuid::Uuid::new_v4().simple().to_string()[..32]);
+        executor
+            .store
+            .create_token_entry(Token {
+                created_by: "0xdeployer".to_string(),
+                token_type: TokenType::ERC20,
+                address: address.clone(),
+                logo_url: String::new(),
+                chain_id: 10143,
+                symbol: "TKN".to_string(),
+                name: "Token".to_string(),
+                decimals: 18,
+                withdraw_limit: "1000".to_string(),
+                top_up_target: Some("lots".to_string()),
+                logo_thumbnail_url: None,
+                created_by_user_id: None,
+            })
+            .await
+            .unwrap();
+
+        let request = TokenTransferRequest {
+            token_address: address.clone(),
+            to: "0xDda173bd23b07007394611D789EF789a9Aae5CF5".to_string(),
+            token_type: TokenType::ERC20,
+            magnification: None,
+            ip: IpNetwork::V4("127.0.0.1".parse().unwrap()),
+            request_id: None,
+        };
+        // refused outright instead of topping up to a target of 0
+        let response = executor
+            .validate_and_get_withdraw_limit(&request)
+            .await
+            .unwrap_err();
+        assert_eq!(
+            response.error.unwrap().code.as_deref(),
+            Some("TOKEN_NOT_CLAIMABLE")
+        );
+
+        sqlx::query!("DELETE FROM tokens WHERE address = $1", address)
+            .execute(&crate::db::init_db(&state.config.db.url).await.unwrap())
+            .await
+            .unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_outflow_pause() {
+        let state = crate::utils::setup().await.unwrap();
+        let executor = state.executor;
+        let address = format!("0x{}00000000", uuid::Uuid::new_v4().simple())
+            .parse::<Address>()
+            .unwrap()
+            .to_checksum(None);
+        executor
+            .store
+            .create_token_entry(Token {
+                created_by: "0xdeployer".to_string(),
+                token_type: TokenType::ERC20,
+                address: address.clone(),
+                logo_url: String::new(),
+                chain_id: 10143,
+                symbol: "TKN".to_string(),
+                name: "Token".to_string(),
+                decimals: 18,
+                withdraw_limit: "1000".to_string(),
+                top_up_target: None,
+                logo_thumbnail_url: None,
+                created_by_user_id: None,
+            })
+            .await
+            .unwrap();
+        let code = |result: Result<OutflowPermit, ExecutorResponse>| {
+            result
+                .err()
+                .map(|response| response.error.unwrap().code.unwrap())
+        };
+
+        assert_eq!(code(executor.check_outflow(&address, 10).await), None);
+
+        assert!(executor
+            .store
+            .pause_token(address.clone(), "test".to_string())
+            .await
+            .unwrap());
+        // however it is cased, the paused token is refused
+        assert_eq!(
+            code(executor.check_outflow(&address, 10).await).as_deref(),
+            Some("FAUCET_PAUSED")
+        );
+        assert_eq!(
+            code(executor.check_outflow(&address.to_lowercase(), 10).await).as_deref(),
+            Some("FAUCET_PAUSED")
+        );
+
+        assert!(executor
+            .store
+            .resume_token(address.clone(), "admin".to_string())
+            .await
+            .unwrap());
+        assert_eq!(
+            code(executor.check_outflow(&address.to_lowercase(), 10).await),
+            None
+        );
+
+        let db = crate::db::init_db(&state.config.db.url).await.unwrap();
+        sqlx::query!("DELETE FROM token_pauses WHERE token_address = $1", address)
+            .execute(&db)
+            .await
+            .unwrap();
+        sqlx::query!("DELETE FROM tokens WHERE address = $1", address)
+            .execute(&db)
+            .await
+            .unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_token_type_mismatch() {
+        let state = crate::utils::setup().await.unwrap();
+        let executor = state.executor;
+        let address = format!("0x{}00000000", uuid::Uuid::new_v4().simple())
+            .parse::<Address>()
+            .unwrap()
+            .to_checksum(None);
+        executor
+            .store
+            .create_token_entry(Token {
+                created_by: "0xdeployer".to_string(),
+                token_type: TokenType::ERC20,
+                address: address.clone(),
+                logo_url: String::new(),
+                chain_id: 10143,
+                symbol: "TKN".to_string(),
+                name: "Token".to_string(),
+                decimals: 18,
+                withdraw_limit: "1000".to_string(),
+                top_up_target: None,
+                logo_thumbnail_url: None,
+                created_by_user_id: None,
+            })
+            .await
+            .unwrap();
+
+        // an ERC-20 claimed as native is refused before its outflow is charged
+        let response = executor
+            .prepare_transfer(&TokenTransferRequest {
+                token_address: address.clone(),
+                to: "0x0000000000000000000000000000000000000001".to_string(),
+                token_type: TokenType::NATIVE,
+                magnification: None,
+                ip: IpNetwork::V4("127.0.0.1".parse().unwrap()),
+                request_id: None,
+            })
+            .await
+            .err()
+            .unwrap();
+        assert_eq!(
+            response.error.unwrap().code.as_deref(),
+            Some("INVALID_REQUEST")
+        );
+
+        let db = crate::db::init_db(&state.config.db.url).await.unwrap();
+        sqlx::query!("DELETE FROM tokens WHERE address = $1", address)
+            .execute(&db)
+            .await
+            .unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_refill_quota() {
+        let state = crate::utils::setup().await.unwrap();
+        let executor = state.executor;
+        let id = uuid::Uuid::new_v4().to_string();
+        let wallet = format!("0x{}00000000", uuid::Uuid::new_v4().simple())
+            .parse::<Address>()
+            .unwrap()
+            .to_checksum(None);
+        let token = format!("0x{}00000000", uuid::Uuid::new_v4().simple())
+            .parse::<Address>()
+            .unwrap()
+            .to_checksum(None);
+        executor
+            .store
+            .create_user(crate::store::User {
+                id: id.clone(),
+                username: id.clone(),
+                avatar_url: String::new(),
+                github_id: id.clone(),
+                access_token: id.clone(),
+                email: None,
+            })
+            .await
+            .unwrap();
+        executor
+            .store
+            .create_subscription(WalletSubscription {
+                id: id.clone(),
+                user_id: id.clone(),
+                wallet_address: wallet.to_lowercase(),
+                token_address: token.to_lowercase(),
+                min_balance: "10".to_string(),
+                refill_amount: "100".to_string(),
+                enabled: true,
+                last_checked_at: None,
+                last_refill_at: None,
+            })
+            .await
+            .unwrap();
+        let transfer = |kind: DripKind| crate::store::TokenTransfer {
+            token_address: token.clone(),
+            token_type: TokenType::ERC20,
+            tx_hash: format!("0x{}", uuid::Uuid::new_v4().simple()),
+            from_address: "0xfaucet".to_string(),
+            to_address: wallet.clone(),
+            amount: "100".to_string(),
+            chain_id: 10143,
+            ip: None,
+            kind,
+        };
+
+        let max = executor.tuning.load().max_scheduled_refills_per_day;
+        for _ in 0..max - 1 {
+            executor
+                .store
+                .create_token_transfer(transfer(DripKind::SCHEDULED))
+                .await
+                .unwrap();
+        }
+        // only scheduled drips count against the quota
+        executor
+            .store
+            .create_token_transfer(transfer(DripKind::CLAIM))
+            .await
+            .unwrap();
+        assert!(!executor.refill_quota_exhausted(id.clone()).await.unwrap());
+
+        // the checksummed transfers count against the lowercased subscription
+        executor
+            .store
+            .create_token_transfer(transfer(DripKind::SCHEDULED))
+            .await
+            .unwrap();
+        assert!(executor.refill_quota_exhausted(id.clone()).await.unwrap());
+
+        let db = crate::db::init_db(&state.config.db.url).await.unwrap();
+        sqlx::query!(
+            "DELETE FROM token_transfers WHERE token_address = $1",
+            token
+        )
+        .execute(&db)
+        .await
+        .unwrap();
+        sqlx::query!("DELETE FROM wallet_subscriptions WHERE id = $1", id)
+            .execute(&db)
+            .await
+            .unwrap();
+        sqlx::query!("DELETE FROM users WHERE id = $1", id)
+            .execute(&db)
+            .await
+            .unwrap();
     }
 }
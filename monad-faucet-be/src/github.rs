@@ -0,0 +1,405 @@
+use std::{fmt, time::Duration};
+
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine};
+use chrono::Utc;
+use hmac::{Hmac, Mac};
+use reqwest::{header, StatusCode};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use uuid::Uuid;
+
+use crate::{
+    config::{AuthConfig, GithubConfig, JwtKey},
+    constants::github,
+    session::{self, TokenError},
+};
+
+#[derive(Debug)]
+pub enum GithubError {
+    /// GitHub could not be reached or its response not read
+    Request(reqwest::Error),
+    /// GitHub answered with an unexpected status
+    Status(StatusCode),
+    /// GitHub refused to exchange the code, with its OAuth error code such as
+    /// `bad_verification_code`
+    Rejected(String),
+    /// The response was not the JSON we expect or lacked a field we need
+    InvalidResponse(String),
+}
+
+impl fmt::Display for GithubError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            GithubError::Request(e) => write!(f, "Request to GitHub failed: {}", e),
+            GithubError::Status(status) => write!(f, "GitHub answered {}", status),
+            GithubError::Rejected(code) => write!(f, "GitHub rejected the code: {}", code),
+            GithubError::InvalidResponse(e) => write!(f, "Invalid GitHub response: {}", e),
+        }
+    }
+}
+
+impl From<reqwest::Error> for GithubError {
+    fn from(e: reqwest::Error) -> Self {
+        GithubError::Request(e)
+    }
+}
+
+/// The GitHub account of a user signing in
+#[derive(Debug, Clone, Deserialize)]
+pub struct GithubUser {
+    pub id: i64,
+    pub login: String,
+    pub avatar_url: String,
+    pub email: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct AccessTokenResponse {
+    access_token: Option<String>,
+    error: Option<String>,
+}
+
+/// Claims of a login state, signed with the JWT keys. The client keeps the
+/// token while the user is on GitHub and sends it back with the callback.
+/// It holds no secret, the PKCE verifier is derived from the state on the
+/// server.
+#[derive(Debug, Serialize, Deserialize)]
+struct LoginStateClaims {
+    /// Sent to GitHub as `state` and expected back with the code
+    state: String,
+    exp: usize,
+    iss: String,
+    aud: String,
+}
+
+/// Where to send the user to sign in, and the token to keep until they come back
+#[derive(Debug)]
+pub struct LoginStart {
+    pub authorize_url: String,
+    pub state_token: String,
+}
+
+/// Login states are signed with the same keys as access tokens, their own
+/// audience keeps one from being used as the other
+fn login_state_audience(auth: &AuthConfig) -> String {
+    format!("{}/github-login", auth.jwt_audience)
+}
+
+/// PKCE verifier of the login with `state`, keyed with the key that signed
+/// its state token. GitHub only ever sees its challenge.
+fn code_verifier(key: &JwtKey, state: &str) -> String {
+    let mut mac = Hmac::<Sha256>::new_from_slice(key.secret.as_bytes())
+        .expect("HMAC accepts keys of any size");
+    mac.update(b"github-login:");
+    mac.update(state.as_bytes());
+    URL_SAFE_NO_PAD.encode(mac.finalize().into_bytes())
+}
+
+fn code_challenge(code_verifier: &str) -> String {
+    URL_SAFE_NO_PAD.encode(Sha256::digest(code_verifier.as_bytes()))
+}
+
+/// Starts a GitHub login with a fresh state and PKCE verifier
+pub fn start_login(
+    auth: &AuthConfig,
+    config: &GithubConfig,
+) -> Result<LoginStart, jsonwebtoken::errors::Error> {
+    // validated when the config was loaded, `session::sign` signs with it too
+    let key = auth.jwt_keys.first().expect("No JWT signing key");
+    let claims = LoginStateClaims {
+        state: Uuid::new_v4().simple().to_string(),
+        exp: (Utc::now().timestamp() + github::LOGIN_STATE_TTL_SECS) as usize,
+        iss: auth.jwt_issuer.clone(),
+        aud: login_state_audience(auth),
+    };
+
+    // validated when the config was loaded
+    let mut authorize_url =
+        reqwest::Url::parse(&config.authorize_url).expect("Invalid GitHub authorize URL");
+    authorize_url
+        .query_pairs_mut()
+        .append_pair("client_id", &config.client_id)
+        .append_pair("redirect_uri", &config.redirect_uri)
+        .append_pair("scope", github::SCOPE)
+        .append_pair("state", &claims.state)
+        .append_pair(
+            "code_challenge",
+            &code_challenge(&code_verifier(key, &claims.state)),
+        )
+        .append_pair("code_challenge_method", "S256");
+
+    Ok(LoginStart {
+        authorize_url: authorize_url.to_string(),
+        state_token: session::sign(auth, &claims)?,
+    })
+}
+
+/// Checks that GitHub came back with the `state` of the login started for
+/// `state_token`, and returns its PKCE verifier
+pub fn finish_login(
+    auth: &AuthConfig,
+    state_token: &str,
+    state: &str,
+) -> Result<String, TokenError> {
+    let claims: LoginStateClaims = session::verify(auth, state_token, &login_state_audience(auth))?;
+    if claims.state != state {
+        return Err(TokenError::Invalid);
+    }
+    // the token was verified with the key its header names
+    let key = jsonwebtoken::decode_header(state_token)
+        .ok()
+        .and_then(|header| header.kid)
+        .and_then(|kid| auth.jwt_keys.iter().find(|key| key.id == kid))
+        .ok_or(TokenError::Invalid)?;
+    Ok(code_verifier(key, &claims.state))
+}
+
+/// Client for GitHub's OAuth web flow, at the endpoints of [`GithubConfig`]
+#[derive(Debug, Clone)]
+pub struct GithubClient {
+    config: GithubConfig,
+    client: reqwest::Client,
+}
+
+impl GithubClient {
+    pub fn new(config: &GithubConfig) -> Result<Self, GithubError> {
+        Ok(Self {
+            config: config.clone(),
+            client: reqwest::Client::builder()
+                .timeout(Duration::from_secs(github::REQUEST_TIMEOUT_SECS))
+                .user_agent("monad-faucet")
+                .build()?,
+        })
+    }
+
+    /// Exchanges the code of the redirect for an access token
+    pub async fn exchange_code(
+        &self,
+        code: &str,
+        code_verifier: &str,
+    ) -> Result<String, GithubError> {
+        let response = self
+            .client
+            .post(&self.config.token_url)
+            .header(header::ACCEPT, "application/json")
+            .form(&[
+                ("client_id", self.config.client_id.as_str()),
+                ("client_secret", self.config.client_secret.as_str()),
+                ("code", code),
+                ("redirect_uri", self.config.redirect_uri.as_str()),
+                ("code_verifier", code_verifier),
+            ])
+            .send()
+            .await?;
+
+        // GitHub answers a bad code with a 200 and an `error`, only its code
+        // is kept since the rest of the response may hold secrets
+        match read_json::<AccessTokenResponse>(response).await? {
+            AccessTokenResponse {
+                access_token: Some(access_token),
+                ..
+            } => Ok(access_token),
+            AccessTokenResponse { error, .. } => Err(GithubError::Rejected(
+                error.unwrap_or_else(|| "unknown".to_string()),
+            )),
+        }
+    }
+
+    pub async fn get_user(&self, access_token: &str) -> Result<GithubUser, GithubError> {
+        let response = self
+            .client
+            .get(format!(
+                "{}/user",
+                self.config.api_url.trim_end_matches('/')
+            ))
+            .header(header::ACCEPT, "application/vnd.github+json")
+            .bearer_auth(access_token)
+            .send()
+            .await?;
+        read_json(response).await
+    }
+}
+
+async fn read_json<T: DeserializeOwned>(response: reqwest::Response) -> Result<T, GithubError> {
+    let status = response.status();
+    if !status.is_success() {
+        return Err(GithubError::Status(status));
+    }
+    let body = response.text().await?;
+    serde_json::from_str(&body).map_err(|e| GithubError::InvalidResponse(e.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use httpmock::{
+        Method::{GET, POST},
+        MockServer,
+    };
+
+    use super::*;
+    use crate::config::JwtKey;
+
+    fn auth_config() -> AuthConfig {
+        AuthConfig {
+            jwt_keys: vec![JwtKey {
+                id: "1".to_string(),
+                secret: "qV2zScNYyR6bB6".to_string(),
+            }],
+            jwt_issuer: "monad-faucet".to_string(),
+            jwt_audience: "monad-faucet".to_string(),
+            access_token_ttl_secs: 900,
+            refresh_token_ttl_days: 30,
+            admin_user_ids: Vec::new(),
+            github: None,
+        }
+    }
+
+    fn github_config(server: &MockServer) -> GithubConfig {
+        GithubConfig {
+            client_id: "client".to_string(),
+            client_secret: "secret".to_string(),
+            redirect_uri: "http://localhost:5173/auth".to_string(),
+            authorize_url: server.url("/login/oauth/authorize"),
+            token_url: server.url("/login/oauth/access_token"),
+            api_url: server.url("/api"),
+        }
+    }
+
+    #[test]
+    fn test_login_state() {
+        let server = MockServer::start();
+        let auth = auth_config();
+        let login = start_login(&auth, &github_config(&server)).unwrap();
+
+        let url = reqwest::Url::parse(&login.authorize_url).unwrap();
+        let query: std::collections::HashMap<_, _> = url.query_pairs().into_owned().collect();
+        assert_eq!(query["client_id"], "client");
+        assert_eq!(query["code_challenge_method"], "S256");
+
+        let code_verifier = finish_login(&auth, &login.state_token, &query["state"]).unwrap();
+        assert_eq!(query["code_challenge"], code_challenge(&code_verifier));
+        // RFC 7636 wants at least 43 characters
+        assert_eq!(code_verifier.len(), 43);
+        // the state token the client holds does not carry the verifier
+        let payload = login.state_token.split('.').nth(1).unwrap();
+        let payload = String::from_utf8(URL_SAFE_NO_PAD.decode(payload).unwrap()).unwrap();
+        assert!(!payload.contains(&code_verifier));
+
+        // a callback with another login's state is refused
+        let other = start_login(&auth, &github_config(&server)).unwrap();
+        assert_eq!(
+            finish_login(&auth, &other.state_token, &query["state"]).unwrap_err(),
+            TokenError::Invalid
+        );
+        // and so is an access token passed off as a login state
+        let access_token = session::issue_access_token(&auth, "user", "session").unwrap();
+        assert_eq!(
+            finish_login(&auth, &access_token, &query["state"]).unwrap_err(),
+            TokenError::Invalid
+        );
+    }
+
+    #[test]
+    fn test_code_challenge() {
+        // RFC 7636 appendix B
+        assert_eq!(
+            code_challenge("dBjftJeZ4CVP-mB92K27uhbUJU1p1r_wW1gFWFOEjXk"),
+            "E9Melhoa2OwvFrEMTJguCHaoeK1t8URWbuGJSstw-cM"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_exchange_code() {
+        let server = MockServer::start();
+        let client = GithubClient::new(&github_config(&server)).unwrap();
+        let mock = server.mock(|when, then| {
+            when.method(POST)
+                .path("/login/oauth/access_token")
+                .x_www_form_urlencoded_tuple("code", "good")
+                .x_www_form_urlencoded_tuple("code_verifier", "verifier");
+            then.status(200)
+                .json_body(serde_json::json!({ "access_token": "gho_token" }));
+        });
+        server.mock(|when, then| {
+            when.method(POST)
+                .path("/login/oauth/access_token")
+                .x_www_form_urlencoded_tuple("code", "bad");
+            then.status(200)
+                .json_body(serde_json::json!({ "error": "bad_verification_code" }));
+        });
+        server.mock(|when, then| {
+            when.method(POST)
+                .path("/login/oauth/access_token")
+                .x_www_form_urlencoded_tuple("code", "down");
+            then.status(502).body("Bad Gateway");
+        });
+
+        assert_eq!(
+            client.exchange_code("good", "verifier").await.unwrap(),
+            "gho_token"
+        );
+        mock.assert();
+        assert!(matches!(
+            client.exchange_code("bad", "verifier").await,
+            Err(GithubError::Rejected(code)) if code == "bad_verification_code"
+        ));
+        assert!(matches!(
+            client.exchange_code("down", "verifier").await,
+            Err(GithubError::Status(StatusCode::BAD_GATEWAY))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_get_user() {
+        let server = MockServer::start();
+        let client = GithubClient::new(&github_config(&server)).unwrap();
+        server.mock(|when, then| {
+            when.method(GET)
+                .path("/api/user")
+                .header("authorization", "Bearer gho_token");
+            then.status(200).json_body(serde_json::json!({
+                "id": 1,
+                "login": "octocat",
+                "avatar_url": "https://avatars.githubusercontent.com/u/1",
+                "email": null
+            }));
+        });
+        server.mock(|when, then| {
+            when.method(GET)
+                .path("/api/user")
+                .header("authorization", "Bearer partial");
+            then.status(200).json_body(serde_json::json!({ "id": 2 }));
+        });
+        server.mock(|when, then| {
+            when.method(GET)
+                .path("/api/user")
+                .header("authorization", "Bearer expired");
+            then.status(401)
+                .json_body(serde_json::json!({ "message": "Bad credentials" }));
+        });
+
+        let user = client.get_user("gho_token").await.unwrap();
+        assert_eq!(user.id, 1);
+        assert_eq!(user.login, "octocat");
+        assert_eq!(user.email, None);
+        // a missing field is an error rather than a panic
+        assert!(matches!(
+            client.get_user("partial").await,
+            Err(GithubError::InvalidResponse(e)) if e.contains("login")
+        ));
+        assert!(matches!(
+            client.get_user("expired").await,
+            Err(GithubError::Status(StatusCode::UNAUTHORIZED))
+        ));
+        // unreachable GitHub
+        let mut config = github_config(&server);
+        config.api_url = "http://127.0.0.1:1".to_string();
+        assert!(matches!(
+            GithubClient::new(&config)
+                .unwrap()
+                .get_user("gho_token")
+                .await,
+            Err(GithubError::Request(_))
+        ));
+    }
+}
@@ -1,10 +1,19 @@
+pub mod admin;
+pub mod api_keys;
 pub mod auth;
+pub mod bulk_drip;
+pub mod claims;
 pub mod deploy_erc20;
+pub mod error;
+pub mod events;
 pub mod health;
 pub mod middleware;
 pub mod response;
+pub mod subscriptions;
 pub mod test_auth;
 pub mod tokens;
 pub mod turnstile_captcha;
 pub mod user;
+pub mod validation;
+pub mod webhooks;
 pub mod withdraw;
@@ -0,0 +1,208 @@
+use axum::{
+    extract::{Path, State},
+    Json,
+};
+use tracing::{error, info};
+
+use crate::{
+    config::TuningConfig,
+    events::FaucetEvent,
+    executor::{ErrorResponse, QueueName, QueueState},
+    store::{Store, TokenPause},
+    AppState,
+};
+
+use super::{
+    error::ApiError,
+    middleware::AuthUser,
+    response::Response,
+    validation::{normalize_address, ApiPath},
+};
+
+/// Reloads the config file and environment, swapping in the new tuning. Jobs
+/// already running finish with the values they started with.
+#[utoipa::path(
+    post,
+    path = "/admin/reload",
+    tag = "admin",
+    responses(
+        (status = 200, description = "The tuning now in effect", body = Response<TuningConfig>),
+        (status = 403, description = "Not an admin", body = Response<ErrorResponse>),
+        (status = 422, description = "The new config is invalid, the current one is kept", body = Response<ErrorResponse>),
+    ),
+    security(("github" = []))
+)]
+pub async fn reload_config(
+    auth_user: AuthUser,
+    State(state): State<AppState>,
+) -> Result<Json<Response<TuningConfig>>, ApiError> {
+    if !auth_user.is_admin(&state.config) || auth_user.api_key_id.is_some() {
+        return Err(ApiError::Forbidden("Only admins can reload the config"));
+    }
+
+    match state.tuning.reload() {
+        Ok(tuning) => {
+            info!(?tuning, user_id = %auth_user.user_id, "Reloaded config");
+            Ok(Response::ok(tuning.as_ref().clone()))
+        }
+        Err(e) => {
+            error!("Keeping the current config, {}", e);
+            Err(ApiError::InvalidConfig(e.0.join("; ")))
+        }
+    }
+}
+
+/// Tokens whose drips are paused by the outflow circuit breaker
+#[utoipa::path(
+    get,
+    path = "/admin/pauses",
+    tag = "admin",
+    responses(
+        (status = 200, description = "Paused tokens", body = Response<Vec<TokenPause>>),
+        (status = 403, description = "Not an admin", body = Response<ErrorResponse>),
+    ),
+    security(("github" = []))
+)]
+pub async fn paused_tokens(
+    auth_user: AuthUser,
+    State(state): State<AppState>,
+) -> Result<Json<Response<Vec<TokenPause>>>, ApiError> {
+    if !auth_user.is_admin(&state.config) || auth_user.api_key_id.is_some() {
+        return Err(ApiError::Forbidden("Only admins can see paused tokens"));
+    }
+
+    match state.store.get_paused_tokens().await {
+        Ok(pauses) => Ok(Response::ok(pauses)),
+        Err(e) => {
+            error!("Error fetching paused tokens {}", e);
+            Err(ApiError::Internal("Failed to fetch paused tokens"))
+        }
+    }
+}
+
+/// Resumes drips of a token paused by the circuit breaker. Only outflow sent
+/// from now on counts towards the caps, so the token is not paused again
+/// straight away.
+#[utoipa::path(
+    post,
+    path = "/admin/tokens/{address}/resume",
+    tag = "admin",
+    params(("address" = String, Path, description = "Paused token")),
+    responses(
+        (status = 200, description = "The lifted pause", body = Response<TokenPause>),
+        (status = 403, description = "Not an admin", body = Response<ErrorResponse>),
+        (status = 404, description = "The token is not paused", body = Response<ErrorResponse>),
+    ),
+    security(("github" = []))
+)]
+pub async fn resume_token(
+    auth_user: AuthUser,
+    State(state): State<AppState>,
+    Path(address): Path<String>,
+) -> Result<Json<Response<TokenPause>>, ApiError> {
+    if !auth_user.is_admin(&state.config) || auth_user.api_key_id.is_some() {
+        return Err(ApiError::Forbidden("Only admins can resume tokens"));
+    }
+    let address = normalize_address("token", &address)?;
+
+    let resumed = async {
+        let resumed = state
+            .store
+            .resume_token(address.clone(), auth_user.user_id.clone())
+            .await?;
+        let pause = state.store.get_token_pause(address.clone()).await?;
+        Ok::<_, sqlx::Error>((resumed, pause))
+    }
+    .await;
+
+    match resumed {
+        Ok((true, Some(pause))) => {
+            info!(user_id = %auth_user.user_id, "Resumed drips of {}", address);
+            state.executor.events.publish(FaucetEvent::TokenResumed {
+                token_address: address,
+            });
+            Ok(Response::ok(pause))
+        }
+        Ok(_) => Err(ApiError::NotFound("Paused token")),
+        Err(e) => {
+            error!("Error resuming token {} {}", address, e);
+            Err(ApiError::Internal("Failed to resume token"))
+        }
+    }
+}
+
+/// Executor queues with how many jobs wait in each and whether it is paused
+#[utoipa::path(
+    get,
+    path = "/admin/queues",
+    tag = "admin",
+    responses(
+        (status = 200, description = "Every queue", body = Response<Vec<QueueState>>),
+        (status = 403, description = "Not an admin", body = Response<ErrorResponse>),
+    ),
+    security(("github" = []))
+)]
+pub async fn queues(
+    auth_user: AuthUser,
+    State(state): State<AppState>,
+) -> Result<Json<Response<Vec<QueueState>>>, ApiError> {
+    if !auth_user.is_admin(&state.config) || auth_user.api_key_id.is_some() {
+        return Err(ApiError::Forbidden("Only admins can see the queues"));
+    }
+
+    Ok(Response::ok(state.executor.queue_states()))
+}
+
+/// Stops the executor from picking up jobs of a queue. Requests keep being
+/// queued, and wait for the queue to be resumed or time out.
+#[utoipa::path(
+    post,
+    path = "/admin/queues/{queue}/pause",
+    tag = "admin",
+    params(("queue" = QueueName, Path)),
+    responses(
+        (status = 200, description = "Every queue after pausing", body = Response<Vec<QueueState>>),
+        (status = 403, description = "Not an admin", body = Response<ErrorResponse>),
+    ),
+    security(("github" = []))
+)]
+pub async fn pause_queue(
+    auth_user: AuthUser,
+    State(state): State<AppState>,
+    ApiPath(queue): ApiPath<QueueName>,
+) -> Result<Json<Response<Vec<QueueState>>>, ApiError> {
+    if !auth_user.is_admin(&state.config) || auth_user.api_key_id.is_some() {
+        return Err(ApiError::Forbidden("Only admins can pause queues"));
+    }
+
+    if state.executor.pause_queue(queue) {
+        info!(user_id = %auth_user.user_id, "Paused the {:?} queue", queue);
+    }
+    Ok(Response::ok(state.executor.queue_states()))
+}
+
+#[utoipa::path(
+    post,
+    path = "/admin/queues/{queue}/resume",
+    tag = "admin",
+    params(("queue" = QueueName, Path)),
+    responses(
+        (status = 200, description = "Every queue after resuming", body = Response<Vec<QueueState>>),
+        (status = 403, description = "Not an admin", body = Response<ErrorResponse>),
+    ),
+    security(("github" = []))
+)]
+pub async fn resume_queue(
+    auth_user: AuthUser,
+    State(state): State<AppState>,
+    ApiPath(queue): ApiPath<QueueName>,
+) -> Result<Json<Response<Vec<QueueState>>>, ApiError> {
+    if !auth_user.is_admin(&state.config) || auth_user.api_key_id.is_some() {
+        return Err(ApiError::Forbidden("Only admins can resume queues"));
+    }
+
+    if state.executor.resume_queue(queue) {
+        info!(user_id = %auth_user.user_id, "Resumed the {:?} queue", queue);
+    }
+    Ok(Response::ok(state.executor.queue_states()))
+}
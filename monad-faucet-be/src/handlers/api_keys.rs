@@ -0,0 +1,207 @@
+use axum::{
+    extract::{Path, State},
+    Json,
+};
+use tracing::error;
+use utoipa::ToSchema;
+use uuid::Uuid;
+
+use crate::{
+    executor::ErrorResponse,
+    session::hash_secret,
+    store::{ApiKey, ApiKeyQuota, Store},
+    AppState,
+};
+
+use super::{
+    error::ApiError,
+    middleware::AuthUser,
+    response::Response,
+    validation::{normalize_address, Valid, Validate},
+};
+
+const API_KEY_PREFIX: &str = "mfk_";
+
+#[derive(Debug, serde::Deserialize, ToSchema)]
+pub struct CreateApiKeyRequest {
+    pub owner_user_id: String,
+    pub name: String,
+    pub drips_per_day: i32,
+    pub quotas: Vec<ApiKeyQuota>,
+}
+
+#[derive(Debug, serde::Serialize, ToSchema)]
+pub struct CreateApiKeyResponse {
+    /// The plaintext key, only ever returned once at creation
+    pub key: String,
+    pub api_key: ApiKey,
+}
+
+impl Validate for CreateApiKeyRequest {
+    fn validate(&mut self) -> Result<(), ApiError> {
+        if self.name.is_empty() || self.drips_per_day <= 0 || self.quotas.is_empty() {
+            return Err(ApiError::InvalidRequest(
+                "An API key needs a name, a positive daily drip count and quotas".to_string(),
+            ));
+        }
+        for quota in &mut self.quotas {
+            quota.token_address = normalize_address("token", &quota.token_address)?;
+            if quota.max_amount_per_day.parse::<u128>().is_err() {
+                return Err(ApiError::InvalidAmount("Invalid quota amount".to_string()));
+            }
+        }
+        Ok(())
+    }
+}
+
+fn generate_api_key() -> String {
+    format!(
+        "{}{}{}",
+        API_KEY_PREFIX,
+        Uuid::new_v4().simple(),
+        Uuid::new_v4().simple()
+    )
+}
+
+/// Issues a new API key for `owner_user_id`. Only admins may create keys since
+/// the quotas are part of the request.
+#[utoipa::path(
+    post,
+    path = "/api-keys",
+    tag = "api-keys",
+    request_body = CreateApiKeyRequest,
+    responses(
+        (status = 200, description = "The key, shown only this once", body = Response<CreateApiKeyResponse>),
+        (status = 400, description = "Missing name, drip count or quotas", body = Response<ErrorResponse>),
+        (status = 403, description = "Not an admin", body = Response<ErrorResponse>),
+        (status = 404, description = "The owner does not exist", body = Response<ErrorResponse>),
+    ),
+    security(("github" = []))
+)]
+pub async fn create_api_key(
+    auth_user: AuthUser,
+    State(state): State<AppState>,
+    Valid(payload): Valid<CreateApiKeyRequest>,
+) -> Result<Json<Response<CreateApiKeyResponse>>, ApiError> {
+    if !auth_user.is_admin(&state.config) || auth_user.api_key_id.is_some() {
+        return Err(ApiError::Forbidden("Only admins can create API keys"));
+    }
+
+    if state
+        .store
+        .get_user_by_id(payload.owner_user_id.clone())
+        .await
+        .is_err()
+    {
+        return Err(ApiError::NotFound("Owner user"));
+    }
+
+    let key = generate_api_key();
+    let api_key = state
+        .store
+        .create_api_key(
+            ApiKey {
+                id: Uuid::new_v4().to_string(),
+                user_id: payload.owner_user_id,
+                name: payload.name,
+                key_hash: hash_secret(&key),
+                key_prefix: key[..API_KEY_PREFIX.len() + 8].to_string(),
+                drips_per_day: payload.drips_per_day,
+                revoked: false,
+            },
+            payload.quotas,
+        )
+        .await
+        .map_err(|e| {
+            error!("Failed to create api key {}", e);
+            ApiError::Internal("Failed to create API key")
+        })?;
+
+    Ok(Response::ok(CreateApiKeyResponse { key, api_key }))
+}
+
+/// Lists the API keys owned by the authenticated user.
+#[utoipa::path(
+    get,
+    path = "/api-keys",
+    tag = "api-keys",
+    responses(
+        (status = 200, description = "Keys of the user, without the keys themselves", body = Response<Vec<ApiKey>>),
+        (status = 401, description = "Not signed in with GitHub", body = Response<ErrorResponse>),
+    ),
+    security(("github" = []))
+)]
+pub async fn api_keys(
+    auth_user: AuthUser,
+    State(state): State<AppState>,
+) -> Result<Json<Response<Vec<ApiKey>>>, ApiError> {
+    if !auth_user.is_github_authenticated {
+        return Err(ApiError::Unauthorized("Please sign in with Github"));
+    }
+
+    let keys = state
+        .store
+        .get_api_keys_by_user(auth_user.user_id)
+        .await
+        .map_err(|e| {
+            error!("Failed to fetch api keys {}", e);
+            ApiError::Internal("Failed to fetch API keys")
+        })?;
+
+    Ok(Response::ok(keys))
+}
+
+/// Revokes an API key. Allowed for the key's owner and for admins.
+#[utoipa::path(
+    delete,
+    path = "/api-keys/{id}",
+    tag = "api-keys",
+    params(("id" = String, Path)),
+    responses(
+        (status = 200, description = "The revoked key", body = Response<ApiKey>),
+        (status = 403, description = "Neither the owner nor an admin", body = Response<ErrorResponse>),
+        (status = 404, description = "No such key", body = Response<ErrorResponse>),
+    ),
+    security(("github" = []))
+)]
+pub async fn revoke_api_key(
+    auth_user: AuthUser,
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+) -> Result<Json<Response<ApiKey>>, ApiError> {
+    let api_key = state
+        .store
+        .get_api_key_by_id(id.clone())
+        .await
+        .map_err(|_| ApiError::NotFound("API key"))?;
+
+    if !auth_user.is_github_authenticated
+        || (api_key.user_id != auth_user.user_id && !auth_user.is_admin(&state.config))
+    {
+        return Err(ApiError::Forbidden("Not allowed to revoke this key"));
+    }
+
+    state.store.revoke_api_key(id).await.map_err(|e| {
+        error!("Failed to revoke api key {}", e);
+        ApiError::Internal("Failed to revoke API key")
+    })?;
+
+    Ok(Response::ok(ApiKey {
+        revoked: true,
+        ..api_key
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_api_key_hash() {
+        let key = generate_api_key();
+        assert!(key.starts_with(API_KEY_PREFIX));
+        assert_eq!(key.len(), API_KEY_PREFIX.len() + 64);
+        assert_eq!(hash_secret(&key), hash_secret(&key));
+        assert_ne!(hash_secret(&key), hash_secret(&generate_api_key()));
+    }
+}
@@ -1,246 +1,295 @@
-use std::time::Duration;
-
-use crate::{executor::ErrorResponse, store::Store};
-use axum::{
-    extract::{Query, State},
-    http::StatusCode,
-    Error, Json,
-};
-use chrono::Utc;
-use jsonwebtoken::{decode, encode, Algorithm, DecodingKey, EncodingKey, Header, Validation};
-use reqwest::{header, Client};
-use serde::{Deserialize, Serialize};
-use tracing::error;
+use crate::store::Store;
+use axum::{extract::State, Json};
+use tracing::{error, warn};
+use utoipa::ToSchema;
 use uuid::Uuid;
 
-use crate::{handlers::response::Response, store::User, AppState};
+use crate::{
+    executor::ErrorResponse,
+    github::{self, GithubClient, GithubError},
+    handlers::response::Response,
+    session::{self, SessionError, TokenError, Tokens},
+    store::User,
+    AppState,
+};
 
-use super::response::res_err;
+use super::{
+    error::ApiError,
+    validation::{Valid, Validate},
+};
 
-#[derive(serde::Deserialize)]
-pub struct AuthGithubQuery {
+/// Sent in the body rather than the query, so that the code and state token
+/// stay out of access logs and traces
+#[derive(Debug, serde::Deserialize, ToSchema)]
+pub struct AuthGithubRequest {
+    /// Code GitHub redirected back with
     pub code: String,
+    /// State GitHub redirected back with
+    pub state: String,
+    /// `state_token` of the `GET /auth/github/start` that began this login
+    pub state_token: String,
 }
 
-#[derive(serde::Serialize)]
-pub struct AuthGithubResponse {
-    pub token: String,
+#[derive(serde::Serialize, ToSchema)]
+pub struct GithubLoginStartResponse {
+    /// Where to send the user to sign in on GitHub
+    pub authorize_url: String,
+    /// Keep until GitHub redirects back and send it along with the code, it
+    /// ties the redirect to this login and holds its PKCE verifier
+    pub state_token: String,
 }
 
-pub async fn auth(
-    State(state): State<AppState>,
-    Query(query): Query<AuthGithubQuery>,
-) -> Result<Json<Response<AuthGithubResponse>>, (StatusCode, Json<Response<ErrorResponse>>)> {
-    let code = query.code;
-    if code.is_empty() {
-        return Err((StatusCode::BAD_REQUEST, res_err("No code provided")));
-    }
+#[derive(serde::Serialize, ToSchema)]
+pub struct SessionTokensResponse {
+    /// Access token to send as `Authorization: Bearer <token>`, short lived
+    pub token: String,
+    /// Trades for new tokens on `POST /auth/refresh`, works once
+    pub refresh_token: String,
+    /// Seconds until `token` expires
+    pub expires_in: i64,
+}
 
-    let access_token = get_access_token_from_code(
-        &state.config.github_client_id,
-        &state.config.github_client_secret,
-        &code,
-        &state.config.github_redirect_uri,
-    )
-    .await
-    .map_err(|e| {
-        (
-            StatusCode::INTERNAL_SERVER_ERROR,
-            res_err(e.to_string().as_str()),
-        )
-    })?;
-    let user = get_user_info(&access_token).await.map_err(|e| {
-        (
-            StatusCode::INTERNAL_SERVER_ERROR,
-            res_err(e.to_string().as_str()),
-        )
-    })?;
-    if let Err(e) = state.store.create_user(user.clone()).await {
-        if let sqlx::Error::Database(db_err) = &e {
-            if db_err.code().map(|code| code == "23505").unwrap_or(false) {
-                let existing_user = state
-                    .store
-                    .get_user_by_github_id(user.github_id.clone())
-                    .await
-                    .map_err(|e| {
-                        (
-                            StatusCode::INTERNAL_SERVER_ERROR,
-                            res_err(e.to_string().as_str()),
-                        )
-                    })?;
-                // Use existing user details
-                let token =
-                    generate_jwt(&existing_user.id, &state.config.jwt_secret).map_err(|e| {
-                        error!("Error generating jwt {}", e);
-                        (
-                            StatusCode::INTERNAL_SERVER_ERROR,
-                            res_err("Failed to authenticate user!"),
-                        )
-                    })?;
-                return Ok(Response::ok(AuthGithubResponse { token }));
-            }
-        } else {
-            return Err((
-                StatusCode::INTERNAL_SERVER_ERROR,
-                res_err(e.to_string().as_str()),
-            ));
+impl From<Tokens> for SessionTokensResponse {
+    fn from(tokens: Tokens) -> Self {
+        SessionTokensResponse {
+            token: tokens.access_token,
+            refresh_token: tokens.refresh_token,
+            expires_in: tokens.expires_in,
         }
     }
+}
 
-    let token = generate_jwt(&user.id, &state.config.jwt_secret).map_err(|e| {
-        error!("Error generating jwt {}", e);
-        (
-            StatusCode::INTERNAL_SERVER_ERROR,
-            res_err("Failed to authenticate user!"),
-        )
-    })?;
+#[derive(Debug, serde::Deserialize, ToSchema)]
+pub struct RefreshRequest {
+    pub refresh_token: String,
+}
 
-    Ok(Response::ok(AuthGithubResponse { token }))
+#[derive(Debug, serde::Deserialize, ToSchema)]
+pub struct LogoutRequest {
+    pub refresh_token: String,
+    /// End every session of the user instead of only this one
+    #[serde(default)]
+    pub everywhere: bool,
 }
 
-async fn get_access_token_from_code(
-    client_id: &str,
-    client_secret: &str,
-    code: &str,
-    redirect_uri: &str,
-) -> Result<String, Error> {
-    let client = Client::new();
-    println!("Code: {}", code);
-    let query_params = [
-        ("client_id", client_id),
-        ("client_secret", client_secret),
-        ("code", code),
-        ("redirect_uri", redirect_uri),
-    ];
-    let response = client
-        .post("https://github.com/login/oauth/access_token")
-        .query(&query_params)
-        .header("Accept", "application/json")
-        .send()
-        .await
-        .expect("Failed to send request");
-
-    if response.status().is_success() {
-        let response_body = response.text().await.expect("Failed to get response body");
-        let response_json: serde_json::Value =
-            serde_json::from_str(&response_body).expect("Failed to parse response body");
-        println!("Response: {:?}", response_json);
-        let access_token = match response_json["access_token"].as_str() {
-            Some(token) => token.to_string(),
-            None => {
-                return Err(Error::new("Failed to get access token"));
-            }
-        };
-        Ok(access_token)
-    } else {
-        Err(Error::new("Failed to get access token"))
-    }
+#[derive(Debug, serde::Serialize, ToSchema)]
+pub struct LogoutResponse {
+    /// Number of sessions ended, 0 for an unknown or already ended session
+    pub revoked_sessions: u64,
 }
 
-async fn get_user_info(access_token: &str) -> Result<User, Error> {
-    let client = Client::new();
-    let response = client
-        .get("https://api.github.com/user")
-        .header(header::ACCEPT, "application/json")
-        .header(header::AUTHORIZATION, format!("Bearer {}", access_token))
-        .header(header::USER_AGENT, "axum-github-auth")
-        .header(header::CONTENT_TYPE, "application/json")
-        .send()
-        .await
-        .expect("Failed to send request");
-
-    if response.status().is_success() {
-        let response_body = response.text().await.expect("Failed to get response body");
-        let response_json: serde_json::Value =
-            serde_json::from_str(&response_body).expect("Failed to parse response body");
-
-        let user = User {
-            id: Uuid::new_v4().to_string(),
-            username: response_json["login"].as_str().unwrap().to_string(),
-            avatar_url: response_json["avatar_url"].as_str().unwrap().to_string(),
-            email: Some(
-                response_json["email"]
-                    .as_str()
-                    .map_or_else(|| "".to_string(), |s| s.to_string()),
-            ),
-            github_id: response_json["id"].as_i64().unwrap_or_default().to_string(),
-            access_token: access_token.to_string(),
-        };
-        Ok(user)
-    } else {
-        Err(Error::new(
-            format!(
-                "Failed to get user info: {}",
-                response.text().await.unwrap()
-            )
-            .as_str(),
-        ))
+impl Validate for AuthGithubRequest {
+    fn validate(&mut self) -> Result<(), ApiError> {
+        if self.code.is_empty() || self.state.is_empty() || self.state_token.is_empty() {
+            return Err(ApiError::InvalidRequest(
+                "code, state and state_token are required".to_string(),
+            ));
+        }
+        Ok(())
     }
 }
 
-#[derive(Debug, Serialize, Deserialize)]
-pub struct Claims {
-    pub sub: String, // User ID (or other unique identifier)
-    pub exp: usize,  // Expiration timestamp
+impl Validate for RefreshRequest {
+    fn validate(&mut self) -> Result<(), ApiError> {
+        if self.refresh_token.is_empty() {
+            return Err(ApiError::InvalidRequest(
+                "No refresh token provided".to_string(),
+            ));
+        }
+        Ok(())
+    }
 }
 
-fn generate_jwt(user_id: &str, secret_key: &str) -> Result<String, jsonwebtoken::errors::Error> {
-    let expiration = Utc::now() + Duration::from_secs(24 * 60 * 60); // 1 day
+impl Validate for LogoutRequest {
+    fn validate(&mut self) -> Result<(), ApiError> {
+        if self.refresh_token.is_empty() {
+            return Err(ApiError::InvalidRequest(
+                "No refresh token provided".to_string(),
+            ));
+        }
+        Ok(())
+    }
+}
 
-    let claims = Claims {
-        sub: user_id.to_string(),
-        exp: expiration.timestamp() as usize,
+/// Starts a GitHub login, the user is sent to `authorize_url` and comes
+/// back to the redirect URI with a code for `POST /auth`
+#[utoipa::path(
+    get,
+    path = "/auth/github/start",
+    tag = "auth",
+    responses(
+        (status = 200, description = "Login started", body = Response<GithubLoginStartResponse>),
+        (status = 503, description = "GitHub login is not configured", body = Response<ErrorResponse>),
+    )
+)]
+pub async fn github_start(
+    State(state): State<AppState>,
+) -> Result<Json<Response<GithubLoginStartResponse>>, ApiError> {
+    let Some(config) = &state.config.auth.github else {
+        return Err(ApiError::FeatureDisabled("GitHub login is disabled"));
     };
 
-    // Encode the JWT token
-    let header = Header::new(Algorithm::HS256);
-    let token = encode(
-        &header,
-        &claims,
-        &EncodingKey::from_secret(secret_key.as_ref()),
-    )?;
-
-    Ok(token)
+    let login = github::start_login(&state.config.auth, config).map_err(|e| {
+        error!("Error signing the login state {}", e);
+        ApiError::Internal("Failed to start the login")
+    })?;
+    Ok(Response::ok(GithubLoginStartResponse {
+        authorize_url: login.authorize_url,
+        state_token: login.state_token,
+    }))
 }
 
-pub fn validate_and_decode_jwt(
-    token: &str,
-    secret_key: &str,
-) -> Result<Claims, jsonwebtoken::errors::Error> {
-    let token = decode::<Claims>(
-        &token,
-        &DecodingKey::from_secret(secret_key.as_ref()),
-        &Validation::default(),
+/// Signs in with the code of a GitHub OAuth redirect, which must carry the
+/// state of a login begun on `GET /auth/github/start`
+#[utoipa::path(
+    post,
+    path = "/auth",
+    tag = "auth",
+    request_body = AuthGithubRequest,
+    responses(
+        (status = 200, description = "Signed in", body = Response<SessionTokensResponse>),
+        (status = 400, description = "No code, state or state token", body = Response<ErrorResponse>),
+        (status = 401, description = "The state does not match, the login expired or GitHub rejected the code", body = Response<ErrorResponse>),
+        (status = 502, description = "GitHub failed or could not be reached", body = Response<ErrorResponse>),
+        (status = 503, description = "GitHub login is not configured", body = Response<ErrorResponse>),
+    )
+)]
+pub async fn auth(
+    State(state): State<AppState>,
+    Valid(payload): Valid<AuthGithubRequest>,
+) -> Result<Json<Response<SessionTokensResponse>>, ApiError> {
+    let Some(config) = &state.config.auth.github else {
+        return Err(ApiError::FeatureDisabled("GitHub login is disabled"));
+    };
+
+    // a redirect that was not started by this client is refused before the
+    // code is ever sent to GitHub
+    let code_verifier = github::finish_login(
+        &state.config.auth,
+        &payload.state_token,
+        &payload.state,
     )
-    .map_err(|e| e)?;
-    if token.claims.exp < Utc::now().timestamp() as usize {
-        return Err(jsonwebtoken::errors::Error::from(
-            jsonwebtoken::errors::ErrorKind::ExpiredSignature,
-        ));
+    .map_err(|e| match e {
+        TokenError::Expired => ApiError::Unauthorized("Login expired, please try again"),
+        TokenError::Invalid => ApiError::Unauthorized("Invalid login state, please try again"),
+    })?;
+
+    let client = GithubClient::new(config).map_err(github_error)?;
+    let access_token = client
+        .exchange_code(&payload.code, &code_verifier)
+        .await
+        .map_err(github_error)?;
+    let github_user = client.get_user(&access_token).await.map_err(github_error)?;
+
+    let mut user = User {
+        id: Uuid::new_v4().to_string(),
+        username: github_user.login,
+        avatar_url: github_user.avatar_url,
+        email: github_user.email,
+        github_id: github_user.id.to_string(),
+        access_token,
+    };
+    if let Err(e) = state.store.create_user(user.clone()).await {
+        match &e {
+            sqlx::Error::Database(db_err)
+                if db_err.code().map(|code| code == "23505").unwrap_or(false) =>
+            {
+                // Use existing user details
+                user = state
+                    .store
+                    .get_user_by_github_id(user.github_id.clone())
+                    .await
+                    .map_err(|e| {
+                        error!("Error fetching user by github id {}", e);
+                        ApiError::Internal("Failed to authenticate user!")
+                    })?;
+            }
+            _ => {
+                error!("Error creating user {}", e);
+                return Err(ApiError::Internal("Failed to authenticate user!"));
+            }
+        }
     }
-    Ok(token.claims)
+
+    let tokens = session::start(&state.store, &state.config.auth, &user.id)
+        .await
+        .map_err(|e| {
+            error!("Error starting a session {}", e);
+            ApiError::Internal("Failed to authenticate user!")
+        })?;
+    Ok(Response::ok(tokens.into()))
 }
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-
-    #[tokio::test]
-    async fn test_get_user_info() {
-        // give a valid access token
-        let user = get_user_info("").await.unwrap();
-        println!("{:?}", user);
-        // give a valid username
-        assert_eq!(user.username, "");
+fn github_error(e: GithubError) -> ApiError {
+    match e {
+        GithubError::Rejected(_) => {
+            warn!("{}", e);
+            ApiError::Unauthorized("GitHub login failed, please try again")
+        }
+        e => {
+            error!("{}", e);
+            ApiError::Upstream("GitHub is unavailable, please try again")
+        }
     }
+}
 
-    #[tokio::test]
-    async fn test_generate_jwt() {
-        let id = "12345678900987654321";
-        let secret = "qV2zScNYyR6bB6";
-        let token = generate_jwt(id, secret).unwrap();
-        println!("{}", token);
-        let claims = validate_and_decode_jwt(&token, secret).unwrap();
-        assert_eq!(claims.sub, id);
+/// Trades a refresh token for a new access token and refresh token. Each
+/// refresh token works once; presenting one again ends its session.
+#[utoipa::path(
+    post,
+    path = "/auth/refresh",
+    tag = "auth",
+    request_body = RefreshRequest,
+    responses(
+        (status = 200, description = "New tokens", body = Response<SessionTokensResponse>),
+        (status = 400, description = "No refresh token", body = Response<ErrorResponse>),
+        (status = 401, description = "Unknown, expired, revoked or reused refresh token", body = Response<ErrorResponse>),
+    )
+)]
+pub async fn refresh(
+    State(state): State<AppState>,
+    Valid(payload): Valid<RefreshRequest>,
+) -> Result<Json<Response<SessionTokensResponse>>, ApiError> {
+    match session::refresh(&state.store, &state.config.auth, &payload.refresh_token).await {
+        Ok(tokens) => Ok(Response::ok(tokens.into())),
+        Err(SessionError::InvalidRefreshToken) => Err(ApiError::Unauthorized(
+            "Invalid refresh token, please sign in again",
+        )),
+        Err(SessionError::RefreshTokenReused) => {
+            warn!("Refresh token reused, its session was revoked");
+            Err(ApiError::Unauthorized(
+                "Invalid refresh token, please sign in again",
+            ))
+        }
+        Err(e) => {
+            error!("Error refreshing a session {}", e);
+            Err(ApiError::Internal("Failed to refresh the session"))
+        }
     }
 }
+
+/// Ends the session of a refresh token, its access tokens stop working
+/// right away
+#[utoipa::path(
+    post,
+    path = "/auth/logout",
+    tag = "auth",
+    request_body = LogoutRequest,
+    responses(
+        (status = 200, description = "Sessions ended", body = Response<LogoutResponse>),
+        (status = 400, description = "No refresh token", body = Response<ErrorResponse>),
+    )
+)]
+pub async fn logout(
+    State(state): State<AppState>,
+    Valid(payload): Valid<LogoutRequest>,
+) -> Result<Json<Response<LogoutResponse>>, ApiError> {
+    let revoked_sessions =
+        session::revoke(&state.store, &payload.refresh_token, payload.everywhere)
+            .await
+            .map_err(|e| {
+                error!("Error ending a session {}", e);
+                ApiError::Internal("Failed to sign out")
+            })?;
+    Ok(Response::ok(LogoutResponse { revoked_sessions }))
+}
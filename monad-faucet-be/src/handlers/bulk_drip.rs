@@ -0,0 +1,111 @@
+use std::{net::SocketAddr, time::Duration};
+
+use axum::{
+    extract::{ConnectInfo, State},
+    Json,
+};
+use tracing::error;
+use utoipa::ToSchema;
+
+use crate::{
+    events::FaucetEvent,
+    executor::{BulkDrip, BulkDripRequest, BulkDripResult, ErrorResponse},
+    telemetry::QueueTrace,
+    AppState,
+};
+
+use super::{
+    error::ApiError,
+    middleware::{AuthUser, RequestId},
+    response::Response,
+    validation::{Valid, Validate},
+};
+
+/// Most drips accepted in a single bulk job
+const MAX_BULK_DRIPS: usize = 100;
+
+#[derive(Debug, serde::Deserialize, ToSchema)]
+pub struct BulkDripPayload {
+    pub drips: Vec<BulkDrip>,
+}
+
+impl Validate for BulkDripPayload {
+    fn validate(&mut self) -> Result<(), ApiError> {
+        if self.drips.is_empty() || self.drips.len() > MAX_BULK_DRIPS {
+            return Err(ApiError::InvalidRequest(format!(
+                "Between 1 and {} drips can be sent at once",
+                MAX_BULK_DRIPS
+            )));
+        }
+        self.drips.iter_mut().try_for_each(BulkDrip::validate)
+    }
+}
+
+/// Funds a list of addresses in one executor job. Requires
+/// `Authorization: ApiKey ...` and is bounded by that key's quotas.
+#[utoipa::path(
+    post,
+    path = "/drip/bulk",
+    tag = "drips",
+    request_body = BulkDripPayload,
+    responses(
+        (status = 200, description = "Outcome per drip", body = Response<Vec<BulkDripResult>>),
+        (status = 400, description = "No drips, too many, or an invalid address", body = Response<ErrorResponse>),
+        (status = 401, description = "Missing or invalid API key", body = Response<ErrorResponse>),
+        (status = 503, description = "Shutting down", body = Response<ErrorResponse>),
+    ),
+    security(("api_key" = []))
+)]
+pub async fn bulk_drip(
+    auth_user: AuthUser,
+    State(state): State<AppState>,
+    ConnectInfo(addr): ConnectInfo<SocketAddr>,
+    RequestId(request_id): RequestId,
+    Valid(payload): Valid<BulkDripPayload>,
+) -> Result<Json<Response<Vec<BulkDripResult>>>, ApiError> {
+    let Some(api_key_id) = auth_user.api_key_id else {
+        return Err(ApiError::Unauthorized("An API key is required"));
+    };
+
+    if state.executor.is_shutting_down() {
+        return Err(ApiError::ShuttingDown);
+    }
+
+    let (tx, rx) = tokio::sync::oneshot::channel();
+    let queued: Vec<_> = payload
+        .drips
+        .iter()
+        .map(|drip| FaucetEvent::DripQueued {
+            token_address: drip.token_address.clone(),
+            to: drip.to.clone(),
+        })
+        .collect();
+    state.executor.enqueue(
+        &state.executor.bulk_queue,
+        (
+            BulkDripRequest {
+                api_key_id,
+                drips: payload.drips,
+                ip: addr.ip().into(),
+                request_id: Some(request_id),
+            },
+            tx,
+            QueueTrace::new("bulk_drip"),
+        ),
+    )?;
+    for event in queued {
+        state.executor.events.publish(event);
+    }
+
+    match tokio::time::timeout(Duration::from_secs(300), rx).await {
+        Ok(Ok(results)) => Ok(Response::ok(results)),
+        Ok(Err(e)) => {
+            error!("Error at /drip/bulk {}", e);
+            Err(ApiError::Internal("Something went wrong"))
+        }
+        Err(e) => {
+            error!("Error at /drip/bulk {}", e);
+            Err(ApiError::Timeout)
+        }
+    }
+}
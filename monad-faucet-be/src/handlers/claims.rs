@@ -0,0 +1,38 @@
+use axum::{
+    extract::{Path, State},
+    Json,
+};
+use tracing::error;
+
+use crate::executor::ErrorResponse;
+use crate::store::{Store, TokenTransfer};
+use crate::AppState;
+
+use super::{error::ApiError, response::Response, validation::normalize_address};
+
+/// Latest transfers received by `address`, including scheduled top-ups,
+/// API key drips and deploy allocations (see `kind`)
+#[utoipa::path(
+    get,
+    path = "/claims/{address}",
+    tag = "drips",
+    params(("address" = String, Path, description = "Receiving wallet, in any case")),
+    responses(
+        (status = 200, description = "Latest transfers to the wallet", body = Response<Vec<TokenTransfer>>),
+        (status = 400, description = "Invalid wallet address", body = Response<ErrorResponse>),
+    )
+)]
+pub async fn claims(
+    State(state): State<AppState>,
+    Path(address): Path<String>,
+) -> Result<Json<Response<Vec<TokenTransfer>>>, ApiError> {
+    let address = normalize_address("wallet", &address)?;
+    let transfers = match state.store.get_token_transfers_to(address).await {
+        Ok(transfers) => transfers,
+        Err(e) => {
+            error!("Error fetching claims {}", e);
+            return Err(ApiError::Internal("Failed to fetch claims"));
+        }
+    };
+    Ok(Response::ok(transfers))
+}
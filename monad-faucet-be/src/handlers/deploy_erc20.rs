@@ -1,149 +1,411 @@
 use axum::body::Bytes;
-use std::time::Duration;
+use chrono::{DateTime, Utc};
+use futures::{Stream, StreamExt};
+use tokio::sync::broadcast::{self, error::RecvError};
 
-use crate::executor::ErrorResponse;
-use crate::executor::TokenDeployRequest;
+use crate::constants;
+use crate::events::FaucetEvent;
+use crate::executor::{supply_allocations, ErrorResponse, TokenDeployRequest};
+use crate::idempotency::{self, Claim};
+use crate::logo::{self, LogoError};
+use crate::store::{DeployJob, DeployStep, PgStore, Store};
+use crate::telemetry::QueueTrace;
 use crate::AppState;
-use axum::extract::State;
-use axum::http::StatusCode;
+use axum::extract::{Path, State};
+use axum::response::sse::{Event, KeepAlive, Sse};
 use axum::response::Json;
 use uuid::Uuid;
 
-use super::middleware::AuthUser;
-use super::response::res_err;
+use super::error::ApiError;
+use super::middleware::{AuthUser, IdempotencyKey, RequestId};
 use super::response::Response;
-use super::response::ResponseStatus;
+use super::validation::{normalize_address, Validate};
+use axum::extract::multipart::MultipartError;
 use axum::extract::Multipart;
+use axum::http::StatusCode;
+use utoipa::ToSchema;
 
 use tracing::error;
 
-#[derive(Debug, serde::Serialize)]
+#[derive(Debug, serde::Serialize, ToSchema)]
 pub struct DeployErc20Response {
-    pub contract_address: String,
+    /// Follow the deploy through `/v1/deploy/jobs/:id`
+    pub job_id: String,
 }
 
-#[derive(Debug, serde::Deserialize)]
+#[derive(Debug, serde::Deserialize, ToSchema)]
 pub struct TokenDeployRequestData {
     pub name: String,
     pub symbol: String,
+    /// In base units
     pub total_supply: String,
     pub decimals: u8,
     pub deployer_address: String,
+    #[schema(value_type = String, example = "203.0.113.7")]
     pub ip: ipnetwork::IpNetwork,
 }
 
+/// Multipart body of `POST /deploy/erc20`, only described for the docs since
+/// the handler reads the parts itself
+#[derive(ToSchema)]
+#[allow(dead_code)]
+struct DeployErc20Form {
+    /// [`TokenDeployRequestData`] as JSON
+    #[schema(content_media_type = "application/json")]
+    data: String,
+    /// The logo, a PNG, JPEG, WebP or SVG image
+    #[schema(value_type = String, format = Binary)]
+    file: Vec<u8>,
+}
+
+impl Validate for TokenDeployRequestData {
+    fn validate(&mut self) -> Result<(), ApiError> {
+        self.deployer_address = normalize_address("deployer", &self.deployer_address)?;
+        if self.name.is_empty()
+            || self.symbol.is_empty()
+            || !(1..=constants::faucet::MAX_TOKEN_DECIMALS).contains(&self.decimals)
+        {
+            return Err(ApiError::InvalidRequest(format!(
+                "A token needs a name, a symbol and 1 to {} decimals",
+                constants::faucet::MAX_TOKEN_DECIMALS
+            )));
+        }
+        let supply = match self.total_supply.parse::<u128>() {
+            Ok(supply) if supply > 0 => supply,
+            _ => {
+                return Err(ApiError::InvalidAmount(
+                    "Total supply must be a positive integer".to_string(),
+                ))
+            }
+        };
+        if supply_allocations(supply, self.decimals).is_none() {
+            return Err(ApiError::InvalidAmount(
+                "Total supply is too large for the decimals".to_string(),
+            ));
+        }
+        Ok(())
+    }
+}
+
+/// Queues the deploy of an ERC20 token, answering with the job to follow
+#[utoipa::path(
+    post,
+    path = "/deploy/erc20",
+    tag = "deploy",
+    request_body(content = DeployErc20Form, content_type = "multipart/form-data"),
+    params(
+        ("Idempotency-Key" = Option<String>, Header, description = "Retries with the same key get the first job instead of a second deploy, signed in callers only"),
+    ),
+    responses(
+        (status = 200, description = "Deploy queued", body = Response<DeployErc20Response>),
+        (status = 400, description = "Missing or invalid token data or logo", body = Response<ErrorResponse>),
+        (status = 401, description = "Idempotency-Key sent without signing in", body = Response<ErrorResponse>),
+        (status = 409, description = "A token with the symbol exists", body = Response<ErrorResponse>),
+        (status = 413, description = "Logo too large", body = Response<ErrorResponse>),
+        (status = 422, description = "Idempotency-Key reused", body = Response<ErrorResponse>),
+        (status = 503, description = "Deploys disabled or shutting down", body = Response<ErrorResponse>),
+    ),
+    security((), ("github" = []))
+)]
 #[allow(unused_variables)]
 pub async fn deploy_erc20(
     auth_user: AuthUser,
     State(state): State<AppState>,
+    RequestId(request_id): RequestId,
+    IdempotencyKey(idempotency_key): IdempotencyKey,
     mut multipart: Multipart,
-) -> Result<Json<Response<DeployErc20Response>>, (StatusCode, Json<Response<ErrorResponse>>)> {
+) -> Result<Json<Response<DeployErc20Response>>, ApiError> {
     // if !auth_user.is_github_authenticated {
-    //     return Err((
-    //         StatusCode::UNAUTHORIZED,
-    //         res_err("Please authenticate with Github to deploy your tokens"),
+    //     return Err(ApiError::Unauthorized(
+    //         "Please authenticate with Github to deploy your tokens",
     //     ));
     // }
-    let mut token_data: Option<TokenDeployRequestData> = None;
+    if state.executor.is_shutting_down() {
+        return Err(ApiError::ShuttingDown);
+    }
+    idempotency::check_caller(&auth_user, idempotency_key.as_ref())?;
+    if state.config.storage.is_none() {
+        return Err(ApiError::FeatureDisabled("Token deploys are disabled"));
+    }
+    let mut token_data: Option<(TokenDeployRequestData, String)> = None;
     let mut file_data: Option<Bytes> = None;
-    let mut unique_file_name: Option<String> = None;
 
-    while let Some(field) = multipart.next_field().await.unwrap() {
+    loop {
+        let field = match multipart.next_field().await {
+            Ok(Some(field)) => field,
+            Ok(None) => break,
+            Err(e) => {
+                error!("Error deploying erc20 {}", e);
+                return Err(multipart_error(e));
+            }
+        };
         let field_name = field.name().unwrap_or("").to_string();
 
         if field_name == "file" {
-            let _file_name = field.file_name().unwrap().to_string();
-            let content_type = field.content_type().unwrap().to_string();
-            let data = field.bytes().await.unwrap();
-
-            let file_ext = _file_name.split('.').last().unwrap();
-
-            unique_file_name = Some(format!("{}.{}", Uuid::new_v4(), file_ext));
-            file_data = Some(data);
+            // the file name and content type sent by the client are ignored,
+            // the real format is sniffed from the bytes when processing
+            file_data = match field.bytes().await {
+                Ok(data) => Some(data),
+                Err(e) => {
+                    error!("Error deploying erc20 {}", e);
+                    return Err(multipart_error(e));
+                }
+            };
         } else if field_name == "data" {
             let json_data = match field.text().await {
                 Ok(text) => text,
                 Err(e) => {
                     error!("Error deploying erc20 {}", e);
-                    return Err((
-                        StatusCode::BAD_REQUEST,
-                        res_err(&format!("Failed to read JSON data: {}", e)),
-                    ));
+                    return Err(multipart_error(e));
                 }
             };
 
-            token_data = match serde_json::from_str(&json_data) {
-                Ok(data) => Some(data),
+            token_data = match serde_json::from_str::<TokenDeployRequestData>(&json_data) {
+                Ok(mut data) => {
+                    data.validate()?;
+                    Some((data, json_data))
+                }
                 Err(e) => {
-                    error!("Error deploying erc20 {}", e);
-                    return Err((
-                        StatusCode::BAD_REQUEST,
-                        res_err(&format!("Invalid JSON data: {}", e)),
-                    ));
+                    return Err(ApiError::InvalidRequest(format!(
+                        "Invalid JSON data: {}",
+                        e
+                    )));
                 }
             };
         }
     }
 
-    if let (Some(token), Some(file_name), Some(data)) = (token_data, unique_file_name, file_data) {
-        let (tx, rx) = tokio::sync::oneshot::channel();
+    let Some(data) = file_data else {
+        return Err(ApiError::InvalidRequest("No file uploaded".to_string()));
+    };
+    let Some((token, token_json)) = token_data else {
+        return Err(ApiError::InvalidRequest(
+            "No token data provided".to_string(),
+        ));
+    };
+    let request_hash = idempotency::request_hash(&[token_json.as_bytes(), &data[..]]);
+
+    // decoding and re-encoding images is CPU bound, keep it off the runtime
+    let logo = match tokio::task::spawn_blocking(move || logo::process(&data)).await {
+        Ok(Ok(logo)) => logo,
+        Ok(Err(e @ LogoError::TooLarge)) => return Err(ApiError::PayloadTooLarge(e.to_string())),
+        Ok(Err(e)) => return Err(ApiError::InvalidRequest(e.to_string())),
+        Err(e) => {
+            error!("Error processing logo {}", e);
+            return Err(ApiError::Internal("Something went wrong"));
+        }
+    };
 
+    let job_id = Uuid::new_v4().to_string();
+    let scope = format!("deploy:{}", auth_user.user_id);
+    if let Some(key) = &idempotency_key {
+        match idempotency::claim(
+            &state.store,
+            scope.clone(),
+            key.clone(),
+            request_hash,
+            Some(job_id.clone()),
+        )
+        .await
         {
-            let supply = token.total_supply.parse::<u128>().unwrap_or_default();
-            let mut queue = state.executor.deploy_queue.lock().map_err(|e| {
-                error!("Error deploying erc20 {}", e);
-                (
-                    StatusCode::INTERNAL_SERVER_ERROR,
-                    res_err(&format!("Something went wrong")),
-                )
-            })?;
-            let token = TokenDeployRequest {
-                file_name,
-                file_data: data.to_vec(),
+            Ok(Claim::New) => {}
+            // the job was created along with the key, follow it for its status
+            Ok(Claim::Repeat(record)) => {
+                return Ok(Response::ok(DeployErc20Response {
+                    job_id: record.deploy_job_id.unwrap_or_default(),
+                }))
+            }
+            Ok(Claim::Mismatch) => return Err(idempotency::key_reused().into()),
+            Err(e) => {
+                error!("Failed to claim idempotency key {}", e);
+                return Err(ApiError::Internal("Something went wrong"));
+            }
+        }
+    }
+
+    let supply = token.total_supply.parse::<u128>().unwrap_or_default();
+    let now = Utc::now();
+    let job = DeployJob {
+        id: job_id,
+        // anonymous callers have an empty id, which no user row matches
+        user_id: Some(auth_user.user_id).filter(|id| !id.is_empty()),
+        symbol: token.symbol.clone(),
+        step: DeployStep::QUEUED,
+        deploy_tx_hash: None,
+        contract_address: None,
+        allocation_tx_hashes: vec![],
+        error: None,
+        error_code: None,
+        created_at: now,
+        updated_at: now,
+    };
+    if let Err(e) = state.store.create_deploy_job(job.clone()).await {
+        error!("Error creating deploy job {}", e);
+        if let Some(key) = idempotency_key {
+            if let Err(e) = state.store.delete_idempotency_key(scope, key).await {
+                error!("Failed to release idempotency key {}", e);
+            }
+        }
+        return Err(ApiError::Internal("Something went wrong"));
+    }
+
+    let job_id = job.id.clone();
+    if let Err(e) = state.executor.enqueue(
+        &state.executor.deploy_queue,
+        (
+            TokenDeployRequest {
+                job: job.clone(),
+                logo,
                 name: token.name,
                 symbol: token.symbol,
                 total_supply: supply,
                 decimals: token.decimals,
                 deployer_address: token.deployer_address,
                 ip: token.ip,
-            };
-            queue.push_back((token, tx));
+                request_id: Some(request_id),
+            },
+            QueueTrace::new("deploy"),
+        ),
+    ) {
+        // the job was stored already, fail it so nobody follows it forever
+        let error = ErrorResponse::from(ApiError::from(e));
+        let failed = DeployJob {
+            step: DeployStep::FAILED,
+            error: Some(error.message),
+            error_code: error.code,
+            updated_at: Utc::now(),
+            ..job
+        };
+        if let Err(e) = state.store.update_deploy_job(failed).await {
+            error!("Failed to persist deploy job {}: {}", job_id, e);
+        }
+        if let Some(key) = idempotency_key {
+            if let Err(e) = state.store.delete_idempotency_key(scope, key).await {
+                error!("Failed to release idempotency key {}", e);
+            }
         }
+        return Err(e.into());
+    }
 
-        return match tokio::time::timeout(Duration::from_secs(300), rx).await {
-            Ok(response) => match response {
-                Ok(response) => match response.clone().status {
-                    ResponseStatus::Success => match response.clone().data {
-                        Some(data) => Ok(Response::ok(DeployErc20Response {
-                            contract_address: data.tx_hash,
-                        })),
-                        None => Err((
-                            StatusCode::INTERNAL_SERVER_ERROR,
-                            res_err(&response.error.unwrap().message),
-                        )),
-                    },
-                    ResponseStatus::Error => Err((
-                        StatusCode::INTERNAL_SERVER_ERROR,
-                        res_err(&response.error.unwrap().message),
-                    )),
-                },
-                Err(e) => {
-                    error!("Error deploying erc20 {}", e);
-                    Err((
-                        StatusCode::INTERNAL_SERVER_ERROR,
-                        res_err("Executor dropped the response channel"),
-                    ))
+    Ok(Response::ok(DeployErc20Response { job_id }))
+}
+
+/// Multipart errors describe the request body, e.g. a missing boundary or a
+/// field over the body limit
+fn multipart_error(e: MultipartError) -> ApiError {
+    match e.status() {
+        StatusCode::PAYLOAD_TOO_LARGE => ApiError::PayloadTooLarge(e.body_text()),
+        _ => ApiError::InvalidRequest(e.body_text()),
+    }
+}
+
+async fn find_deploy_job(state: &AppState, id: String) -> Result<DeployJob, ApiError> {
+    state.store.get_deploy_job(id).await.map_err(|e| match e {
+        sqlx::Error::RowNotFound => ApiError::NotFound("Deploy job"),
+        e => {
+            error!("Error fetching deploy job {}", e);
+            ApiError::Internal("Failed to fetch deploy job")
+        }
+    })
+}
+
+/// Current state of a deploy job. Job ids are unguessable and the job only
+/// holds public on-chain data, so no auth is required; this also lets
+/// `EventSource`, which cannot send headers, follow `deploy_job_events`.
+#[utoipa::path(
+    get,
+    path = "/deploy/jobs/{id}",
+    tag = "deploy",
+    params(("id" = String, Path)),
+    responses(
+        (status = 200, description = "The job", body = Response<DeployJob>),
+        (status = 404, description = "No such job", body = Response<ErrorResponse>),
+    )
+)]
+pub async fn deploy_job(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+) -> Result<Json<Response<DeployJob>>, ApiError> {
+    Ok(Response::ok(find_deploy_job(&state, id).await?))
+}
+
+/// State of a `deploy_job_events` stream
+struct JobFollower {
+    id: String,
+    receiver: broadcast::Receiver<FaucetEvent>,
+    store: PgStore,
+    /// The stored job, sent before any live update
+    current: Option<DeployJob>,
+    last_update: DateTime<Utc>,
+    finished: bool,
+}
+
+/// Streams a deploy job as Server-Sent Events, one `progress` event per
+/// change, closing once the job is registered or failed.
+#[utoipa::path(
+    get,
+    path = "/deploy/jobs/{id}/events",
+    tag = "deploy",
+    params(("id" = String, Path)),
+    responses(
+        (status = 200, description = "`progress` events, each a `DeployJob` as JSON", content_type = "text/event-stream", body = String),
+        (status = 404, description = "No such job", body = Response<ErrorResponse>),
+    )
+)]
+pub async fn deploy_job_events(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+) -> Result<Sse<impl Stream<Item = Result<Event, axum::Error>>>, ApiError> {
+    // subscribe before reading the store so no update in between is missed,
+    // updates the stored job already contains are skipped by `updated_at`
+    let receiver = state.executor.events.subscribe();
+    let current = find_deploy_job(&state, id.clone()).await?;
+
+    let follower = JobFollower {
+        id,
+        receiver,
+        store: state.store.clone(),
+        current: Some(current),
+        last_update: DateTime::<Utc>::MIN_UTC,
+        finished: false,
+    };
+
+    let stream = futures::stream::unfold(follower, |mut follower| async move {
+        if follower.finished {
+            return None;
+        }
+
+        let job = match follower.current.take() {
+            Some(job) => job,
+            None => loop {
+                match follower.receiver.recv().await {
+                    Ok(FaucetEvent::DeployProgress(job))
+                        if job.id == follower.id && job.updated_at > follower.last_update =>
+                    {
+                        break job
+                    }
+                    Ok(_) => continue,
+                    // missed some events, catch up from the store
+                    Err(RecvError::Lagged(_)) => {
+                        match follower.store.get_deploy_job(follower.id.clone()).await {
+                            Ok(job) if job.updated_at > follower.last_update => break job,
+                            Ok(_) => continue,
+                            Err(_) => return None,
+                        }
+                    }
+                    Err(RecvError::Closed) => return None,
                 }
             },
-            Err(e) => {
-                error!("Error deploying erc20 {}", e);
-                Err((StatusCode::REQUEST_TIMEOUT, res_err(&"Request timed out")))
-            }
         };
-    }
 
-    return Err((
-        StatusCode::INTERNAL_SERVER_ERROR,
-        res_err("No file uploaded"),
-    ));
+        follower.last_update = job.updated_at;
+        follower.finished = job.step.is_finished();
+        let event = Event::default().event("progress").json_data(&job);
+        Some((event, follower))
+    });
+    // closed on shutdown, followers reconnect and pick up the stored job
+    let executor = state.executor.clone();
+    let stream = stream.take_until(async move { executor.shutdown_started().await });
+
+    Ok(Sse::new(stream).keep_alive(KeepAlive::default()))
 }
@@ -0,0 +1,244 @@
+use axum::{
+    extract::rejection::{JsonRejection, PathRejection},
+    http::StatusCode,
+    response::IntoResponse,
+};
+use chrono::{DateTime, Utc};
+
+use crate::executor::{EnqueueError, ErrorResponse};
+
+use super::response::Response;
+
+/// Error answered by a handler. Every variant has a stable `code` that
+/// clients can match on and a status derived from it; `message` is for
+/// humans and may change.
+#[derive(Debug, Clone)]
+pub enum ApiError {
+    /// Malformed body, path or query, with what was wrong with it
+    InvalidRequest(String),
+    /// The named field is not a 20 byte hex address
+    InvalidAddress(&'static str),
+    InvalidAmount(String),
+    Unauthorized(&'static str),
+    Forbidden(&'static str),
+    /// The named resource, e.g. `"Webhook"`, does not exist or is not visible
+    /// to the user
+    NotFound(&'static str),
+    TokenNotFound,
+    Conflict(String),
+    /// A reloaded config failed validation, with all of its problems
+    InvalidConfig(String),
+    /// The user claimed recently and can claim again at `next_access`
+    CooldownActive {
+        next_access: DateTime<Utc>,
+    },
+    PayloadTooLarge(String),
+    /// Part of the API turned off by the config, e.g. GitHub login
+    FeatureDisabled(&'static str),
+    ShuttingDown,
+    Timeout,
+    /// A service we depend on, e.g. GitHub, failed or answered nonsense
+    Upstream(&'static str),
+    /// Error answered by an executor job, passed on with its own code
+    Job(ErrorResponse),
+    /// Something failed on our side. Only the given message is answered, the
+    /// cause is logged where it happened.
+    Internal(&'static str),
+}
+
+impl ApiError {
+    pub fn code(&self) -> &str {
+        match self {
+            ApiError::InvalidRequest(_) => "INVALID_REQUEST",
+            ApiError::InvalidAddress(_) => "INVALID_ADDRESS",
+            ApiError::InvalidAmount(_) => "INVALID_AMOUNT",
+            ApiError::Unauthorized(_) => "UNAUTHORIZED",
+            ApiError::Forbidden(_) => "FORBIDDEN",
+            ApiError::NotFound(_) => "NOT_FOUND",
+            ApiError::TokenNotFound => "TOKEN_NOT_FOUND",
+            ApiError::Conflict(_) => "CONFLICT",
+            ApiError::InvalidConfig(_) => "INVALID_CONFIG",
+            ApiError::CooldownActive { .. } => "COOLDOWN_ACTIVE",
+            ApiError::PayloadTooLarge(_) => "PAYLOAD_TOO_LARGE",
+            ApiError::FeatureDisabled(_) => "FEATURE_DISABLED",
+            ApiError::ShuttingDown => "SHUTTING_DOWN",
+            ApiError::Timeout => "TIMEOUT",
+            ApiError::Upstream(_) => "UPSTREAM_UNAVAILABLE",
+            ApiError::Job(e) => e.code.as_deref().unwrap_or("INTERNAL_ERROR"),
+            ApiError::Internal(_) => "INTERNAL_ERROR",
+        }
+    }
+
+    pub fn status(&self) -> StatusCode {
+        status_for_code(self.code())
+    }
+
+    pub fn message(&self) -> String {
+        match self {
+            ApiError::InvalidRequest(message)
+            | ApiError::InvalidAmount(message)
+            | ApiError::Conflict(message)
+            | ApiError::InvalidConfig(message)
+            | ApiError::PayloadTooLarge(message) => message.clone(),
+            ApiError::InvalidAddress(field) => format!("Invalid {} address", field),
+            ApiError::Unauthorized(message)
+            | ApiError::Forbidden(message)
+            | ApiError::FeatureDisabled(message)
+            | ApiError::Upstream(message)
+            | ApiError::Internal(message) => message.to_string(),
+            ApiError::NotFound(resource) => format!("{} not found", resource),
+            ApiError::TokenNotFound => "Token not found".to_string(),
+            ApiError::CooldownActive { next_access } => {
+                ErrorResponse::cooldown_active(*next_access).message
+            }
+            ApiError::ShuttingDown => ErrorResponse::shutting_down().message,
+            ApiError::Timeout => "Request timed out".to_string(),
+            ApiError::Job(e) => e.message.clone(),
+        }
+    }
+}
+
+/// HTTP status of an error code. Executor jobs and handlers share the codes,
+/// so this is the single place deciding how each one is answered.
+pub fn status_for_code(code: &str) -> StatusCode {
+    match code {
+        "INVALID_REQUEST" | "INVALID_ADDRESS" | "INVALID_AMOUNT" | "INVALID_BATCH_SIZE" => {
+            StatusCode::BAD_REQUEST
+        }
+        "UNAUTHORIZED" => StatusCode::UNAUTHORIZED,
+        "FORBIDDEN" | "TOKEN_NOT_ALLOWED" => StatusCode::FORBIDDEN,
+        "NOT_FOUND" | "TOKEN_NOT_FOUND" => StatusCode::NOT_FOUND,
+        "CONFLICT" | "ABOVE_TOP_UP_TARGET" | "REQUEST_IN_PROGRESS" => StatusCode::CONFLICT,
+        "PAYLOAD_TOO_LARGE" => StatusCode::PAYLOAD_TOO_LARGE,
+        "IDEMPOTENCY_KEY_REUSED"
+        | "INVALID_CONFIG"
+        | "TOKEN_NOT_CLAIMABLE"
+        | "TRANSACTION_WOULD_REVERT" => StatusCode::UNPROCESSABLE_ENTITY,
+        "COOLDOWN_ACTIVE" | "QUOTA_EXCEEDED" => StatusCode::TOO_MANY_REQUESTS,
+        "RPC_UNAVAILABLE" | "UPSTREAM_UNAVAILABLE" | "SIMULATION_FAILED" | "TRANSACTION_FAILED" => {
+            StatusCode::BAD_GATEWAY
+        }
+        "INSUFFICIENT_FAUCET_BALANCE" | "FAUCET_PAUSED" | "FEATURE_DISABLED" | "SHUTTING_DOWN" => {
+            StatusCode::SERVICE_UNAVAILABLE
+        }
+        "TIMEOUT" => StatusCode::GATEWAY_TIMEOUT,
+        _ => StatusCode::INTERNAL_SERVER_ERROR,
+    }
+}
+
+impl From<ApiError> for ErrorResponse {
+    fn from(e: ApiError) -> Self {
+        match e {
+            ApiError::Job(e) => ErrorResponse {
+                code: Some(e.code.unwrap_or_else(|| "INTERNAL_ERROR".to_string())),
+                ..e
+            },
+            e => ErrorResponse {
+                message: e.message(),
+                next_access: match &e {
+                    ApiError::CooldownActive { next_access } => Some(*next_access),
+                    _ => None,
+                },
+                code: Some(e.code().to_string()),
+            },
+        }
+    }
+}
+
+impl From<ErrorResponse> for ApiError {
+    fn from(e: ErrorResponse) -> Self {
+        match (e.code.as_deref(), e.next_access) {
+            (Some("COOLDOWN_ACTIVE"), Some(next_access)) => {
+                ApiError::CooldownActive { next_access }
+            }
+            _ => ApiError::Job(e),
+        }
+    }
+}
+
+impl From<EnqueueError> for ApiError {
+    fn from(e: EnqueueError) -> Self {
+        match e {
+            EnqueueError::ShuttingDown => ApiError::ShuttingDown,
+            EnqueueError::Poisoned => ApiError::Internal("Something went wrong"),
+        }
+    }
+}
+
+impl IntoResponse for ApiError {
+    fn into_response(self) -> axum::response::Response {
+        let status = self.status();
+        (status, Response::error(ErrorResponse::from(self))).into_response()
+    }
+}
+
+// rejections of the axum extractors only describe the request, never our
+// internals, so their text is passed on
+
+impl From<JsonRejection> for ApiError {
+    fn from(e: JsonRejection) -> Self {
+        ApiError::InvalidRequest(e.body_text())
+    }
+}
+
+impl From<PathRejection> for ApiError {
+    fn from(e: PathRejection) -> Self {
+        ApiError::InvalidRequest(e.body_text())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_api_error_status() {
+        assert_eq!(
+            ApiError::InvalidAddress("to").status(),
+            StatusCode::BAD_REQUEST
+        );
+        assert_eq!(ApiError::TokenNotFound.status(), StatusCode::NOT_FOUND);
+        assert_eq!(
+            ApiError::Upstream("GitHub is unavailable").status(),
+            StatusCode::BAD_GATEWAY
+        );
+        assert_eq!(
+            ApiError::CooldownActive {
+                next_access: Utc::now()
+            }
+            .status(),
+            StatusCode::TOO_MANY_REQUESTS
+        );
+
+        // job errors are answered by their own code
+        let job = |code: Option<&str>| {
+            ApiError::Job(ErrorResponse {
+                message: "Insufficient balance".to_string(),
+                next_access: None,
+                code: code.map(str::to_string),
+            })
+        };
+        assert_eq!(
+            job(Some("INSUFFICIENT_FAUCET_BALANCE")).status(),
+            StatusCode::SERVICE_UNAVAILABLE
+        );
+        assert_eq!(job(None).status(), StatusCode::INTERNAL_SERVER_ERROR);
+        assert_eq!(job(None).code(), "INTERNAL_ERROR");
+    }
+
+    #[test]
+    fn test_api_error_body() {
+        let body = ErrorResponse::from(ApiError::InvalidAddress("wallet"));
+        assert_eq!(body.message, "Invalid wallet address");
+        assert_eq!(body.code.as_deref(), Some("INVALID_ADDRESS"));
+
+        let next_access = Utc::now();
+        let body = ErrorResponse::from(ApiError::from(ErrorResponse::cooldown_active(next_access)));
+        assert_eq!(body.next_access, Some(next_access));
+        assert_eq!(body.code.as_deref(), Some("COOLDOWN_ACTIVE"));
+
+        let body = ErrorResponse::from(ApiError::Internal("Failed to fetch tokens"));
+        assert_eq!(body.message, "Failed to fetch tokens");
+        assert_eq!(body.code.as_deref(), Some("INTERNAL_ERROR"));
+    }
+}
@@ -0,0 +1,170 @@
+use axum::{
+    extract::{
+        ws::{Message, WebSocket, WebSocketUpgrade},
+        Query, State,
+    },
+    response::{
+        sse::{Event, KeepAlive, Sse},
+        IntoResponse,
+    },
+};
+use futures::{Stream, StreamExt};
+use serde::Deserialize;
+use tokio::sync::broadcast::{self, error::RecvError};
+use utoipa::IntoParams;
+
+use crate::{events::FaucetEvent, executor::Executor, AppState};
+
+#[derive(Debug, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct EventsQuery {
+    /// Only stream events about this token
+    pub token: Option<String>,
+}
+
+impl EventsQuery {
+    fn matches(&self, event: &FaucetEvent) -> bool {
+        if !event.is_public() {
+            return false;
+        }
+        match &self.token {
+            Some(token) => event
+                .token_address()
+                .is_some_and(|address| address.eq_ignore_ascii_case(token)),
+            None => true,
+        }
+    }
+}
+
+/// Live feed of faucet activity as Server-Sent Events. Recipient addresses
+/// and transaction hashes are redacted, `?token=` narrows the feed to a
+/// single token.
+#[utoipa::path(
+    get,
+    path = "/events",
+    tag = "events",
+    params(EventsQuery),
+    responses(
+        (status = 200, description = "One event per drip, deploy or pause, as JSON", content_type = "text/event-stream", body = String),
+    )
+)]
+pub async fn events(
+    State(state): State<AppState>,
+    Query(query): Query<EventsQuery>,
+) -> Sse<impl Stream<Item = Result<Event, axum::Error>>> {
+    let receiver = state.executor.events.subscribe();
+
+    let stream = futures::stream::unfold((receiver, query), |(mut receiver, query)| async move {
+        loop {
+            match receiver.recv().await {
+                Ok(event) if query.matches(&event) => {
+                    let event = Event::default().json_data(event.redacted());
+                    return Some((event, (receiver, query)));
+                }
+                // slow clients skip what they missed rather than
+                // holding back the bus
+                Ok(_) | Err(RecvError::Lagged(_)) => continue,
+                Err(RecvError::Closed) => return None,
+            }
+        }
+    });
+    // open streams would otherwise hold back a graceful shutdown forever
+    let executor = state.executor.clone();
+    let stream = stream.take_until(async move { executor.shutdown_started().await });
+
+    Sse::new(stream).keep_alive(KeepAlive::default())
+}
+
+/// Same feed as [`events`] over a WebSocket, one JSON text message per event.
+#[utoipa::path(
+    get,
+    path = "/events/ws",
+    tag = "events",
+    params(EventsQuery),
+    responses(
+        (status = 101, description = "Switched to a WebSocket carrying the same events as `/events`"),
+    )
+)]
+pub async fn events_ws(
+    ws: WebSocketUpgrade,
+    State(state): State<AppState>,
+    Query(query): Query<EventsQuery>,
+) -> impl IntoResponse {
+    let receiver = state.executor.events.subscribe();
+    let executor = state.executor.clone();
+    ws.on_upgrade(move |socket| forward_events(socket, receiver, query, executor))
+}
+
+async fn forward_events(
+    mut socket: WebSocket,
+    mut receiver: broadcast::Receiver<FaucetEvent>,
+    query: EventsQuery,
+    executor: Executor,
+) {
+    loop {
+        tokio::select! {
+            _ = executor.shutdown_started() => {
+                let _ = socket.send(Message::Close(None)).await;
+                return;
+            },
+            event = receiver.recv() => match event {
+                Ok(event) if query.matches(&event) => {
+                    let Ok(text) = serde_json::to_string(&event.redacted()) else {
+                        continue;
+                    };
+                    if socket.send(Message::Text(text)).await.is_err() {
+                        return;
+                    }
+                }
+                Ok(_) | Err(RecvError::Lagged(_)) => {}
+                Err(RecvError::Closed) => return,
+            },
+            message = socket.recv() => match message {
+                // the feed only goes one way, anything but a close is ignored
+                Some(Ok(Message::Close(_))) | Some(Err(_)) | None => return,
+                Some(Ok(_)) => {}
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::store::{DeployJob, DeployStep};
+    use chrono::Utc;
+
+    #[test]
+    fn test_events_query_matches() {
+        let event = FaucetEvent::DripQueued {
+            token_address: "0xAbC".to_string(),
+            to: "0xto".to_string(),
+        };
+
+        assert!(EventsQuery { token: None }.matches(&event));
+        assert!(EventsQuery {
+            token: Some("0xabc".to_string())
+        }
+        .matches(&event));
+        assert!(!EventsQuery {
+            token: Some("0xdef".to_string())
+        }
+        .matches(&event));
+
+        // deploy progress is only streamed to followers of the job
+        let progress = FaucetEvent::DeployProgress(DeployJob {
+            id: "job".to_string(),
+            user_id: Some("user".to_string()),
+            symbol: "TKN".to_string(),
+            step: DeployStep::QUEUED,
+            deploy_tx_hash: None,
+            contract_address: None,
+            allocation_tx_hashes: vec![],
+            error: None,
+            error_code: None,
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+        });
+        assert!(!EventsQuery { token: None }.matches(&progress));
+    }
+}
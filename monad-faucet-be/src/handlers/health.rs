@@ -1,16 +1,21 @@
 use axum::Json;
-use reqwest::StatusCode;
 use serde::Serialize;
+use utoipa::ToSchema;
 
-use crate::handlers::response::Response;
+use crate::handlers::{error::ApiError, response::Response};
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, ToSchema)]
 pub struct HealthCheckResponse {
     status: String,
 }
 
-pub async fn health_check(
-) -> Result<Json<Response<HealthCheckResponse>>, (StatusCode, Json<Response<()>>)> {
+#[utoipa::path(
+    get,
+    path = "/health",
+    tag = "health",
+    responses((status = 200, description = "The faucet is up", body = Response<HealthCheckResponse>))
+)]
+pub async fn health_check() -> Result<Json<Response<HealthCheckResponse>>, ApiError> {
     Ok(Response::ok(HealthCheckResponse {
         status: "OK".to_string(),
     }))
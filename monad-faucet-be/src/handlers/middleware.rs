@@ -1,18 +1,32 @@
+use crate::config::Config;
+use crate::constants::{api, idempotency};
+use crate::session::{hash_secret, verify_access_token, TokenError};
+use crate::store::Store;
 use crate::AppState;
-use crate::{executor::ErrorResponse, handlers::auth::validate_and_decode_jwt};
 use axum::{
     async_trait,
-    extract::FromRequestParts,
-    http::{request::Parts, StatusCode},
-    Json,
+    extract::{FromRequestParts, Request},
+    http::{request::Parts, HeaderName},
+    middleware::Next,
+    response::Response,
 };
 
-use super::response::{res_err, Response};
+use super::error::ApiError;
 
 #[derive(Clone, Debug)]
 pub struct AuthUser {
     pub user_id: String,
+    /// Signed in with GitHub in a browser session. False for API keys, whose
+    /// features are gated on `api_key_id` instead.
     pub is_github_authenticated: bool,
+    /// Set when the request was authenticated with `Authorization: ApiKey ...`
+    pub api_key_id: Option<String>,
+}
+
+impl AuthUser {
+    pub fn is_admin(&self, config: &Config) -> bool {
+        !self.user_id.is_empty() && config.auth.admin_user_ids.contains(&self.user_id)
+    }
 }
 
 #[async_trait]
@@ -20,35 +34,299 @@ impl<S> FromRequestParts<S> for AuthUser
 where
     S: Send + Sync,
 {
-    type Rejection = (StatusCode, Json<Response<ErrorResponse>>);
+    type Rejection = ApiError;
 
     async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, Self::Rejection> {
         let state: &AppState = match parts.extensions.get() {
             Some(state) => state,
             None => {
-                return Err((
-                    StatusCode::INTERNAL_SERVER_ERROR,
-                    res_err("Failed to get state"),
-                ));
+                tracing::error!("AppState is missing from the request extensions");
+                return Err(ApiError::Internal("Something went wrong"));
             }
         };
 
         if let Some(auth_header) = parts.headers.get("Authorization") {
             if let Ok(token) = auth_header.to_str() {
                 if let Some(token) = token.strip_prefix("Bearer ") {
-                    if let Ok(claims) = validate_and_decode_jwt(token, &state.config.jwt_secret) {
-                        return Ok(AuthUser {
-                            user_id: claims.sub,
-                            is_github_authenticated: true,
-                        });
+                    // tokens that are not ours at all fall back to an anonymous
+                    // user, ours are refused once expired or signed out
+                    match verify_access_token(&state.config.auth, token) {
+                        Ok(claims) => {
+                            return match state.store.get_auth_session(claims.sid).await {
+                                Ok(session)
+                                    if session.is_live() && session.user_id == claims.sub =>
+                                {
+                                    Ok(AuthUser {
+                                        user_id: claims.sub,
+                                        is_github_authenticated: true,
+                                        api_key_id: None,
+                                    })
+                                }
+                                Ok(_) | Err(sqlx::Error::RowNotFound) => Err(
+                                    ApiError::Unauthorized("Session ended, please sign in again"),
+                                ),
+                                Err(e) => {
+                                    tracing::error!("Error fetching auth session {}", e);
+                                    Err(ApiError::Internal("Something went wrong"))
+                                }
+                            };
+                        }
+                        Err(TokenError::Expired) => {
+                            return Err(ApiError::Unauthorized("Access token expired, refresh it"));
+                        }
+                        Err(TokenError::Invalid) => {}
                     }
                 }
+                if let Some(key) = token.strip_prefix("ApiKey ") {
+                    // an explicit but invalid key is rejected rather than
+                    // silently downgraded to an anonymous user
+                    return match state.store.get_api_key_by_hash(hash_secret(key)).await {
+                        Ok(api_key) if !api_key.revoked => Ok(AuthUser {
+                            user_id: api_key.user_id,
+                            is_github_authenticated: false,
+                            api_key_id: Some(api_key.id),
+                        }),
+                        _ => Err(ApiError::Unauthorized("Invalid API key")),
+                    };
+                }
             }
         }
 
         Ok(AuthUser {
             user_id: String::new(),
             is_github_authenticated: false,
+            api_key_id: None,
         })
     }
 }
+
+pub static REQUEST_ID_HEADER: HeaderName = HeaderName::from_static("x-request-id");
+
+/// Whether a client chosen request ID is safe to log and echo back: 1 to
+/// `MAX_REQUEST_ID_LEN` ASCII letters, digits, `-` or `_`
+pub fn is_valid_request_id(id: &str) -> bool {
+    (1..=api::MAX_REQUEST_ID_LEN).contains(&id.len())
+        && id
+            .bytes()
+            .all(|b| b.is_ascii_alphanumeric() || b == b'-' || b == b'_')
+}
+
+/// Drops an invalid `x-request-id` sent by the client, so that the request ID
+/// layer generates a fresh one in its place
+pub async fn sanitize_request_id(mut request: Request, next: Next) -> Response {
+    // a repeated header is dropped too rather than picking one of its values
+    let mut ids = request.headers().get_all(&REQUEST_ID_HEADER).iter();
+    let valid = matches!(
+        (ids.next(), ids.next()),
+        (Some(id), None) if id.to_str().is_ok_and(is_valid_request_id)
+    );
+    if !valid {
+        request.headers_mut().remove(&REQUEST_ID_HEADER);
+    }
+    next.run(request).await
+}
+
+/// ID of the request from `x-request-id`, which the request ID layer sets
+/// when the client didn't send a valid one
+#[derive(Clone, Debug)]
+pub struct RequestId(pub String);
+
+#[async_trait]
+impl<S> FromRequestParts<S> for RequestId
+where
+    S: Send + Sync,
+{
+    type Rejection = std::convert::Infallible;
+
+    async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, Self::Rejection> {
+        let id = parts
+            .headers
+            .get(&REQUEST_ID_HEADER)
+            .and_then(|id| id.to_str().ok())
+            .filter(|id| is_valid_request_id(id))
+            .map(str::to_string)
+            .unwrap_or_else(|| uuid::Uuid::new_v4().to_string());
+        Ok(RequestId(id))
+    }
+}
+
+pub static IDEMPOTENCY_KEY_HEADER: HeaderName = HeaderName::from_static("idempotency-key");
+
+/// Client chosen key from `Idempotency-Key`, retries of a request with the
+/// same key get its first outcome instead of queuing it again
+#[derive(Clone, Debug)]
+pub struct IdempotencyKey(pub Option<String>);
+
+#[async_trait]
+impl<S> FromRequestParts<S> for IdempotencyKey
+where
+    S: Send + Sync,
+{
+    type Rejection = ApiError;
+
+    async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, Self::Rejection> {
+        let Some(key) = parts.headers.get(&IDEMPOTENCY_KEY_HEADER) else {
+            return Ok(IdempotencyKey(None));
+        };
+        // `to_str` lets spaces and tabs through, which are not visible
+        match key.to_str() {
+            Ok(key)
+                if !key.is_empty()
+                    && key.len() <= idempotency::MAX_KEY_LEN
+                    && key.bytes().all(|b| b.is_ascii_graphic()) =>
+            {
+                Ok(IdempotencyKey(Some(key.to_string())))
+            }
+            _ => Err(ApiError::InvalidRequest(format!(
+                "Idempotency-Key must be 1 to {} visible ASCII characters",
+                idempotency::MAX_KEY_LEN
+            ))),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use axum::http::Request;
+
+    use super::*;
+    use crate::store::{ApiKey, User};
+    use crate::utils::setup;
+
+    async fn extract(state: &AppState, authorization: &str) -> Result<AuthUser, ApiError> {
+        let (mut parts, _) = Request::builder()
+            .header("Authorization", authorization)
+            .extension(state.clone())
+            .body(())
+            .unwrap()
+            .into_parts();
+        AuthUser::from_request_parts(&mut parts, &()).await
+    }
+
+    #[tokio::test]
+    async fn test_api_key_user() {
+        let state = setup().await.unwrap();
+        let id = uuid::Uuid::new_v4().simple().to_string();
+        state
+            .store
+            .create_user(User {
+                id: id.clone(),
+                username: id.clone(),
+                github_id: id.clone(),
+                access_token: id.clone(),
+                avatar_url: String::new(),
+                email: None,
+            })
+            .await
+            .unwrap();
+        let key = format!("mfk_{}", id);
+        state
+            .store
+            .create_api_key(
+                ApiKey {
+                    id: id.clone(),
+                    user_id: id.clone(),
+                    name: "test".to_string(),
+                    key_hash: hash_secret(&key),
+                    key_prefix: key[..8].to_string(),
+                    drips_per_day: 1,
+                    revoked: false,
+                },
+                vec![],
+            )
+            .await
+            .unwrap();
+
+        // an API key is not a GitHub session, it only unlocks API key features
+        let user = extract(&state, &format!("ApiKey {}", key)).await.unwrap();
+        assert_eq!(user.user_id, id);
+        assert_eq!(user.api_key_id.as_deref(), Some(id.as_str()));
+        assert!(!user.is_github_authenticated);
+
+        assert!(matches!(
+            extract(&state, "ApiKey mfk_unknown").await,
+            Err(ApiError::Unauthorized(_))
+        ));
+        state.store.revoke_api_key(id.clone()).await.unwrap();
+        assert!(matches!(
+            extract(&state, &format!("ApiKey {}", key)).await,
+            Err(ApiError::Unauthorized(_))
+        ));
+
+        // anything else is an anonymous user
+        let user = extract(&state, "Basic dXNlcjpwYXNz").await.unwrap();
+        assert!(user.user_id.is_empty());
+        assert!(user.api_key_id.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_request_id() {
+        use axum::{body::Body, routing::get, Router};
+        use tower::ServiceExt;
+        use tower_http::request_id::{MakeRequestUuid, SetRequestIdLayer};
+
+        let app = Router::new()
+            .route("/", get(|RequestId(id): RequestId| async move { id }))
+            .layer(SetRequestIdLayer::new(
+                REQUEST_ID_HEADER.clone(),
+                MakeRequestUuid,
+            ))
+            .layer(axum::middleware::from_fn(sanitize_request_id));
+        let request_id = |ids: &[&str]| {
+            let app = app.clone();
+            let mut request = Request::builder().uri("/");
+            for id in ids {
+                request = request.header(&REQUEST_ID_HEADER, *id);
+            }
+            let request = request.body(Body::empty()).unwrap();
+            async move {
+                let body = app.oneshot(request).await.unwrap().into_body();
+                let body = axum::body::to_bytes(body, usize::MAX).await.unwrap();
+                String::from_utf8(body.to_vec()).unwrap()
+            }
+        };
+
+        assert_eq!(request_id(&["abc-DEF_123"]).await, "abc-DEF_123");
+        let max = "a".repeat(api::MAX_REQUEST_ID_LEN);
+        assert_eq!(request_id(&[&max]).await, max);
+
+        let too_long = "a".repeat(api::MAX_REQUEST_ID_LEN + 1);
+        for ids in [
+            vec![],
+            vec![""],
+            vec!["id with spaces"],
+            vec!["<script>alert(1)</script>"],
+            vec![too_long.as_str()],
+            vec!["first", "second"],
+        ] {
+            let id = request_id(&ids).await;
+            assert!(
+                uuid::Uuid::parse_str(&id).is_ok(),
+                "{:?} kept as {}",
+                ids,
+                id
+            );
+        }
+    }
+
+    #[tokio::test]
+    async fn test_idempotency_key() {
+        let extract = |key: &str| {
+            let (mut parts, _) = Request::builder()
+                .header(&IDEMPOTENCY_KEY_HEADER, key)
+                .body(())
+                .unwrap()
+                .into_parts();
+            async move { IdempotencyKey::from_request_parts(&mut parts, &()).await }
+        };
+
+        assert_eq!(
+            extract("retry-1:abc").await.unwrap().0.as_deref(),
+            Some("retry-1:abc")
+        );
+        let too_long = "a".repeat(idempotency::MAX_KEY_LEN + 1);
+        for key in ["", "with space", "with\ttab", too_long.as_str()] {
+            assert!(extract(key).await.is_err(), "{:?} accepted", key);
+        }
+    }
+}
@@ -1,15 +1,15 @@
 use axum::Json;
 use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
 
-use crate::executor::ErrorResponse;
-
-#[derive(Serialize, Deserialize, Debug, Clone)]
+#[derive(Serialize, Deserialize, Debug, Clone, ToSchema)]
 pub enum ResponseStatus {
     Success,
     Error,
 }
 
-#[derive(Serialize, Deserialize, Debug, Clone)]
+/// Envelope of every JSON answer, `data` on success and `error` otherwise
+#[derive(Serialize, Deserialize, Debug, Clone, ToSchema)]
 pub struct Response<T> {
     pub status: ResponseStatus,
     #[serde(skip_serializing_if = "Option::is_none")]
@@ -35,10 +35,3 @@ impl<T> Response<T> {
         })
     }
 }
-
-pub fn res_err(data: &str) -> Json<Response<ErrorResponse>> {
-    Response::error(ErrorResponse {
-        message: data.to_string(),
-        next_access: None,
-    })
-}
@@ -0,0 +1,284 @@
+use axum::{
+    extract::{Path, State},
+    Json,
+};
+use tracing::error;
+use utoipa::ToSchema;
+use uuid::Uuid;
+
+use crate::{
+    executor::ErrorResponse,
+    store::{Store, WalletSubscription},
+    AppState,
+};
+
+use super::{
+    error::ApiError,
+    middleware::AuthUser,
+    response::Response,
+    validation::{normalize_address, Valid, Validate},
+};
+
+#[derive(Debug, serde::Deserialize, ToSchema)]
+pub struct CreateSubscriptionRequest {
+    pub wallet_address: String,
+    pub token_address: String,
+    /// Refill once the wallet holds less than this, in base units
+    pub min_balance: String,
+    /// In base units, at most what a GitHub user can claim at once
+    pub refill_amount: String,
+}
+
+#[derive(Debug, serde::Deserialize, ToSchema)]
+pub struct UpdateSubscriptionRequest {
+    pub min_balance: Option<String>,
+    pub refill_amount: Option<String>,
+    pub enabled: Option<bool>,
+}
+
+impl Validate for CreateSubscriptionRequest {
+    fn validate(&mut self) -> Result<(), ApiError> {
+        self.wallet_address = normalize_address("wallet", &self.wallet_address)?;
+        self.token_address = normalize_address("token", &self.token_address)?;
+        Ok(())
+    }
+}
+
+impl Validate for UpdateSubscriptionRequest {
+    fn validate(&mut self) -> Result<(), ApiError> {
+        if self.min_balance.is_none() && self.refill_amount.is_none() && self.enabled.is_none() {
+            return Err(ApiError::InvalidRequest("Nothing to update".to_string()));
+        }
+        Ok(())
+    }
+}
+
+fn require_github_user(auth_user: &AuthUser) -> Result<(), ApiError> {
+    if !auth_user.is_github_authenticated {
+        return Err(ApiError::Unauthorized(
+            "Please authenticate with Github to manage scheduled top-ups",
+        ));
+    }
+    Ok(())
+}
+
+/// Checks the amounts of a subscription and caps the refill amount at what a
+/// Github authenticated user could claim manually.
+async fn validate_amounts(
+    state: &AppState,
+    token_address: &str,
+    min_balance: &str,
+    refill_amount: &str,
+) -> Result<(), ApiError> {
+    let token = state
+        .store
+        .get_token_by_address(token_address.to_string())
+        .await
+        .map_err(|_| ApiError::TokenNotFound)?;
+
+    let (Ok(min_balance), Ok(refill_amount)) =
+        (min_balance.parse::<u128>(), refill_amount.parse::<u128>())
+    else {
+        return Err(ApiError::InvalidAmount("Invalid amount".to_string()));
+    };
+
+    let max_refill = token.withdraw_limit.parse::<u128>().unwrap_or_default()
+        * state.tuning.load().magnification_github_auth as u128;
+    if min_balance == 0 || refill_amount == 0 || refill_amount > max_refill {
+        return Err(ApiError::InvalidAmount(format!(
+            "Refill amount must be between 1 and {} and min balance above 0",
+            max_refill
+        )));
+    }
+
+    Ok(())
+}
+
+/// Loads a subscription, making sure it belongs to the authenticated user.
+async fn get_owned_subscription(
+    state: &AppState,
+    auth_user: &AuthUser,
+    id: String,
+) -> Result<WalletSubscription, ApiError> {
+    let subscription = state
+        .store
+        .get_subscription_by_id(id)
+        .await
+        .map_err(|_| ApiError::NotFound("Subscription"))?;
+
+    if subscription.user_id != auth_user.user_id {
+        return Err(ApiError::NotFound("Subscription"));
+    }
+    Ok(subscription)
+}
+
+/// Scheduled top-ups of the user's wallets
+#[utoipa::path(
+    get,
+    path = "/subscriptions",
+    tag = "subscriptions",
+    responses(
+        (status = 200, description = "Subscriptions of the user", body = Response<Vec<WalletSubscription>>),
+        (status = 401, description = "Not signed in with GitHub", body = Response<ErrorResponse>),
+    ),
+    security(("github" = []))
+)]
+pub async fn subscriptions(
+    auth_user: AuthUser,
+    State(state): State<AppState>,
+) -> Result<Json<Response<Vec<WalletSubscription>>>, ApiError> {
+    require_github_user(&auth_user)?;
+
+    let subscriptions = state
+        .store
+        .get_subscriptions_by_user(auth_user.user_id)
+        .await
+        .map_err(|e| {
+            error!("Failed to fetch subscriptions {}", e);
+            ApiError::Internal("Failed to fetch subscriptions")
+        })?;
+
+    Ok(Response::ok(subscriptions))
+}
+
+#[utoipa::path(
+    post,
+    path = "/subscriptions",
+    tag = "subscriptions",
+    request_body = CreateSubscriptionRequest,
+    responses(
+        (status = 200, description = "The new subscription", body = Response<WalletSubscription>),
+        (status = 400, description = "Invalid address or amounts", body = Response<ErrorResponse>),
+        (status = 401, description = "Not signed in with GitHub", body = Response<ErrorResponse>),
+        (status = 404, description = "Unknown token", body = Response<ErrorResponse>),
+        (status = 409, description = "The wallet is already subscribed to the token", body = Response<ErrorResponse>),
+    ),
+    security(("github" = []))
+)]
+pub async fn create_subscription(
+    auth_user: AuthUser,
+    State(state): State<AppState>,
+    Valid(payload): Valid<CreateSubscriptionRequest>,
+) -> Result<Json<Response<WalletSubscription>>, ApiError> {
+    require_github_user(&auth_user)?;
+
+    validate_amounts(
+        &state,
+        &payload.token_address,
+        &payload.min_balance,
+        &payload.refill_amount,
+    )
+    .await?;
+
+    let subscription = state
+        .store
+        .create_subscription(WalletSubscription {
+            id: Uuid::new_v4().to_string(),
+            user_id: auth_user.user_id,
+            wallet_address: payload.wallet_address,
+            token_address: payload.token_address,
+            min_balance: payload.min_balance,
+            refill_amount: payload.refill_amount,
+            enabled: true,
+            last_checked_at: None,
+            last_refill_at: None,
+        })
+        .await
+        .map_err(|e| {
+            if let sqlx::Error::Database(db_err) = &e {
+                if db_err.code().map(|code| code == "23505").unwrap_or(false) {
+                    return ApiError::Conflict(
+                        "This wallet is already registered for the token".to_string(),
+                    );
+                }
+            }
+            error!("Failed to create subscription {}", e);
+            ApiError::Internal("Failed to create subscription")
+        })?;
+
+    Ok(Response::ok(subscription))
+}
+
+#[utoipa::path(
+    put,
+    path = "/subscriptions/{id}",
+    tag = "subscriptions",
+    params(("id" = String, Path)),
+    request_body = UpdateSubscriptionRequest,
+    responses(
+        (status = 200, description = "The updated subscription", body = Response<WalletSubscription>),
+        (status = 400, description = "Nothing to update or invalid amounts", body = Response<ErrorResponse>),
+        (status = 401, description = "Not signed in with GitHub", body = Response<ErrorResponse>),
+        (status = 404, description = "No such subscription of the user", body = Response<ErrorResponse>),
+    ),
+    security(("github" = []))
+)]
+pub async fn update_subscription(
+    auth_user: AuthUser,
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+    Valid(payload): Valid<UpdateSubscriptionRequest>,
+) -> Result<Json<Response<WalletSubscription>>, ApiError> {
+    require_github_user(&auth_user)?;
+
+    let mut subscription = get_owned_subscription(&state, &auth_user, id).await?;
+    if let Some(min_balance) = payload.min_balance {
+        subscription.min_balance = min_balance;
+    }
+    if let Some(refill_amount) = payload.refill_amount {
+        subscription.refill_amount = refill_amount;
+    }
+    if let Some(enabled) = payload.enabled {
+        subscription.enabled = enabled;
+    }
+    validate_amounts(
+        &state,
+        &subscription.token_address,
+        &subscription.min_balance,
+        &subscription.refill_amount,
+    )
+    .await?;
+
+    let subscription = state
+        .store
+        .update_subscription(subscription)
+        .await
+        .map_err(|e| {
+            error!("Failed to update subscription {}", e);
+            ApiError::Internal("Failed to update subscription")
+        })?;
+
+    Ok(Response::ok(subscription))
+}
+
+#[utoipa::path(
+    delete,
+    path = "/subscriptions/{id}",
+    tag = "subscriptions",
+    params(("id" = String, Path)),
+    responses(
+        (status = 200, description = "The deleted subscription", body = Response<WalletSubscription>),
+        (status = 401, description = "Not signed in with GitHub", body = Response<ErrorResponse>),
+        (status = 404, description = "No such subscription of the user", body = Response<ErrorResponse>),
+    ),
+    security(("github" = []))
+)]
+pub async fn delete_subscription(
+    auth_user: AuthUser,
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+) -> Result<Json<Response<WalletSubscription>>, ApiError> {
+    require_github_user(&auth_user)?;
+
+    let subscription = get_owned_subscription(&state, &auth_user, id).await?;
+    state
+        .store
+        .delete_subscription(subscription.id.clone())
+        .await
+        .map_err(|e| {
+            error!("Failed to delete subscription {}", e);
+            ApiError::Internal("Failed to delete subscription")
+        })?;
+
+    Ok(Response::ok(subscription))
+}
@@ -1,10 +1,21 @@
-use axum::{http::StatusCode, Json};
+use axum::Json;
 
-use super::{middleware::AuthUser, response::Response};
+use crate::executor::ErrorResponse;
 
+use super::{error::ApiError, middleware::AuthUser, response::Response};
+
+/// Greets the caller, for checking a token or API key
+#[utoipa::path(
+    get,
+    path = "/test_auth",
+    tag = "auth",
+    responses(
+        (status = 200, description = "Who the request is authenticated as", body = Response<String>),
+        (status = 401, description = "Invalid API key", body = Response<ErrorResponse>),
+    ),
+    security((), ("github" = []), ("api_key" = []))
+)]
 #[axum::debug_handler]
-pub async fn test_auth(
-    auth_user: AuthUser,
-) -> Result<Json<Response<String>>, (StatusCode, Json<Response<()>>)> {
+pub async fn test_auth(auth_user: AuthUser) -> Result<Json<Response<String>>, ApiError> {
     Ok(Response::ok(format!("Hello, {}", auth_user.user_id)))
 }
@@ -1,21 +1,28 @@
 use axum::{extract::State, Json};
-use reqwest::StatusCode;
 use tracing::error;
 
 use crate::executor::ErrorResponse;
 use crate::store::{Store, Token};
 use crate::AppState;
 
-use super::response::{res_err, Response};
+use super::{error::ApiError, response::Response};
 
-pub async fn tokens(
-    State(state): State<AppState>,
-) -> Result<Json<Response<Vec<Token>>>, (StatusCode, Json<Response<ErrorResponse>>)> {
+/// Every token the faucet drips
+#[utoipa::path(
+    get,
+    path = "/tokens",
+    tag = "drips",
+    responses(
+        (status = 200, description = "Claimable tokens", body = Response<Vec<Token>>),
+        (status = 500, description = "Tokens could not be fetched", body = Response<ErrorResponse>),
+    )
+)]
+pub async fn tokens(State(state): State<AppState>) -> Result<Json<Response<Vec<Token>>>, ApiError> {
     let tokens = match state.store.get_all_tokens().await {
         Ok(tokens) => tokens,
         Err(e) => {
             error!("Error fetching tokens {}", e);
-            return Err((StatusCode::INTERNAL_SERVER_ERROR, res_err(&e.to_string())));
+            return Err(ApiError::Internal("Failed to fetch tokens"));
         }
     };
     Ok(Response::ok(tokens))
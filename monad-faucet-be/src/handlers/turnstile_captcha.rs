@@ -1,31 +1,59 @@
-use axum::{extract::State, http::StatusCode, Json};
+use axum::{extract::State, Json};
 use reqwest::Client;
 use serde_json::json;
 use tracing::error;
+use utoipa::ToSchema;
 
 use crate::{executor::ErrorResponse, handlers::response::Response, AppState};
 
-use super::response::res_err;
+use super::{
+    error::ApiError,
+    validation::{Valid, Validate},
+};
 
-#[derive(serde::Deserialize, Debug)]
+#[derive(serde::Deserialize, Debug, ToSchema)]
 pub struct TurnstilePayload {
     pub token: String,
 }
 
-#[derive(serde::Serialize)]
+impl Validate for TurnstilePayload {
+    fn validate(&mut self) -> Result<(), ApiError> {
+        if self.token.is_empty() {
+            return Err(ApiError::InvalidRequest(
+                "No captcha token provided".to_string(),
+            ));
+        }
+        Ok(())
+    }
+}
+
+#[derive(serde::Serialize, ToSchema)]
 pub struct TurnstileResponse {
     pub success: bool,
 }
 
+/// Checks a Cloudflare Turnstile token
+#[utoipa::path(
+    post,
+    path = "/verify-turnstile-captcha",
+    tag = "drips",
+    request_body = TurnstilePayload,
+    responses(
+        (status = 200, description = "Whether the captcha was solved", body = Response<TurnstileResponse>),
+        (status = 400, description = "No token", body = Response<ErrorResponse>),
+    )
+)]
 pub async fn verify_turnstile_captcha(
     State(state): State<AppState>,
-    Json(body): Json<TurnstilePayload>,
-) -> Result<Json<Response<TurnstileResponse>>, (StatusCode, Json<Response<ErrorResponse>>)> {
-    if body.token.is_empty() {
-        return Err((StatusCode::BAD_REQUEST, res_err("Invalid request")));
-    }
-
-    let success = verify(&state.config.captcha_secret_key, &body.token).await;
+    Valid(body): Valid<TurnstilePayload>,
+) -> Result<Json<Response<TurnstileResponse>>, ApiError> {
+    let policies = &state.config.policies;
+    let success = match &policies.captcha_secret_key {
+        _ if policies.captcha_disabled => true,
+        Some(secret_key) => verify(secret_key, &body.token).await,
+        // without a secret nothing can be checked, so nothing passes
+        None => false,
+    };
     Ok(Response::ok(TurnstileResponse { success }))
 }
 
@@ -8,26 +8,35 @@ use crate::{
     AppState,
 };
 
-use super::middleware::AuthUser;
+use super::{error::ApiError, middleware::AuthUser};
 
+/// Profile of the signed in GitHub user
+#[utoipa::path(
+    get,
+    path = "/user",
+    tag = "auth",
+    responses(
+        (status = 200, description = "The signed in user", body = Response<User>),
+        (status = 401, description = "Not signed in with GitHub", body = Response<ErrorResponse>),
+        (status = 404, description = "The user was deleted", body = Response<ErrorResponse>),
+    ),
+    security(("github" = []))
+)]
 #[axum::debug_handler]
 pub async fn user(
     State(state): State<AppState>,
     auth_user: AuthUser,
-) -> Result<Json<Response<User>>, Json<Response<ErrorResponse>>> {
-    let user = state
-        .store
-        .get_user_by_id(auth_user.user_id.to_string())
-        .await;
+) -> Result<Json<Response<User>>, ApiError> {
+    if !auth_user.is_github_authenticated {
+        return Err(ApiError::Unauthorized("Please sign in with Github"));
+    }
 
-    match user {
+    match state.store.get_user_by_id(auth_user.user_id).await {
         Ok(user) => Ok(Response::ok(user)),
+        Err(sqlx::Error::RowNotFound) => Err(ApiError::NotFound("User")),
         Err(e) => {
             error!("Error at users handler {}", e);
-            return Err(Response::error(ErrorResponse {
-                message: format!("Failed to get user: {:?}", e),
-                next_access: None,
-            }));
+            Err(ApiError::Internal("Failed to get user"))
         }
     }
 }
@@ -0,0 +1,104 @@
+use alloy::primitives::Address;
+use axum::{
+    async_trait,
+    extract::{FromRequest, FromRequestParts, Request},
+    Json,
+};
+use serde::de::DeserializeOwned;
+
+use crate::executor::{BatchTransferRequest, BulkDrip, TokenTransferRequest};
+
+use super::error::ApiError;
+
+/// Checks of a request body beyond what deserializing it covers
+pub trait Validate {
+    /// Rejects the request or normalizes it in place, e.g. checksums its
+    /// addresses so that the rest of the faucet sees one spelling of each
+    fn validate(&mut self) -> Result<(), ApiError>;
+}
+
+/// JSON body that was deserialized and validated, rejected with an
+/// [`ApiError`] otherwise
+#[derive(Debug, Clone)]
+pub struct Valid<T>(pub T);
+
+#[async_trait]
+impl<S, T> FromRequest<S> for Valid<T>
+where
+    S: Send + Sync,
+    T: DeserializeOwned + Validate,
+{
+    type Rejection = ApiError;
+
+    async fn from_request(req: Request, state: &S) -> Result<Self, Self::Rejection> {
+        let Json(mut value) = Json::<T>::from_request(req, state).await?;
+        value.validate()?;
+        Ok(Valid(value))
+    }
+}
+
+/// [`axum::extract::Path`] rejecting malformed segments with an [`ApiError`]
+#[derive(Debug, FromRequestParts)]
+#[from_request(via(axum::extract::Path), rejection(ApiError))]
+pub struct ApiPath<T>(pub T);
+
+/// Parses an address in any case, returning its checksummed form
+pub fn normalize_address(field: &'static str, address: &str) -> Result<String, ApiError> {
+    address
+        .trim()
+        .parse::<Address>()
+        .map(|address| address.to_checksum(None))
+        .map_err(|_| ApiError::InvalidAddress(field))
+}
+
+impl Validate for TokenTransferRequest {
+    fn validate(&mut self) -> Result<(), ApiError> {
+        self.token_address = normalize_address("token", &self.token_address)?;
+        self.to = normalize_address("to", &self.to)?;
+        Ok(())
+    }
+}
+
+impl Validate for BatchTransferRequest {
+    fn validate(&mut self) -> Result<(), ApiError> {
+        self.to = normalize_address("to", &self.to)?;
+        for token_address in &mut self.token_addresses {
+            *token_address = normalize_address("token", token_address)?;
+        }
+        Ok(())
+    }
+}
+
+impl Validate for BulkDrip {
+    fn validate(&mut self) -> Result<(), ApiError> {
+        self.token_address = normalize_address("token", &self.token_address)?;
+        self.to = normalize_address("to", &self.to)?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_normalize_address() {
+        let checksummed = "0xDda173bd23b07007394611D789EF789a9Aae5CF5";
+        assert_eq!(
+            normalize_address("to", &checksummed.to_lowercase()).unwrap(),
+            checksummed
+        );
+        assert_eq!(
+            normalize_address("to", &checksummed.to_uppercase().replace("0X", "0x")).unwrap(),
+            checksummed
+        );
+        assert_eq!(
+            normalize_address("to", &format!(" {} ", checksummed)).unwrap(),
+            checksummed
+        );
+
+        let error = normalize_address("to", "0xDda173bd").unwrap_err();
+        assert_eq!(error.code(), "INVALID_ADDRESS");
+        assert!(normalize_address("to", "not an address").is_err());
+    }
+}
@@ -0,0 +1,413 @@
+use axum::{
+    extract::{Path, State},
+    Json,
+};
+use chrono::Utc;
+use tracing::error;
+use utoipa::ToSchema;
+use uuid::Uuid;
+
+use crate::{
+    constants::webhooks,
+    executor::ErrorResponse,
+    store::{Store, Token, Webhook, WebhookDelivery},
+    webhooks::{check_url, USER_WEBHOOK_EVENTS, WEBHOOK_EVENTS},
+    AppState,
+};
+
+use super::{
+    error::ApiError,
+    middleware::AuthUser,
+    response::Response,
+    validation::{normalize_address, Valid, Validate},
+};
+
+/// Most deliveries returned by the delivery log
+const DELIVERY_LOG_LIMIT: i64 = 100;
+
+#[derive(Debug, serde::Deserialize, ToSchema)]
+pub struct CreateWebhookRequest {
+    /// An https URL
+    pub url: String,
+    pub events: Vec<String>,
+}
+
+#[derive(Debug, serde::Serialize, ToSchema)]
+pub struct CreateWebhookResponse {
+    /// Secret the payloads are signed with, only ever returned once at creation
+    pub secret: String,
+    pub webhook: Webhook,
+}
+
+/// Loads a token, making sure the user deployed it or is an admin.
+async fn get_managed_token(
+    state: &AppState,
+    auth_user: &AuthUser,
+    token_address: String,
+) -> Result<Token, ApiError> {
+    let token = state
+        .store
+        .get_token_by_address(token_address)
+        .await
+        .map_err(|_| ApiError::TokenNotFound)?;
+
+    let is_owner = token.created_by_user_id.as_deref() == Some(auth_user.user_id.as_str());
+    if auth_user.api_key_id.is_some() || !(is_owner || auth_user.is_admin(&state.config)) {
+        return Err(ApiError::Forbidden(
+            "Only the token deployer or an admin can manage its webhooks",
+        ));
+    }
+    Ok(token)
+}
+
+/// Loads a webhook whose token the user is allowed to manage, or one
+/// without a token that the user created.
+async fn get_managed_webhook(
+    state: &AppState,
+    auth_user: &AuthUser,
+    id: String,
+) -> Result<Webhook, ApiError> {
+    let webhook = state
+        .store
+        .get_webhook_by_id(id)
+        .await
+        .map_err(|_| ApiError::NotFound("Webhook"))?;
+    match &webhook.token_address {
+        Some(token_address) => {
+            get_managed_token(state, auth_user, token_address.clone()).await?;
+        }
+        None => {
+            let is_creator = webhook.created_by == auth_user.user_id;
+            if auth_user.api_key_id.is_some() || !(is_creator || auth_user.is_admin(&state.config))
+            {
+                return Err(ApiError::Forbidden(
+                    "Only the webhook's creator or an admin can manage it",
+                ));
+            }
+        }
+    }
+    Ok(webhook)
+}
+
+/// Checks `events` are one or more of the `allowed` ones
+fn check_events(events: &[String], allowed: &[&str]) -> Result<(), ApiError> {
+    if events.is_empty()
+        || events
+            .iter()
+            .any(|event| !allowed.contains(&event.as_str()))
+    {
+        return Err(ApiError::InvalidRequest(format!(
+            "Events must be one or more of {}",
+            allowed.join(", ")
+        )));
+    }
+    Ok(())
+}
+
+async fn check_public_url(url: &str) -> Result<(), ApiError> {
+    check_url(url).await.map_err(|e| {
+        ApiError::InvalidRequest(format!("Webhook URL must point to a public address: {}", e))
+    })
+}
+
+fn generate_secret() -> String {
+    format!(
+        "whsec_{}{}",
+        Uuid::new_v4().simple(),
+        Uuid::new_v4().simple()
+    )
+}
+
+impl Validate for CreateWebhookRequest {
+    fn validate(&mut self) -> Result<(), ApiError> {
+        match reqwest::Url::parse(&self.url) {
+            Ok(url) if url.scheme() == "https" && url.host().is_some() => Ok(()),
+            _ => Err(ApiError::InvalidRequest(
+                "Webhook URL must be a valid https URL".to_string(),
+            )),
+        }
+    }
+}
+
+/// Webhooks of a token
+#[utoipa::path(
+    get,
+    path = "/tokens/{address}/webhooks",
+    tag = "webhooks",
+    params(("address" = String, Path, description = "Token address")),
+    responses(
+        (status = 200, description = "Webhooks of the token", body = Response<Vec<Webhook>>),
+        (status = 403, description = "Neither the token deployer nor an admin", body = Response<ErrorResponse>),
+        (status = 404, description = "Unknown token", body = Response<ErrorResponse>),
+    ),
+    security(("github" = []))
+)]
+pub async fn token_webhooks(
+    auth_user: AuthUser,
+    State(state): State<AppState>,
+    Path(token_address): Path<String>,
+) -> Result<Json<Response<Vec<Webhook>>>, ApiError> {
+    let token_address = normalize_address("token", &token_address)?;
+    let token = get_managed_token(&state, &auth_user, token_address).await?;
+
+    let webhooks = state
+        .store
+        .get_webhooks_by_token(token.address)
+        .await
+        .map_err(|e| {
+            error!("Failed to fetch webhooks {}", e);
+            ApiError::Internal("Failed to fetch webhooks")
+        })?;
+    Ok(Response::ok(webhooks))
+}
+
+#[utoipa::path(
+    post,
+    path = "/tokens/{address}/webhooks",
+    tag = "webhooks",
+    params(("address" = String, Path, description = "Token address")),
+    request_body = CreateWebhookRequest,
+    responses(
+        (status = 200, description = "The webhook with its signing secret, shown only this once", body = Response<CreateWebhookResponse>),
+        (status = 400, description = "Invalid or non public URL, or invalid events", body = Response<ErrorResponse>),
+        (status = 403, description = "Neither the token deployer nor an admin", body = Response<ErrorResponse>),
+        (status = 404, description = "Unknown token", body = Response<ErrorResponse>),
+        (status = 409, description = "The token has as many webhooks as allowed", body = Response<ErrorResponse>),
+    ),
+    security(("github" = []))
+)]
+pub async fn create_webhook(
+    auth_user: AuthUser,
+    State(state): State<AppState>,
+    Path(token_address): Path<String>,
+    Valid(payload): Valid<CreateWebhookRequest>,
+) -> Result<Json<Response<CreateWebhookResponse>>, ApiError> {
+    let token_address = normalize_address("token", &token_address)?;
+    let token = get_managed_token(&state, &auth_user, token_address).await?;
+    check_events(&payload.events, &WEBHOOK_EVENTS)?;
+    check_public_url(&payload.url).await?;
+
+    let existing = state
+        .store
+        .get_webhooks_by_token(token.address.clone())
+        .await
+        .map_err(|e| {
+            error!("Failed to fetch webhooks {}", e);
+            ApiError::Internal("Failed to create webhook")
+        })?;
+    if existing.len() >= webhooks::MAX_WEBHOOKS_PER_TOKEN {
+        return Err(ApiError::Conflict(format!(
+            "A token can have at most {} webhooks",
+            webhooks::MAX_WEBHOOKS_PER_TOKEN
+        )));
+    }
+
+    let secret = generate_secret();
+    let webhook = Webhook {
+        id: Uuid::new_v4().to_string(),
+        token_address: Some(token.address),
+        url: payload.url,
+        secret: secret.clone(),
+        events: payload.events,
+        created_by: auth_user.user_id,
+        created_at: Utc::now(),
+    };
+    state
+        .store
+        .create_webhook(webhook.clone())
+        .await
+        .map_err(|e| {
+            error!("Failed to create webhook {}", e);
+            ApiError::Internal("Failed to create webhook")
+        })?;
+
+    Ok(Response::ok(CreateWebhookResponse { secret, webhook }))
+}
+
+/// Webhooks of the signed in user that are not tied to a token
+#[utoipa::path(
+    get,
+    path = "/webhooks",
+    tag = "webhooks",
+    responses(
+        (status = 200, description = "The user's webhooks", body = Response<Vec<Webhook>>),
+        (status = 401, description = "Not signed in with GitHub", body = Response<ErrorResponse>),
+    ),
+    security(("github" = []))
+)]
+pub async fn user_webhooks(
+    auth_user: AuthUser,
+    State(state): State<AppState>,
+) -> Result<Json<Response<Vec<Webhook>>>, ApiError> {
+    if !auth_user.is_github_authenticated {
+        return Err(ApiError::Unauthorized("Please sign in with Github"));
+    }
+
+    let webhooks = state
+        .store
+        .get_webhooks_by_user(auth_user.user_id)
+        .await
+        .map_err(|e| {
+            error!("Failed to fetch webhooks {}", e);
+            ApiError::Internal("Failed to fetch webhooks")
+        })?;
+    Ok(Response::ok(webhooks))
+}
+
+/// Creates a webhook of the signed in user, notified about the tokens they
+/// deploy
+#[utoipa::path(
+    post,
+    path = "/webhooks",
+    tag = "webhooks",
+    request_body = CreateWebhookRequest,
+    responses(
+        (status = 200, description = "The webhook with its signing secret, shown only this once", body = Response<CreateWebhookResponse>),
+        (status = 400, description = "Invalid or non public URL, or invalid events", body = Response<ErrorResponse>),
+        (status = 401, description = "Not signed in with GitHub", body = Response<ErrorResponse>),
+        (status = 409, description = "The user has as many webhooks as allowed", body = Response<ErrorResponse>),
+    ),
+    security(("github" = []))
+)]
+pub async fn create_user_webhook(
+    auth_user: AuthUser,
+    State(state): State<AppState>,
+    Valid(payload): Valid<CreateWebhookRequest>,
+) -> Result<Json<Response<CreateWebhookResponse>>, ApiError> {
+    if !auth_user.is_github_authenticated {
+        return Err(ApiError::Unauthorized("Please sign in with Github"));
+    }
+    check_events(&payload.events, &USER_WEBHOOK_EVENTS)?;
+    check_public_url(&payload.url).await?;
+
+    let existing = state
+        .store
+        .get_webhooks_by_user(auth_user.user_id.clone())
+        .await
+        .map_err(|e| {
+            error!("Failed to fetch webhooks {}", e);
+            ApiError::Internal("Failed to create webhook")
+        })?;
+    if existing.len() >= webhooks::MAX_WEBHOOKS_PER_USER {
+        return Err(ApiError::Conflict(format!(
+            "A user can have at most {} webhooks",
+            webhooks::MAX_WEBHOOKS_PER_USER
+        )));
+    }
+
+    let secret = generate_secret();
+    let webhook = Webhook {
+        id: Uuid::new_v4().to_string(),
+        token_address: None,
+        url: payload.url,
+        secret: secret.clone(),
+        events: payload.events,
+        created_by: auth_user.user_id,
+        created_at: Utc::now(),
+    };
+    state
+        .store
+        .create_webhook(webhook.clone())
+        .await
+        .map_err(|e| {
+            error!("Failed to create webhook {}", e);
+            ApiError::Internal("Failed to create webhook")
+        })?;
+
+    Ok(Response::ok(CreateWebhookResponse { secret, webhook }))
+}
+
+#[utoipa::path(
+    delete,
+    path = "/webhooks/{id}",
+    tag = "webhooks",
+    params(("id" = String, Path)),
+    responses(
+        (status = 200, description = "The deleted webhook", body = Response<Webhook>),
+        (status = 403, description = "Neither the token deployer, the webhook's creator nor an admin", body = Response<ErrorResponse>),
+        (status = 404, description = "No such webhook", body = Response<ErrorResponse>),
+    ),
+    security(("github" = []))
+)]
+pub async fn delete_webhook(
+    auth_user: AuthUser,
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+) -> Result<Json<Response<Webhook>>, ApiError> {
+    let webhook = get_managed_webhook(&state, &auth_user, id).await?;
+
+    state
+        .store
+        .delete_webhook(webhook.id.clone())
+        .await
+        .map_err(|e| {
+            error!("Failed to delete webhook {}", e);
+            ApiError::Internal("Failed to delete webhook")
+        })?;
+    Ok(Response::ok(webhook))
+}
+
+/// Latest deliveries of a webhook with their status, attempts and last error
+#[utoipa::path(
+    get,
+    path = "/webhooks/{id}/deliveries",
+    tag = "webhooks",
+    params(("id" = String, Path)),
+    responses(
+        (status = 200, description = "Latest deliveries, newest first", body = Response<Vec<WebhookDelivery>>),
+        (status = 403, description = "Neither the token deployer, the webhook's creator nor an admin", body = Response<ErrorResponse>),
+        (status = 404, description = "No such webhook", body = Response<ErrorResponse>),
+    ),
+    security(("github" = []))
+)]
+pub async fn webhook_deliveries(
+    auth_user: AuthUser,
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+) -> Result<Json<Response<Vec<WebhookDelivery>>>, ApiError> {
+    let webhook = get_managed_webhook(&state, &auth_user, id).await?;
+
+    let deliveries = state
+        .store
+        .get_webhook_deliveries(webhook.id, DELIVERY_LOG_LIMIT)
+        .await
+        .map_err(|e| {
+            error!("Failed to fetch webhook deliveries {}", e);
+            ApiError::Internal("Failed to fetch webhook deliveries")
+        })?;
+    Ok(Response::ok(deliveries))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_validate_webhook() {
+        let validate_webhook = |url: &str| {
+            CreateWebhookRequest {
+                url: url.to_string(),
+                events: vec!["DRIP_CONFIRMED".to_string()],
+            }
+            .validate()
+        };
+
+        assert!(validate_webhook("https://example.com/hook").is_ok());
+        assert!(validate_webhook("http://example.com/hook").is_err());
+        assert!(validate_webhook("not a url").is_err());
+    }
+
+    #[test]
+    fn test_check_events() {
+        let events = |events: &[&str]| events.iter().map(|e| e.to_string()).collect::<Vec<_>>();
+
+        assert!(check_events(&events(&["DRIP_CONFIRMED"]), &WEBHOOK_EVENTS).is_ok());
+        assert!(check_events(&events(&[]), &WEBHOOK_EVENTS).is_err());
+        assert!(check_events(&events(&["DRIP_SENT"]), &WEBHOOK_EVENTS).is_err());
+        // a token's webhooks are created after its deploy, so they would never see it
+        assert!(check_events(&events(&["TOKEN_DEPLOYED"]), &WEBHOOK_EVENTS).is_err());
+        assert!(check_events(&events(&["TOKEN_DEPLOYED"]), &USER_WEBHOOK_EVENTS).is_ok());
+        // a user's webhooks only hear about their own deploys
+        assert!(check_events(&events(&["DRIP_CONFIRMED"]), &USER_WEBHOOK_EVENTS).is_err());
+    }
+}
@@ -1,73 +1,274 @@
 use std::time::Duration;
 
 use crate::{
-    executor::{ErrorResponse, TokenTransferRequest},
+    events::FaucetEvent,
+    executor::{
+        BatchTransferRequest, BatchTransferResult, ErrorResponse, ExecutorResponse,
+        TokenTransferRequest,
+    },
     faucet::DripResponse,
+    idempotency::{self, Claim},
+    store::{IdempotencyRecord, PgStore, Store},
+    telemetry::QueueTrace,
     utils::magnify_faucet_drip,
     AppState,
 };
 use axum::{extract::State, Json};
 
 use super::{
-    middleware::AuthUser,
+    error::ApiError,
+    middleware::{AuthUser, IdempotencyKey, RequestId},
     response::{Response, ResponseStatus},
+    validation::Valid,
 };
 
-use tracing::error;
-
-#[derive(serde::Serialize, Debug)]
-pub struct WithdrawErc20Response {
-    pub tx_hash: String,
-}
+use tracing::{error, Instrument};
 
+/// Drips a token to a wallet, more of it for GitHub authenticated users
+#[utoipa::path(
+    post,
+    path = "/withdraw",
+    tag = "drips",
+    request_body = TokenTransferRequest,
+    params(
+        ("Idempotency-Key" = Option<String>, Header, description = "Retries with the same key get the first outcome instead of a second drip, signed in callers only"),
+    ),
+    responses(
+        (status = 200, description = "Drip sent", body = Response<DripResponse>),
+        (status = 400, description = "Malformed request or address", body = Response<ErrorResponse>),
+        (status = 401, description = "Idempotency-Key sent without signing in", body = Response<ErrorResponse>),
+        (status = 409, description = "A request with the same Idempotency-Key is in progress", body = Response<ErrorResponse>),
+        (status = 422, description = "Idempotency-Key reused or token not claimable", body = Response<ErrorResponse>),
+        (status = 429, description = "Claimed too recently", body = Response<ErrorResponse>),
+        (status = 503, description = "Faucet out of funds, paused or shutting down", body = Response<ErrorResponse>),
+    ),
+    security((), ("github" = []))
+)]
 #[axum::debug_handler]
 #[allow(unused_variables)]
 pub async fn withdraw(
     auth_user: AuthUser,
     State(state): State<AppState>,
-    Json(mut payload): Json<TokenTransferRequest>,
-) -> Result<Json<Response<DripResponse>>, Json<Response<ErrorResponse>>> {
+    RequestId(request_id): RequestId,
+    IdempotencyKey(idempotency_key): IdempotencyKey,
+    Valid(mut payload): Valid<TokenTransferRequest>,
+) -> Result<Json<Response<DripResponse>>, ApiError> {
+    if state.executor.is_shutting_down() {
+        return Err(ApiError::ShuttingDown);
+    }
+    idempotency::check_caller(&auth_user, idempotency_key.as_ref())?;
+
+    let scope = format!("withdraw:{}", auth_user.user_id);
+    if let Some(key) = &idempotency_key {
+        let token_type = serde_json::to_string(&payload.token_type).unwrap_or_default();
+        let hash = idempotency::request_hash(&[
+            payload.token_address.as_bytes(),
+            payload.to.as_bytes(),
+            token_type.as_bytes(),
+            payload.ip.to_string().as_bytes(),
+        ]);
+        match idempotency::claim(&state.store, scope.clone(), key.clone(), hash, None).await {
+            Ok(Claim::New) => {}
+            Ok(Claim::Repeat(record)) => return replay(record),
+            Ok(Claim::Mismatch) => return Err(idempotency::key_reused().into()),
+            Err(e) => {
+                error!("Failed to claim idempotency key {}", e);
+                return Err(ApiError::Internal("Something went wrong"));
+            }
+        }
+    }
+
     let (tx, rx) = tokio::sync::oneshot::channel();
     // maliciously setting magnification in req payload doesn't show any effect
     let magnification = magnify_faucet_drip(
-        state.config.orderbook_url.clone(),
+        &state.tuning.load(),
+        state.config.policies.orderbook_url.clone(),
         auth_user.clone(),
         payload.to.clone(),
     )
     .await;
     payload.magnification = Some(magnification);
-    {
-        let mut queue = state.executor.withdraw_queue.lock().map_err(|e| {
-            error!("Failed to lock withdraw queue {}", e);
-            Response::error(ErrorResponse {
-                message: "Something went wrong".to_string(),
-                next_access: None,
-            })
-        })?;
-        queue.push_back((payload, tx));
-    }
-
-    match tokio::time::timeout(Duration::from_secs(60), rx).await {
-        Ok(response) => match response {
-            Ok(response) => match response.clone().status {
-                ResponseStatus::Success => Ok(Response::ok(DripResponse {
-                    tx_hash: response.data.clone().unwrap().tx_hash,
-                    amount: response.data.unwrap().amount,
-                    magnification,
-                })),
-                ResponseStatus::Error => Err(Response::error(response.error.unwrap())),
-            },
-            Err(e) => Err(Response::error(ErrorResponse {
-                message: "Executor dropped the response channel".to_string(),
-                next_access: None,
-            })),
-        },
+    payload.request_id = Some(request_id);
+    let queued = FaucetEvent::DripQueued {
+        token_address: payload.token_address.clone(),
+        to: payload.to.clone(),
+    };
+    if let Err(e) = state.executor.enqueue(
+        &state.executor.withdraw_queue,
+        (payload, tx, QueueTrace::new("withdraw")),
+    ) {
+        if let Some(key) = idempotency_key {
+            record_outcome(&state.store, scope, key, None).await;
+        }
+        return Err(e.into());
+    }
+    state.executor.events.publish(queued);
+
+    // waits on for the job after the request timed out, so that a retry with
+    // the same key gets its outcome rather than queuing a second drip
+    let store = state.store.clone();
+    let outcome = tokio::spawn(
+        async move {
+            let outcome = rx
+                .await
+                .ok()
+                .map(|response| drip_outcome(response, magnification));
+            if let Some(key) = idempotency_key {
+                record_outcome(&store, scope, key, outcome.as_ref()).await;
+            }
+            outcome
+        }
+        .in_current_span(),
+    );
+
+    match tokio::time::timeout(Duration::from_secs(60), outcome).await {
+        Ok(Ok(Some(Ok(drip)))) => Ok(Response::ok(drip)),
+        Ok(Ok(Some(Err(e)))) => Err(e.into()),
+        Ok(_) => {
+            error!("Executor dropped the response channel of /withdraw");
+            Err(ApiError::Internal("Something went wrong"))
+        }
         Err(e) => {
             error!("Error at /withdraw {}", e);
-            Err(Response::error(ErrorResponse {
-                message: "Request timed out".to_string(),
-                next_access: None,
-            }))
+            Err(ApiError::Timeout)
+        }
+    }
+}
+
+fn drip_outcome(
+    response: ExecutorResponse,
+    magnification: u8,
+) -> Result<DripResponse, ErrorResponse> {
+    match (response.status, response.data, response.error) {
+        (ResponseStatus::Success, Some(data), _) => Ok(DripResponse {
+            tx_hash: data.tx_hash,
+            amount: data.amount,
+            magnification,
+        }),
+        (_, _, Some(error)) => Err(error),
+        _ => Err(ApiError::Internal("Something went wrong").into()),
+    }
+}
+
+/// Stores the outcome of a withdraw for retries with its key, or frees the
+/// key when the job never ran so that a retry can queue it again
+async fn record_outcome(
+    store: &PgStore,
+    scope: String,
+    key: String,
+    outcome: Option<&Result<DripResponse, ErrorResponse>>,
+) {
+    let ran = match outcome {
+        Some(Ok(_)) => true,
+        Some(Err(e)) => e.code.as_deref() != Some("SHUTTING_DOWN"),
+        None => false,
+    };
+    let result = match (ran, serde_json::to_string(&outcome)) {
+        (true, Ok(response)) => {
+            store
+                .complete_idempotency_key(scope, key.clone(), response)
+                .await
+        }
+        _ => store.delete_idempotency_key(scope, key.clone()).await,
+    };
+    if let Err(e) = result {
+        error!(
+            "Failed to record the outcome of idempotency key {} {}",
+            key, e
+        );
+    }
+}
+
+/// Answers a repeated withdraw with the outcome of the first one
+fn replay(record: IdempotencyRecord) -> Result<Json<Response<DripResponse>>, ApiError> {
+    let Some(response) = record.response else {
+        return Err(idempotency::in_progress().into());
+    };
+    match serde_json::from_str::<Option<Result<DripResponse, ErrorResponse>>>(&response) {
+        Ok(Some(Ok(drip))) => Ok(Response::ok(drip)),
+        Ok(Some(Err(e))) => Err(e.into()),
+        outcome => {
+            error!(
+                "Unreadable outcome of idempotency key {} {:?}",
+                record.key, outcome
+            );
+            Err(ApiError::Internal("Something went wrong"))
+        }
+    }
+}
+
+/// Drips several tokens to one wallet, answering how each of them went
+#[utoipa::path(
+    post,
+    path = "/withdraw/batch",
+    tag = "drips",
+    request_body = BatchTransferRequest,
+    responses(
+        (status = 200, description = "Outcome per token", body = Response<Vec<BatchTransferResult>>),
+        (status = 400, description = "Malformed request or too many tokens", body = Response<ErrorResponse>),
+        (status = 503, description = "Shutting down", body = Response<ErrorResponse>),
+    ),
+    security((), ("github" = []))
+)]
+#[axum::debug_handler]
+pub async fn withdraw_batch(
+    auth_user: AuthUser,
+    State(state): State<AppState>,
+    RequestId(request_id): RequestId,
+    Valid(mut payload): Valid<BatchTransferRequest>,
+) -> Result<Json<Response<Vec<BatchTransferResult>>>, ApiError> {
+    if state.executor.is_shutting_down() {
+        return Err(ApiError::ShuttingDown);
+    }
+    let max_batch_tokens = state.tuning.load().max_batch_tokens;
+    if payload.token_addresses.is_empty() || payload.token_addresses.len() > max_batch_tokens {
+        return Err(ErrorResponse {
+            message: format!(
+                "Between 1 and {} tokens can be claimed at once",
+                max_batch_tokens
+            ),
+            next_access: None,
+            code: Some("INVALID_BATCH_SIZE".to_string()),
+        }
+        .into());
+    }
+
+    let (tx, rx) = tokio::sync::oneshot::channel();
+    // maliciously setting magnification in req payload doesn't show any effect
+    let magnification = magnify_faucet_drip(
+        &state.tuning.load(),
+        state.config.policies.orderbook_url.clone(),
+        auth_user.clone(),
+        payload.to.clone(),
+    )
+    .await;
+    payload.magnification = Some(magnification);
+    payload.request_id = Some(request_id);
+    let queued: Vec<_> = payload
+        .token_addresses
+        .iter()
+        .map(|token_address| FaucetEvent::DripQueued {
+            token_address: token_address.clone(),
+            to: payload.to.clone(),
+        })
+        .collect();
+    state.executor.enqueue(
+        &state.executor.batch_queue,
+        (payload, tx, QueueTrace::new("withdraw_batch")),
+    )?;
+    for event in queued {
+        state.executor.events.publish(event);
+    }
+
+    match tokio::time::timeout(Duration::from_secs(120), rx).await {
+        Ok(Ok(results)) => Ok(Response::ok(results)),
+        Ok(Err(e)) => {
+            error!("Error at /withdraw/batch {}", e);
+            Err(ApiError::Internal("Something went wrong"))
+        }
+        Err(e) => {
+            error!("Error at /withdraw/batch {}", e);
+            Err(ApiError::Timeout)
         }
     }
 }
@@ -77,15 +278,25 @@ mod tests {
     use axum::{extract::State, Json};
 
     use crate::{
-        executor::TokenTransferRequest,
-        handlers::{middleware::AuthUser, withdraw::withdraw},
+        executor::{ErrorResponse, TokenTransferRequest},
+        faucet::DripResponse,
+        handlers::{
+            middleware::{AuthUser, IdempotencyKey, RequestId},
+            response::Response,
+            validation::Valid,
+            withdraw::withdraw,
+        },
         store::TokenType,
         utils::setup,
     };
 
+    fn request_id() -> RequestId {
+        RequestId(uuid::Uuid::new_v4().to_string())
+    }
+
     #[tokio::test]
     async fn test_erc20_withdraw_github_auth() {
-        let state = setup().await;
+        let state = setup().await.unwrap();
         let executor_clone = state.executor.clone();
         tokio::spawn(async move {
             executor_clone.process_queue().await;
@@ -93,20 +304,30 @@ mod tests {
         let auth_user = AuthUser {
             is_github_authenticated: true,
             user_id: "7313fc8b-491c-4275-b247-a7c489f88441".to_string(),
+            api_key_id: None,
         };
-        let payload = Json(TokenTransferRequest {
+        let payload = Valid(TokenTransferRequest {
             to: "0xDda173bd23b07007394611D789EF789a9Aae5CF5".to_string(),
             token_address: "0xb1baC9E12095043045d19F3E7a988D0C63dC2523".to_string(),
             token_type: TokenType::ERC20,
             ip: ipnetwork::IpNetwork::V4("60.243.163.4".parse().unwrap()),
             magnification: None,
+            request_id: None,
         });
-        let response = withdraw(auth_user, State(state), payload).await.unwrap();
+        let response = withdraw(
+            auth_user,
+            State(state),
+            request_id(),
+            IdempotencyKey(None),
+            payload,
+        )
+        .await
+        .unwrap();
         println!("{:?}", response);
     }
     #[tokio::test]
     async fn test_erc20_withdraw_no_auth() {
-        let state = setup().await;
+        let state = setup().await.unwrap();
         let executor_clone = state.executor.clone();
         tokio::spawn(async move {
             executor_clone.process_queue().await;
@@ -114,20 +335,30 @@ mod tests {
         let auth_user = AuthUser {
             is_github_authenticated: false,
             user_id: "7313fc8b-491c-4275-b247-a7c489f88441".to_string(),
+            api_key_id: None,
         };
-        let payload = Json(TokenTransferRequest {
+        let payload = Valid(TokenTransferRequest {
             to: "0xd53D4f100AaBA314bF033f99f86a312BfbdDF113".to_string(),
             token_address: "0xb1baC9E12095043045d19F3E7a988D0C63dC2523".to_string(),
             token_type: TokenType::ERC20,
             ip: ipnetwork::IpNetwork::V4("60.143.163.20".parse().unwrap()),
             magnification: None,
+            request_id: None,
         });
-        let response = withdraw(auth_user, State(state), payload).await.unwrap();
+        let response = withdraw(
+            auth_user,
+            State(state),
+            request_id(),
+            IdempotencyKey(None),
+            payload,
+        )
+        .await
+        .unwrap();
         println!("{:?}", response);
     }
     #[tokio::test]
     async fn test_erc20_withdraw_garden_user() {
-        let state = setup().await;
+        let state = setup().await.unwrap();
         let executor_clone = state.executor.clone();
         tokio::spawn(async move {
             executor_clone.process_queue().await;
@@ -135,21 +366,31 @@ mod tests {
         let auth_user = AuthUser {
             is_github_authenticated: true,
             user_id: "7313fc8b-491c-4275-b247-a7c489f88441".to_string(),
+            api_key_id: None,
         };
-        let payload = Json(TokenTransferRequest {
+        let payload = Valid(TokenTransferRequest {
             to: "0x41154d8D32dA87A7c565e964CD191243B728EDF7".to_string(),
             token_address: "0xb1baC9E12095043045d19F3E7a988D0C63dC2523".to_string(),
             token_type: TokenType::ERC20,
             ip: ipnetwork::IpNetwork::V4("60.243.163.20".parse().unwrap()),
             magnification: None,
+            request_id: None,
         });
-        let response = withdraw(auth_user, State(state), payload).await.unwrap();
+        let response = withdraw(
+            auth_user,
+            State(state),
+            request_id(),
+            IdempotencyKey(None),
+            payload,
+        )
+        .await
+        .unwrap();
         println!("{:?}", response);
     }
 
     #[tokio::test]
     async fn test_native_token_withdraw_github_auth() {
-        let state = setup().await;
+        let state = setup().await.unwrap();
         let executor_clone = state.executor.clone();
         tokio::spawn(async move {
             executor_clone.process_queue().await;
@@ -157,22 +398,32 @@ mod tests {
         let auth_user = AuthUser {
             is_github_authenticated: true,
             user_id: "7313fc8b-491c-4275-b247-a7c489f88441".to_string(),
+            api_key_id: None,
         };
-        let payload = Json(TokenTransferRequest {
+        let payload = Valid(TokenTransferRequest {
             to: "0xDda173bd23b07007394611D789EF789a9Aae5CF5".to_string(),
             // need to test on native token by replacing the address
             token_address: "0xb1baC9E12095043045d19F3E7a988D0C63dC2523".to_string(),
             token_type: TokenType::NATIVE,
             ip: ipnetwork::IpNetwork::V4("60.243.163.1".parse().unwrap()),
             magnification: None,
+            request_id: None,
         });
-        let response = withdraw(auth_user, State(state), payload).await.unwrap();
+        let response = withdraw(
+            auth_user,
+            State(state),
+            request_id(),
+            IdempotencyKey(None),
+            payload,
+        )
+        .await
+        .unwrap();
         println!("{:?}", response);
     }
 
     #[tokio::test]
     async fn test_erc20_withdraw_no_auth_load_test() {
-        let state = setup().await;
+        let state = setup().await.unwrap();
         let executor_clone = state.executor.clone();
         tokio::spawn(async move {
             executor_clone.process_queue().await;
@@ -188,18 +439,28 @@ mod tests {
                 let auth_user = AuthUser {
                     is_github_authenticated: false,
                     user_id: "7313fc8b-491c-4275-b247-a7c489f88441".to_string(),
+                    api_key_id: None,
                 };
 
                 let ip = format!("60.103.163.{}", i + 1);
-                let payload = Json(TokenTransferRequest {
+                let payload = Valid(TokenTransferRequest {
                     to: "0xd53D4f100AaBA314bF033f99f86a312BfbdDF113".to_string(),
                     token_address: "0xb1baC9E12095043045d19F3E7a988D0C63dC2523".to_string(),
                     token_type: TokenType::ERC20,
                     ip: ipnetwork::IpNetwork::V4(ip.parse().unwrap()),
                     magnification: None,
+                    request_id: None,
                 });
 
-                let response = withdraw(auth_user, State(state), payload).await.unwrap();
+                let response = withdraw(
+                    auth_user,
+                    State(state),
+                    request_id(),
+                    IdempotencyKey(None),
+                    payload,
+                )
+                .await
+                .unwrap();
                 let request_duration = request_start.elapsed();
                 println!(
                     "Request {} completed in {:?} with response: {:?}",
@@ -231,4 +492,63 @@ mod tests {
         writeln!(file, "Number of successful requests: {}", durations.len())
             .expect("Failed to write to file");
     }
+
+    #[tokio::test]
+    async fn test_withdraw_idempotency_key() {
+        let state = setup().await.unwrap();
+        let executor_clone = state.executor.clone();
+        tokio::spawn(async move {
+            executor_clone.process_queue().await;
+        });
+        let auth_user = AuthUser {
+            is_github_authenticated: true,
+            user_id: "7313fc8b-491c-4275-b247-a7c489f88441".to_string(),
+            api_key_id: None,
+        };
+        let payload = |to: &str| {
+            Valid(TokenTransferRequest {
+                to: to.to_string(),
+                token_address: "0xb1baC9E12095043045d19F3E7a988D0C63dC2523".to_string(),
+                token_type: TokenType::ERC20,
+                ip: ipnetwork::IpNetwork::V4("60.243.163.9".parse().unwrap()),
+                magnification: None,
+                request_id: None,
+            })
+        };
+        let key = IdempotencyKey(Some(uuid::Uuid::new_v4().to_string()));
+        let outcome = |response: Result<Json<Response<DripResponse>>, _>| match response {
+            Ok(Json(response)) => serde_json::to_value(response),
+            Err(e) => serde_json::to_value(ErrorResponse::from(e)),
+        };
+
+        let first = withdraw(
+            auth_user.clone(),
+            State(state.clone()),
+            request_id(),
+            key.clone(),
+            payload("0xDda173bd23b07007394611D789EF789a9Aae5CF5"),
+        )
+        .await;
+        // the retry gets the first outcome instead of a second drip
+        let retry = withdraw(
+            auth_user.clone(),
+            State(state.clone()),
+            request_id(),
+            key.clone(),
+            payload("0xDda173bd23b07007394611D789EF789a9Aae5CF5"),
+        )
+        .await;
+        assert_eq!(outcome(first).unwrap(), outcome(retry).unwrap());
+
+        let reused = withdraw(
+            auth_user,
+            State(state),
+            request_id(),
+            key,
+            payload("0xd53D4f100AaBA314bF033f99f86a312BfbdDF113"),
+        )
+        .await
+        .unwrap_err();
+        assert_eq!(reused.code(), "IDEMPOTENCY_KEY_REUSED");
+    }
 }
@@ -0,0 +1,119 @@
+use chrono::Utc;
+use sha2::{Digest, Sha256};
+
+use crate::{
+    constants::idempotency,
+    executor::ErrorResponse,
+    handlers::{error::ApiError, middleware::AuthUser},
+    store::{IdempotencyRecord, PgStore, Store},
+};
+
+/// Outcome of presenting an `Idempotency-Key`
+#[derive(Debug)]
+pub enum Claim {
+    /// First request with the key, which now belongs to it
+    New,
+    /// The same request was sent before, see its record for how it went
+    Repeat(IdempotencyRecord),
+    /// The key was first sent with a different request
+    Mismatch,
+}
+
+/// SHA-256 over the parts of a request, each prefixed with its length so
+/// that moving bytes from one part to the next changes the hash
+pub fn request_hash(parts: &[&[u8]]) -> String {
+    let mut hasher = Sha256::new();
+    for part in parts {
+        hasher.update((part.len() as u64).to_be_bytes());
+        hasher.update(part);
+    }
+    alloy::hex::encode(hasher.finalize())
+}
+
+/// Claims `key` for a request, unless it was used within the last
+/// `KEY_TTL_HOURS`. A deploy passes the job it is about to create.
+pub async fn claim(
+    store: &PgStore,
+    scope: String,
+    key: String,
+    request_hash: String,
+    deploy_job_id: Option<String>,
+) -> Result<Claim, sqlx::Error> {
+    let now = Utc::now();
+    let record = IdempotencyRecord {
+        scope: scope.clone(),
+        key: key.clone(),
+        request_hash: request_hash.clone(),
+        response: None,
+        deploy_job_id,
+        created_at: now,
+        locked_until: now + chrono::Duration::seconds(idempotency::IN_FLIGHT_LEASE_SECS),
+    };
+    let expired_before = now - chrono::Duration::hours(idempotency::KEY_TTL_HOURS);
+    if store.claim_idempotency_key(record, expired_before).await? {
+        return Ok(Claim::New);
+    }
+
+    let existing = store.get_idempotency_key(scope, key).await?;
+    if existing.request_hash != request_hash {
+        return Ok(Claim::Mismatch);
+    }
+    Ok(Claim::Repeat(existing))
+}
+
+/// Anonymous callers cannot be told apart, so their keys would share one
+/// scope and replay each other's outcomes. They are refused keys instead.
+pub fn check_caller(auth_user: &AuthUser, key: Option<&String>) -> Result<(), ApiError> {
+    if key.is_some() && auth_user.user_id.is_empty() {
+        return Err(ApiError::Unauthorized("Sign in to send an Idempotency-Key"));
+    }
+    Ok(())
+}
+
+pub fn key_reused() -> ErrorResponse {
+    ErrorResponse {
+        message: "This Idempotency-Key was already used for a different request".to_string(),
+        next_access: None,
+        code: Some("IDEMPOTENCY_KEY_REUSED".to_string()),
+    }
+}
+
+pub fn in_progress() -> ErrorResponse {
+    ErrorResponse {
+        message: "A request with this Idempotency-Key is still being processed".to_string(),
+        next_access: None,
+        code: Some("REQUEST_IN_PROGRESS".to_string()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_request_hash() {
+        let hash = request_hash(&[b"0xtoken", b"0xto"]);
+        assert_eq!(hash.len(), 64);
+        assert_eq!(hash, request_hash(&[b"0xtoken", b"0xto"]));
+        assert_ne!(hash, request_hash(&[b"0xtoken", b"0xtO"]));
+        // parts are delimited, not concatenated
+        assert_ne!(hash, request_hash(&[b"0xtoke", b"n0xto"]));
+    }
+
+    #[test]
+    fn test_check_caller() {
+        let user = |user_id: &str| AuthUser {
+            user_id: user_id.to_string(),
+            is_github_authenticated: !user_id.is_empty(),
+            api_key_id: None,
+        };
+        let key = "key".to_string();
+
+        assert!(check_caller(&user("1"), Some(&key)).is_ok());
+        assert!(check_caller(&user(""), None).is_ok());
+        assert!(matches!(
+            check_caller(&user(""), Some(&key)),
+            Err(ApiError::Unauthorized(_))
+        ));
+    }
+}
@@ -0,0 +1,332 @@
+use std::io::Cursor;
+
+use image::{imageops::FilterType, DynamicImage, ImageFormat, ImageReader, Limits};
+use resvg::{tiny_skia, usvg};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+use crate::constants::logo;
+
+/// Image formats accepted for token logos, detected from magic bytes
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum LogoFormat {
+    Png,
+    Jpeg,
+    WebP,
+    Svg,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum LogoError {
+    Empty,
+    TooLarge,
+    UnsupportedFormat,
+    TooManyPixels(u32, u32),
+    TooComplex,
+    Invalid(String),
+}
+
+impl std::fmt::Display for LogoError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            LogoError::Empty => write!(f, "Logo file is empty"),
+            LogoError::TooLarge => {
+                write!(f, "Logo must be at most {} bytes", logo::MAX_BYTES)
+            }
+            LogoError::UnsupportedFormat => {
+                write!(f, "Logo must be a PNG, JPEG, WebP or SVG image")
+            }
+            LogoError::TooManyPixels(width, height) => write!(
+                f,
+                "Logo is {}x{}, at most {}x{} pixels are allowed",
+                width,
+                height,
+                logo::MAX_DIMENSION,
+                logo::MAX_DIMENSION
+            ),
+            LogoError::TooComplex => write!(
+                f,
+                "Logo must have at most {} SVG elements",
+                logo::MAX_SVG_NODES
+            ),
+            LogoError::Invalid(reason) => write!(f, "Invalid logo: {}", reason),
+        }
+    }
+}
+
+/// A validated logo, re-encoded into a full size image of at most `FULL_SIZE`
+/// pixels a side and a square thumbnail
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProcessedLogo {
+    /// SHA-256 of the uploaded bytes, used to store identical logos once
+    pub hash: String,
+    pub full: Vec<u8>,
+    pub full_content_type: String,
+    pub full_extension: String,
+    /// `THUMBNAIL_SIZE` x `THUMBNAIL_SIZE` PNG
+    pub thumbnail: Vec<u8>,
+}
+
+/// Detects the real image type of `data` regardless of the client supplied
+/// file name or content type.
+pub fn sniff(data: &[u8]) -> Option<LogoFormat> {
+    if data.starts_with(&[0x89, b'P', b'N', b'G', 0x0D, 0x0A, 0x1A, 0x0A]) {
+        return Some(LogoFormat::Png);
+    }
+    if data.starts_with(&[0xFF, 0xD8, 0xFF]) {
+        return Some(LogoFormat::Jpeg);
+    }
+    if data.len() >= 12 && &data[0..4] == b"RIFF" && &data[8..12] == b"WEBP" {
+        return Some(LogoFormat::WebP);
+    }
+
+    let head = String::from_utf8_lossy(&data[..data.len().min(1024)]);
+    let head = head.trim_start_matches('\u{feff}').trim_start();
+    if head.starts_with('<') && head.contains("<svg") {
+        return Some(LogoFormat::Svg);
+    }
+
+    None
+}
+
+/// Validates and normalizes an uploaded logo.
+///
+/// Raster images are decoded within pixel limits, scaled down to `FULL_SIZE`
+/// and re-encoded as PNG, which also strips any metadata. SVGs are parsed and
+/// written back by `usvg`, which drops scripts, event handlers and external
+/// references, and are refused before rendering if their intrinsic size or
+/// element count is out of bounds.
+pub fn process(data: &[u8]) -> Result<ProcessedLogo, LogoError> {
+    if data.is_empty() {
+        return Err(LogoError::Empty);
+    }
+    if data.len() > logo::MAX_BYTES {
+        return Err(LogoError::TooLarge);
+    }
+
+    let hash = alloy::hex::encode(Sha256::digest(data));
+
+    match sniff(data).ok_or(LogoError::UnsupportedFormat)? {
+        LogoFormat::Png => process_raster(data, ImageFormat::Png, hash),
+        LogoFormat::Jpeg => process_raster(data, ImageFormat::Jpeg, hash),
+        LogoFormat::WebP => process_raster(data, ImageFormat::WebP, hash),
+        LogoFormat::Svg => process_svg(data, hash),
+    }
+}
+
+fn encode_png(image: &DynamicImage) -> Result<Vec<u8>, LogoError> {
+    let mut buf = Vec::new();
+    image
+        .write_to(&mut Cursor::new(&mut buf), ImageFormat::Png)
+        .map_err(|e| LogoError::Invalid(e.to_string()))?;
+    Ok(buf)
+}
+
+fn process_raster(
+    data: &[u8],
+    format: ImageFormat,
+    hash: String,
+) -> Result<ProcessedLogo, LogoError> {
+    let mut reader = ImageReader::with_format(Cursor::new(data), format);
+    let (width, height) = reader
+        .into_dimensions()
+        .map_err(|e| LogoError::Invalid(e.to_string()))?;
+    if width > logo::MAX_DIMENSION || height > logo::MAX_DIMENSION {
+        return Err(LogoError::TooManyPixels(width, height));
+    }
+
+    reader = ImageReader::with_format(Cursor::new(data), format);
+    let mut limits = Limits::default();
+    limits.max_image_width = Some(logo::MAX_DIMENSION);
+    limits.max_image_height = Some(logo::MAX_DIMENSION);
+    reader.limits(limits);
+    let image = reader
+        .decode()
+        .map_err(|e| LogoError::Invalid(e.to_string()))?;
+
+    // center crop to a square before scaling so thumbnails are never distorted
+    let side = width.min(height);
+    let thumbnail = image
+        .crop_imm((width - side) / 2, (height - side) / 2, side, side)
+        .resize_exact(
+            logo::THUMBNAIL_SIZE,
+            logo::THUMBNAIL_SIZE,
+            FilterType::Lanczos3,
+        );
+
+    // `resize` keeps the aspect ratio within the bounds
+    let full = if width > logo::FULL_SIZE || height > logo::FULL_SIZE {
+        image.resize(logo::FULL_SIZE, logo::FULL_SIZE, FilterType::Lanczos3)
+    } else {
+        image
+    };
+
+    Ok(ProcessedLogo {
+        hash,
+        full: encode_png(&full)?,
+        full_content_type: "image/png".to_string(),
+        full_extension: "png".to_string(),
+        thumbnail: encode_png(&thumbnail)?,
+    })
+}
+
+fn process_svg(data: &[u8], hash: String) -> Result<ProcessedLogo, LogoError> {
+    let options = usvg::Options {
+        // never read files or decode embedded images referenced by the SVG
+        image_href_resolver: usvg::ImageHrefResolver {
+            resolve_data: Box::new(|_, _, _| None),
+            resolve_string: Box::new(|_, _| None),
+        },
+        ..Default::default()
+    };
+
+    let tree =
+        usvg::Tree::from_data(data, &options).map_err(|e| LogoError::Invalid(e.to_string()))?;
+
+    let size = tree.size();
+    if size.width() > logo::MAX_DIMENSION as f32 || size.height() > logo::MAX_DIMENSION as f32 {
+        return Err(LogoError::TooManyPixels(
+            size.width().ceil() as u32,
+            size.height().ceil() as u32,
+        ));
+    }
+    if count_nodes(tree.root()) > logo::MAX_SVG_NODES {
+        return Err(LogoError::TooComplex);
+    }
+    let sanitized = tree.to_string(&usvg::WriteOptions::default());
+
+    let thumbnail_size = logo::THUMBNAIL_SIZE as f32;
+    let scale = thumbnail_size / size.width().max(size.height());
+    let mut pixmap = tiny_skia::Pixmap::new(logo::THUMBNAIL_SIZE, logo::THUMBNAIL_SIZE)
+        .ok_or(LogoError::Invalid("empty thumbnail".to_string()))?;
+    resvg::render(
+        &tree,
+        tiny_skia::Transform::from_row(
+            scale,
+            0.0,
+            0.0,
+            scale,
+            (thumbnail_size - size.width() * scale) / 2.0,
+            (thumbnail_size - size.height() * scale) / 2.0,
+        ),
+        &mut pixmap.as_mut(),
+    );
+    let thumbnail = pixmap
+        .encode_png()
+        .map_err(|e| LogoError::Invalid(e.to_string()))?;
+
+    Ok(ProcessedLogo {
+        hash,
+        full: sanitized.into_bytes(),
+        full_content_type: "image/svg+xml".to_string(),
+        full_extension: "svg".to_string(),
+        thumbnail,
+    })
+}
+
+/// Counts the elements of `group` including the ones referenced through clip
+/// paths, masks, patterns and filters, which are rendered as well
+fn count_nodes(group: &usvg::Group) -> usize {
+    group
+        .children()
+        .iter()
+        .map(|node| {
+            let mut count = 1;
+            if let usvg::Node::Group(group) = node {
+                count += count_nodes(group);
+            }
+            node.subroots(|subroot| count += count_nodes(subroot));
+            count
+        })
+        .sum()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use image::{Rgba, RgbaImage};
+
+    fn png(width: u32, height: u32) -> Vec<u8> {
+        let image =
+            DynamicImage::ImageRgba8(RgbaImage::from_pixel(width, height, Rgba([255, 0, 0, 255])));
+        encode_png(&image).unwrap()
+    }
+
+    #[test]
+    fn test_sniff() {
+        assert_eq!(sniff(&png(1, 1)), Some(LogoFormat::Png));
+        assert_eq!(sniff(&[0xFF, 0xD8, 0xFF, 0xE0]), Some(LogoFormat::Jpeg));
+        assert_eq!(sniff(b"RIFF\0\0\0\0WEBPVP8 "), Some(LogoFormat::WebP));
+        assert_eq!(
+            sniff(b"<?xml version=\"1.0\"?>\n<svg xmlns=\"http://www.w3.org/2000/svg\"/>"),
+            Some(LogoFormat::Svg)
+        );
+        assert_eq!(sniff(b"GIF89a"), None);
+        assert_eq!(sniff(b"<html><script></script></html>"), None);
+    }
+
+    #[test]
+    fn test_process_png() {
+        let logo = process(&png(300, 200)).unwrap();
+        assert_eq!(logo.full_extension, "png");
+
+        let thumbnail = image::load_from_memory(&logo.thumbnail).unwrap();
+        assert_eq!(thumbnail.width(), logo::THUMBNAIL_SIZE);
+        assert_eq!(thumbnail.height(), logo::THUMBNAIL_SIZE);
+
+        // identical uploads hash the same so they are stored once
+        assert_eq!(logo.hash, process(&png(300, 200)).unwrap().hash);
+
+        let full = image::load_from_memory(&process(&png(1000, 500)).unwrap().full).unwrap();
+        assert_eq!(full.width(), logo::FULL_SIZE);
+        assert_eq!(full.height(), logo::FULL_SIZE / 2);
+    }
+
+    #[test]
+    fn test_process_rejects() {
+        assert_eq!(process(&[]).unwrap_err(), LogoError::Empty);
+        assert_eq!(
+            process(b"GIF89a....").unwrap_err(),
+            LogoError::UnsupportedFormat
+        );
+        assert_eq!(
+            process(&png(logo::MAX_DIMENSION + 1, 1)).unwrap_err(),
+            LogoError::TooManyPixels(logo::MAX_DIMENSION + 1, 1)
+        );
+    }
+
+    #[test]
+    fn test_process_svg_strips_scripts() {
+        let svg =
+            br#"<svg xmlns="http://www.w3.org/2000/svg" width="10" height="10" onload="alert(1)">
+            <script>alert(1)</script>
+            <rect width="10" height="10" fill="red"/>
+        </svg>"#;
+        let logo = process(svg).unwrap();
+        let sanitized = String::from_utf8(logo.full).unwrap();
+        assert!(!sanitized.contains("script"));
+        assert!(!sanitized.contains("onload"));
+        assert_eq!(logo.full_content_type, "image/svg+xml");
+    }
+
+    #[test]
+    fn test_process_svg_rejects() {
+        let huge = br#"<svg xmlns="http://www.w3.org/2000/svg" width="100000" height="10">
+            <rect width="10" height="10" fill="red"/>
+        </svg>"#;
+        assert_eq!(
+            process(huge).unwrap_err(),
+            LogoError::TooManyPixels(100000, 10)
+        );
+
+        let rects = r#"<rect width="1" height="1"/>"#.repeat(logo::MAX_SVG_NODES + 1);
+        let complex = format!(
+            r#"<svg xmlns="http://www.w3.org/2000/svg" width="10" height="10">{}</svg>"#,
+            rects
+        );
+        assert_eq!(
+            process(complex.as_bytes()).unwrap_err(),
+            LogoError::TooComplex
+        );
+    }
+}
@@ -1,16 +1,24 @@
-use crate::common::setup_tracing_with_webhook;
-use crate::config::Config;
+use crate::common::setup_tracing_with_sinks;
+use crate::config::{Config, StorageConfig, Tuning};
 use crate::handlers::{
-    auth::auth, deploy_erc20::deploy_erc20, health::health_check, test_auth::test_auth,
-    tokens::tokens, turnstile_captcha::verify_turnstile_captcha, user::user, withdraw::withdraw,
+    health::health_check,
+    middleware::{sanitize_request_id, REQUEST_ID_HEADER},
+};
+use axum::{
+    body::Body,
+    extract::MatchedPath,
+    http::{HeaderValue, Request},
+    routing::get,
 };
-use axum::{routing::get, routing::post, Router};
 use executor::Executor;
 use reqwest::Method;
+use std::net::SocketAddr;
 use store::PgStore;
 use tokio::net::TcpListener;
 use tower_http::cors::{AllowHeaders, CorsLayer};
-use tower_http::trace::{DefaultMakeSpan, DefaultOnRequest, DefaultOnResponse, TraceLayer};
+use tower_http::request_id::{MakeRequestUuid, PropagateRequestIdLayer, SetRequestIdLayer};
+use tower_http::services::ServeDir;
+use tower_http::trace::{DefaultOnRequest, DefaultOnResponse, TraceLayer};
 use tracing::Level;
 use utils::setup;
 
@@ -18,16 +26,29 @@ mod common;
 mod config;
 mod constants;
 mod db;
+mod events;
 mod executor;
 mod faucet;
+mod github;
 mod handlers;
+mod idempotency;
+mod logo;
+mod object_store;
+mod openapi;
+mod routes;
+mod session;
+mod signer;
 mod store;
+mod telemetry;
 mod utils;
+mod webhooks;
 
 #[derive(Clone)]
 pub struct AppState {
     pub store: PgStore,
     pub config: Config,
+    /// Runtime tunable drip parameters, reloaded on SIGHUP or `POST /admin/reload`
+    pub tuning: Tuning,
     pub executor: Executor,
 }
 
@@ -35,53 +56,148 @@ const ZERO_ADDRESS: &str = "0x0000000000000000000000000000000000000000";
 
 #[tokio::main]
 async fn main() {
-    let state = setup().await;
-    match state.config.discord_webhook.clone() {
-        Some(webhook) => {
-            setup_tracing_with_webhook(&webhook, "Monad faucet", tracing::Level::WARN, None)
-                .expect("Failed to setup tracing with webhook");
-        }
-        None => {
-            tracing_subscriber::fmt().pretty().init();
-        }
+    let state = setup().await.unwrap_or_else(|e| {
+        // tracing is set up from the config, so there is nowhere else to report to
+        eprintln!("{:#}", e);
+        std::process::exit(1);
+    });
+    setup_tracing_with_sinks(
+        &state.config.alerts.sinks,
+        "Monad faucet",
+        state.config.logging.format,
+        state.config.logging.otlp.as_ref(),
+    )
+    .expect("Failed to setup tracing with alert sinks");
+
+    tokio::spawn(state.tuning.clone().reload_on_sighup());
+
+    let policies = &state.config.policies;
+    if policies.captcha_disabled {
+        tracing::warn!("Captchas are disabled, every captcha passes unchecked");
+    } else if policies.captcha_secret_key.is_none() {
+        tracing::warn!("No captcha secret key is configured, every captcha is refused");
     }
 
     let executor_clone = state.executor.clone();
 
-    tokio::spawn(async move {
+    let executor_task = tokio::spawn(async move {
         executor_clone.process_queue().await;
     });
 
+    let executor_clone = state.executor.clone();
+    tokio::spawn(async move {
+        shutdown_signal().await;
+        tracing::info!("Shutting down, finishing in-flight jobs");
+        executor_clone.shutdown();
+    });
+
     let cors = CorsLayer::new()
-        .allow_methods(vec![Method::GET, Method::POST])
-        .allow_origin(vec![
-            "https://faucet.wtf".parse().unwrap(),
-            "http://localhost:5173".parse().unwrap(),
-        ])
-        .allow_headers(AllowHeaders::any());
-
-    let app = Router::new()
+        .allow_methods(vec![Method::GET, Method::POST, Method::PUT, Method::DELETE])
+        .allow_origin(
+            state
+                .config
+                .cors
+                .allowed_origins
+                .iter()
+                // validated when the config was loaded
+                .map(|origin| origin.parse().expect("Invalid CORS origin"))
+                .collect::<Vec<HeaderValue>>(),
+        )
+        .allow_headers(AllowHeaders::any())
+        .expose_headers([REQUEST_ID_HEADER.clone()]);
+
+    let (router, api) = routes::router().split_for_parts();
+    let router = router
         .route("/", get(health_check))
-        .route("/health", get(health_check))
-        .route("/verify-turnstile-captcha", post(verify_turnstile_captcha))
-        .route("/auth", get(auth))
-        .route("/user", get(user))
-        .route("/test_auth", get(test_auth))
-        .route("/withdraw", post(withdraw))
-        .route("/deploy/erc20", post(deploy_erc20))
-        .route("/tokens", get(tokens))
+        .merge(routes::legacy())
+        .merge(openapi::docs(api));
+
+    // logos stored on the local filesystem are served by the backend itself
+    let router = match &state.config.storage {
+        Some(StorageConfig::Local { dir, .. }) => {
+            router.nest_service("/assets", ServeDir::new(dir))
+        }
+        _ => router,
+    };
+
+    let app = router
         .layer(axum::Extension(state.clone()))
         .layer(cors)
         .layer(
             TraceLayer::new_for_http()
-                .make_span_with(DefaultMakeSpan::new().level(Level::INFO))
+                .make_span_with(|request: &Request<Body>| {
+                    let request_id = request
+                        .headers()
+                        .get(&REQUEST_ID_HEADER)
+                        .and_then(|id| id.to_str().ok())
+                        .unwrap_or_default();
+                    // exported spans are named after the route rather than the raw URI
+                    let route = request
+                        .extensions()
+                        .get::<MatchedPath>()
+                        .map(MatchedPath::as_str)
+                        .unwrap_or_default();
+                    tracing::info_span!(
+                        "request",
+                        otel.name = %format!("{} {}", request.method(), route),
+                        method = %request.method(),
+                        // the query is left out, it may carry codes or tokens
+                        path = %request.uri().path(),
+                        request_id,
+                    )
+                })
                 .on_request(DefaultOnRequest::new().level(Level::INFO))
                 .on_response(DefaultOnResponse::new().level(Level::INFO)),
         )
+        // set before the trace layer so every request span has an ID
+        .layer(PropagateRequestIdLayer::new(REQUEST_ID_HEADER.clone()))
+        .layer(SetRequestIdLayer::new(
+            REQUEST_ID_HEADER.clone(),
+            MakeRequestUuid,
+        ))
+        // client sent IDs end up in logs and responses, invalid ones are replaced
+        .layer(axum::middleware::from_fn(sanitize_request_id))
         .with_state(state.clone());
 
-    let addr = format!("{}:{}", state.clone().config.host, state.config.port).to_string();
+    let addr = format!("{}:{}", state.config.server.host, state.config.server.port).to_string();
     let tcp_listener = TcpListener::bind(&addr).await.unwrap();
-    println!("Listening on {}", &addr);
-    axum::serve(tcp_listener, app).await.unwrap();
+    tracing::info!("Listening on {}", &addr);
+    let executor_clone = state.executor.clone();
+    // stops accepting connections, requests in flight still get their response
+    axum::serve(
+        tcp_listener,
+        app.into_make_service_with_connect_info::<SocketAddr>(),
+    )
+    .with_graceful_shutdown(async move { executor_clone.shutdown_started().await })
+    .await
+    .unwrap();
+
+    if let Err(e) = executor_task.await {
+        tracing::error!("Executor stopped abnormally {}", e);
+    }
+    // flushes spans still waiting for export
+    opentelemetry::global::shutdown_tracer_provider();
+    tracing::info!("Shut down");
+}
+
+/// Resolves on SIGTERM or Ctrl+C
+async fn shutdown_signal() {
+    use tokio::signal::unix::{signal, SignalKind};
+
+    let terminate = async {
+        match signal(SignalKind::terminate()) {
+            Ok(mut terminate) => {
+                terminate.recv().await;
+            }
+            Err(e) => {
+                tracing::error!("Failed to listen for SIGTERM {}", e);
+                std::future::pending::<()>().await;
+            }
+        }
+    };
+
+    tokio::select! {
+        _ = terminate => {}
+        _ = tokio::signal::ctrl_c() => {}
+    }
 }
@@ -0,0 +1,282 @@
+use std::{path::PathBuf, sync::Arc};
+
+use chrono::Utc;
+use hmac::{Hmac, Mac};
+use sha2::{Digest, Sha256};
+
+use crate::config::StorageConfig;
+
+/// Folder every token logo is stored under, whichever backend is used
+pub const LOGO_PREFIX: &str = "monad-faucet";
+
+/// Storage for token logos and other public assets.
+#[async_trait::async_trait]
+pub trait ObjectStore: Send + Sync + std::fmt::Debug {
+    /// Stores `data` under `key` and returns the public URL it is served from.
+    async fn put(&self, key: &str, data: Vec<u8>, content_type: &str) -> Result<String, String>;
+}
+
+/// Builds the object store selected in the config.
+pub fn from_config(config: &StorageConfig) -> Arc<dyn ObjectStore> {
+    match config {
+        StorageConfig::Imgix {
+            api_key,
+            source_id,
+            public_url,
+        } => Arc::new(ImgixStore {
+            api_key: api_key.clone(),
+            source_id: source_id.clone(),
+            public_url: public_url.clone(),
+            client: reqwest::Client::new(),
+        }),
+        StorageConfig::S3 {
+            endpoint,
+            bucket,
+            region,
+            access_key,
+            secret_key,
+            public_url,
+        } => Arc::new(S3Store {
+            endpoint: endpoint.trim_end_matches('/').to_string(),
+            bucket: bucket.clone(),
+            region: region.clone(),
+            access_key: access_key.clone(),
+            secret_key: secret_key.clone(),
+            public_url: public_url.clone(),
+            client: reqwest::Client::new(),
+        }),
+        StorageConfig::Local { dir, public_url } => Arc::new(LocalFsStore {
+            dir: PathBuf::from(dir),
+            public_url: public_url.clone(),
+        }),
+    }
+}
+
+fn join_url(base: &str, key: &str) -> String {
+    format!("{}/{}", base.trim_end_matches('/'), key)
+}
+
+/// Uploads through the imgix source upload API.
+#[derive(Debug)]
+pub struct ImgixStore {
+    api_key: String,
+    source_id: String,
+    public_url: String,
+    client: reqwest::Client,
+}
+
+#[async_trait::async_trait]
+impl ObjectStore for ImgixStore {
+    async fn put(&self, key: &str, data: Vec<u8>, content_type: &str) -> Result<String, String> {
+        let url = format!(
+            "https://api.imgix.com/api/v1/sources/{}/upload/{}",
+            self.source_id, key
+        );
+        let response = self
+            .client
+            .post(url)
+            .header("Authorization", format!("Bearer {}", self.api_key))
+            .header("Content-Type", content_type)
+            .body(data)
+            .send()
+            .await
+            .map_err(|e| e.to_string())?;
+
+        if response.status().is_success() {
+            Ok(join_url(&self.public_url, key))
+        } else {
+            Err(response.text().await.unwrap_or_default())
+        }
+    }
+}
+
+/// Uploads to any S3 compatible API (AWS, MinIO, R2, ...) using path-style
+/// requests signed with AWS Signature Version 4.
+#[derive(Debug)]
+pub struct S3Store {
+    endpoint: String,
+    bucket: String,
+    region: String,
+    access_key: String,
+    secret_key: String,
+    public_url: String,
+    client: reqwest::Client,
+}
+
+type HmacSha256 = Hmac<Sha256>;
+
+fn hmac_sha256(key: &[u8], data: &[u8]) -> Vec<u8> {
+    let mut mac = HmacSha256::new_from_slice(key).expect("HMAC accepts keys of any size");
+    mac.update(data);
+    mac.finalize().into_bytes().to_vec()
+}
+
+fn sha256_hex(data: &[u8]) -> String {
+    alloy::hex::encode(Sha256::digest(data))
+}
+
+/// Derives the SigV4 signing key for a date (`YYYYMMDD`), region and service.
+fn signing_key(secret_key: &str, date: &str, region: &str, service: &str) -> Vec<u8> {
+    let k_date = hmac_sha256(format!("AWS4{}", secret_key).as_bytes(), date.as_bytes());
+    let k_region = hmac_sha256(&k_date, region.as_bytes());
+    let k_service = hmac_sha256(&k_region, service.as_bytes());
+    hmac_sha256(&k_service, b"aws4_request")
+}
+
+/// Percent-encodes a path segment as required by SigV4 canonical URIs.
+fn uri_encode(segment: &str) -> String {
+    segment
+        .bytes()
+        .map(|b| match b {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' => {
+                (b as char).to_string()
+            }
+            _ => format!("%{:02X}", b),
+        })
+        .collect()
+}
+
+#[async_trait::async_trait]
+impl ObjectStore for S3Store {
+    async fn put(&self, key: &str, data: Vec<u8>, content_type: &str) -> Result<String, String> {
+        let url = reqwest::Url::parse(&self.endpoint).map_err(|e| e.to_string())?;
+        let host = match (url.host_str(), url.port()) {
+            (Some(host), Some(port)) => format!("{}:{}", host, port),
+            (Some(host), None) => host.to_string(),
+            (None, _) => return Err("Invalid S3 endpoint".to_string()),
+        };
+
+        let now = Utc::now();
+        let amz_date = now.format("%Y%m%dT%H%M%SZ").to_string();
+        let date = now.format("%Y%m%d").to_string();
+        let payload_hash = sha256_hex(&data);
+        let canonical_uri = format!(
+            "/{}/{}",
+            uri_encode(&self.bucket),
+            key.split('/').map(uri_encode).collect::<Vec<_>>().join("/")
+        );
+
+        let signed_headers = "content-type;host;x-amz-content-sha256;x-amz-date";
+        let canonical_request = format!(
+            "PUT\n{}\n\ncontent-type:{}\nhost:{}\nx-amz-content-sha256:{}\nx-amz-date:{}\n\n{}\n{}",
+            canonical_uri, content_type, host, payload_hash, amz_date, signed_headers, payload_hash
+        );
+        let scope = format!("{}/{}/s3/aws4_request", date, self.region);
+        let string_to_sign = format!(
+            "AWS4-HMAC-SHA256\n{}\n{}\n{}",
+            amz_date,
+            scope,
+            sha256_hex(canonical_request.as_bytes())
+        );
+        let signature = alloy::hex::encode(hmac_sha256(
+            &signing_key(&self.secret_key, &date, &self.region, "s3"),
+            string_to_sign.as_bytes(),
+        ));
+        let authorization = format!(
+            "AWS4-HMAC-SHA256 Credential={}/{}, SignedHeaders={}, Signature={}",
+            self.access_key, scope, signed_headers, signature
+        );
+
+        let response = self
+            .client
+            .put(format!("{}{}", self.endpoint, canonical_uri))
+            .header("Content-Type", content_type)
+            .header("x-amz-content-sha256", payload_hash)
+            .header("x-amz-date", amz_date)
+            .header("Authorization", authorization)
+            .body(data)
+            .send()
+            .await
+            .map_err(|e| e.to_string())?;
+
+        if response.status().is_success() {
+            Ok(join_url(&self.public_url, key))
+        } else {
+            Err(response.text().await.unwrap_or_default())
+        }
+    }
+}
+
+/// Writes objects to a local directory which the backend itself serves under
+/// `/assets`. Meant for local development and CI.
+#[derive(Debug)]
+pub struct LocalFsStore {
+    dir: PathBuf,
+    public_url: String,
+}
+
+#[async_trait::async_trait]
+impl ObjectStore for LocalFsStore {
+    async fn put(&self, key: &str, data: Vec<u8>, _content_type: &str) -> Result<String, String> {
+        if key
+            .split('/')
+            .any(|segment| segment.is_empty() || segment == "..")
+        {
+            return Err("Invalid object key".to_string());
+        }
+
+        let path = self.dir.join(key);
+        if let Some(parent) = path.parent() {
+            tokio::fs::create_dir_all(parent)
+                .await
+                .map_err(|e| e.to_string())?;
+        }
+        tokio::fs::write(&path, data)
+            .await
+            .map_err(|e| e.to_string())?;
+
+        Ok(join_url(&self.public_url, &format!("assets/{}", key)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_signing_key() {
+        // example from the AWS SigV4 documentation
+        let key = signing_key(
+            "wJalrXUtnFEMI/K7MDENG+bPxRfiCYEXAMPLEKEY",
+            "20120215",
+            "us-east-1",
+            "iam",
+        );
+        assert_eq!(
+            alloy::hex::encode(key),
+            "f4780e2d9f65fa895f9c67b32ce1baf0b0d8a43505a000a1a9e090d414db404d"
+        );
+    }
+
+    #[test]
+    fn test_uri_encode() {
+        assert_eq!(uri_encode("logo 1.png"), "logo%201.png");
+        assert_eq!(uri_encode("a~b_c-d.e"), "a~b_c-d.e");
+    }
+
+    #[tokio::test]
+    async fn test_local_fs_store() {
+        let dir = std::env::temp_dir().join(uuid::Uuid::new_v4().to_string());
+        let store = LocalFsStore {
+            dir: dir.clone(),
+            public_url: "http://localhost:6969/".to_string(),
+        };
+
+        let url = store
+            .put("monad-faucet/logo.png", vec![1, 2, 3], "image/png")
+            .await
+            .unwrap();
+        assert_eq!(url, "http://localhost:6969/assets/monad-faucet/logo.png");
+        assert_eq!(
+            std::fs::read(dir.join("monad-faucet/logo.png")).unwrap(),
+            vec![1, 2, 3]
+        );
+
+        assert!(store
+            .put("../escape.png", vec![1], "image/png")
+            .await
+            .is_err());
+
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+}
@@ -0,0 +1,410 @@
+use axum::{routing::get, Json, Router};
+use utoipa::{
+    openapi::security::{ApiKey, ApiKeyValue, HttpAuthScheme, HttpBuilder, SecurityScheme},
+    Modify, OpenApi,
+};
+use utoipa_scalar::{Scalar, Servable};
+
+use crate::AppState;
+
+/// Root of the OpenAPI document. Paths and schemas are added by the
+/// `#[utoipa::path]` of each handler as [`crate::routes`] registers it.
+#[derive(OpenApi)]
+#[openapi(
+    info(
+        title = "Monad faucet API",
+        description = "Drips testnet tokens, deploys ERC20 tokens and manages API keys, scheduled top-ups and webhooks. Errors are answered with a stable `code`."
+    ),
+    modifiers(&SecurityAddon),
+    tags(
+        (name = "drips", description = "Claiming tokens and their history"),
+        (name = "deploy", description = "Deploying ERC20 tokens"),
+        (name = "auth", description = "GitHub login"),
+        (name = "api-keys", description = "Keys for `POST /drip/bulk`"),
+        (name = "subscriptions", description = "Scheduled top-ups of wallets"),
+        (name = "webhooks", description = "Notifications about a token"),
+        (name = "events", description = "Live activity feed"),
+        (name = "admin", description = "Operating the faucet, admins only"),
+        (name = "health", description = "Liveness"),
+    )
+)]
+pub struct ApiDoc;
+
+struct SecurityAddon;
+
+impl Modify for SecurityAddon {
+    fn modify(&self, openapi: &mut utoipa::openapi::OpenApi) {
+        let components = openapi.components.get_or_insert_with(Default::default);
+        components.add_security_scheme(
+            "github",
+            SecurityScheme::Http(
+                HttpBuilder::new()
+                    .scheme(HttpAuthScheme::Bearer)
+                    .bearer_format("JWT")
+                    .description(Some("Token returned by `POST /auth`"))
+                    .build(),
+            ),
+        );
+        components.add_security_scheme(
+            "api_key",
+            SecurityScheme::ApiKey(ApiKey::Header(ApiKeyValue::with_description(
+                "Authorization",
+                "`ApiKey <key>`",
+            ))),
+        );
+    }
+}
+
+/// Serves `api` at `/openapi.json` along with a docs UI rendering it at
+/// `/docs`
+pub fn docs(api: utoipa::openapi::OpenApi) -> Router<AppState> {
+    let spec = api.clone();
+    Router::new()
+        .route(
+            "/openapi.json",
+            get(move || {
+                let spec = spec.clone();
+                async move { Json(spec) }
+            }),
+        )
+        .merge(Scalar::with_url("/docs", api))
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::Utc;
+    use serde::Serialize;
+    use serde_json::{json, Value};
+
+    use crate::{
+        config::TuningConfig,
+        executor::{BatchTransferResult, ErrorResponse, ExecutorResponse, QueueName, QueueState},
+        faucet::DripResponse,
+        handlers::response::{Response, ResponseStatus},
+        routes,
+        store::{
+            ApiKey, DeliveryStatus, DeployJob, DeployStep, DripKind, Token, TokenPause,
+            TokenTransfer, TokenType, WalletSubscription, Webhook, WebhookDelivery,
+        },
+    };
+
+    fn spec() -> Value {
+        let (_, api) = routes::router().split_for_parts();
+        serde_json::to_value(api).unwrap()
+    }
+
+    fn resolve<'a>(spec: &'a Value, schema: &'a Value) -> &'a Value {
+        match schema["$ref"].as_str() {
+            Some(reference) => {
+                let name = reference.trim_start_matches("#/components/schemas/");
+                resolve(spec, &spec["components"]["schemas"][name])
+            }
+            None => schema,
+        }
+    }
+
+    /// Checks a serialized value against its schema in the spec: its types,
+    /// required properties, and that it has no property the spec is missing
+    fn conforms(spec: &Value, schema: &Value, value: &Value) -> Result<(), String> {
+        let schema = resolve(spec, schema);
+        if schema.is_null() {
+            return Err(format!("no schema for {}", value));
+        }
+
+        // flattened structs, the parts together describe one object
+        if let Some(parts) = schema["allOf"].as_array() {
+            let mut merged = json!({ "type": "object", "properties": {}, "required": [] });
+            for part in parts {
+                let part = resolve(spec, part);
+                if let Some(properties) = part["properties"].as_object() {
+                    for (name, property) in properties {
+                        merged["properties"][name] = property.clone();
+                    }
+                }
+                if let Some(required) = part["required"].as_array() {
+                    merged["required"]
+                        .as_array_mut()
+                        .unwrap()
+                        .extend(required.iter().cloned());
+                }
+            }
+            return conforms(spec, &merged, value);
+        }
+        for key in ["oneOf", "anyOf"] {
+            if let Some(options) = schema[key].as_array() {
+                return match options.iter().any(|o| conforms(spec, o, value).is_ok()) {
+                    true => Ok(()),
+                    false => Err(format!("{} matches none of {}", value, schema[key])),
+                };
+            }
+        }
+        if let Some(values) = schema["enum"].as_array() {
+            if !values.contains(value) {
+                return Err(format!("{} is not one of {:?}", value, values));
+            }
+        }
+
+        let types: Vec<&str> = match &schema["type"] {
+            Value::String(t) => vec![t.as_str()],
+            Value::Array(types) => types.iter().filter_map(Value::as_str).collect(),
+            _ => return Ok(()),
+        };
+        let actual = match value {
+            Value::Null => "null",
+            Value::Bool(_) => "boolean",
+            Value::Number(n) if n.is_f64() => "number",
+            Value::Number(_) => "integer",
+            Value::String(_) => "string",
+            Value::Array(_) => "array",
+            Value::Object(_) => "object",
+        };
+        if !types
+            .iter()
+            .any(|t| *t == actual || (*t == "number" && actual == "integer"))
+        {
+            return Err(format!("{} is not of type {:?}", value, types));
+        }
+
+        match value {
+            Value::Array(items) => items
+                .iter()
+                .try_for_each(|item| conforms(spec, &schema["items"], item)),
+            Value::Object(fields) => {
+                let empty = serde_json::Map::new();
+                let properties = schema["properties"].as_object().unwrap_or(&empty);
+                for required in schema["required"].as_array().into_iter().flatten() {
+                    let required = required.as_str().unwrap_or_default();
+                    if !fields.contains_key(required) {
+                        return Err(format!("{} is missing required {}", value, required));
+                    }
+                }
+                for (name, field) in fields {
+                    let Some(property) = properties.get(name) else {
+                        return Err(format!("{} is serialized but not in the spec", name));
+                    };
+                    conforms(spec, property, field).map_err(|e| format!("{}: {}", name, e))?;
+                }
+                Ok(())
+            }
+            _ => Ok(()),
+        }
+    }
+
+    fn assert_schema(spec: &Value, name: &str, value: impl Serialize) {
+        let schema = json!({ "$ref": format!("#/components/schemas/{}", name) });
+        let value = serde_json::to_value(value).unwrap();
+        if let Err(e) = conforms(spec, &schema, &value) {
+            panic!("{} drifted from the spec: {}", name, e);
+        }
+    }
+
+    fn assert_response(spec: &Value, method: &str, path: &str, value: impl Serialize) {
+        let schema = &spec["paths"][path][method]["responses"]["200"]["content"]
+            ["application/json"]["schema"];
+        let value = serde_json::to_value(value).unwrap();
+        if let Err(e) = conforms(spec, schema, &value) {
+            panic!("{} {} drifted from the spec: {}", method, path, e);
+        }
+    }
+
+    #[test]
+    fn test_openapi_matches_serialized_types() {
+        let spec = spec();
+        let now = Utc::now();
+        let drip = DripResponse {
+            tx_hash: "0xhash".to_string(),
+            amount: "1000".to_string(),
+            magnification: 10,
+        };
+
+        assert_response(&spec, "post", "/v1/withdraw", Response::ok(drip.clone()).0);
+        assert_response(
+            &spec,
+            "post",
+            "/v1/withdraw/batch",
+            Response::ok(vec![BatchTransferResult {
+                token_address: "0xtoken".to_string(),
+                response: ExecutorResponse {
+                    status: ResponseStatus::Success,
+                    error: None,
+                    data: Some(drip),
+                },
+            }])
+            .0,
+        );
+        assert_schema(&spec, "ErrorResponse", ErrorResponse::shutting_down());
+        assert_schema(&spec, "ErrorResponse", ErrorResponse::cooldown_active(now));
+        assert_schema(
+            &spec,
+            "Token",
+            Token {
+                created_by: "0xdeployer".to_string(),
+                token_type: TokenType::ERC20,
+                address: "0xtoken".to_string(),
+                logo_url: "https://example.com/logo.png".to_string(),
+                chain_id: 10143,
+                symbol: "TKN".to_string(),
+                name: "Token".to_string(),
+                decimals: 18,
+                withdraw_limit: "1000".to_string(),
+                top_up_target: None,
+                logo_thumbnail_url: Some("https://example.com/thumb.png".to_string()),
+                created_by_user_id: Some("user".to_string()),
+            },
+        );
+        assert_schema(
+            &spec,
+            "TokenTransfer",
+            TokenTransfer {
+                token_address: "0xtoken".to_string(),
+                token_type: TokenType::NATIVE,
+                tx_hash: "0xhash".to_string(),
+                from_address: "0xfaucet".to_string(),
+                to_address: "0xto".to_string(),
+                amount: "1000".to_string(),
+                chain_id: 10143,
+                ip: Some("203.0.113.7".parse().unwrap()),
+                kind: DripKind::API_KEY,
+            },
+        );
+        assert_schema(
+            &spec,
+            "DeployJob",
+            DeployJob {
+                id: "job".to_string(),
+                user_id: Some("user".to_string()),
+                symbol: "TKN".to_string(),
+                step: DeployStep::FAILED,
+                deploy_tx_hash: Some("0xhash".to_string()),
+                contract_address: None,
+                allocation_tx_hashes: vec!["0xhash".to_string()],
+                error: Some("Failed to deploy contract".to_string()),
+                error_code: Some("DEPLOY_FAILED".to_string()),
+                created_at: now,
+                updated_at: now,
+            },
+        );
+        assert_schema(
+            &spec,
+            "ApiKey",
+            ApiKey {
+                id: "key".to_string(),
+                user_id: "user".to_string(),
+                name: "CI".to_string(),
+                key_hash: "0xhash".to_string(),
+                key_prefix: "mfk_12345678".to_string(),
+                drips_per_day: 100,
+                revoked: false,
+            },
+        );
+        assert_schema(
+            &spec,
+            "WalletSubscription",
+            WalletSubscription {
+                id: "subscription".to_string(),
+                user_id: "user".to_string(),
+                wallet_address: "0xwallet".to_string(),
+                token_address: "0xtoken".to_string(),
+                min_balance: "10".to_string(),
+                refill_amount: "100".to_string(),
+                enabled: true,
+                last_checked_at: Some(now),
+                last_refill_at: None,
+            },
+        );
+        assert_schema(
+            &spec,
+            "Webhook",
+            Webhook {
+                id: "webhook".to_string(),
+                token_address: Some("0xtoken".to_string()),
+                url: "https://example.com/hook".to_string(),
+                secret: "whsec_secret".to_string(),
+                events: vec!["DRIP_CONFIRMED".to_string()],
+                created_by: "user".to_string(),
+                created_at: now,
+            },
+        );
+        assert_schema(
+            &spec,
+            "WebhookDelivery",
+            WebhookDelivery {
+                id: "delivery".to_string(),
+                webhook_id: "webhook".to_string(),
+                event_type: "DRIP_CONFIRMED".to_string(),
+                payload: "{}".to_string(),
+                status: DeliveryStatus::FAILED,
+                attempts: 3,
+                next_attempt_at: now,
+                last_status_code: Some(500),
+                last_error: None,
+                created_at: now,
+            },
+        );
+        assert_schema(
+            &spec,
+            "TokenPause",
+            TokenPause {
+                token_address: "0xtoken".to_string(),
+                paused: true,
+                reason: Some("Hourly outflow cap reached".to_string()),
+                paused_at: Some(now),
+                resumed_at: None,
+                resumed_by: None,
+            },
+        );
+        assert_schema(
+            &spec,
+            "QueueState",
+            QueueState {
+                queue: QueueName::WithdrawBatch,
+                paused: false,
+                length: 2,
+            },
+        );
+        assert_schema(&spec, "TuningConfig", TuningConfig::default());
+    }
+
+    #[test]
+    fn test_openapi_references_resolve() {
+        fn refs<'a>(value: &'a Value, found: &mut Vec<&'a str>) {
+            match value {
+                Value::Object(fields) => {
+                    if let Some(reference) = fields.get("$ref").and_then(Value::as_str) {
+                        found.push(reference);
+                    }
+                    fields.values().for_each(|v| refs(v, found));
+                }
+                Value::Array(items) => items.iter().for_each(|v| refs(v, found)),
+                _ => {}
+            }
+        }
+
+        let spec = spec();
+        let mut found = vec![];
+        refs(&spec, &mut found);
+        assert!(!found.is_empty());
+        for reference in found {
+            assert!(
+                !resolve(&spec, &json!({ "$ref": reference })).is_null(),
+                "{} is not in the spec",
+                reference
+            );
+        }
+
+        // every operation says what it answers on success
+        for (path, operations) in spec["paths"].as_object().unwrap() {
+            for (method, operation) in operations.as_object().unwrap() {
+                let responses = operation["responses"].as_object().unwrap();
+                assert!(
+                    responses
+                        .keys()
+                        .any(|status| status.starts_with('2') || status == "101"),
+                    "{} {} documents no success response",
+                    method,
+                    path
+                );
+            }
+        }
+    }
+}
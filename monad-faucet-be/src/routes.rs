@@ -0,0 +1,122 @@
+use axum::{
+    extract::Request,
+    http::{header, HeaderName, HeaderValue},
+    middleware::{self, Next},
+    response::Response,
+    Router,
+};
+use utoipa::OpenApi;
+use utoipa_axum::{router::OpenApiRouter, routes};
+
+use crate::{constants::api, handlers::health, openapi::ApiDoc, AppState};
+
+pub mod v1;
+
+static DEPRECATION_HEADER: HeaderName = HeaderName::from_static("deprecation");
+static SUNSET_HEADER: HeaderName = HeaderName::from_static("sunset");
+
+/// Every documented route, one router module per API version. Routes are
+/// registered from their handler's `#[utoipa::path]`, so a route cannot be
+/// served without being documented and the spec cannot list a path that is
+/// not served.
+pub fn router() -> OpenApiRouter<AppState> {
+    OpenApiRouter::with_openapi(ApiDoc::openapi())
+        .routes(routes!(health::health_check))
+        .nest("/v1", v1::router())
+}
+
+/// The v1 routes at the unversioned paths they were served from before
+/// `/v1`, answering with `Deprecation` and `Sunset` headers. Left out of the
+/// spec so that new clients only see `/v1`.
+pub fn legacy() -> Router<AppState> {
+    let (router, _) = v1::unversioned().split_for_parts();
+    router.layer(middleware::from_fn(deprecated))
+}
+
+async fn deprecated(request: Request, next: Next) -> Response {
+    let successor = HeaderValue::from_str(&format!(
+        "</v1{}>; rel=\"successor-version\"",
+        request.uri().path()
+    ));
+    let mut response = next.run(request).await;
+
+    let headers = response.headers_mut();
+    headers.insert(
+        DEPRECATION_HEADER.clone(),
+        HeaderValue::from_static(api::LEGACY_DEPRECATION),
+    );
+    headers.insert(
+        SUNSET_HEADER.clone(),
+        HeaderValue::from_static(api::LEGACY_SUNSET),
+    );
+    if let Ok(successor) = successor {
+        headers.append(header::LINK, successor);
+    }
+    response
+}
+
+#[cfg(test)]
+mod tests {
+    use axum::{body::Body, http::StatusCode};
+    use tower::ServiceExt;
+
+    use super::*;
+
+    #[tokio::test]
+    async fn test_legacy_routes_are_deprecated() {
+        let app = legacy().with_state(crate::utils::setup().await.unwrap());
+        let request = |method: &str, uri: &str| {
+            axum::http::Request::builder()
+                .method(method)
+                .uri(uri)
+                .body(Body::empty())
+                .unwrap()
+        };
+
+        let response = app
+            .clone()
+            .oneshot(request("GET", "/tokens"))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let headers = response.headers();
+        assert_eq!(headers[&DEPRECATION_HEADER], api::LEGACY_DEPRECATION);
+        assert_eq!(headers[&SUNSET_HEADER], api::LEGACY_SUNSET);
+        assert_eq!(
+            headers[header::LINK],
+            "</v1/tokens>; rel=\"successor-version\""
+        );
+
+        // routes added along with or after /v1 have no unversioned alias
+        for (method, uri) in [
+            ("POST", "/auth"),
+            ("GET", "/auth/github/start"),
+            ("POST", "/auth/refresh"),
+            ("POST", "/auth/logout"),
+            ("GET", "/webhooks"),
+        ] {
+            let response = app.clone().oneshot(request(method, uri)).await.unwrap();
+            assert_eq!(
+                response.status(),
+                StatusCode::NOT_FOUND,
+                "{} {}",
+                method,
+                uri
+            );
+        }
+    }
+
+    #[test]
+    fn test_spec_only_lists_versioned_routes() {
+        let (_, api) = router().split_for_parts();
+        let paths: Vec<&String> = api.paths.paths.keys().collect();
+        assert!(paths.iter().any(|path| *path == "/v1/withdraw"));
+        for path in paths {
+            assert!(
+                path.starts_with("/v1/") || path == "/health",
+                "{} is not versioned",
+                path
+            );
+        }
+    }
+}
@@ -0,0 +1,478 @@
+use axum::extract::DefaultBodyLimit;
+use utoipa_axum::{router::OpenApiRouter, router::UtoipaMethodRouterExt, routes};
+
+use crate::{
+    constants,
+    handlers::{
+        admin, api_keys, auth, bulk_drip, claims, deploy_erc20, events, subscriptions, test_auth,
+        tokens, turnstile_captcha, user, webhooks, withdraw,
+    },
+    AppState,
+};
+
+/// Version 1 of the API, mounted under `/v1`. Its JSON shapes are pinned by
+/// the tests below: fields can be added, but renaming, retyping or removing
+/// one needs a new version.
+pub fn router() -> OpenApiRouter<AppState> {
+    unversioned()
+        .routes(routes!(auth::auth))
+        .routes(routes!(auth::github_start))
+        .routes(routes!(auth::refresh))
+        .routes(routes!(auth::logout))
+        .routes(routes!(
+            webhooks::user_webhooks,
+            webhooks::create_user_webhook
+        ))
+}
+
+/// The v1 routes that were served before `/v1`, the only ones still aliased
+/// at their unversioned paths. Routes added since, like the user's own
+/// `/webhooks`, and `/auth` whose contract changed with the GitHub login
+/// state, are only served under `/v1`.
+pub fn unversioned() -> OpenApiRouter<AppState> {
+    OpenApiRouter::new()
+        .routes(routes!(turnstile_captcha::verify_turnstile_captcha))
+        .routes(routes!(user::user))
+        .routes(routes!(test_auth::test_auth))
+        .routes(routes!(withdraw::withdraw))
+        .routes(routes!(withdraw::withdraw_batch))
+        // room for the logo plus the multipart framing and token data
+        .routes(
+            routes!(deploy_erc20::deploy_erc20).layer(DefaultBodyLimit::max(
+                constants::logo::MAX_BYTES + 64 * 1024,
+            )),
+        )
+        .routes(routes!(deploy_erc20::deploy_job))
+        .routes(routes!(deploy_erc20::deploy_job_events))
+        .routes(routes!(tokens::tokens))
+        .routes(routes!(api_keys::api_keys, api_keys::create_api_key))
+        .routes(routes!(api_keys::revoke_api_key))
+        .routes(routes!(bulk_drip::bulk_drip))
+        .routes(routes!(
+            subscriptions::subscriptions,
+            subscriptions::create_subscription
+        ))
+        .routes(routes!(
+            subscriptions::update_subscription,
+            subscriptions::delete_subscription
+        ))
+        .routes(routes!(claims::claims))
+        .routes(routes!(webhooks::token_webhooks, webhooks::create_webhook))
+        .routes(routes!(webhooks::delete_webhook))
+        .routes(routes!(webhooks::webhook_deliveries))
+        .routes(routes!(events::events))
+        .routes(routes!(events::events_ws))
+        .routes(routes!(admin::reload_config))
+        .routes(routes!(admin::paused_tokens))
+        .routes(routes!(admin::resume_token))
+        .routes(routes!(admin::queues))
+        .routes(routes!(admin::pause_queue))
+        .routes(routes!(admin::resume_queue))
+}
+
+/// The v1 contract. A failing test here means the change would break v1
+/// clients: make the new field optional or start a new version instead of
+/// updating the expected JSON.
+#[cfg(test)]
+mod tests {
+    use chrono::{TimeZone, Utc};
+    use serde::Serialize;
+    use serde_json::{json, Value};
+
+    use crate::{
+        config::TuningConfig,
+        executor::{
+            BatchTransferRequest, BatchTransferResult, BulkDripResult, ErrorResponse,
+            ExecutorResponse, QueueName, QueueState, TokenTransferRequest,
+        },
+        faucet::DripResponse,
+        handlers::{
+            api_keys::{CreateApiKeyRequest, CreateApiKeyResponse},
+            bulk_drip::BulkDripPayload,
+            deploy_erc20::{DeployErc20Response, TokenDeployRequestData},
+            error::ApiError,
+            response::{Response, ResponseStatus},
+            subscriptions::{CreateSubscriptionRequest, UpdateSubscriptionRequest},
+            webhooks::{CreateWebhookRequest, CreateWebhookResponse},
+        },
+        store::{
+            ApiKey, DeliveryStatus, DeployJob, DeployStep, DripKind, Token, TokenPause,
+            TokenTransfer, TokenType, WalletSubscription, Webhook, WebhookDelivery,
+        },
+    };
+
+    fn shape(value: impl Serialize) -> Value {
+        serde_json::to_value(value).unwrap()
+    }
+
+    fn drip() -> DripResponse {
+        DripResponse {
+            tx_hash: "0xhash".to_string(),
+            amount: "1000".to_string(),
+            magnification: 10,
+        }
+    }
+
+    #[test]
+    fn test_v1_drip_shapes() {
+        assert_eq!(
+            shape(Response::ok(drip()).0),
+            json!({
+                "status": "Success",
+                "data": { "tx_hash": "0xhash", "amount": "1000", "magnification": 10 }
+            })
+        );
+
+        let succeeded = ExecutorResponse {
+            status: ResponseStatus::Success,
+            error: None,
+            data: Some(drip()),
+        };
+        assert_eq!(
+            shape(BatchTransferResult {
+                token_address: "0xtoken".to_string(),
+                response: succeeded.clone(),
+            }),
+            json!({
+                "token_address": "0xtoken",
+                "status": "Success",
+                "error": null,
+                "data": { "tx_hash": "0xhash", "amount": "1000", "magnification": 10 }
+            })
+        );
+        assert_eq!(
+            shape(BulkDripResult {
+                to: "0xto".to_string(),
+                token_address: "0xtoken".to_string(),
+                response: ExecutorResponse {
+                    status: ResponseStatus::Error,
+                    error: Some(ErrorResponse::shutting_down()),
+                    data: None,
+                },
+            }),
+            json!({
+                "to": "0xto",
+                "token_address": "0xtoken",
+                "status": "Error",
+                "error": {
+                    "message": "The faucet is restarting, please try again shortly",
+                    "code": "SHUTTING_DOWN"
+                },
+                "data": null
+            })
+        );
+    }
+
+    #[test]
+    fn test_v1_error_shapes() {
+        assert_eq!(
+            shape(Response::error(ErrorResponse::from(ApiError::InvalidAddress("to"))).0),
+            json!({
+                "status": "Error",
+                "error": { "message": "Invalid to address", "code": "INVALID_ADDRESS" }
+            })
+        );
+
+        let next_access = Utc.with_ymd_and_hms(2026, 1, 2, 3, 4, 5).unwrap();
+        let error = shape(ErrorResponse::cooldown_active(next_access));
+        assert_eq!(error["next_access"], "2026-01-02T03:04:05Z");
+        assert_eq!(error["code"], "COOLDOWN_ACTIVE");
+    }
+
+    #[test]
+    fn test_v1_request_shapes() {
+        // as sent by the frontend
+        let withdraw: TokenTransferRequest = serde_json::from_value(json!({
+            "token_address": "0xtoken",
+            "to": "0xto",
+            "token_type": "ERC20",
+            "magnification": 1,
+            "ip": "203.0.113.7"
+        }))
+        .unwrap();
+        assert_eq!(withdraw.to, "0xto");
+
+        serde_json::from_value::<BatchTransferRequest>(json!({
+            "to": "0xto",
+            "token_addresses": ["0xtoken"],
+            "ip": "203.0.113.7"
+        }))
+        .unwrap();
+        serde_json::from_value::<BulkDripPayload>(json!({
+            "drips": [{ "to": "0xto", "token_address": "0xtoken", "amount": "1000" }]
+        }))
+        .unwrap();
+        serde_json::from_value::<TokenDeployRequestData>(json!({
+            "name": "Token",
+            "symbol": "TKN",
+            "total_supply": "1000000",
+            "decimals": 18,
+            "deployer_address": "0xdeployer",
+            "ip": "203.0.113.7"
+        }))
+        .unwrap();
+        serde_json::from_value::<CreateApiKeyRequest>(json!({
+            "owner_user_id": "user",
+            "name": "CI",
+            "drips_per_day": 100,
+            "quotas": [{ "token_address": "0xtoken", "max_amount_per_day": "1000" }]
+        }))
+        .unwrap();
+        serde_json::from_value::<CreateSubscriptionRequest>(json!({
+            "wallet_address": "0xwallet",
+            "token_address": "0xtoken",
+            "min_balance": "10",
+            "refill_amount": "100"
+        }))
+        .unwrap();
+        serde_json::from_value::<UpdateSubscriptionRequest>(json!({ "enabled": false })).unwrap();
+        serde_json::from_value::<CreateWebhookRequest>(json!({
+            "url": "https://example.com/hook",
+            "events": ["DRIP_CONFIRMED"]
+        }))
+        .unwrap();
+    }
+
+    #[test]
+    fn test_v1_resource_shapes() {
+        let at = Utc.with_ymd_and_hms(2026, 1, 2, 3, 4, 5).unwrap();
+
+        assert_eq!(
+            shape(Token {
+                created_by: "0xdeployer".to_string(),
+                token_type: TokenType::ERC20,
+                address: "0xtoken".to_string(),
+                logo_url: "https://example.com/logo.png".to_string(),
+                chain_id: 10143,
+                symbol: "TKN".to_string(),
+                name: "Token".to_string(),
+                decimals: 18,
+                withdraw_limit: "1000".to_string(),
+                top_up_target: None,
+                logo_thumbnail_url: Some("https://example.com/thumb.png".to_string()),
+                created_by_user_id: Some("user".to_string()),
+            }),
+            json!({
+                "created_by": "0xdeployer",
+                "token_type": "ERC20",
+                "address": "0xtoken",
+                "logo_url": "https://example.com/logo.png",
+                "chain_id": 10143,
+                "symbol": "TKN",
+                "name": "Token",
+                "decimals": 18,
+                "withdraw_limit": "1000",
+                "top_up_target": null,
+                "logo_thumbnail_url": "https://example.com/thumb.png"
+            })
+        );
+        assert_eq!(
+            shape(TokenTransfer {
+                token_address: "0xtoken".to_string(),
+                token_type: TokenType::NATIVE,
+                tx_hash: "0xhash".to_string(),
+                from_address: "0xfaucet".to_string(),
+                to_address: "0xto".to_string(),
+                amount: "1000".to_string(),
+                chain_id: 10143,
+                ip: Some("203.0.113.7".parse().unwrap()),
+                kind: DripKind::SCHEDULED,
+            }),
+            json!({
+                "token_address": "0xtoken",
+                "token_type": "NATIVE",
+                "tx_hash": "0xhash",
+                "from_address": "0xfaucet",
+                "to_address": "0xto",
+                "amount": "1000",
+                "chain_id": 10143,
+                "kind": "SCHEDULED"
+            })
+        );
+        assert_eq!(
+            shape(DeployErc20Response {
+                job_id: "job".to_string()
+            }),
+            json!({ "job_id": "job" })
+        );
+        assert_eq!(
+            shape(DeployJob {
+                id: "job".to_string(),
+                user_id: Some("user".to_string()),
+                symbol: "TKN".to_string(),
+                step: DeployStep::ALLOCATING_SUPPLY,
+                deploy_tx_hash: Some("0xhash".to_string()),
+                contract_address: Some("0xtoken".to_string()),
+                allocation_tx_hashes: vec!["0xallocation".to_string()],
+                error: None,
+                error_code: None,
+                created_at: at,
+                updated_at: at,
+            }),
+            json!({
+                "id": "job",
+                "symbol": "TKN",
+                "step": "ALLOCATING_SUPPLY",
+                "deploy_tx_hash": "0xhash",
+                "contract_address": "0xtoken",
+                "allocation_tx_hashes": ["0xallocation"],
+                "error": null,
+                "error_code": null,
+                "created_at": "2026-01-02T03:04:05Z",
+                "updated_at": "2026-01-02T03:04:05Z"
+            })
+        );
+
+        let api_key = ApiKey {
+            id: "key".to_string(),
+            user_id: "user".to_string(),
+            name: "CI".to_string(),
+            key_hash: "0xhash".to_string(),
+            key_prefix: "mfk_12345678".to_string(),
+            drips_per_day: 100,
+            revoked: false,
+        };
+        assert_eq!(
+            shape(CreateApiKeyResponse {
+                key: "mfk_key".to_string(),
+                api_key,
+            }),
+            json!({
+                "key": "mfk_key",
+                "api_key": {
+                    "id": "key",
+                    "user_id": "user",
+                    "name": "CI",
+                    "key_prefix": "mfk_12345678",
+                    "drips_per_day": 100,
+                    "revoked": false
+                }
+            })
+        );
+        assert_eq!(
+            shape(WalletSubscription {
+                id: "subscription".to_string(),
+                user_id: "user".to_string(),
+                wallet_address: "0xwallet".to_string(),
+                token_address: "0xtoken".to_string(),
+                min_balance: "10".to_string(),
+                refill_amount: "100".to_string(),
+                enabled: true,
+                last_checked_at: Some(at),
+                last_refill_at: None,
+            }),
+            json!({
+                "id": "subscription",
+                "user_id": "user",
+                "wallet_address": "0xwallet",
+                "token_address": "0xtoken",
+                "min_balance": "10",
+                "refill_amount": "100",
+                "enabled": true,
+                "last_checked_at": "2026-01-02T03:04:05Z",
+                "last_refill_at": null
+            })
+        );
+
+        let webhook = Webhook {
+            id: "webhook".to_string(),
+            token_address: Some("0xtoken".to_string()),
+            url: "https://example.com/hook".to_string(),
+            secret: "whsec_secret".to_string(),
+            events: vec!["TOKEN_PAUSED".to_string()],
+            created_by: "user".to_string(),
+            created_at: at,
+        };
+        assert_eq!(
+            shape(CreateWebhookResponse {
+                secret: "whsec_secret".to_string(),
+                webhook,
+            }),
+            json!({
+                "secret": "whsec_secret",
+                "webhook": {
+                    "id": "webhook",
+                    "token_address": "0xtoken",
+                    "url": "https://example.com/hook",
+                    "events": ["TOKEN_PAUSED"],
+                    "created_by": "user",
+                    "created_at": "2026-01-02T03:04:05Z"
+                }
+            })
+        );
+        assert_eq!(
+            shape(WebhookDelivery {
+                id: "delivery".to_string(),
+                webhook_id: "webhook".to_string(),
+                event_type: "TOKEN_PAUSED".to_string(),
+                payload: "{}".to_string(),
+                status: DeliveryStatus::DELIVERED,
+                attempts: 1,
+                next_attempt_at: at,
+                last_status_code: Some(200),
+                last_error: None,
+                created_at: at,
+            }),
+            json!({
+                "id": "delivery",
+                "webhook_id": "webhook",
+                "event_type": "TOKEN_PAUSED",
+                "payload": "{}",
+                "status": "DELIVERED",
+                "attempts": 1,
+                "next_attempt_at": "2026-01-02T03:04:05Z",
+                "last_status_code": 200,
+                "last_error": null,
+                "created_at": "2026-01-02T03:04:05Z"
+            })
+        );
+    }
+
+    #[test]
+    fn test_v1_admin_shapes() {
+        assert_eq!(
+            shape(TokenPause {
+                token_address: "0xtoken".to_string(),
+                paused: true,
+                reason: Some("Hourly outflow cap reached".to_string()),
+                paused_at: Some(Utc.with_ymd_and_hms(2026, 1, 2, 3, 4, 5).unwrap()),
+                resumed_at: None,
+                resumed_by: None,
+            }),
+            json!({
+                "token_address": "0xtoken",
+                "paused": true,
+                "reason": "Hourly outflow cap reached",
+                "paused_at": "2026-01-02T03:04:05Z",
+                "resumed_at": null,
+                "resumed_by": null
+            })
+        );
+        assert_eq!(
+            shape(QueueState {
+                queue: QueueName::WithdrawBatch,
+                paused: false,
+                length: 2,
+            }),
+            json!({ "queue": "withdraw_batch", "paused": false, "length": 2 })
+        );
+
+        let tuning = shape(TuningConfig::default());
+        let mut keys: Vec<&String> = tuning.as_object().unwrap().keys().collect();
+        keys.sort();
+        assert_eq!(
+            keys,
+            [
+                "daily_outflow_cap_drips",
+                "hourly_outflow_cap_drips",
+                "low_balance_drips",
+                "magnification_garden_user",
+                "magnification_github_auth",
+                "magnification_no_auth",
+                "max_batch_tokens",
+                "max_scheduled_refills_per_day",
+                "withdraw_limit_denominator",
+            ]
+        );
+    }
+}
@@ -0,0 +1,392 @@
+use std::fmt;
+
+use chrono::{Duration, Utc};
+use jsonwebtoken::{
+    decode, decode_header, encode, errors::ErrorKind, Algorithm, DecodingKey, EncodingKey, Header,
+    Validation,
+};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use uuid::Uuid;
+
+use crate::{
+    config::AuthConfig,
+    store::{AuthSession, PgStore, Store},
+};
+
+const REFRESH_TOKEN_PREFIX: &str = "mfr_";
+
+/// Claims of an access token
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Claims {
+    /// User ID
+    pub sub: String,
+    pub exp: usize,
+    pub iat: usize,
+    pub iss: String,
+    pub aud: String,
+    /// The [`AuthSession`] the token was issued for
+    pub sid: String,
+}
+
+/// Why a token signed with the JWT keys was refused
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum TokenError {
+    /// Not one of our tokens: malformed, signed with an unknown key, or for
+    /// another issuer or audience
+    Invalid,
+    /// One of ours past its `exp`, the client should refresh it
+    Expired,
+}
+
+#[derive(Debug)]
+pub enum SessionError {
+    /// Unknown refresh token, or one of an expired or revoked session
+    InvalidRefreshToken,
+    /// A refresh token that was already exchanged came back, so someone holds
+    /// a copy of it. Its session was revoked.
+    RefreshTokenReused,
+    Store(sqlx::Error),
+    Jwt(jsonwebtoken::errors::Error),
+}
+
+impl fmt::Display for SessionError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SessionError::InvalidRefreshToken => write!(f, "Invalid refresh token"),
+            SessionError::RefreshTokenReused => write!(f, "Refresh token reused"),
+            SessionError::Store(e) => write!(f, "Store error: {}", e),
+            SessionError::Jwt(e) => write!(f, "JWT error: {}", e),
+        }
+    }
+}
+
+impl From<sqlx::Error> for SessionError {
+    fn from(e: sqlx::Error) -> Self {
+        SessionError::Store(e)
+    }
+}
+
+impl From<jsonwebtoken::errors::Error> for SessionError {
+    fn from(e: jsonwebtoken::errors::Error) -> Self {
+        SessionError::Jwt(e)
+    }
+}
+
+/// What a client gets when it signs in or refreshes
+#[derive(Debug)]
+pub struct Tokens {
+    pub access_token: String,
+    pub refresh_token: String,
+    /// Seconds until the access token expires
+    pub expires_in: i64,
+}
+
+/// Signs claims with the first of the configured keys, naming it in the
+/// `kid` header
+pub fn sign<T: Serialize>(
+    auth: &AuthConfig,
+    claims: &T,
+) -> Result<String, jsonwebtoken::errors::Error> {
+    // validated when the config was loaded
+    let key = auth.jwt_keys.first().expect("No JWT signing key");
+    let mut header = Header::new(Algorithm::HS256);
+    header.kid = Some(key.id.clone());
+    encode(
+        &header,
+        claims,
+        &EncodingKey::from_secret(key.secret.as_bytes()),
+    )
+}
+
+/// Checks the signature, expiry and issuer of a token signed by [`sign`],
+/// and that it was meant for `audience`
+pub fn verify<T: DeserializeOwned>(
+    auth: &AuthConfig,
+    token: &str,
+    audience: &str,
+) -> Result<T, TokenError> {
+    let header = decode_header(token).map_err(|_| TokenError::Invalid)?;
+    let key = header
+        .kid
+        .and_then(|kid| auth.jwt_keys.iter().find(|key| key.id == kid))
+        .ok_or(TokenError::Invalid)?;
+
+    let mut validation = Validation::new(Algorithm::HS256);
+    validation.set_issuer(&[&auth.jwt_issuer]);
+    validation.set_audience(&[audience]);
+    validation.set_required_spec_claims(&["exp", "iss", "aud"]);
+
+    // the signature is checked before the claims, so only our own tokens
+    // can come back as expired
+    match decode::<T>(
+        token,
+        &DecodingKey::from_secret(key.secret.as_bytes()),
+        &validation,
+    ) {
+        Ok(token) => Ok(token.claims),
+        Err(e) if *e.kind() == ErrorKind::ExpiredSignature => Err(TokenError::Expired),
+        Err(_) => Err(TokenError::Invalid),
+    }
+}
+
+pub fn issue_access_token(
+    auth: &AuthConfig,
+    user_id: &str,
+    session_id: &str,
+) -> Result<String, jsonwebtoken::errors::Error> {
+    let now = Utc::now();
+    sign(
+        auth,
+        &Claims {
+            sub: user_id.to_string(),
+            exp: (now + Duration::seconds(auth.access_token_ttl_secs)).timestamp() as usize,
+            iat: now.timestamp() as usize,
+            iss: auth.jwt_issuer.clone(),
+            aud: auth.jwt_audience.clone(),
+            sid: session_id.to_string(),
+        },
+    )
+}
+
+/// Whether its session is still live is up to the caller
+pub fn verify_access_token(auth: &AuthConfig, token: &str) -> Result<Claims, TokenError> {
+    verify(auth, token, &auth.jwt_audience)
+}
+
+fn generate_refresh_token() -> String {
+    format!(
+        "{}{}{}",
+        REFRESH_TOKEN_PREFIX,
+        Uuid::new_v4().simple(),
+        Uuid::new_v4().simple()
+    )
+}
+
+/// Refresh tokens and API keys are stored and looked up by this hash, never
+/// in plaintext
+pub fn hash_secret(secret: &str) -> String {
+    alloy::hex::encode(Sha256::digest(secret.as_bytes()))
+}
+
+/// Starts a session for a user who just signed in
+pub async fn start(
+    store: &PgStore,
+    auth: &AuthConfig,
+    user_id: &str,
+) -> Result<Tokens, SessionError> {
+    let refresh_token = generate_refresh_token();
+    let session = AuthSession {
+        id: Uuid::new_v4().to_string(),
+        user_id: user_id.to_string(),
+        refresh_token_hash: hash_secret(&refresh_token),
+        previous_refresh_token_hash: None,
+        expires_at: Utc::now() + Duration::days(auth.refresh_token_ttl_days),
+        revoked_at: None,
+    };
+    store.create_auth_session(session.clone()).await?;
+
+    Ok(Tokens {
+        access_token: issue_access_token(auth, user_id, &session.id)?,
+        refresh_token,
+        expires_in: auth.access_token_ttl_secs,
+    })
+}
+
+/// Trades a refresh token for a new access and refresh token, the presented
+/// one stops working
+pub async fn refresh(
+    store: &PgStore,
+    auth: &AuthConfig,
+    refresh_token: &str,
+) -> Result<Tokens, SessionError> {
+    let hash = hash_secret(refresh_token);
+    let session = match store.get_auth_session_by_refresh_token(hash.clone()).await {
+        Ok(session) => session,
+        Err(sqlx::Error::RowNotFound) => return Err(SessionError::InvalidRefreshToken),
+        Err(e) => return Err(e.into()),
+    };
+    if !session.is_live() {
+        return Err(SessionError::InvalidRefreshToken);
+    }
+    if session.refresh_token_hash != hash {
+        // whoever exchanged this token before got its replacement, so either
+        // they or someone else has a copy; neither can be told apart
+        store.revoke_auth_session(session.id).await?;
+        return Err(SessionError::RefreshTokenReused);
+    }
+
+    let next_refresh_token = generate_refresh_token();
+    let rotated = store
+        .rotate_refresh_token(session.id.clone(), hash, hash_secret(&next_refresh_token))
+        .await?;
+    if !rotated {
+        // lost a race with a concurrent refresh or logout
+        return Err(SessionError::InvalidRefreshToken);
+    }
+
+    Ok(Tokens {
+        access_token: issue_access_token(auth, &session.user_id, &session.id)?,
+        refresh_token: next_refresh_token,
+        expires_in: auth.access_token_ttl_secs,
+    })
+}
+
+/// Ends the session of a refresh token, or every session of its user when
+/// `everywhere` is set. Returns how many sessions were ended; unknown tokens,
+/// replaced ones and those of ended sessions end none.
+pub async fn revoke(
+    store: &PgStore,
+    refresh_token: &str,
+    everywhere: bool,
+) -> Result<u64, SessionError> {
+    let hash = hash_secret(refresh_token);
+    let session = match store.get_auth_session_by_refresh_token(hash.clone()).await {
+        Ok(session) => session,
+        Err(sqlx::Error::RowNotFound) => return Ok(0),
+        Err(e) => return Err(e.into()),
+    };
+    // a stale token may have leaked from anywhere, it must not sign the user
+    // out of every device
+    if !session.is_live() || session.refresh_token_hash != hash {
+        return Ok(0);
+    }
+
+    if everywhere {
+        return Ok(store.revoke_user_auth_sessions(session.user_id).await?);
+    }
+    store.revoke_auth_session(session.id).await?;
+    Ok(1)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::JwtKey;
+
+    fn auth_config(keys: &[(&str, &str)]) -> AuthConfig {
+        AuthConfig {
+            jwt_keys: keys
+                .iter()
+                .map(|(id, secret)| JwtKey {
+                    id: id.to_string(),
+                    secret: secret.to_string(),
+                })
+                .collect(),
+            jwt_issuer: "monad-faucet".to_string(),
+            jwt_audience: "monad-faucet".to_string(),
+            access_token_ttl_secs: 900,
+            refresh_token_ttl_days: 30,
+            admin_user_ids: Vec::new(),
+            github: None,
+        }
+    }
+
+    #[test]
+    fn test_access_token() {
+        let auth = auth_config(&[("2026-10", "qV2zScNYyR6bB6")]);
+        let token = issue_access_token(&auth, "user", "session").unwrap();
+        assert_eq!(
+            decode_header(&token).unwrap().kid.as_deref(),
+            Some("2026-10")
+        );
+
+        let claims = verify_access_token(&auth, &token).unwrap();
+        assert_eq!(claims.sub, "user");
+        assert_eq!(claims.sid, "session");
+        assert_eq!(claims.exp - claims.iat, 900);
+
+        // tokens of another deployment are not ours
+        let mut other = auth_config(&[("2026-10", "qV2zScNYyR6bB6")]);
+        other.jwt_audience = "other".to_string();
+        assert_eq!(
+            verify_access_token(&other, &token).unwrap_err(),
+            TokenError::Invalid
+        );
+        assert_eq!(
+            verify_access_token(&auth_config(&[("2026-10", "other")]), &token).unwrap_err(),
+            TokenError::Invalid
+        );
+        assert_eq!(
+            verify_access_token(&auth, "null").unwrap_err(),
+            TokenError::Invalid
+        );
+
+        let mut expired = auth_config(&[("2026-10", "qV2zScNYyR6bB6")]);
+        expired.access_token_ttl_secs = -3600;
+        let token = issue_access_token(&expired, "user", "session").unwrap();
+        assert_eq!(
+            verify_access_token(&auth, &token).unwrap_err(),
+            TokenError::Expired
+        );
+    }
+
+    #[test]
+    fn test_key_rotation() {
+        let old = auth_config(&[("old", "first secret")]);
+        let token = issue_access_token(&old, "user", "session").unwrap();
+
+        // the new key signs while the old one is still accepted
+        let rotated = auth_config(&[("new", "second secret"), ("old", "first secret")]);
+        assert!(verify_access_token(&rotated, &token).is_ok());
+        let token = issue_access_token(&rotated, "user", "session").unwrap();
+        assert_eq!(decode_header(&token).unwrap().kid.as_deref(), Some("new"));
+        assert_eq!(
+            verify_access_token(&old, &token).unwrap_err(),
+            TokenError::Invalid
+        );
+    }
+
+    #[test]
+    fn test_refresh_token() {
+        let token = generate_refresh_token();
+        assert!(token.starts_with(REFRESH_TOKEN_PREFIX));
+        assert_eq!(token.len(), REFRESH_TOKEN_PREFIX.len() + 64);
+        assert_eq!(hash_secret(&token).len(), 64);
+        assert_ne!(hash_secret(&token), hash_secret(&generate_refresh_token()));
+    }
+
+    #[tokio::test]
+    async fn test_revoke() {
+        dotenv::dotenv().ok();
+        let database_url = std::env::var("DATABASE_URL").expect("DATABASE_URL must be set");
+        let store = PgStore::new(crate::db::init_db(&database_url).await.unwrap());
+        let auth = auth_config(&[("1", "qV2zScNYyR6bB6")]);
+        let user_id = Uuid::new_v4().to_string();
+        store
+            .create_user(crate::store::User {
+                id: user_id.clone(),
+                username: "revoke".to_string(),
+                avatar_url: String::new(),
+                email: None,
+                github_id: user_id.clone(),
+                access_token: user_id.clone(),
+            })
+            .await
+            .unwrap();
+
+        let first = start(&store, &auth, &user_id).await.unwrap();
+        let other = start(&store, &auth, &user_id).await.unwrap();
+        let second = refresh(&store, &auth, &first.refresh_token).await.unwrap();
+
+        // the replaced token ends nothing, not even with `everywhere`
+        assert_eq!(revoke(&store, &first.refresh_token, true).await.unwrap(), 0);
+        assert_eq!(revoke(&store, "unknown", true).await.unwrap(), 0);
+        assert!(refresh(&store, &auth, &other.refresh_token).await.is_ok());
+
+        assert_eq!(
+            revoke(&store, &second.refresh_token, true).await.unwrap(),
+            2
+        );
+        // nor does the token of an ended session
+        assert_eq!(
+            revoke(&store, &second.refresh_token, false).await.unwrap(),
+            0
+        );
+
+        sqlx::query!("DELETE FROM users WHERE id = $1", user_id)
+            .execute(&crate::db::init_db(&database_url).await.unwrap())
+            .await
+            .unwrap();
+    }
+}
@@ -0,0 +1,269 @@
+use std::time::Duration;
+
+use alloy::{
+    consensus::SignableTransaction,
+    network::{EthereumWallet, TxSigner},
+    primitives::{Address, Bytes, PrimitiveSignature as Signature, B256},
+    signers::local::PrivateKeySigner,
+};
+use serde::{Deserialize, Serialize};
+
+use crate::config::SignerConfig;
+
+/// An account the faucet sends transactions from.
+#[derive(Debug, Clone)]
+pub enum Signer {
+    /// Key held in memory, either given raw or decrypted from a keystore
+    Local(PrivateKeySigner),
+    Remote(RemoteSigner),
+}
+
+/// The faucet's accounts, loaded once at startup.
+#[derive(Debug, Clone)]
+pub struct Signers {
+    /// Sends drips and receives 80% of every deployed token
+    pub faucet: Signer,
+    /// Deploys token contracts
+    pub deployer: Signer,
+}
+
+impl Signers {
+    pub fn from_config(faucet: &SignerConfig, deployer: &SignerConfig) -> eyre::Result<Self> {
+        Ok(Self {
+            faucet: Signer::from_config(faucet)
+                .map_err(|e| eyre::eyre!("Failed to load the faucet signer: {}", e))?,
+            deployer: Signer::from_config(deployer)
+                .map_err(|e| eyre::eyre!("Failed to load the deployer signer: {}", e))?,
+        })
+    }
+}
+
+impl Signer {
+    pub fn from_config(config: &SignerConfig) -> eyre::Result<Self> {
+        match config {
+            SignerConfig::Key { key } => Ok(Self::Local(key.parse()?)),
+            SignerConfig::Keystore {
+                path,
+                passphrase_file,
+            } => {
+                let passphrase = std::fs::read_to_string(passphrase_file).map_err(|e| {
+                    eyre::eyre!("Failed to read passphrase file {}: {}", passphrase_file, e)
+                })?;
+                // editors and `echo` leave a trailing newline behind
+                let passphrase = passphrase.trim_end_matches(['\n', '\r']);
+                let signer = PrivateKeySigner::decrypt_keystore(path, passphrase)
+                    .map_err(|e| eyre::eyre!("Failed to decrypt keystore {}: {}", path, e))?;
+                Ok(Self::Local(signer))
+            }
+            SignerConfig::Remote {
+                url,
+                address,
+                auth_token,
+            } => Ok(Self::Remote(RemoteSigner::new(
+                url,
+                address.parse()?,
+                auth_token.clone(),
+            )?)),
+        }
+    }
+
+    pub fn address(&self) -> Address {
+        match self {
+            Self::Local(signer) => signer.address(),
+            Self::Remote(signer) => signer.address,
+        }
+    }
+
+    pub fn wallet(&self) -> EthereumWallet {
+        match self {
+            Self::Local(signer) => EthereumWallet::new(signer.clone()),
+            Self::Remote(signer) => EthereumWallet::new(signer.clone()),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct RemoteSignRequest {
+    pub address: Address,
+    /// Signing hash of the transaction, chain ID included
+    pub hash: B256,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct RemoteSignResponse {
+    /// 65 bytes, `r || s || v`
+    pub signature: Bytes,
+}
+
+/// Signs through an HTTP service holding the key, such as a KMS or HSM proxy.
+///
+/// Every transaction is sent as a [`RemoteSignRequest`] to `POST {url}`, which
+/// answers with a [`RemoteSignResponse`]. Signatures that do not recover to
+/// `address` are rejected.
+#[derive(Debug, Clone)]
+pub struct RemoteSigner {
+    url: String,
+    address: Address,
+    auth_token: Option<String>,
+    client: reqwest::Client,
+}
+
+impl RemoteSigner {
+    pub fn new(url: &str, address: Address, auth_token: Option<String>) -> eyre::Result<Self> {
+        Ok(Self {
+            url: url.to_string(),
+            address,
+            auth_token,
+            client: reqwest::Client::builder()
+                .timeout(Duration::from_secs(10))
+                .build()
+                .map_err(|e| eyre::eyre!("Failed to build the remote signer client: {}", e))?,
+        })
+    }
+
+    async fn sign_hash(&self, hash: B256) -> eyre::Result<Signature> {
+        let mut request = self.client.post(&self.url).json(&RemoteSignRequest {
+            address: self.address,
+            hash,
+        });
+        if let Some(token) = &self.auth_token {
+            request = request.bearer_auth(token);
+        }
+
+        let response = request.send().await?.error_for_status()?;
+        let RemoteSignResponse { signature } = response.json().await?;
+        let signature = Signature::from_raw(&signature)?;
+        let signer = signature.recover_address_from_prehash(&hash)?;
+        if signer != self.address {
+            return Err(eyre::eyre!(
+                "Remote signer signed with {} instead of {}",
+                signer,
+                self.address
+            ));
+        }
+        Ok(signature)
+    }
+}
+
+#[async_trait::async_trait]
+impl TxSigner<Signature> for RemoteSigner {
+    fn address(&self) -> Address {
+        self.address
+    }
+
+    async fn sign_transaction(
+        &self,
+        tx: &mut dyn SignableTransaction<Signature>,
+    ) -> alloy::signers::Result<Signature> {
+        self.sign_hash(tx.signature_hash()).await.map_err(|e| {
+            tracing::error!("Remote signer {} failed {}", self.url, e);
+            alloy::signers::Error::other(e.to_string())
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloy::{
+        consensus::TxEip1559,
+        network::TxSignerSync,
+        primitives::{address, U256},
+    };
+    use httpmock::{Method::POST, MockServer};
+
+    use super::*;
+
+    const KEY: &str = "0x59c6995e998f97a5a0044966f0945389dc9e86dae88c7a8412f4603b6b78690d";
+
+    fn transaction() -> TxEip1559 {
+        TxEip1559 {
+            chain_id: 10143,
+            nonce: 1,
+            gas_limit: 21_000,
+            max_fee_per_gas: 50_000_000_000,
+            max_priority_fee_per_gas: 2_000_000_000,
+            to: address!("0x000000000000000000000000000000000000dEaD").into(),
+            value: U256::from(1),
+            ..Default::default()
+        }
+    }
+
+    #[tokio::test]
+    async fn test_remote_signer() {
+        // stand-in remote signer answering with a signature made locally
+        let local: PrivateKeySigner = KEY.parse().unwrap();
+        let mut tx = transaction();
+        let signature = local.sign_transaction_sync(&mut tx).unwrap();
+
+        let response = RemoteSignResponse {
+            signature: signature.as_bytes().to_vec().into(),
+        };
+
+        let server = MockServer::start();
+        let mock = server.mock(|when, then| {
+            when.method(POST)
+                .path("/sign")
+                .header("authorization", "Bearer token")
+                .json_body_obj(&RemoteSignRequest {
+                    address: local.address(),
+                    hash: tx.signature_hash(),
+                });
+            then.status(200).json_body_obj(&response);
+        });
+        server.mock(|when, then| {
+            when.method(POST).path("/impostor");
+            then.status(200).json_body_obj(&response);
+        });
+
+        let remote = RemoteSigner::new(
+            &server.url("/sign"),
+            local.address(),
+            Some("token".to_string()),
+        )
+        .unwrap();
+        let remote_signature = remote.sign_transaction(&mut transaction()).await.unwrap();
+        assert_eq!(remote_signature, signature);
+        mock.assert();
+
+        // a signature from any other key is refused
+        let impostor = RemoteSigner::new(
+            &server.url("/impostor"),
+            address!("0x000000000000000000000000000000000000dEaD"),
+            Some("token".to_string()),
+        )
+        .unwrap();
+        assert!(impostor.sign_transaction(&mut transaction()).await.is_err());
+    }
+
+    #[test]
+    fn test_keystore_signer() {
+        let dir = std::env::temp_dir().join(uuid::Uuid::new_v4().to_string());
+        std::fs::create_dir_all(&dir).unwrap();
+        let local: PrivateKeySigner = KEY.parse().unwrap();
+        PrivateKeySigner::encrypt_keystore(
+            &dir,
+            &mut rand::thread_rng(),
+            local.to_bytes(),
+            "passphrase",
+            Some("faucet.json"),
+        )
+        .unwrap();
+        std::fs::write(dir.join("passphrase"), "passphrase\n").unwrap();
+
+        let signer = Signer::from_config(&SignerConfig::Keystore {
+            path: dir.join("faucet.json").to_string_lossy().to_string(),
+            passphrase_file: dir.join("passphrase").to_string_lossy().to_string(),
+        })
+        .unwrap();
+        assert_eq!(signer.address(), local.address());
+
+        std::fs::write(dir.join("passphrase"), "wrong").unwrap();
+        assert!(Signer::from_config(&SignerConfig::Keystore {
+            path: dir.join("faucet.json").to_string_lossy().to_string(),
+            passphrase_file: dir.join("passphrase").to_string_lossy().to_string(),
+        })
+        .is_err());
+
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+}
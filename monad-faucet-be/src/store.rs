@@ -1,7 +1,10 @@
+use std::collections::HashMap;
+
 use chrono::{DateTime, Duration, Utc};
 use ipnetwork::IpNetwork;
 use serde::{Deserialize, Serialize};
 use sqlx::{Pool, Postgres};
+use utoipa::ToSchema;
 
 #[async_trait::async_trait]
 pub trait Store {
@@ -17,13 +20,163 @@ pub trait Store {
     async fn create_token_entry(&self, token: Token) -> Result<Token, sqlx::Error>;
     async fn get_all_tokens(&self) -> Result<Vec<Token>, sqlx::Error>;
     async fn get_token_by_address(&self, address: String) -> Result<Token, sqlx::Error>;
+    async fn get_logo_by_hash(&self, hash: String) -> Result<Logo, sqlx::Error>;
+    async fn create_logo(&self, logo: Logo) -> Result<(), sqlx::Error>;
     async fn get_token_from_symbol(&self, symbol: String) -> Result<Token, sqlx::Error>;
+    // only used by the cooldown check, which is disabled for now
+    #[allow(dead_code)]
     async fn get_next_access(
         &self,
         field_name: FieldType,
         field_value: &str,
         token_address: &str,
     ) -> DateTime<Utc>;
+
+    async fn create_api_key(
+        &self,
+        api_key: ApiKey,
+        quotas: Vec<ApiKeyQuota>,
+    ) -> Result<ApiKey, sqlx::Error>;
+    async fn get_api_key_by_id(&self, id: String) -> Result<ApiKey, sqlx::Error>;
+    async fn get_api_key_by_hash(&self, key_hash: String) -> Result<ApiKey, sqlx::Error>;
+    async fn get_api_keys_by_user(&self, user_id: String) -> Result<Vec<ApiKey>, sqlx::Error>;
+    async fn revoke_api_key(&self, id: String) -> Result<(), sqlx::Error>;
+    async fn get_api_key_quotas(&self, api_key_id: String)
+        -> Result<Vec<ApiKeyQuota>, sqlx::Error>;
+    async fn create_api_key_usage(
+        &self,
+        api_key_id: String,
+        token_address: String,
+        tx_hash: String,
+        amount: String,
+    ) -> Result<(), sqlx::Error>;
+    async fn get_api_key_usage_since(
+        &self,
+        api_key_id: String,
+        since: DateTime<Utc>,
+    ) -> Result<ApiKeyUsage, sqlx::Error>;
+
+    async fn get_token_transfers_to(
+        &self,
+        to_address: String,
+    ) -> Result<Vec<TokenTransfer>, sqlx::Error>;
+
+    async fn create_subscription(
+        &self,
+        subscription: WalletSubscription,
+    ) -> Result<WalletSubscription, sqlx::Error>;
+    async fn get_subscription_by_id(&self, id: String) -> Result<WalletSubscription, sqlx::Error>;
+    async fn get_subscriptions_by_user(
+        &self,
+        user_id: String,
+    ) -> Result<Vec<WalletSubscription>, sqlx::Error>;
+    async fn update_subscription(
+        &self,
+        subscription: WalletSubscription,
+    ) -> Result<WalletSubscription, sqlx::Error>;
+    async fn delete_subscription(&self, id: String) -> Result<(), sqlx::Error>;
+    async fn get_due_subscriptions(
+        &self,
+        checked_before: DateTime<Utc>,
+    ) -> Result<Vec<WalletSubscription>, sqlx::Error>;
+    async fn mark_subscription_checked(
+        &self,
+        id: String,
+        refilled: bool,
+    ) -> Result<(), sqlx::Error>;
+    async fn count_scheduled_refills_since(
+        &self,
+        user_id: String,
+        since: DateTime<Utc>,
+    ) -> Result<i64, sqlx::Error>;
+    async fn create_deploy_job(&self, job: DeployJob) -> Result<(), sqlx::Error>;
+    async fn get_deploy_job(&self, id: String) -> Result<DeployJob, sqlx::Error>;
+    async fn update_deploy_job(&self, job: DeployJob) -> Result<(), sqlx::Error>;
+    /// Fails jobs left unfinished by a previous run, returns how many there were
+    async fn fail_unfinished_deploy_jobs(&self, reason: String) -> Result<u64, sqlx::Error>;
+    async fn create_webhook(&self, webhook: Webhook) -> Result<(), sqlx::Error>;
+    async fn get_webhook_by_id(&self, id: String) -> Result<Webhook, sqlx::Error>;
+    async fn get_webhooks_by_token(
+        &self,
+        token_address: String,
+    ) -> Result<Vec<Webhook>, sqlx::Error>;
+    /// Webhooks of a user that are not tied to a token
+    async fn get_webhooks_by_user(&self, user_id: String) -> Result<Vec<Webhook>, sqlx::Error>;
+    async fn delete_webhook(&self, id: String) -> Result<(), sqlx::Error>;
+    async fn create_webhook_delivery(&self, delivery: WebhookDelivery) -> Result<(), sqlx::Error>;
+    /// Takes up to `limit` due deliveries, pushing their next attempt past
+    /// `lease` so other dispatchers skip them while they are being sent
+    async fn claim_due_webhook_deliveries(
+        &self,
+        limit: i64,
+        lease: Duration,
+    ) -> Result<Vec<WebhookDelivery>, sqlx::Error>;
+    async fn update_webhook_delivery(&self, delivery: WebhookDelivery) -> Result<(), sqlx::Error>;
+    async fn get_webhook_deliveries(
+        &self,
+        webhook_id: String,
+        limit: i64,
+    ) -> Result<Vec<WebhookDelivery>, sqlx::Error>;
+    /// Amount of a token dripped since a point in time, deploy allocations excluded
+    async fn get_token_outflow_since(
+        &self,
+        token_address: String,
+        since: DateTime<Utc>,
+    ) -> Result<u128, sqlx::Error>;
+    async fn get_token_pause(
+        &self,
+        token_address: String,
+    ) -> Result<Option<TokenPause>, sqlx::Error>;
+    async fn get_paused_tokens(&self) -> Result<Vec<TokenPause>, sqlx::Error>;
+    /// Returns false if the token was already paused
+    async fn pause_token(&self, token_address: String, reason: String)
+        -> Result<bool, sqlx::Error>;
+    /// Returns false if the token was not paused
+    async fn resume_token(
+        &self,
+        token_address: String,
+        user_id: String,
+    ) -> Result<bool, sqlx::Error>;
+    /// Stores a key unless an unexpired one exists whose withdraw finished or
+    /// still holds its lease, returns whether it was stored
+    async fn claim_idempotency_key(
+        &self,
+        record: IdempotencyRecord,
+        expired_before: DateTime<Utc>,
+    ) -> Result<bool, sqlx::Error>;
+    async fn get_idempotency_key(
+        &self,
+        scope: String,
+        key: String,
+    ) -> Result<IdempotencyRecord, sqlx::Error>;
+    async fn complete_idempotency_key(
+        &self,
+        scope: String,
+        key: String,
+        response: String,
+    ) -> Result<(), sqlx::Error>;
+    async fn delete_idempotency_key(&self, scope: String, key: String) -> Result<(), sqlx::Error>;
+    /// Drops keys of withdraws whose lease ran out without an outcome, which
+    /// an instance that stopped never finished. Returns how many there were.
+    async fn release_unfinished_idempotency_keys(&self) -> Result<u64, sqlx::Error>;
+    async fn create_auth_session(&self, session: AuthSession) -> Result<(), sqlx::Error>;
+    async fn get_auth_session(&self, id: String) -> Result<AuthSession, sqlx::Error>;
+    /// The session whose current or previous refresh token has this hash
+    async fn get_auth_session_by_refresh_token(
+        &self,
+        refresh_token_hash: String,
+    ) -> Result<AuthSession, sqlx::Error>;
+    /// Replaces the refresh token of a live session, returns false if
+    /// `current_hash` was already replaced or the session revoked
+    async fn rotate_refresh_token(
+        &self,
+        id: String,
+        current_hash: String,
+        new_hash: String,
+    ) -> Result<bool, sqlx::Error>;
+    async fn revoke_auth_session(&self, id: String) -> Result<(), sqlx::Error>;
+    /// Returns how many sessions were still live
+    async fn revoke_user_auth_sessions(&self, user_id: String) -> Result<u64, sqlx::Error>;
 }
 
 #[derive(Clone, Debug)]
@@ -31,7 +184,7 @@ pub struct PgStore {
     db: Pool<Postgres>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
 pub struct User {
     pub id: String,
     pub username: String,
@@ -41,14 +194,27 @@ pub struct User {
     pub email: Option<String>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize, sqlx::Type)]
+#[allow(clippy::upper_case_acronyms)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, sqlx::Type, ToSchema)]
 #[sqlx(type_name = "token_type", rename_all = "UPPERCASE")]
 pub enum TokenType {
     ERC20,
     NATIVE,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+/// What triggered a token transfer
+#[allow(non_camel_case_types, clippy::upper_case_acronyms)]
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize, sqlx::Type, ToSchema)]
+#[sqlx(type_name = "drip_kind", rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum DripKind {
+    #[default]
+    CLAIM,
+    SCHEDULED,
+    API_KEY,
+    DEPLOY,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
 pub struct TokenTransfer {
     pub token_address: String,
     pub token_type: TokenType,
@@ -57,10 +223,13 @@ pub struct TokenTransfer {
     pub to_address: String,
     pub amount: String,
     pub chain_id: i32,
-    pub ip: IpNetwork,
+    #[serde(skip_serializing)]
+    #[schema(ignore)]
+    pub ip: Option<IpNetwork>,
+    pub kind: DripKind,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
 pub struct Token {
     pub created_by: String,
     pub token_type: TokenType,
@@ -71,8 +240,191 @@ pub struct Token {
     pub name: String,
     pub decimals: i32,
     pub withdraw_limit: String,
+    /// When set, drips top the recipient up to this balance (in base units)
+    /// instead of sending a fixed `withdraw_limit`
+    pub top_up_target: Option<String>,
+    /// Square PNG rendition of the logo, NULL for tokens deployed before it existed
+    pub logo_thumbnail_url: Option<String>,
+    /// User who deployed the token through the faucet, `created_by` holds
+    /// their deployer wallet
+    #[serde(skip_serializing)]
+    pub created_by_user_id: Option<String>,
+}
+
+/// A stored logo, keyed by the SHA-256 of the uploaded file
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Logo {
+    pub hash: String,
+    pub logo_url: String,
+    pub thumbnail_url: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct ApiKey {
+    pub id: String,
+    pub user_id: String,
+    pub name: String,
+    #[serde(skip_serializing)]
+    pub key_hash: String,
+    pub key_prefix: String,
+    pub drips_per_day: i32,
+    pub revoked: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct ApiKeyQuota {
+    pub token_address: String,
+    pub max_amount_per_day: String,
+}
+
+/// Drips made with an API key since a point in time
+#[derive(Debug, Clone, Default)]
+pub struct ApiKeyUsage {
+    pub drips: i64,
+    /// Total amount dripped per token address, in base units
+    pub amounts: HashMap<String, u128>,
+}
+
+/// A wallet that is automatically refilled once its balance drops below
+/// `min_balance`
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct WalletSubscription {
+    pub id: String,
+    pub user_id: String,
+    pub wallet_address: String,
+    pub token_address: String,
+    pub min_balance: String,
+    pub refill_amount: String,
+    pub enabled: bool,
+    pub last_checked_at: Option<DateTime<Utc>>,
+    pub last_refill_at: Option<DateTime<Utc>>,
+}
+
+/// Progress of a token deployment
+#[allow(non_camel_case_types, clippy::upper_case_acronyms)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, sqlx::Type, ToSchema)]
+#[sqlx(type_name = "deploy_step", rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum DeployStep {
+    QUEUED,
+    UPLOADING_LOGO,
+    DEPLOYING,
+    ALLOCATING_SUPPLY,
+    REGISTERED,
+    FAILED,
+}
+
+impl DeployStep {
+    /// Whether the job has stopped and will not change anymore
+    pub fn is_finished(&self) -> bool {
+        matches!(self, DeployStep::REGISTERED | DeployStep::FAILED)
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct DeployJob {
+    pub id: String,
+    /// `None` for anonymous deploys
+    #[serde(skip_serializing)]
+    pub user_id: Option<String>,
+    pub symbol: String,
+    pub step: DeployStep,
+    pub deploy_tx_hash: Option<String>,
+    pub contract_address: Option<String>,
+    pub allocation_tx_hashes: Vec<String>,
+    pub error: Option<String>,
+    pub error_code: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+/// An endpoint notified about events of one token
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct Webhook {
+    pub id: String,
+    /// None for a webhook of `created_by` notified about their own deploys
+    pub token_address: Option<String>,
+    pub url: String,
+    /// Key the payloads are signed with, only shown when the webhook is created
+    #[serde(skip_serializing)]
+    pub secret: String,
+    pub events: Vec<String>,
+    pub created_by: String,
+    pub created_at: DateTime<Utc>,
+}
+
+#[allow(clippy::upper_case_acronyms)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, sqlx::Type, ToSchema)]
+#[sqlx(
+    type_name = "webhook_delivery_status",
+    rename_all = "SCREAMING_SNAKE_CASE"
+)]
+pub enum DeliveryStatus {
+    PENDING,
+    DELIVERED,
+    FAILED,
+}
+
+/// A webhook call, pending in the outbox until it is delivered or gives up
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct WebhookDelivery {
+    pub id: String,
+    pub webhook_id: String,
+    pub event_type: String,
+    pub payload: String,
+    pub status: DeliveryStatus,
+    pub attempts: i32,
+    pub next_attempt_at: DateTime<Utc>,
+    pub last_status_code: Option<i32>,
+    pub last_error: Option<String>,
+    pub created_at: DateTime<Utc>,
+}
+
+/// Circuit breaker state of a token, tripped when its outflow exceeds a cap
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct TokenPause {
+    pub token_address: String,
+    pub paused: bool,
+    pub reason: Option<String>,
+    pub paused_at: Option<DateTime<Utc>>,
+    /// Outflow before this no longer counts towards the caps
+    pub resumed_at: Option<DateTime<Utc>>,
+    pub resumed_by: Option<String>,
+}
+
+/// A sign in, kept alive by rotating its refresh token
+#[derive(Debug, Clone)]
+pub struct AuthSession {
+    pub id: String,
+    pub user_id: String,
+    pub refresh_token_hash: String,
+    /// The refresh token the current one replaced
+    pub previous_refresh_token_hash: Option<String>,
+    pub expires_at: DateTime<Utc>,
+    pub revoked_at: Option<DateTime<Utc>>,
 }
 
+impl AuthSession {
+    pub fn is_live(&self) -> bool {
+        self.revoked_at.is_none() && self.expires_at > Utc::now()
+    }
+}
+
+/// An `Idempotency-Key` and the request it was first sent with
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct IdempotencyRecord {
+    pub scope: String,
+    pub key: String,
+    pub request_hash: String,
+    /// JSON outcome of a withdraw, `None` while it is in flight
+    pub response: Option<String>,
+    pub deploy_job_id: Option<String>,
+    pub created_at: DateTime<Utc>,
+    /// Until when an in-flight withdraw holds the key, after which a retry
+    /// may claim it again
+    pub locked_until: DateTime<Utc>,
+}
+
+#[allow(non_camel_case_types, dead_code)]
 #[derive(Debug, PartialEq)]
 pub enum FieldType {
     ip,
@@ -86,6 +438,7 @@ impl PgStore {
 
 #[async_trait::async_trait]
 impl Store for PgStore {
+    #[tracing::instrument(name = "db.get_user_by_id", skip_all)]
     async fn get_user_by_id(&self, id: String) -> Result<User, sqlx::Error> {
         let user = sqlx::query!("SELECT * FROM users WHERE id = $1", id)
             .fetch_one(&self.db)
@@ -100,6 +453,7 @@ impl Store for PgStore {
         })
     }
 
+    #[tracing::instrument(name = "db.get_next_access", skip_all)]
     async fn get_next_access(
         &self,
         field_name: FieldType, // "ip" or "to_address"
@@ -137,12 +491,13 @@ impl Store for PgStore {
             Ok(Some(next_access)) => next_access.unwrap_or(Utc::now() + Duration::days(1)),
             Ok(None) => Utc::now() - Duration::days(1),
             Err(e) => {
-                eprintln!("Error while getting next access: {:?}", e);
+                tracing::error!("Error while getting next access: {:?}", e);
                 Utc::now() + Duration::days(1)
             }
         }
     }
 
+    #[tracing::instrument(name = "db.create_user", skip_all)]
     async fn create_user(&self, user: User) -> Result<User, sqlx::Error> {
         let record= sqlx::query!(
             "INSERT INTO users (id, username, github_id, access_token, avatar_url, email) VALUES ($1, $2, $3, $4, $5, $6) RETURNING *",
@@ -165,6 +520,7 @@ impl Store for PgStore {
         })
     }
 
+    #[tracing::instrument(name = "db.get_user_by_github_id", skip_all)]
     async fn get_user_by_github_id(&self, github_id: String) -> Result<User, sqlx::Error> {
         let user = sqlx::query!("SELECT * FROM users WHERE github_id = $1", github_id)
             .fetch_one(&self.db)
@@ -180,14 +536,15 @@ impl Store for PgStore {
         })
     }
 
+    #[tracing::instrument(name = "db.create_token_transfer", skip_all)]
     async fn create_token_transfer(
         &self,
         token_transfer: TokenTransfer,
     ) -> Result<TokenTransfer, sqlx::Error> {
         let record = sqlx::query!(
-            r#"INSERT INTO token_transfers (token_address, token_type, tx_hash, from_address, to_address, amount, chain_id, ip) 
-            VALUES ($1, $2, $3, $4, $5, $6, $7, $8) 
-            RETURNING token_address, token_type as "token_type!: TokenType", tx_hash, from_address, to_address, amount, chain_id, ip"#,
+            r#"INSERT INTO token_transfers (token_address, token_type, tx_hash, from_address, to_address, amount, chain_id, ip, kind) 
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9) 
+            RETURNING token_address, token_type as "token_type!: TokenType", tx_hash, from_address, to_address, amount, chain_id, ip, kind as "kind!: DripKind""#,
             token_transfer.token_address,
             token_transfer.token_type as _,
             token_transfer.tx_hash,
@@ -195,7 +552,8 @@ impl Store for PgStore {
             token_transfer.to_address,
             token_transfer.amount,
             token_transfer.chain_id,
-            token_transfer.ip
+            token_transfer.ip,
+            token_transfer.kind as _
         )
         .fetch_one(&self.db)
         .await?;
@@ -208,17 +566,47 @@ impl Store for PgStore {
             to_address: record.to_address,
             amount: record.amount,
             chain_id: record.chain_id,
-            ip: record
-                .ip
-                .unwrap_or(IpNetwork::V4("0.0.0.0".parse().unwrap())),
+            ip: record.ip,
+            kind: record.kind,
         })
     }
 
+    #[tracing::instrument(name = "db.get_token_transfers_to", skip_all)]
+    async fn get_token_transfers_to(
+        &self,
+        to_address: String,
+    ) -> Result<Vec<TokenTransfer>, sqlx::Error> {
+        let records = sqlx::query!(
+            r#"SELECT token_address, token_type as "token_type!: TokenType", tx_hash, from_address, to_address, amount, chain_id, ip, kind as "kind!: DripKind" 
+            FROM token_transfers WHERE LOWER(to_address) = LOWER($1) 
+            ORDER BY created_at DESC LIMIT 100"#,
+            to_address
+        )
+        .fetch_all(&self.db)
+        .await?;
+
+        Ok(records
+            .into_iter()
+            .map(|record| TokenTransfer {
+                token_address: record.token_address,
+                token_type: record.token_type,
+                tx_hash: record.tx_hash,
+                from_address: record.from_address,
+                to_address: record.to_address,
+                amount: record.amount,
+                chain_id: record.chain_id,
+                ip: record.ip,
+                kind: record.kind,
+            })
+            .collect())
+    }
+
+    #[tracing::instrument(name = "db.create_token_entry", skip_all)]
     async fn create_token_entry(&self, token: Token) -> Result<Token, sqlx::Error> {
         let record = sqlx::query!(
-            r#"INSERT INTO tokens (created_by, token_type, address, logo_url, chain_id, symbol, name, decimals, withdraw_limit) 
-            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9) 
-            RETURNING created_by, token_type as "token_type!: TokenType", address, logo_url, chain_id, symbol, name, decimals, withdraw_limit"#,
+            r#"INSERT INTO tokens (created_by, token_type, address, logo_url, chain_id, symbol, name, decimals, withdraw_limit, top_up_target, logo_thumbnail_url, created_by_user_id) 
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12) 
+            RETURNING created_by, token_type as "token_type!: TokenType", address, logo_url, chain_id, symbol, name, decimals, withdraw_limit, top_up_target, logo_thumbnail_url, created_by_user_id"#,
             token.created_by,
             token.token_type as _,
             token.address,
@@ -227,7 +615,10 @@ impl Store for PgStore {
             token.symbol,
             token.name,
             token.decimals,
-            token.withdraw_limit
+            token.withdraw_limit,
+            token.top_up_target,
+            token.logo_thumbnail_url,
+            token.created_by_user_id
         )
         .fetch_one(&self.db)
         .await?;
@@ -242,12 +633,16 @@ impl Store for PgStore {
             name: record.name,
             decimals: record.decimals,
             withdraw_limit: record.withdraw_limit,
+            top_up_target: record.top_up_target,
+            logo_thumbnail_url: record.logo_thumbnail_url,
+            created_by_user_id: record.created_by_user_id,
         })
     }
 
+    #[tracing::instrument(name = "db.get_token_by_address", skip_all)]
     async fn get_token_by_address(&self, address: String) -> Result<Token, sqlx::Error> {
         let record = sqlx::query!(
-            r#"SELECT created_by, token_type as "token_type!: TokenType", address, logo_url, chain_id, symbol, name, decimals, withdraw_limit 
+            r#"SELECT created_by, token_type as "token_type!: TokenType", address, logo_url, chain_id, symbol, name, decimals, withdraw_limit, top_up_target, logo_thumbnail_url, created_by_user_id 
             FROM tokens WHERE address = $1"#,
             address
         )
@@ -264,12 +659,46 @@ impl Store for PgStore {
             name: record.name,
             decimals: record.decimals,
             withdraw_limit: record.withdraw_limit,
+            top_up_target: record.top_up_target,
+            logo_thumbnail_url: record.logo_thumbnail_url,
+            created_by_user_id: record.created_by_user_id,
         })
     }
 
+    #[tracing::instrument(name = "db.get_logo_by_hash", skip_all)]
+    async fn get_logo_by_hash(&self, hash: String) -> Result<Logo, sqlx::Error> {
+        let record = sqlx::query!(
+            "SELECT hash, logo_url, thumbnail_url FROM logos WHERE hash = $1",
+            hash
+        )
+        .fetch_one(&self.db)
+        .await?;
+
+        Ok(Logo {
+            hash: record.hash,
+            logo_url: record.logo_url,
+            thumbnail_url: record.thumbnail_url,
+        })
+    }
+
+    #[tracing::instrument(name = "db.create_logo", skip_all)]
+    async fn create_logo(&self, logo: Logo) -> Result<(), sqlx::Error> {
+        sqlx::query!(
+            "INSERT INTO logos (hash, logo_url, thumbnail_url) VALUES ($1, $2, $3) ON CONFLICT (hash) DO NOTHING",
+            logo.hash,
+            logo.logo_url,
+            logo.thumbnail_url
+        )
+        .execute(&self.db)
+        .await?;
+
+        Ok(())
+    }
+
+    #[tracing::instrument(name = "db.get_token_from_symbol", skip_all)]
     async fn get_token_from_symbol(&self, symbol: String) -> Result<Token, sqlx::Error> {
         let record = sqlx::query!(
-            r#"SELECT created_by, token_type as "token_type!: TokenType", address, logo_url, chain_id, symbol, name, decimals, withdraw_limit 
+            r#"SELECT created_by, token_type as "token_type!: TokenType", address, logo_url, chain_id, symbol, name, decimals, withdraw_limit, top_up_target, logo_thumbnail_url, created_by_user_id 
             FROM tokens WHERE symbol = $1"#,
             symbol
         )
@@ -286,12 +715,16 @@ impl Store for PgStore {
             name: record.name,
             decimals: record.decimals,
             withdraw_limit: record.withdraw_limit,
+            top_up_target: record.top_up_target,
+            logo_thumbnail_url: record.logo_thumbnail_url,
+            created_by_user_id: record.created_by_user_id,
         })
     }
 
+    #[tracing::instrument(name = "db.get_all_tokens", skip_all)]
     async fn get_all_tokens(&self) -> Result<Vec<Token>, sqlx::Error> {
         let records = sqlx::query!(
-            r#"SELECT created_by, token_type as "token_type!: TokenType", address, logo_url, chain_id, symbol, name, decimals, withdraw_limit 
+            r#"SELECT created_by, token_type as "token_type!: TokenType", address, logo_url, chain_id, symbol, name, decimals, withdraw_limit, top_up_target, logo_thumbnail_url, created_by_user_id 
             FROM tokens"#
         )
         .fetch_all(&self.db)
@@ -309,9 +742,969 @@ impl Store for PgStore {
                 name: record.name,
                 decimals: record.decimals,
                 withdraw_limit: record.withdraw_limit,
+                top_up_target: record.top_up_target,
+                logo_thumbnail_url: record.logo_thumbnail_url,
+                created_by_user_id: record.created_by_user_id,
+            })
+            .collect())
+    }
+
+    #[tracing::instrument(name = "db.create_api_key", skip_all)]
+    async fn create_api_key(
+        &self,
+        api_key: ApiKey,
+        quotas: Vec<ApiKeyQuota>,
+    ) -> Result<ApiKey, sqlx::Error> {
+        let mut tx = self.db.begin().await?;
+
+        let record = sqlx::query!(
+            r#"INSERT INTO api_keys (id, user_id, name, key_hash, key_prefix, drips_per_day) 
+            VALUES ($1, $2, $3, $4, $5, $6) 
+            RETURNING id, user_id, name, key_hash, key_prefix, drips_per_day, revoked_at"#,
+            api_key.id,
+            api_key.user_id,
+            api_key.name,
+            api_key.key_hash,
+            api_key.key_prefix,
+            api_key.drips_per_day
+        )
+        .fetch_one(&mut *tx)
+        .await?;
+
+        for quota in quotas {
+            sqlx::query!(
+                "INSERT INTO api_key_quotas (api_key_id, token_address, max_amount_per_day) VALUES ($1, $2, $3)",
+                record.id,
+                quota.token_address,
+                quota.max_amount_per_day
+            )
+            .execute(&mut *tx)
+            .await?;
+        }
+
+        tx.commit().await?;
+
+        Ok(ApiKey {
+            id: record.id,
+            user_id: record.user_id,
+            name: record.name,
+            key_hash: record.key_hash,
+            key_prefix: record.key_prefix,
+            drips_per_day: record.drips_per_day,
+            revoked: record.revoked_at.is_some(),
+        })
+    }
+
+    #[tracing::instrument(name = "db.get_api_key_by_id", skip_all)]
+    async fn get_api_key_by_id(&self, id: String) -> Result<ApiKey, sqlx::Error> {
+        let record = sqlx::query!(
+            "SELECT id, user_id, name, key_hash, key_prefix, drips_per_day, revoked_at FROM api_keys WHERE id = $1",
+            id
+        )
+        .fetch_one(&self.db)
+        .await?;
+
+        Ok(ApiKey {
+            id: record.id,
+            user_id: record.user_id,
+            name: record.name,
+            key_hash: record.key_hash,
+            key_prefix: record.key_prefix,
+            drips_per_day: record.drips_per_day,
+            revoked: record.revoked_at.is_some(),
+        })
+    }
+
+    #[tracing::instrument(name = "db.get_api_key_by_hash", skip_all)]
+    async fn get_api_key_by_hash(&self, key_hash: String) -> Result<ApiKey, sqlx::Error> {
+        let record = sqlx::query!(
+            "SELECT id, user_id, name, key_hash, key_prefix, drips_per_day, revoked_at FROM api_keys WHERE key_hash = $1",
+            key_hash
+        )
+        .fetch_one(&self.db)
+        .await?;
+
+        Ok(ApiKey {
+            id: record.id,
+            user_id: record.user_id,
+            name: record.name,
+            key_hash: record.key_hash,
+            key_prefix: record.key_prefix,
+            drips_per_day: record.drips_per_day,
+            revoked: record.revoked_at.is_some(),
+        })
+    }
+
+    #[tracing::instrument(name = "db.get_api_keys_by_user", skip_all)]
+    async fn get_api_keys_by_user(&self, user_id: String) -> Result<Vec<ApiKey>, sqlx::Error> {
+        let records = sqlx::query!(
+            "SELECT id, user_id, name, key_hash, key_prefix, drips_per_day, revoked_at FROM api_keys WHERE user_id = $1 ORDER BY created_at DESC",
+            user_id
+        )
+        .fetch_all(&self.db)
+        .await?;
+
+        Ok(records
+            .into_iter()
+            .map(|record| ApiKey {
+                id: record.id,
+                user_id: record.user_id,
+                name: record.name,
+                key_hash: record.key_hash,
+                key_prefix: record.key_prefix,
+                drips_per_day: record.drips_per_day,
+                revoked: record.revoked_at.is_some(),
+            })
+            .collect())
+    }
+
+    #[tracing::instrument(name = "db.revoke_api_key", skip_all)]
+    async fn revoke_api_key(&self, id: String) -> Result<(), sqlx::Error> {
+        sqlx::query!(
+            "UPDATE api_keys SET revoked_at = NOW(), updated_at = NOW() WHERE id = $1 AND revoked_at IS NULL",
+            id
+        )
+        .execute(&self.db)
+        .await?;
+        Ok(())
+    }
+
+    #[tracing::instrument(name = "db.get_api_key_quotas", skip_all)]
+    async fn get_api_key_quotas(
+        &self,
+        api_key_id: String,
+    ) -> Result<Vec<ApiKeyQuota>, sqlx::Error> {
+        let records = sqlx::query!(
+            "SELECT token_address, max_amount_per_day FROM api_key_quotas WHERE api_key_id = $1",
+            api_key_id
+        )
+        .fetch_all(&self.db)
+        .await?;
+
+        Ok(records
+            .into_iter()
+            .map(|record| ApiKeyQuota {
+                token_address: record.token_address,
+                max_amount_per_day: record.max_amount_per_day,
+            })
+            .collect())
+    }
+
+    #[tracing::instrument(name = "db.create_api_key_usage", skip_all)]
+    async fn create_api_key_usage(
+        &self,
+        api_key_id: String,
+        token_address: String,
+        tx_hash: String,
+        amount: String,
+    ) -> Result<(), sqlx::Error> {
+        sqlx::query!(
+            "INSERT INTO api_key_usage (api_key_id, token_address, tx_hash, amount) VALUES ($1, $2, $3, $4)",
+            api_key_id,
+            token_address,
+            tx_hash,
+            amount
+        )
+        .execute(&self.db)
+        .await?;
+        Ok(())
+    }
+
+    #[tracing::instrument(name = "db.get_api_key_usage_since", skip_all)]
+    async fn get_api_key_usage_since(
+        &self,
+        api_key_id: String,
+        since: DateTime<Utc>,
+    ) -> Result<ApiKeyUsage, sqlx::Error> {
+        let records = sqlx::query!(
+            r#"SELECT token_address, COUNT(*) as "drips!", SUM(amount::NUMERIC)::TEXT as "amount!" 
+            FROM api_key_usage 
+            WHERE api_key_id = $1 AND created_at > $2 
+            GROUP BY token_address"#,
+            api_key_id,
+            since
+        )
+        .fetch_all(&self.db)
+        .await?;
+
+        let mut usage = ApiKeyUsage::default();
+        for record in records {
+            usage.drips += record.drips;
+            usage.amounts.insert(
+                record.token_address,
+                record.amount.parse::<u128>().unwrap_or(u128::MAX),
+            );
+        }
+        Ok(usage)
+    }
+
+    #[tracing::instrument(name = "db.create_subscription", skip_all)]
+    async fn create_subscription(
+        &self,
+        subscription: WalletSubscription,
+    ) -> Result<WalletSubscription, sqlx::Error> {
+        let record = sqlx::query!(
+            r#"INSERT INTO wallet_subscriptions (id, user_id, wallet_address, token_address, min_balance, refill_amount, enabled) 
+            VALUES ($1, $2, $3, $4, $5, $6, $7) 
+            RETURNING id, user_id, wallet_address, token_address, min_balance, refill_amount, enabled, last_checked_at, last_refill_at"#,
+            subscription.id,
+            subscription.user_id,
+            subscription.wallet_address,
+            subscription.token_address,
+            subscription.min_balance,
+            subscription.refill_amount,
+            subscription.enabled
+        )
+        .fetch_one(&self.db)
+        .await?;
+
+        Ok(WalletSubscription {
+            id: record.id,
+            user_id: record.user_id,
+            wallet_address: record.wallet_address,
+            token_address: record.token_address,
+            min_balance: record.min_balance,
+            refill_amount: record.refill_amount,
+            enabled: record.enabled,
+            last_checked_at: record.last_checked_at,
+            last_refill_at: record.last_refill_at,
+        })
+    }
+
+    #[tracing::instrument(name = "db.get_subscription_by_id", skip_all)]
+    async fn get_subscription_by_id(&self, id: String) -> Result<WalletSubscription, sqlx::Error> {
+        let record = sqlx::query!(
+            "SELECT id, user_id, wallet_address, token_address, min_balance, refill_amount, enabled, last_checked_at, last_refill_at FROM wallet_subscriptions WHERE id = $1",
+            id
+        )
+        .fetch_one(&self.db)
+        .await?;
+
+        Ok(WalletSubscription {
+            id: record.id,
+            user_id: record.user_id,
+            wallet_address: record.wallet_address,
+            token_address: record.token_address,
+            min_balance: record.min_balance,
+            refill_amount: record.refill_amount,
+            enabled: record.enabled,
+            last_checked_at: record.last_checked_at,
+            last_refill_at: record.last_refill_at,
+        })
+    }
+
+    #[tracing::instrument(name = "db.get_subscriptions_by_user", skip_all)]
+    async fn get_subscriptions_by_user(
+        &self,
+        user_id: String,
+    ) -> Result<Vec<WalletSubscription>, sqlx::Error> {
+        let records = sqlx::query!(
+            "SELECT id, user_id, wallet_address, token_address, min_balance, refill_amount, enabled, last_checked_at, last_refill_at FROM wallet_subscriptions WHERE user_id = $1 ORDER BY created_at DESC",
+            user_id
+        )
+        .fetch_all(&self.db)
+        .await?;
+
+        Ok(records
+            .into_iter()
+            .map(|record| WalletSubscription {
+                id: record.id,
+                user_id: record.user_id,
+                wallet_address: record.wallet_address,
+                token_address: record.token_address,
+                min_balance: record.min_balance,
+                refill_amount: record.refill_amount,
+                enabled: record.enabled,
+                last_checked_at: record.last_checked_at,
+                last_refill_at: record.last_refill_at,
+            })
+            .collect())
+    }
+
+    #[tracing::instrument(name = "db.update_subscription", skip_all)]
+    async fn update_subscription(
+        &self,
+        subscription: WalletSubscription,
+    ) -> Result<WalletSubscription, sqlx::Error> {
+        let record = sqlx::query!(
+            r#"UPDATE wallet_subscriptions 
+            SET min_balance = $2, refill_amount = $3, enabled = $4, updated_at = NOW() 
+            WHERE id = $1 
+            RETURNING id, user_id, wallet_address, token_address, min_balance, refill_amount, enabled, last_checked_at, last_refill_at"#,
+            subscription.id,
+            subscription.min_balance,
+            subscription.refill_amount,
+            subscription.enabled
+        )
+        .fetch_one(&self.db)
+        .await?;
+
+        Ok(WalletSubscription {
+            id: record.id,
+            user_id: record.user_id,
+            wallet_address: record.wallet_address,
+            token_address: record.token_address,
+            min_balance: record.min_balance,
+            refill_amount: record.refill_amount,
+            enabled: record.enabled,
+            last_checked_at: record.last_checked_at,
+            last_refill_at: record.last_refill_at,
+        })
+    }
+
+    #[tracing::instrument(name = "db.delete_subscription", skip_all)]
+    async fn delete_subscription(&self, id: String) -> Result<(), sqlx::Error> {
+        sqlx::query!("DELETE FROM wallet_subscriptions WHERE id = $1", id)
+            .execute(&self.db)
+            .await?;
+        Ok(())
+    }
+
+    #[tracing::instrument(name = "db.get_due_subscriptions", skip_all)]
+    async fn get_due_subscriptions(
+        &self,
+        checked_before: DateTime<Utc>,
+    ) -> Result<Vec<WalletSubscription>, sqlx::Error> {
+        let records = sqlx::query!(
+            r#"SELECT id, user_id, wallet_address, token_address, min_balance, refill_amount, enabled, last_checked_at, last_refill_at FROM wallet_subscriptions 
+            WHERE enabled AND (last_checked_at IS NULL OR last_checked_at < $1) 
+            ORDER BY last_checked_at ASC NULLS FIRST"#,
+            checked_before
+        )
+        .fetch_all(&self.db)
+        .await?;
+
+        Ok(records
+            .into_iter()
+            .map(|record| WalletSubscription {
+                id: record.id,
+                user_id: record.user_id,
+                wallet_address: record.wallet_address,
+                token_address: record.token_address,
+                min_balance: record.min_balance,
+                refill_amount: record.refill_amount,
+                enabled: record.enabled,
+                last_checked_at: record.last_checked_at,
+                last_refill_at: record.last_refill_at,
+            })
+            .collect())
+    }
+
+    #[tracing::instrument(name = "db.mark_subscription_checked", skip_all)]
+    async fn mark_subscription_checked(
+        &self,
+        id: String,
+        refilled: bool,
+    ) -> Result<(), sqlx::Error> {
+        sqlx::query!(
+            r#"UPDATE wallet_subscriptions 
+            SET last_checked_at = NOW(), last_refill_at = CASE WHEN $2 THEN NOW() ELSE last_refill_at END 
+            WHERE id = $1"#,
+            id,
+            refilled
+        )
+        .execute(&self.db)
+        .await?;
+        Ok(())
+    }
+
+    #[tracing::instrument(name = "db.count_scheduled_refills_since", skip_all)]
+    async fn count_scheduled_refills_since(
+        &self,
+        user_id: String,
+        since: DateTime<Utc>,
+    ) -> Result<i64, sqlx::Error> {
+        let count = sqlx::query_scalar!(
+            r#"SELECT COUNT(*) as "count!" FROM token_transfers t 
+            JOIN wallet_subscriptions s 
+              ON LOWER(s.wallet_address) = LOWER(t.to_address) AND LOWER(s.token_address) = LOWER(t.token_address) 
+            WHERE s.user_id = $1 AND t.kind = 'SCHEDULED' AND t.created_at > $2"#,
+            user_id,
+            since
+        )
+        .fetch_one(&self.db)
+        .await?;
+        Ok(count)
+    }
+
+    #[tracing::instrument(name = "db.create_deploy_job", skip_all)]
+    async fn create_deploy_job(&self, job: DeployJob) -> Result<(), sqlx::Error> {
+        sqlx::query!(
+            r#"INSERT INTO deploy_jobs (id, user_id, symbol, step, created_at, updated_at) 
+            VALUES ($1, $2, $3, $4, $5, $6)"#,
+            job.id,
+            job.user_id,
+            job.symbol,
+            job.step as _,
+            job.created_at,
+            job.updated_at
+        )
+        .execute(&self.db)
+        .await?;
+        Ok(())
+    }
+
+    #[tracing::instrument(name = "db.get_deploy_job", skip_all)]
+    async fn get_deploy_job(&self, id: String) -> Result<DeployJob, sqlx::Error> {
+        let record = sqlx::query!(
+            r#"SELECT id, user_id, symbol, step as "step!: DeployStep", deploy_tx_hash, contract_address, 
+            allocation_tx_hashes, error, error_code, created_at, updated_at 
+            FROM deploy_jobs WHERE id = $1"#,
+            id
+        )
+        .fetch_one(&self.db)
+        .await?;
+
+        Ok(DeployJob {
+            id: record.id,
+            user_id: record.user_id,
+            symbol: record.symbol,
+            step: record.step,
+            deploy_tx_hash: record.deploy_tx_hash,
+            contract_address: record.contract_address,
+            allocation_tx_hashes: record.allocation_tx_hashes,
+            error: record.error,
+            error_code: record.error_code,
+            created_at: record.created_at,
+            updated_at: record.updated_at,
+        })
+    }
+
+    #[tracing::instrument(name = "db.update_deploy_job", skip_all)]
+    async fn update_deploy_job(&self, job: DeployJob) -> Result<(), sqlx::Error> {
+        sqlx::query!(
+            r#"UPDATE deploy_jobs 
+            SET step = $2, deploy_tx_hash = $3, contract_address = $4, allocation_tx_hashes = $5, 
+            error = $6, error_code = $7, updated_at = $8 
+            WHERE id = $1"#,
+            job.id,
+            job.step as _,
+            job.deploy_tx_hash,
+            job.contract_address,
+            &job.allocation_tx_hashes,
+            job.error,
+            job.error_code,
+            job.updated_at
+        )
+        .execute(&self.db)
+        .await?;
+        Ok(())
+    }
+
+    #[tracing::instrument(name = "db.fail_unfinished_deploy_jobs", skip_all)]
+    async fn fail_unfinished_deploy_jobs(&self, reason: String) -> Result<u64, sqlx::Error> {
+        let result = sqlx::query!(
+            r#"UPDATE deploy_jobs 
+            SET step = 'FAILED', error = $1, updated_at = NOW() 
+            WHERE step NOT IN ('REGISTERED', 'FAILED')"#,
+            reason
+        )
+        .execute(&self.db)
+        .await?;
+        Ok(result.rows_affected())
+    }
+
+    #[tracing::instrument(name = "db.create_webhook", skip_all)]
+    async fn create_webhook(&self, webhook: Webhook) -> Result<(), sqlx::Error> {
+        sqlx::query!(
+            r#"INSERT INTO webhooks (id, token_address, url, secret, events, created_by, created_at) 
+            VALUES ($1, $2, $3, $4, $5, $6, $7)"#,
+            webhook.id,
+            webhook.token_address,
+            webhook.url,
+            webhook.secret,
+            &webhook.events,
+            webhook.created_by,
+            webhook.created_at
+        )
+        .execute(&self.db)
+        .await?;
+        Ok(())
+    }
+
+    #[tracing::instrument(name = "db.get_webhook_by_id", skip_all)]
+    async fn get_webhook_by_id(&self, id: String) -> Result<Webhook, sqlx::Error> {
+        let record = sqlx::query!(
+            r#"SELECT id, token_address, url, secret, events, created_by, created_at 
+            FROM webhooks WHERE id = $1"#,
+            id
+        )
+        .fetch_one(&self.db)
+        .await?;
+
+        Ok(Webhook {
+            id: record.id,
+            token_address: record.token_address,
+            url: record.url,
+            secret: record.secret,
+            events: record.events,
+            created_by: record.created_by,
+            created_at: record.created_at,
+        })
+    }
+
+    #[tracing::instrument(name = "db.get_webhooks_by_token", skip_all)]
+    async fn get_webhooks_by_token(
+        &self,
+        token_address: String,
+    ) -> Result<Vec<Webhook>, sqlx::Error> {
+        let records = sqlx::query!(
+            r#"SELECT id, token_address, url, secret, events, created_by, created_at 
+            FROM webhooks WHERE token_address = $1 ORDER BY created_at"#,
+            token_address
+        )
+        .fetch_all(&self.db)
+        .await?;
+
+        Ok(records
+            .into_iter()
+            .map(|record| Webhook {
+                id: record.id,
+                token_address: record.token_address,
+                url: record.url,
+                secret: record.secret,
+                events: record.events,
+                created_by: record.created_by,
+                created_at: record.created_at,
+            })
+            .collect())
+    }
+
+    #[tracing::instrument(name = "db.get_webhooks_by_user", skip_all)]
+    async fn get_webhooks_by_user(&self, user_id: String) -> Result<Vec<Webhook>, sqlx::Error> {
+        let records = sqlx::query!(
+            r#"SELECT id, token_address, url, secret, events, created_by, created_at 
+            FROM webhooks WHERE created_by = $1 AND token_address IS NULL ORDER BY created_at"#,
+            user_id
+        )
+        .fetch_all(&self.db)
+        .await?;
+
+        Ok(records
+            .into_iter()
+            .map(|record| Webhook {
+                id: record.id,
+                token_address: record.token_address,
+                url: record.url,
+                secret: record.secret,
+                events: record.events,
+                created_by: record.created_by,
+                created_at: record.created_at,
+            })
+            .collect())
+    }
+
+    #[tracing::instrument(name = "db.delete_webhook", skip_all)]
+    async fn delete_webhook(&self, id: String) -> Result<(), sqlx::Error> {
+        sqlx::query!("DELETE FROM webhooks WHERE id = $1", id)
+            .execute(&self.db)
+            .await?;
+        Ok(())
+    }
+
+    #[tracing::instrument(name = "db.create_webhook_delivery", skip_all)]
+    async fn create_webhook_delivery(&self, delivery: WebhookDelivery) -> Result<(), sqlx::Error> {
+        sqlx::query!(
+            r#"INSERT INTO webhook_deliveries (id, webhook_id, event_type, payload, status, attempts, next_attempt_at, created_at) 
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8)"#,
+            delivery.id,
+            delivery.webhook_id,
+            delivery.event_type,
+            delivery.payload,
+            delivery.status as _,
+            delivery.attempts,
+            delivery.next_attempt_at,
+            delivery.created_at
+        )
+        .execute(&self.db)
+        .await?;
+        Ok(())
+    }
+
+    #[tracing::instrument(name = "db.claim_due_webhook_deliveries", skip_all)]
+    async fn claim_due_webhook_deliveries(
+        &self,
+        limit: i64,
+        lease: Duration,
+    ) -> Result<Vec<WebhookDelivery>, sqlx::Error> {
+        let records = sqlx::query!(
+            r#"UPDATE webhook_deliveries 
+            SET next_attempt_at = $2, updated_at = NOW() 
+            WHERE id IN (
+                SELECT id FROM webhook_deliveries 
+                WHERE status = 'PENDING' AND next_attempt_at <= NOW() 
+                ORDER BY next_attempt_at LIMIT $1 
+                FOR UPDATE SKIP LOCKED
+            ) 
+            RETURNING id, webhook_id, event_type, payload, status as "status!: DeliveryStatus", attempts, 
+            next_attempt_at, last_status_code, last_error, created_at"#,
+            limit,
+            Utc::now() + lease
+        )
+        .fetch_all(&self.db)
+        .await?;
+
+        Ok(records
+            .into_iter()
+            .map(|record| WebhookDelivery {
+                id: record.id,
+                webhook_id: record.webhook_id,
+                event_type: record.event_type,
+                payload: record.payload,
+                status: record.status,
+                attempts: record.attempts,
+                next_attempt_at: record.next_attempt_at,
+                last_status_code: record.last_status_code,
+                last_error: record.last_error,
+                created_at: record.created_at,
             })
             .collect())
     }
+
+    #[tracing::instrument(name = "db.update_webhook_delivery", skip_all)]
+    async fn update_webhook_delivery(&self, delivery: WebhookDelivery) -> Result<(), sqlx::Error> {
+        sqlx::query!(
+            r#"UPDATE webhook_deliveries 
+            SET status = $2, attempts = $3, next_attempt_at = $4, last_status_code = $5, last_error = $6, updated_at = NOW() 
+            WHERE id = $1"#,
+            delivery.id,
+            delivery.status as _,
+            delivery.attempts,
+            delivery.next_attempt_at,
+            delivery.last_status_code,
+            delivery.last_error
+        )
+        .execute(&self.db)
+        .await?;
+        Ok(())
+    }
+
+    #[tracing::instrument(name = "db.get_webhook_deliveries", skip_all)]
+    async fn get_webhook_deliveries(
+        &self,
+        webhook_id: String,
+        limit: i64,
+    ) -> Result<Vec<WebhookDelivery>, sqlx::Error> {
+        let records = sqlx::query!(
+            r#"SELECT id, webhook_id, event_type, payload, status as "status!: DeliveryStatus", attempts, 
+            next_attempt_at, last_status_code, last_error, created_at 
+            FROM webhook_deliveries WHERE webhook_id = $1 
+            ORDER BY created_at DESC LIMIT $2"#,
+            webhook_id,
+            limit
+        )
+        .fetch_all(&self.db)
+        .await?;
+
+        Ok(records
+            .into_iter()
+            .map(|record| WebhookDelivery {
+                id: record.id,
+                webhook_id: record.webhook_id,
+                event_type: record.event_type,
+                payload: record.payload,
+                status: record.status,
+                attempts: record.attempts,
+                next_attempt_at: record.next_attempt_at,
+                last_status_code: record.last_status_code,
+                last_error: record.last_error,
+                created_at: record.created_at,
+            })
+            .collect())
+    }
+
+    #[tracing::instrument(name = "db.get_token_outflow_since", skip_all)]
+    async fn get_token_outflow_since(
+        &self,
+        token_address: String,
+        since: DateTime<Utc>,
+    ) -> Result<u128, sqlx::Error> {
+        let amount = sqlx::query_scalar!(
+            r#"SELECT COALESCE(SUM(amount::NUMERIC), 0)::TEXT as "amount!" 
+            FROM token_transfers 
+            WHERE token_address = $1 AND kind <> 'DEPLOY' AND created_at > $2"#,
+            token_address,
+            since
+        )
+        .fetch_one(&self.db)
+        .await?;
+        Ok(amount.parse::<u128>().unwrap_or(u128::MAX))
+    }
+
+    #[tracing::instrument(name = "db.get_token_pause", skip_all)]
+    async fn get_token_pause(
+        &self,
+        token_address: String,
+    ) -> Result<Option<TokenPause>, sqlx::Error> {
+        let record = sqlx::query!(
+            r#"SELECT token_address, paused, reason, paused_at, resumed_at, resumed_by 
+            FROM token_pauses WHERE token_address = $1"#,
+            token_address
+        )
+        .fetch_optional(&self.db)
+        .await?;
+
+        Ok(record.map(|record| TokenPause {
+            token_address: record.token_address,
+            paused: record.paused,
+            reason: record.reason,
+            paused_at: record.paused_at,
+            resumed_at: record.resumed_at,
+            resumed_by: record.resumed_by,
+        }))
+    }
+
+    #[tracing::instrument(name = "db.get_paused_tokens", skip_all)]
+    async fn get_paused_tokens(&self) -> Result<Vec<TokenPause>, sqlx::Error> {
+        let records = sqlx::query!(
+            r#"SELECT token_address, paused, reason, paused_at, resumed_at, resumed_by 
+            FROM token_pauses WHERE paused ORDER BY paused_at"#
+        )
+        .fetch_all(&self.db)
+        .await?;
+
+        Ok(records
+            .into_iter()
+            .map(|record| TokenPause {
+                token_address: record.token_address,
+                paused: record.paused,
+                reason: record.reason,
+                paused_at: record.paused_at,
+                resumed_at: record.resumed_at,
+                resumed_by: record.resumed_by,
+            })
+            .collect())
+    }
+
+    #[tracing::instrument(name = "db.pause_token", skip_all)]
+    async fn pause_token(
+        &self,
+        token_address: String,
+        reason: String,
+    ) -> Result<bool, sqlx::Error> {
+        let result = sqlx::query!(
+            r#"INSERT INTO token_pauses (token_address, paused, reason, paused_at) 
+            VALUES ($1, TRUE, $2, NOW()) 
+            ON CONFLICT (token_address) DO UPDATE 
+            SET paused = TRUE, reason = EXCLUDED.reason, paused_at = EXCLUDED.paused_at 
+            WHERE NOT token_pauses.paused"#,
+            token_address,
+            reason
+        )
+        .execute(&self.db)
+        .await?;
+        Ok(result.rows_affected() > 0)
+    }
+
+    #[tracing::instrument(name = "db.resume_token", skip_all)]
+    async fn resume_token(
+        &self,
+        token_address: String,
+        user_id: String,
+    ) -> Result<bool, sqlx::Error> {
+        let result = sqlx::query!(
+            r#"UPDATE token_pauses SET paused = FALSE, resumed_at = NOW(), resumed_by = $2 
+            WHERE token_address = $1 AND paused"#,
+            token_address,
+            user_id
+        )
+        .execute(&self.db)
+        .await?;
+        Ok(result.rows_affected() > 0)
+    }
+
+    #[tracing::instrument(name = "db.claim_idempotency_key", skip_all)]
+    async fn claim_idempotency_key(
+        &self,
+        record: IdempotencyRecord,
+        expired_before: DateTime<Utc>,
+    ) -> Result<bool, sqlx::Error> {
+        let result = sqlx::query!(
+            r#"INSERT INTO idempotency_keys (scope, key, request_hash, response, deploy_job_id, created_at, locked_until) 
+            VALUES ($1, $2, $3, $4, $5, $6, $7) 
+            ON CONFLICT (scope, key) DO UPDATE 
+            SET request_hash = EXCLUDED.request_hash, response = EXCLUDED.response, 
+                deploy_job_id = EXCLUDED.deploy_job_id, created_at = EXCLUDED.created_at, 
+                locked_until = EXCLUDED.locked_until 
+            WHERE idempotency_keys.created_at < $8 
+                OR (idempotency_keys.response IS NULL AND idempotency_keys.deploy_job_id IS NULL 
+                    AND idempotency_keys.locked_until < $6)"#,
+            record.scope,
+            record.key,
+            record.request_hash,
+            record.response,
+            record.deploy_job_id,
+            record.created_at,
+            record.locked_until,
+            expired_before
+        )
+        .execute(&self.db)
+        .await?;
+        Ok(result.rows_affected() > 0)
+    }
+
+    #[tracing::instrument(name = "db.get_idempotency_key", skip_all)]
+    async fn get_idempotency_key(
+        &self,
+        scope: String,
+        key: String,
+    ) -> Result<IdempotencyRecord, sqlx::Error> {
+        let record = sqlx::query!(
+            r#"SELECT scope, key, request_hash, response, deploy_job_id, created_at, locked_until 
+            FROM idempotency_keys WHERE scope = $1 AND key = $2"#,
+            scope,
+            key
+        )
+        .fetch_one(&self.db)
+        .await?;
+
+        Ok(IdempotencyRecord {
+            scope: record.scope,
+            key: record.key,
+            request_hash: record.request_hash,
+            response: record.response,
+            deploy_job_id: record.deploy_job_id,
+            created_at: record.created_at,
+            locked_until: record.locked_until,
+        })
+    }
+
+    #[tracing::instrument(name = "db.complete_idempotency_key", skip_all)]
+    async fn complete_idempotency_key(
+        &self,
+        scope: String,
+        key: String,
+        response: String,
+    ) -> Result<(), sqlx::Error> {
+        sqlx::query!(
+            r#"UPDATE idempotency_keys SET response = $3 WHERE scope = $1 AND key = $2"#,
+            scope,
+            key,
+            response
+        )
+        .execute(&self.db)
+        .await?;
+        Ok(())
+    }
+
+    #[tracing::instrument(name = "db.delete_idempotency_key", skip_all)]
+    async fn delete_idempotency_key(&self, scope: String, key: String) -> Result<(), sqlx::Error> {
+        sqlx::query!(
+            "DELETE FROM idempotency_keys WHERE scope = $1 AND key = $2",
+            scope,
+            key
+        )
+        .execute(&self.db)
+        .await?;
+        Ok(())
+    }
+
+    #[tracing::instrument(name = "db.release_unfinished_idempotency_keys", skip_all)]
+    async fn release_unfinished_idempotency_keys(&self) -> Result<u64, sqlx::Error> {
+        let result = sqlx::query!(
+            "DELETE FROM idempotency_keys 
+            WHERE response IS NULL AND deploy_job_id IS NULL AND locked_until < NOW()"
+        )
+        .execute(&self.db)
+        .await?;
+        Ok(result.rows_affected())
+    }
+    #[tracing::instrument(name = "db.create_auth_session", skip_all)]
+    async fn create_auth_session(&self, session: AuthSession) -> Result<(), sqlx::Error> {
+        sqlx::query!(
+            r#"INSERT INTO auth_sessions (id, user_id, refresh_token_hash, previous_refresh_token_hash, expires_at, revoked_at) 
+            VALUES ($1, $2, $3, $4, $5, $6)"#,
+            session.id,
+            session.user_id,
+            session.refresh_token_hash,
+            session.previous_refresh_token_hash,
+            session.expires_at,
+            session.revoked_at
+        )
+        .execute(&self.db)
+        .await?;
+        Ok(())
+    }
+    #[tracing::instrument(name = "db.get_auth_session", skip_all)]
+    async fn get_auth_session(&self, id: String) -> Result<AuthSession, sqlx::Error> {
+        let record = sqlx::query!(
+            r#"SELECT id, user_id, refresh_token_hash, previous_refresh_token_hash, expires_at, revoked_at 
+            FROM auth_sessions WHERE id = $1"#,
+            id
+        )
+        .fetch_one(&self.db)
+        .await?;
+
+        Ok(AuthSession {
+            id: record.id,
+            user_id: record.user_id,
+            refresh_token_hash: record.refresh_token_hash,
+            previous_refresh_token_hash: record.previous_refresh_token_hash,
+            expires_at: record.expires_at,
+            revoked_at: record.revoked_at,
+        })
+    }
+    #[tracing::instrument(name = "db.get_auth_session_by_refresh_token", skip_all)]
+    async fn get_auth_session_by_refresh_token(
+        &self,
+        refresh_token_hash: String,
+    ) -> Result<AuthSession, sqlx::Error> {
+        let record = sqlx::query!(
+            r#"SELECT id, user_id, refresh_token_hash, previous_refresh_token_hash, expires_at, revoked_at 
+            FROM auth_sessions WHERE refresh_token_hash = $1 OR previous_refresh_token_hash = $1"#,
+            refresh_token_hash
+        )
+        .fetch_one(&self.db)
+        .await?;
+
+        Ok(AuthSession {
+            id: record.id,
+            user_id: record.user_id,
+            refresh_token_hash: record.refresh_token_hash,
+            previous_refresh_token_hash: record.previous_refresh_token_hash,
+            expires_at: record.expires_at,
+            revoked_at: record.revoked_at,
+        })
+    }
+    #[tracing::instrument(name = "db.rotate_refresh_token", skip_all)]
+    async fn rotate_refresh_token(
+        &self,
+        id: String,
+        current_hash: String,
+        new_hash: String,
+    ) -> Result<bool, sqlx::Error> {
+        let result = sqlx::query!(
+            r#"UPDATE auth_sessions SET refresh_token_hash = $3, previous_refresh_token_hash = $2, updated_at = NOW() 
+            WHERE id = $1 AND refresh_token_hash = $2 AND revoked_at IS NULL AND expires_at > NOW()"#,
+            id,
+            current_hash,
+            new_hash
+        )
+        .execute(&self.db)
+        .await?;
+        Ok(result.rows_affected() > 0)
+    }
+    #[tracing::instrument(name = "db.revoke_auth_session", skip_all)]
+    async fn revoke_auth_session(&self, id: String) -> Result<(), sqlx::Error> {
+        sqlx::query!(
+            "UPDATE auth_sessions SET revoked_at = NOW(), updated_at = NOW() WHERE id = $1 AND revoked_at IS NULL",
+            id
+        )
+        .execute(&self.db)
+        .await?;
+        Ok(())
+    }
+    #[tracing::instrument(name = "db.revoke_user_auth_sessions", skip_all)]
+    async fn revoke_user_auth_sessions(&self, user_id: String) -> Result<u64, sqlx::Error> {
+        let result = sqlx::query!(
+            r#"UPDATE auth_sessions SET revoked_at = NOW(), updated_at = NOW() 
+            WHERE user_id = $1 AND revoked_at IS NULL AND expires_at > NOW()"#,
+            user_id
+        )
+        .execute(&self.db)
+        .await?;
+        Ok(result.rows_affected())
+    }
 }
 
 #[cfg(test)]
@@ -365,4 +1758,190 @@ mod tests {
             .await
             .unwrap();
     }
+
+    #[tokio::test]
+    async fn test_anonymous_deploy_job() {
+        let pool = setup_test_db().await;
+        let store = PgStore::new(pool);
+
+        let now = Utc::now();
+        let job = DeployJob {
+            id: uuid::Uuid::new_v4().to_string(),
+            user_id: None,
+            symbol: "TKN".to_string(),
+            step: DeployStep::QUEUED,
+            deploy_tx_hash: None,
+            contract_address: None,
+            allocation_tx_hashes: vec![],
+            error: None,
+            error_code: None,
+            created_at: now,
+            updated_at: now,
+        };
+        store.create_deploy_job(job.clone()).await.unwrap();
+
+        let fetched_job = store.get_deploy_job(job.id.clone()).await.unwrap();
+        assert_eq!(fetched_job.user_id, None);
+        assert_eq!(fetched_job.step, DeployStep::QUEUED);
+
+        // Cleanup
+        sqlx::query!("DELETE FROM deploy_jobs WHERE id = $1", job.id)
+            .execute(&store.db)
+            .await
+            .unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_claim_due_webhook_deliveries() {
+        let pool = setup_test_db().await;
+        let store = PgStore::new(pool);
+
+        let id = uuid::Uuid::new_v4().simple().to_string();
+        let user = User {
+            id: id.clone(),
+            username: id.clone(),
+            avatar_url: String::new(),
+            github_id: id.clone(),
+            access_token: id.clone(),
+            email: None,
+        };
+        store.create_user(user).await.unwrap();
+        let token_address = format!("0x{}", id);
+        store
+            .create_token_entry(Token {
+                created_by: "0xdeployer".to_string(),
+                token_type: TokenType::ERC20,
+                address: token_address.clone(),
+                logo_url: String::new(),
+                chain_id: 10143,
+                symbol: "TKN".to_string(),
+                name: "Token".to_string(),
+                decimals: 18,
+                withdraw_limit: "1000".to_string(),
+                top_up_target: None,
+                logo_thumbnail_url: None,
+                created_by_user_id: Some(id.clone()),
+            })
+            .await
+            .unwrap();
+        store
+            .create_webhook(Webhook {
+                id: id.clone(),
+                token_address: Some(token_address.clone()),
+                url: "https://example.com/hook".to_string(),
+                secret: "secret".to_string(),
+                events: vec!["TOKEN_PAUSED".to_string()],
+                created_by: id.clone(),
+                created_at: Utc::now(),
+            })
+            .await
+            .unwrap();
+        let now = Utc::now();
+        store
+            .create_webhook_delivery(WebhookDelivery {
+                id: id.clone(),
+                webhook_id: id.clone(),
+                event_type: "TOKEN_PAUSED".to_string(),
+                payload: "{}".to_string(),
+                status: DeliveryStatus::PENDING,
+                attempts: 0,
+                next_attempt_at: now - Duration::seconds(1),
+                last_status_code: None,
+                last_error: None,
+                created_at: now,
+            })
+            .await
+            .unwrap();
+
+        // other tests may have due deliveries of their own
+        let claimed = store
+            .claim_due_webhook_deliveries(1000, Duration::minutes(5))
+            .await
+            .unwrap();
+        let delivery = claimed.iter().find(|delivery| delivery.id == id).unwrap();
+        assert!(delivery.next_attempt_at > now + Duration::minutes(4));
+
+        // a second dispatcher does not get it again while it is leased
+        let claimed = store
+            .claim_due_webhook_deliveries(1000, Duration::minutes(5))
+            .await
+            .unwrap();
+        assert!(claimed.iter().all(|delivery| delivery.id != id));
+
+        // Cleanup
+        sqlx::query!("DELETE FROM tokens WHERE address = $1", token_address)
+            .execute(&store.db)
+            .await
+            .unwrap();
+        sqlx::query!("DELETE FROM users WHERE id = $1", id)
+            .execute(&store.db)
+            .await
+            .unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_idempotency_key_lease() {
+        let pool = setup_test_db().await;
+        let store = PgStore::new(pool);
+
+        let scope = format!("withdraw:{}", uuid::Uuid::new_v4());
+        let now = Utc::now();
+        let record = |request_hash: &str, locked_until: DateTime<Utc>| IdempotencyRecord {
+            scope: scope.clone(),
+            key: "key".to_string(),
+            request_hash: request_hash.to_string(),
+            response: None,
+            deploy_job_id: None,
+            created_at: now,
+            locked_until,
+        };
+        let expired_before = now - Duration::hours(24);
+
+        // held by a withdraw that is still running, on this or another instance
+        assert!(store
+            .claim_idempotency_key(record("first", now + Duration::minutes(5)), expired_before)
+            .await
+            .unwrap());
+        assert!(!store
+            .claim_idempotency_key(record("second", now + Duration::minutes(5)), expired_before)
+            .await
+            .unwrap());
+        store.release_unfinished_idempotency_keys().await.unwrap();
+        let held = store
+            .get_idempotency_key(scope.clone(), "key".to_string())
+            .await
+            .unwrap();
+        assert_eq!(held.request_hash, "first");
+
+        // once the lease ran out a retry takes the key over
+        sqlx::query!(
+            "UPDATE idempotency_keys SET locked_until = $2 WHERE scope = $1",
+            scope,
+            now - Duration::seconds(1)
+        )
+        .execute(&store.db)
+        .await
+        .unwrap();
+        assert!(store
+            .claim_idempotency_key(record("second", now + Duration::minutes(5)), expired_before)
+            .await
+            .unwrap());
+
+        // or the release drops it
+        sqlx::query!(
+            "UPDATE idempotency_keys SET locked_until = $2 WHERE scope = $1",
+            scope,
+            now - Duration::seconds(1)
+        )
+        .execute(&store.db)
+        .await
+        .unwrap();
+        store.release_unfinished_idempotency_keys().await.unwrap();
+        assert!(matches!(
+            store
+                .get_idempotency_key(scope.clone(), "key".to_string())
+                .await,
+            Err(sqlx::Error::RowNotFound)
+        ));
+    }
 }
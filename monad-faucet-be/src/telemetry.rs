@@ -0,0 +1,114 @@
+use opentelemetry::KeyValue;
+use opentelemetry_otlp::{SpanExporter, WithExportConfig};
+use opentelemetry_sdk::{runtime, trace::TracerProvider, Resource};
+use tracing::{info_span, Span};
+
+use crate::config::OtlpConfig;
+
+/// Tracer provider exporting spans in batches over OTLP/HTTP to
+/// `{endpoint}/v1/traces`.
+pub fn tracer_provider(
+    config: &OtlpConfig,
+) -> Result<TracerProvider, opentelemetry::trace::TraceError> {
+    let exporter = SpanExporter::builder()
+        .with_http()
+        .with_endpoint(format!(
+            "{}/v1/traces",
+            config.endpoint.trim_end_matches('/')
+        ))
+        .build()?;
+
+    Ok(TracerProvider::builder()
+        .with_batch_exporter(exporter, runtime::Tokio)
+        .with_resource(Resource::new([KeyValue::new(
+            "service.name",
+            config.service_name.clone(),
+        )]))
+        .build())
+}
+
+/// Carries the trace of whatever queued an executor job across the queue.
+///
+/// Created when the job is pushed, it captures the current span and opens a
+/// `queue_wait` span under it, which ends when the worker picks the job up.
+#[derive(Debug)]
+pub struct QueueTrace {
+    parent: Span,
+    wait: Span,
+}
+
+impl QueueTrace {
+    pub fn new(queue: &'static str) -> Self {
+        let parent = Span::current();
+        let wait = info_span!(parent: &parent, "queue_wait", queue);
+        Self { parent, wait }
+    }
+
+    /// Ends the queue wait, returning the span the job should run under.
+    pub fn dequeue(self) -> Span {
+        drop(self.wait);
+        self.parent
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use axum::{body::Bytes, routing::post, Router};
+    use opentelemetry::trace::TracerProvider as _;
+    use tokio::{net::TcpListener, sync::mpsc};
+    use tracing_subscriber::prelude::*;
+
+    use super::*;
+
+    fn contains(haystack: &[u8], needle: &str) -> bool {
+        haystack
+            .windows(needle.len())
+            .any(|window| window == needle.as_bytes())
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_export_to_collector() {
+        // stand-in for an OTLP collector, handing over every export it gets
+        let (sender, mut exports) = mpsc::unbounded_channel::<Bytes>();
+        let collector = Router::new().route(
+            "/v1/traces",
+            post(move |body: Bytes| async move {
+                let _ = sender.send(body);
+            }),
+        );
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let endpoint = format!("http://{}", listener.local_addr().unwrap());
+        tokio::spawn(async move { axum::serve(listener, collector).await });
+
+        let provider = tracer_provider(&OtlpConfig {
+            endpoint,
+            service_name: "faucet-test".to_string(),
+        })
+        .unwrap();
+        let subscriber = tracing_subscriber::registry()
+            .with(tracing_opentelemetry::layer().with_tracer(provider.tracer("test")));
+
+        tracing::subscriber::with_default(subscriber, || {
+            let trace = info_span!("request").in_scope(|| QueueTrace::new("withdraw"));
+            let parent = trace.dequeue();
+            info_span!(parent: &parent, "withdraw").in_scope(|| {
+                info_span!("rpc.balance_of").in_scope(|| {});
+            });
+        });
+
+        tokio::task::spawn_blocking(move || provider.force_flush())
+            .await
+            .unwrap();
+        let export = tokio::time::timeout(Duration::from_secs(10), exports.recv())
+            .await
+            .expect("Collector received no spans")
+            .unwrap();
+
+        assert!(contains(&export, "faucet-test"));
+        for name in ["request", "queue_wait", "withdraw", "rpc.balance_of"] {
+            assert!(contains(&export, name), "{} span was not exported", name);
+        }
+    }
+}
@@ -1,92 +1,73 @@
 use crate::{
-    config::Config, constants::faucet, db, executor::Executor, handlers::middleware::AuthUser,
-    store::PgStore, AppState,
+    config::{Config, Tuning, TuningConfig},
+    db,
+    executor::Executor,
+    handlers::middleware::AuthUser,
+    signer::Signers,
+    store::PgStore,
+    AppState,
 };
 use dotenv::dotenv;
-use serde::{Deserialize, Serialize};
-
-use tracing::error;
 
 pub async fn magnify_faucet_drip(
-    orderbook_url: String,
+    tuning: &TuningConfig,
+    _orderbook_url: Option<String>,
     user: AuthUser,
-    wallet_address: String,
+    _wallet_address: String,
 ) -> u8 {
-    let is_github_authenticated = user.is_github_authenticated;
-
-
-    match (is_github_authenticated) {
-        (true) => faucet::MAGNIFICATION_GITHUB_AUTH, 
-        (false) => faucet::MAGNIFICATION_NO_AUTH,
+    match user.is_github_authenticated {
+        true => tuning.magnification_github_auth,
+        false => tuning.magnification_no_auth,
     }
 }
 
-pub async fn setup() -> AppState {
+/// Loads the config and builds the app state from it. Errors are returned
+/// rather than ending the process, `main` decides how to report them.
+pub async fn setup() -> eyre::Result<AppState> {
     dotenv().ok();
-    let config = Config::from_env();
+    let config = Config::load().map_err(|errors| eyre::eyre!("{}", errors))?;
 
-    let db_pool = db::init_db(&config.db_url)
+    let db_pool = db::init_db(&config.db.url)
         .await
-        .expect("Failed to connect to DB");
+        .map_err(|e| eyre::eyre!("Failed to connect to DB: {}", e))?;
 
     let store = PgStore::new(db_pool);
-    let executor = Executor::new(store.clone());
-    let state = AppState {
+    let signers = Signers::from_config(&config.signers.faucet, &config.signers.deployer)?;
+
+    let tuning = Tuning::new(config.tuning.clone());
+    let executor = Executor::new(store.clone(), config.clone(), tuning.clone(), signers);
+    Ok(AppState {
         store,
         config: config.clone(),
+        tuning,
         executor,
-    };
-    state
+    })
 }
 
 #[cfg(test)]
 mod tests {
-    use httpmock::{Method::GET, MockServer};
-
-    use crate::{
-        constants::faucet,
-        handlers::middleware::AuthUser,
-        utils::{magnify_faucet_drip, OrderResponse, Status},
-    };
+    use crate::{config::TuningConfig, handlers::middleware::AuthUser, utils::magnify_faucet_drip};
 
     #[tokio::test]
     async fn test_magnify_faucet_drip() {
-        let orderbook_server = MockServer::start();
-
         let wallet = "0x123";
+        let tuning = TuningConfig::default();
 
         let combinations = vec![
-            (
-                true,
-                true,
-                faucet::MAGNIFICATION_GITHUB_AUTH + faucet::MAGNIFICATION_GARDEN_USER,
-            ),
-            (true, false, faucet::MAGNIFICATION_GITHUB_AUTH),
-            (false, true, faucet::MAGNIFICATION_GARDEN_USER),
-            (false, false, faucet::MAGNIFICATION_NO_AUTH),
+            (true, tuning.magnification_github_auth),
+            (false, tuning.magnification_no_auth),
         ];
 
-        for (github_auth, garden_user, expected) in combinations {
-            let mut response_mock = orderbook_server.mock(|when, then| {
-                when.method(GET).path(format!("/user/{}/count", wallet));
-                then.status(200).json_body_obj(&OrderResponse {
-                    status: Status::Ok,
-                    result: if garden_user { 1 } else { 0 },
-                });
-            });
-
+        for (github_auth, expected) in combinations {
             let user = AuthUser {
                 is_github_authenticated: github_auth,
                 user_id: "1".to_string(),
+                api_key_id: None,
             };
 
-            let maginification =
-                magnify_faucet_drip(orderbook_server.url(""), user, wallet.to_string()).await;
+            let maginification = magnify_faucet_drip(&tuning, None, user, wallet.to_string()).await;
 
             assert_eq!(expected, maginification);
-
-            response_mock.assert_hits(1);
-            response_mock.delete();
         }
     }
 }
@@ -0,0 +1,496 @@
+use std::{
+    net::{IpAddr, SocketAddr},
+    time::Duration,
+};
+
+use chrono::Utc;
+use hmac::{Hmac, Mac};
+use sha2::Sha256;
+use tokio::sync::broadcast::error::RecvError;
+use tracing::{error, warn};
+use uuid::Uuid;
+
+use crate::{
+    constants::webhooks,
+    events::{EventBus, FaucetEvent},
+    store::{DeliveryStatus, PgStore, Store, Webhook, WebhookDelivery},
+};
+
+/// Events token owners can subscribe a token's webhook to
+pub const WEBHOOK_EVENTS: [&str; 3] = ["DRIP_CONFIRMED", "TOKEN_PAUSED", "TOKEN_RESUMED"];
+
+/// Events a user can subscribe a webhook without a token to. A token's
+/// webhooks only exist once it is deployed, so deploys are reported to the
+/// webhooks of the user who deployed it.
+pub const USER_WEBHOOK_EVENTS: [&str; 1] = ["TOKEN_DEPLOYED"];
+
+pub const SIGNATURE_HEADER: &str = "X-Faucet-Signature";
+pub const TIMESTAMP_HEADER: &str = "X-Faucet-Timestamp";
+pub const EVENT_HEADER: &str = "X-Faucet-Event";
+pub const DELIVERY_HEADER: &str = "X-Faucet-Delivery";
+
+/// Signs `{timestamp}.{body}` with the webhook secret. Receivers recompute it
+/// to check the payload came from the faucet and reject stale timestamps to
+/// prevent replays.
+pub fn sign_payload(secret: &str, timestamp: i64, body: &str) -> String {
+    let mut mac =
+        Hmac::<Sha256>::new_from_slice(secret.as_bytes()).expect("HMAC accepts keys of any size");
+    mac.update(format!("{}.{}", timestamp, body).as_bytes());
+    format!("sha256={}", alloy::hex::encode(mac.finalize().into_bytes()))
+}
+
+/// Whether webhooks may call `ip`. Loopback, private, link-local, unique
+/// local and other non routable addresses are refused, so a webhook cannot
+/// reach services on the faucet's own network.
+pub fn is_public_ip(ip: IpAddr) -> bool {
+    match ip {
+        IpAddr::V4(ip) => {
+            let [a, b, ..] = ip.octets();
+            !(ip.is_loopback()
+                || ip.is_private()
+                || ip.is_link_local()
+                || ip.is_unspecified()
+                || ip.is_broadcast()
+                || ip.is_documentation()
+                || ip.is_multicast()
+                // carrier-grade NAT, 100.64.0.0/10
+                || (a == 100 && (b & 0xc0) == 64))
+        }
+        IpAddr::V6(ip) => match ip.to_ipv4_mapped() {
+            Some(ip) => is_public_ip(IpAddr::V4(ip)),
+            None => {
+                let first = ip.segments()[0];
+                !(ip.is_loopback()
+                    || ip.is_unspecified()
+                    || ip.is_multicast()
+                    // unique local, fc00::/7
+                    || (first & 0xfe00) == 0xfc00
+                    // link-local, fe80::/10
+                    || (first & 0xffc0) == 0xfe80)
+            }
+        },
+    }
+}
+
+/// Resolves the host of a webhook URL and checks every address it resolves to
+/// is public
+pub async fn check_url(url: &str) -> Result<(), String> {
+    let url = reqwest::Url::parse(url).map_err(|e| e.to_string())?;
+    let port = url.port_or_known_default().unwrap_or(443);
+    let Some(host) = url.host_str() else {
+        return Err("URL has no host".to_string());
+    };
+    let addrs: Vec<SocketAddr> = match host.trim_matches(['[', ']']).parse::<IpAddr>() {
+        Ok(ip) => vec![SocketAddr::new(ip, port)],
+        Err(_) => tokio::net::lookup_host((host, port))
+            .await
+            .map_err(|e| format!("Failed to resolve {}: {}", host, e))?
+            .collect(),
+    };
+    if addrs.is_empty() || addrs.iter().any(|addr| !is_public_ip(addr.ip())) {
+        return Err("URL does not resolve to a public address".to_string());
+    }
+    Ok(())
+}
+
+/// Resolver of the delivery client, drops non public addresses so a host
+/// that changed its DNS records after the webhook was created cannot point
+/// deliveries at the faucet's network
+struct PublicResolver;
+
+impl reqwest::dns::Resolve for PublicResolver {
+    fn resolve(&self, name: reqwest::dns::Name) -> reqwest::dns::Resolving {
+        Box::pin(async move {
+            let addrs: Vec<SocketAddr> = tokio::net::lookup_host((name.as_str(), 0))
+                .await?
+                .filter(|addr| is_public_ip(addr.ip()))
+                .collect();
+            if addrs.is_empty() {
+                return Err(
+                    format!("{} does not resolve to a public address", name.as_str()).into(),
+                );
+            }
+            Ok(Box::new(addrs.into_iter()) as reqwest::dns::Addrs)
+        })
+    }
+}
+
+/// Delay before retrying a delivery that failed `attempts` times.
+pub fn backoff(attempts: i32) -> Duration {
+    let exponent = attempts.saturating_sub(1).clamp(0, 30) as u32;
+    let secs = webhooks::BACKOFF_BASE_SECS.saturating_mul(2u64.pow(exponent));
+    Duration::from_secs(secs.min(webhooks::MAX_BACKOFF_SECS))
+}
+
+/// Turns bus events into webhook deliveries and sends them from the outbox.
+///
+/// Deliveries are written to Postgres before any request is made, so they
+/// survive restarts and are retried with exponential backoff until they
+/// succeed or run out of attempts.
+#[derive(Debug, Clone)]
+pub struct WebhookDispatcher {
+    store: PgStore,
+    events: EventBus,
+    http_client: reqwest::Client,
+}
+
+impl WebhookDispatcher {
+    pub fn new(store: PgStore, events: EventBus) -> Self {
+        let http_client = reqwest::Client::builder()
+            .timeout(Duration::from_secs(webhooks::REQUEST_TIMEOUT_SECS))
+            .user_agent("Monad-Faucet-Webhooks/1.0")
+            .dns_resolver(std::sync::Arc::new(PublicResolver))
+            // a redirect could lead to an address literal, which skips the resolver
+            .redirect(reqwest::redirect::Policy::none())
+            .build()
+            .expect("Failed to create HTTP client");
+
+        Self {
+            store,
+            events,
+            http_client,
+        }
+    }
+
+    pub async fn run(&self) {
+        let _ = tokio::join!(self.enqueue_events(), self.deliver());
+    }
+
+    async fn enqueue_events(&self) {
+        let mut receiver = self.events.subscribe();
+        loop {
+            let event = match receiver.recv().await {
+                Ok(event) => event,
+                Err(RecvError::Lagged(skipped)) => {
+                    error!("Webhook dispatcher missed {} events", skipped);
+                    continue;
+                }
+                Err(RecvError::Closed) => return,
+            };
+
+            let webhooks = match &event {
+                FaucetEvent::DripConfirmed {
+                    token_address,
+                    success: true,
+                    ..
+                }
+                | FaucetEvent::TokenPaused { token_address, .. }
+                | FaucetEvent::TokenResumed { token_address } => {
+                    self.store
+                        .get_webhooks_by_token(token_address.clone())
+                        .await
+                }
+                FaucetEvent::TokenDeployed { token_address, .. } => {
+                    self.deployer_webhooks(token_address.clone()).await
+                }
+                _ => continue,
+            };
+            match webhooks {
+                Ok(webhooks) => self.enqueue(&event, webhooks).await,
+                Err(e) => error!("Failed to fetch webhooks {}", e),
+            }
+        }
+    }
+
+    /// Webhooks of the user who deployed a token, none for anonymous deploys
+    async fn deployer_webhooks(&self, token_address: String) -> Result<Vec<Webhook>, sqlx::Error> {
+        let token = self.store.get_token_by_address(token_address).await?;
+        match token.created_by_user_id {
+            Some(user_id) => self.store.get_webhooks_by_user(user_id).await,
+            None => Ok(Vec::new()),
+        }
+    }
+
+    /// Queues a delivery of `event` to each of `webhooks` subscribed to it
+    async fn enqueue(&self, event: &FaucetEvent, webhooks: Vec<Webhook>) {
+        let event_type = event.event_type();
+        for webhook in webhooks
+            .into_iter()
+            .filter(|webhook| webhook.events.iter().any(|e| e == event_type))
+        {
+            let id = Uuid::new_v4().to_string();
+            let now = Utc::now();
+            let payload = serde_json::json!({
+                "id": id,
+                "type": event_type,
+                "created_at": now,
+                "data": event,
+            });
+
+            let delivery = WebhookDelivery {
+                id,
+                webhook_id: webhook.id,
+                event_type: event_type.to_string(),
+                payload: payload.to_string(),
+                status: DeliveryStatus::PENDING,
+                attempts: 0,
+                next_attempt_at: now,
+                last_status_code: None,
+                last_error: None,
+                created_at: now,
+            };
+            if let Err(e) = self.store.create_webhook_delivery(delivery).await {
+                error!("Failed to queue webhook delivery {}", e);
+            }
+        }
+    }
+
+    async fn deliver(&self) {
+        loop {
+            match self
+                .store
+                .claim_due_webhook_deliveries(
+                    webhooks::DELIVERY_BATCH_SIZE,
+                    chrono::Duration::seconds(webhooks::CLAIM_LEASE_SECS),
+                )
+                .await
+            {
+                Ok(deliveries) => {
+                    for delivery in deliveries {
+                        self.attempt(delivery).await;
+                    }
+                }
+                Err(e) => error!("Failed to fetch due webhook deliveries {}", e),
+            }
+
+            tokio::time::sleep(Duration::from_secs(webhooks::POLL_INTERVAL_SECS)).await;
+        }
+    }
+
+    async fn attempt(&self, mut delivery: WebhookDelivery) {
+        let webhook = match self
+            .store
+            .get_webhook_by_id(delivery.webhook_id.clone())
+            .await
+        {
+            Ok(webhook) => webhook,
+            Err(e) => {
+                error!("Failed to fetch webhook {} {}", delivery.webhook_id, e);
+                return;
+            }
+        };
+
+        // also catches address literals, which the resolver never sees
+        if let Err(e) = check_url(&webhook.url).await {
+            warn!(
+                "Refusing webhook delivery {} to {}: {}",
+                delivery.id, webhook.url, e
+            );
+            delivery.attempts += 1;
+            delivery.last_status_code = None;
+            delivery.last_error = Some(e);
+            self.finish_attempt(delivery, &webhook.url).await;
+            return;
+        }
+
+        let timestamp = Utc::now().timestamp();
+        let result = self
+            .http_client
+            .post(&webhook.url)
+            .header("Content-Type", "application/json")
+            .header(EVENT_HEADER, &delivery.event_type)
+            .header(DELIVERY_HEADER, &delivery.id)
+            .header(TIMESTAMP_HEADER, timestamp.to_string())
+            .header(
+                SIGNATURE_HEADER,
+                sign_payload(&webhook.secret, timestamp, &delivery.payload),
+            )
+            .body(delivery.payload.clone())
+            .send()
+            .await;
+
+        delivery.attempts += 1;
+        match result {
+            Ok(response) if response.status().is_success() => {
+                delivery.status = DeliveryStatus::DELIVERED;
+                delivery.last_status_code = Some(response.status().as_u16() as i32);
+                delivery.last_error = None;
+            }
+            Ok(response) => {
+                delivery.last_status_code = Some(response.status().as_u16() as i32);
+                delivery.last_error = Some(format!("HTTP {}", response.status()));
+            }
+            Err(e) => {
+                delivery.last_status_code = None;
+                delivery.last_error = Some(e.to_string());
+            }
+        }
+        self.finish_attempt(delivery, &webhook.url).await;
+    }
+
+    /// Schedules the retry of a delivery that is still pending, or gives up
+    /// on it, and saves it
+    async fn finish_attempt(&self, mut delivery: WebhookDelivery, url: &str) {
+        if delivery.status == DeliveryStatus::PENDING {
+            if delivery.attempts >= webhooks::MAX_ATTEMPTS {
+                warn!(
+                    "Giving up on webhook delivery {} to {} after {} attempts",
+                    delivery.id, url, delivery.attempts
+                );
+                delivery.status = DeliveryStatus::FAILED;
+            } else {
+                delivery.next_attempt_at = Utc::now()
+                    + chrono::Duration::from_std(backoff(delivery.attempts)).unwrap_or_default();
+            }
+        }
+
+        if let Err(e) = self.store.update_webhook_delivery(delivery).await {
+            error!("Failed to update webhook delivery {}", e);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sign_payload() {
+        let signature = sign_payload("secret", 1700000000, r#"{"type":"TOKEN_PAUSED"}"#);
+        assert!(signature.starts_with("sha256="));
+        assert_eq!(signature.len(), "sha256=".len() + 64);
+
+        // what a receiver does with the headers and raw body
+        let mut mac = Hmac::<Sha256>::new_from_slice(b"secret").unwrap();
+        mac.update(br#"1700000000.{"type":"TOKEN_PAUSED"}"#);
+        let expected = alloy::hex::decode(signature.trim_start_matches("sha256=")).unwrap();
+        assert!(mac.verify_slice(&expected).is_ok());
+
+        assert_ne!(
+            signature,
+            sign_payload("secret", 1700000001, r#"{"type":"TOKEN_PAUSED"}"#)
+        );
+    }
+
+    #[tokio::test]
+    async fn test_check_url() {
+        assert!(is_public_ip("1.1.1.1".parse().unwrap()));
+        assert!(is_public_ip("2606:4700:4700::1111".parse().unwrap()));
+        for ip in [
+            "127.0.0.1",
+            "10.0.0.1",
+            "172.16.0.1",
+            "192.168.1.1",
+            "169.254.169.254",
+            "100.64.0.1",
+            "0.0.0.0",
+            "::1",
+            "fc00::1",
+            "fd12:3456::1",
+            "fe80::1",
+            "::ffff:127.0.0.1",
+        ] {
+            assert!(!is_public_ip(ip.parse().unwrap()), "{}", ip);
+        }
+
+        assert!(check_url("https://1.1.1.1/hook").await.is_ok());
+        assert!(check_url("https://127.0.0.1/hook").await.is_err());
+        assert!(check_url("https://[::1]:8443/hook").await.is_err());
+        assert!(check_url("https://169.254.169.254/latest").await.is_err());
+        assert!(check_url("https://localhost/hook").await.is_err());
+    }
+
+    #[test]
+    fn test_backoff() {
+        assert_eq!(backoff(1), Duration::from_secs(webhooks::BACKOFF_BASE_SECS));
+        assert_eq!(
+            backoff(2),
+            Duration::from_secs(webhooks::BACKOFF_BASE_SECS * 2)
+        );
+        assert_eq!(
+            backoff(4),
+            Duration::from_secs(webhooks::BACKOFF_BASE_SECS * 8)
+        );
+        assert_eq!(backoff(50), Duration::from_secs(webhooks::MAX_BACKOFF_SECS));
+    }
+
+    #[tokio::test]
+    async fn test_deploys_reach_deployer_webhooks() {
+        let state = crate::utils::setup().await.unwrap();
+        let store = state.store.clone();
+        let dispatcher = WebhookDispatcher::new(store.clone(), EventBus::new());
+        let id = Uuid::new_v4().to_string();
+        let token_address = format!("0x{}", Uuid::new_v4().simple());
+        store
+            .create_user(crate::store::User {
+                id: id.clone(),
+                username: id.clone(),
+                avatar_url: String::new(),
+                github_id: id.clone(),
+                access_token: id.clone(),
+                email: None,
+            })
+            .await
+            .unwrap();
+        store
+            .create_token_entry(crate::store::Token {
+                created_by: "0xdeployer".to_string(),
+                token_type: crate::store::TokenType::ERC20,
+                address: token_address.clone(),
+                logo_url: String::new(),
+                chain_id: 10143,
+                symbol: "TKN".to_string(),
+                name: "Token".to_string(),
+                decimals: 18,
+                withdraw_limit: "1000".to_string(),
+                top_up_target: None,
+                logo_thumbnail_url: None,
+                created_by_user_id: Some(id.clone()),
+            })
+            .await
+            .unwrap();
+        let webhook = |token_address: Option<String>, event: &str| Webhook {
+            id: Uuid::new_v4().to_string(),
+            token_address,
+            url: "https://example.com/hook".to_string(),
+            secret: "secret".to_string(),
+            events: vec![event.to_string()],
+            created_by: id.clone(),
+            created_at: Utc::now(),
+        };
+        let user_webhook = webhook(None, "TOKEN_DEPLOYED");
+        let token_webhook = webhook(Some(token_address.clone()), "TOKEN_PAUSED");
+        store.create_webhook(user_webhook.clone()).await.unwrap();
+        store.create_webhook(token_webhook.clone()).await.unwrap();
+
+        // only the deployer's own webhooks hear about the deploy
+        let webhooks = dispatcher
+            .deployer_webhooks(token_address.clone())
+            .await
+            .unwrap();
+        assert_eq!(
+            webhooks.iter().map(|w| w.id.as_str()).collect::<Vec<_>>(),
+            [user_webhook.id.as_str()]
+        );
+        dispatcher
+            .enqueue(
+                &FaucetEvent::TokenDeployed {
+                    token_address: token_address.clone(),
+                    symbol: "TKN".to_string(),
+                    name: "Token".to_string(),
+                },
+                webhooks,
+            )
+            .await;
+        let deliveries = store
+            .get_webhook_deliveries(user_webhook.id.clone(), 10)
+            .await
+            .unwrap();
+        assert_eq!(deliveries.len(), 1);
+        assert_eq!(deliveries[0].event_type, "TOKEN_DEPLOYED");
+
+        let db = crate::db::init_db(&state.config.db.url).await.unwrap();
+        sqlx::query!("DELETE FROM webhooks WHERE created_by = $1", id)
+            .execute(&db)
+            .await
+            .unwrap();
+        sqlx::query!("DELETE FROM tokens WHERE address = $1", token_address)
+            .execute(&db)
+            .await
+            .unwrap();
+        sqlx::query!("DELETE FROM users WHERE id = $1", id)
+            .execute(&db)
+            .await
+            .unwrap();
+    }
+}
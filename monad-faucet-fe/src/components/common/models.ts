@@ -10,6 +10,7 @@ export type TransferResponse = {
   error?: {
     message: string;
     next_access?: string;
+    code?: string;
   };
   data?: { tx_hash: string; amount: string; magnification: number };
 };
@@ -17,7 +18,26 @@ export type TransferResponse = {
 export type DeployResponse = {
   status?: ResponseStatus;
   error?: string;
-  data?: { contract_address: string };
+  data?: { job_id: string };
+};
+
+export type DeployStep =
+  | "QUEUED"
+  | "UPLOADING_LOGO"
+  | "DEPLOYING"
+  | "ALLOCATING_SUPPLY"
+  | "REGISTERED"
+  | "FAILED";
+
+export type DeployJob = {
+  id: string;
+  symbol: string;
+  step: DeployStep;
+  deploy_tx_hash?: string;
+  contract_address?: string;
+  allocation_tx_hashes: string[];
+  error?: string;
+  error_code?: string;
 };
 
 export type { User };
@@ -1,9 +1,23 @@
 import { FC, useState, useMemo } from "react";
 import { Button } from "../ui/Button";
 import { InputBox } from "../ui/InputBox";
-import { deployToken, isValidEVMAddress } from "../../utils/utils";
+import {
+  deployToken,
+  followDeployJob,
+  isValidEVMAddress,
+} from "../../utils/utils";
+import { DeployStep } from "../common/models";
 import { useTokenListStore } from "../../store/TokenListStore";
 
+const STEP_MESSAGES: Record<DeployStep, string> = {
+  QUEUED: "Waiting for earlier deployments...",
+  UPLOADING_LOGO: "Uploading logo...",
+  DEPLOYING: "Deploying contract...",
+  ALLOCATING_SUPPLY: "Allocating supply...",
+  REGISTERED: "Token deployed successfully",
+  FAILED: "Deployment Error",
+};
+
 type NewTokenComponentProps = {
   onClose: () => void;
 };
@@ -84,9 +98,21 @@ export const NewTokenComponent: FC<NewTokenComponentProps> = ({ onClose }) => {
     }
 
     if (response.data) {
-      setMessage(
-        "Token deployed successfully: " + response.data.contract_address
-      );
+      try {
+        const job = await followDeployJob(response.data.job_id, (progress) =>
+          setMessage(STEP_MESSAGES[progress.step])
+        );
+        setMessage(
+          job.step === "REGISTERED"
+            ? STEP_MESSAGES.REGISTERED + ": " + job.contract_address
+            : STEP_MESSAGES.FAILED + ": " + job.error
+        );
+      } catch (error) {
+        setMessage(
+          "Deployment Error: " +
+            (error instanceof Error ? error.message : String(error))
+        );
+      }
       setIsLoading(false);
     }
   };
@@ -1,3 +1,5 @@
+import type { components, paths } from "../types/api";
+
 const REQUIRED_ENV_VARS = {
   BACKEND_URL: import.meta.env.VITE_BACKEND_URL,
   CAPTCHA_SITE_ID: import.meta.env.VITE_SITE_ID,
@@ -5,6 +7,14 @@ const REQUIRED_ENV_VARS = {
   EXPLORER: import.meta.env.VITE_EXPLORER_URL,
 } as const;
 
+/** Schemas of the backend's answers and requests, see `yarn gen:api` */
+export type ApiSchemas = components["schemas"];
+
+/** A path the backend serves, so a renamed or removed route fails to build */
+type ApiPath = keyof paths;
+
+const url = (path: ApiPath) => REQUIRED_ENV_VARS.BACKEND_URL + path;
+
 export const API = () => {
   Object.entries(REQUIRED_ENV_VARS).forEach(([key, value]) => {
     if (!value) throw new Error(`Missing ${key} in env`);
@@ -13,15 +23,20 @@ export const API = () => {
   return {
     site_Id: REQUIRED_ENV_VARS.CAPTCHA_SITE_ID,
     client_Id: REQUIRED_ENV_VARS.GITHUB_CLIENT_ID,
-    captcha: REQUIRED_ENV_VARS.BACKEND_URL + "/verify-turnstile-captcha",
-    test_auth: REQUIRED_ENV_VARS.BACKEND_URL + "/test_auth",
-    withdrawToken: REQUIRED_ENV_VARS.BACKEND_URL + "/withdraw",
-    deployToken: REQUIRED_ENV_VARS.BACKEND_URL + "/deploy/erc20",
-    user: REQUIRED_ENV_VARS.BACKEND_URL + "/user",
-    upload: REQUIRED_ENV_VARS.BACKEND_URL + "/upload",
-    tokens: REQUIRED_ENV_VARS.BACKEND_URL + "/tokens",
+    captcha: url("/v1/verify-turnstile-captcha"),
+    test_auth: url("/v1/test_auth"),
+    withdrawToken: url("/v1/withdraw"),
+    deployToken: url("/v1/deploy/erc20"),
+    deployJobEvents: (jobId: string) =>
+      url("/v1/deploy/jobs/{id}/events").replace(
+        "{id}",
+        encodeURIComponent(jobId)
+      ),
+    user: url("/v1/user"),
+    tokens: url("/v1/tokens"),
     explorer: REQUIRED_ENV_VARS.EXPLORER,
-    auth: (code: string) =>
-      REQUIRED_ENV_VARS.BACKEND_URL + `/auth?code=${code}`,
+    githubStart: url("/v1/auth/github/start"),
+    // POST { code, state, state_token }, kept out of the URL
+    auth: url("/v1/auth"),
   };
 };
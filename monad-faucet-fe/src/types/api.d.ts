@@ -0,0 +1,3020 @@
+/**
+ * Types of the backend's /openapi.json, regenerate with `yarn gen:api`.
+ * Do not make direct changes to the file.
+ */
+
+export interface paths {
+    "/health": {
+        parameters: {
+            query?: never;
+            header?: never;
+            path?: never;
+            cookie?: never;
+        };
+        get: operations["health_check"];
+        put?: never;
+        post?: never;
+        delete?: never;
+        options?: never;
+        head?: never;
+        patch?: never;
+        trace?: never;
+    };
+    "/v1/admin/pauses": {
+        parameters: {
+            query?: never;
+            header?: never;
+            path?: never;
+            cookie?: never;
+        };
+        /** @description Tokens whose drips are paused by the outflow circuit breaker */
+        get: operations["paused_tokens"];
+        put?: never;
+        post?: never;
+        delete?: never;
+        options?: never;
+        head?: never;
+        patch?: never;
+        trace?: never;
+    };
+    "/v1/admin/queues": {
+        parameters: {
+            query?: never;
+            header?: never;
+            path?: never;
+            cookie?: never;
+        };
+        /** @description Executor queues with how many jobs wait in each and whether it is paused */
+        get: operations["queues"];
+        put?: never;
+        post?: never;
+        delete?: never;
+        options?: never;
+        head?: never;
+        patch?: never;
+        trace?: never;
+    };
+    "/v1/admin/queues/{queue}/pause": {
+        parameters: {
+            query?: never;
+            header?: never;
+            path?: never;
+            cookie?: never;
+        };
+        get?: never;
+        put?: never;
+        /**
+         * @description Stops the executor from picking up jobs of a queue. Requests keep being
+         * queued, and wait for the queue to be resumed or time out.
+         */
+        post: operations["pause_queue"];
+        delete?: never;
+        options?: never;
+        head?: never;
+        patch?: never;
+        trace?: never;
+    };
+    "/v1/admin/queues/{queue}/resume": {
+        parameters: {
+            query?: never;
+            header?: never;
+            path?: never;
+            cookie?: never;
+        };
+        get?: never;
+        put?: never;
+        post: operations["resume_queue"];
+        delete?: never;
+        options?: never;
+        head?: never;
+        patch?: never;
+        trace?: never;
+    };
+    "/v1/admin/reload": {
+        parameters: {
+            query?: never;
+            header?: never;
+            path?: never;
+            cookie?: never;
+        };
+        get?: never;
+        put?: never;
+        /**
+         * @description Reloads the config file and environment, swapping in the new tuning. Jobs
+         * already running finish with the values they started with.
+         */
+        post: operations["reload_config"];
+        delete?: never;
+        options?: never;
+        head?: never;
+        patch?: never;
+        trace?: never;
+    };
+    "/v1/admin/tokens/{address}/resume": {
+        parameters: {
+            query?: never;
+            header?: never;
+            path?: never;
+            cookie?: never;
+        };
+        get?: never;
+        put?: never;
+        /**
+         * @description Resumes drips of a token paused by the circuit breaker. Only outflow sent
+         * from now on counts towards the caps, so the token is not paused again
+         * straight away.
+         */
+        post: operations["resume_token"];
+        delete?: never;
+        options?: never;
+        head?: never;
+        patch?: never;
+        trace?: never;
+    };
+    "/v1/api-keys": {
+        parameters: {
+            query?: never;
+            header?: never;
+            path?: never;
+            cookie?: never;
+        };
+        /** @description Lists the API keys owned by the authenticated user. */
+        get: operations["api_keys"];
+        put?: never;
+        /**
+         * @description Issues a new API key for `owner_user_id`. Only admins may create keys since
+         * the quotas are part of the request.
+         */
+        post: operations["create_api_key"];
+        delete?: never;
+        options?: never;
+        head?: never;
+        patch?: never;
+        trace?: never;
+    };
+    "/v1/api-keys/{id}": {
+        parameters: {
+            query?: never;
+            header?: never;
+            path?: never;
+            cookie?: never;
+        };
+        get?: never;
+        put?: never;
+        post?: never;
+        /** @description Revokes an API key. Allowed for the key's owner and for admins. */
+        delete: operations["revoke_api_key"];
+        options?: never;
+        head?: never;
+        patch?: never;
+        trace?: never;
+    };
+    "/v1/auth": {
+        parameters: {
+            query?: never;
+            header?: never;
+            path?: never;
+            cookie?: never;
+        };
+        get?: never;
+        put?: never;
+        /**
+         * @description Signs in with the code of a GitHub OAuth redirect, which must carry the
+         * state of a login begun on `GET /auth/github/start`
+         */
+        post: operations["auth"];
+        delete?: never;
+        options?: never;
+        head?: never;
+        patch?: never;
+        trace?: never;
+    };
+    "/v1/auth/github/start": {
+        parameters: {
+            query?: never;
+            header?: never;
+            path?: never;
+            cookie?: never;
+        };
+        /**
+         * @description Starts a GitHub login, the user is sent to `authorize_url` and comes
+         * back to the redirect URI with a code for `POST /auth`
+         */
+        get: operations["github_start"];
+        put?: never;
+        post?: never;
+        delete?: never;
+        options?: never;
+        head?: never;
+        patch?: never;
+        trace?: never;
+    };
+    "/v1/auth/logout": {
+        parameters: {
+            query?: never;
+            header?: never;
+            path?: never;
+            cookie?: never;
+        };
+        get?: never;
+        put?: never;
+        /**
+         * @description Ends the session of a refresh token, its access tokens stop working
+         * right away
+         */
+        post: operations["logout"];
+        delete?: never;
+        options?: never;
+        head?: never;
+        patch?: never;
+        trace?: never;
+    };
+    "/v1/auth/refresh": {
+        parameters: {
+            query?: never;
+            header?: never;
+            path?: never;
+            cookie?: never;
+        };
+        get?: never;
+        put?: never;
+        /**
+         * @description Trades a refresh token for a new access token and refresh token. Each
+         * refresh token works once; presenting one again ends its session.
+         */
+        post: operations["refresh"];
+        delete?: never;
+        options?: never;
+        head?: never;
+        patch?: never;
+        trace?: never;
+    };
+    "/v1/claims/{address}": {
+        parameters: {
+            query?: never;
+            header?: never;
+            path?: never;
+            cookie?: never;
+        };
+        /**
+         * @description Latest transfers received by `address`, including scheduled top-ups,
+         * API key drips and deploy allocations (see `kind`)
+         */
+        get: operations["claims"];
+        put?: never;
+        post?: never;
+        delete?: never;
+        options?: never;
+        head?: never;
+        patch?: never;
+        trace?: never;
+    };
+    "/v1/deploy/erc20": {
+        parameters: {
+            query?: never;
+            header?: never;
+            path?: never;
+            cookie?: never;
+        };
+        get?: never;
+        put?: never;
+        /** @description Queues the deploy of an ERC20 token, answering with the job to follow */
+        post: operations["deploy_erc20"];
+        delete?: never;
+        options?: never;
+        head?: never;
+        patch?: never;
+        trace?: never;
+    };
+    "/v1/deploy/jobs/{id}": {
+        parameters: {
+            query?: never;
+            header?: never;
+            path?: never;
+            cookie?: never;
+        };
+        /**
+         * @description Current state of a deploy job. Job ids are unguessable and the job only
+         * holds public on-chain data, so no auth is required; this also lets
+         * `EventSource`, which cannot send headers, follow `deploy_job_events`.
+         */
+        get: operations["deploy_job"];
+        put?: never;
+        post?: never;
+        delete?: never;
+        options?: never;
+        head?: never;
+        patch?: never;
+        trace?: never;
+    };
+    "/v1/deploy/jobs/{id}/events": {
+        parameters: {
+            query?: never;
+            header?: never;
+            path?: never;
+            cookie?: never;
+        };
+        /**
+         * @description Streams a deploy job as Server-Sent Events, one `progress` event per
+         * change, closing once the job is registered or failed.
+         */
+        get: operations["deploy_job_events"];
+        put?: never;
+        post?: never;
+        delete?: never;
+        options?: never;
+        head?: never;
+        patch?: never;
+        trace?: never;
+    };
+    "/v1/drip/bulk": {
+        parameters: {
+            query?: never;
+            header?: never;
+            path?: never;
+            cookie?: never;
+        };
+        get?: never;
+        put?: never;
+        /**
+         * @description Funds a list of addresses in one executor job. Requires
+         * `Authorization: ApiKey ...` and is bounded by that key's quotas.
+         */
+        post: operations["bulk_drip"];
+        delete?: never;
+        options?: never;
+        head?: never;
+        patch?: never;
+        trace?: never;
+    };
+    "/v1/events": {
+        parameters: {
+            query?: never;
+            header?: never;
+            path?: never;
+            cookie?: never;
+        };
+        /**
+         * @description Live feed of faucet activity as Server-Sent Events. Recipient addresses
+         * and transaction hashes are redacted, `?token=` narrows the feed to a
+         * single token.
+         */
+        get: operations["events"];
+        put?: never;
+        post?: never;
+        delete?: never;
+        options?: never;
+        head?: never;
+        patch?: never;
+        trace?: never;
+    };
+    "/v1/events/ws": {
+        parameters: {
+            query?: never;
+            header?: never;
+            path?: never;
+            cookie?: never;
+        };
+        /** @description Same feed as [`events`] over a WebSocket, one JSON text message per event. */
+        get: operations["events_ws"];
+        put?: never;
+        post?: never;
+        delete?: never;
+        options?: never;
+        head?: never;
+        patch?: never;
+        trace?: never;
+    };
+    "/v1/subscriptions": {
+        parameters: {
+            query?: never;
+            header?: never;
+            path?: never;
+            cookie?: never;
+        };
+        /** @description Scheduled top-ups of the user's wallets */
+        get: operations["subscriptions"];
+        put?: never;
+        post: operations["create_subscription"];
+        delete?: never;
+        options?: never;
+        head?: never;
+        patch?: never;
+        trace?: never;
+    };
+    "/v1/subscriptions/{id}": {
+        parameters: {
+            query?: never;
+            header?: never;
+            path?: never;
+            cookie?: never;
+        };
+        get?: never;
+        put: operations["update_subscription"];
+        post?: never;
+        delete: operations["delete_subscription"];
+        options?: never;
+        head?: never;
+        patch?: never;
+        trace?: never;
+    };
+    "/v1/test_auth": {
+        parameters: {
+            query?: never;
+            header?: never;
+            path?: never;
+            cookie?: never;
+        };
+        /** @description Greets the caller, for checking a token or API key */
+        get: operations["test_auth"];
+        put?: never;
+        post?: never;
+        delete?: never;
+        options?: never;
+        head?: never;
+        patch?: never;
+        trace?: never;
+    };
+    "/v1/tokens": {
+        parameters: {
+            query?: never;
+            header?: never;
+            path?: never;
+            cookie?: never;
+        };
+        /** @description Every token the faucet drips */
+        get: operations["tokens"];
+        put?: never;
+        post?: never;
+        delete?: never;
+        options?: never;
+        head?: never;
+        patch?: never;
+        trace?: never;
+    };
+    "/v1/tokens/{address}/webhooks": {
+        parameters: {
+            query?: never;
+            header?: never;
+            path?: never;
+            cookie?: never;
+        };
+        /** @description Webhooks of a token */
+        get: operations["token_webhooks"];
+        put?: never;
+        post: operations["create_webhook"];
+        delete?: never;
+        options?: never;
+        head?: never;
+        patch?: never;
+        trace?: never;
+    };
+    "/v1/user": {
+        parameters: {
+            query?: never;
+            header?: never;
+            path?: never;
+            cookie?: never;
+        };
+        /** @description Profile of the signed in GitHub user */
+        get: operations["user"];
+        put?: never;
+        post?: never;
+        delete?: never;
+        options?: never;
+        head?: never;
+        patch?: never;
+        trace?: never;
+    };
+    "/v1/verify-turnstile-captcha": {
+        parameters: {
+            query?: never;
+            header?: never;
+            path?: never;
+            cookie?: never;
+        };
+        get?: never;
+        put?: never;
+        /** @description Checks a Cloudflare Turnstile token */
+        post: operations["verify_turnstile_captcha"];
+        delete?: never;
+        options?: never;
+        head?: never;
+        patch?: never;
+        trace?: never;
+    };
+    "/v1/webhooks": {
+        parameters: {
+            query?: never;
+            header?: never;
+            path?: never;
+            cookie?: never;
+        };
+        /** @description Webhooks of the signed in user that are not tied to a token */
+        get: operations["user_webhooks"];
+        put?: never;
+        /**
+         * @description Creates a webhook of the signed in user, notified about the tokens they
+         * deploy
+         */
+        post: operations["create_user_webhook"];
+        delete?: never;
+        options?: never;
+        head?: never;
+        patch?: never;
+        trace?: never;
+    };
+    "/v1/webhooks/{id}": {
+        parameters: {
+            query?: never;
+            header?: never;
+            path?: never;
+            cookie?: never;
+        };
+        get?: never;
+        put?: never;
+        post?: never;
+        delete: operations["delete_webhook"];
+        options?: never;
+        head?: never;
+        patch?: never;
+        trace?: never;
+    };
+    "/v1/webhooks/{id}/deliveries": {
+        parameters: {
+            query?: never;
+            header?: never;
+            path?: never;
+            cookie?: never;
+        };
+        /** @description Latest deliveries of a webhook with their status, attempts and last error */
+        get: operations["webhook_deliveries"];
+        put?: never;
+        post?: never;
+        delete?: never;
+        options?: never;
+        head?: never;
+        patch?: never;
+        trace?: never;
+    };
+    "/v1/withdraw": {
+        parameters: {
+            query?: never;
+            header?: never;
+            path?: never;
+            cookie?: never;
+        };
+        get?: never;
+        put?: never;
+        /** @description Drips a token to a wallet, more of it for GitHub authenticated users */
+        post: operations["withdraw"];
+        delete?: never;
+        options?: never;
+        head?: never;
+        patch?: never;
+        trace?: never;
+    };
+    "/v1/withdraw/batch": {
+        parameters: {
+            query?: never;
+            header?: never;
+            path?: never;
+            cookie?: never;
+        };
+        get?: never;
+        put?: never;
+        /** @description Drips several tokens to one wallet, answering how each of them went */
+        post: operations["withdraw_batch"];
+        delete?: never;
+        options?: never;
+        head?: never;
+        patch?: never;
+        trace?: never;
+    };
+}
+export type webhooks = Record<string, never>;
+export interface components {
+    schemas: {
+        ApiKey: {
+            drips_per_day: number;
+            id: string;
+            key_prefix: string;
+            name: string;
+            revoked: boolean;
+            user_id: string;
+        };
+        ApiKeyQuota: {
+            max_amount_per_day: string;
+            token_address: string;
+        };
+        /**
+         * @description Sent in the body rather than the query, so that the code and state token
+         * stay out of access logs and traces
+         */
+        AuthGithubRequest: {
+            /** @description Code GitHub redirected back with */
+            code: string;
+            /** @description State GitHub redirected back with */
+            state: string;
+            /** @description `state_token` of the `GET /auth/github/start` that began this login */
+            state_token: string;
+        };
+        /** @description Claims several tokens for one recipient in a single executor job */
+        BatchTransferRequest: {
+            /** @example "203.0.113.7" */
+            ip: string;
+            /** @description Ignored, the faucet decides the magnification */
+            magnification?: number | null;
+            to: string;
+            token_addresses: string[];
+        };
+        BatchTransferResult: (components["schemas"]["ExecutorResponse"]) & ({
+            token_address: string;
+        });
+        BulkDrip: {
+            /** @description Amount in the token's base units */
+            amount: string;
+            to: string;
+            token_address: string;
+        };
+        BulkDripPayload: {
+            drips: components["schemas"]["BulkDrip"][];
+        };
+        BulkDripResult: (components["schemas"]["ExecutorResponse"]) & ({
+            to: string;
+            token_address: string;
+        });
+        CreateApiKeyRequest: {
+            drips_per_day: number;
+            name: string;
+            owner_user_id: string;
+            quotas: components["schemas"]["ApiKeyQuota"][];
+        };
+        CreateApiKeyResponse: {
+            api_key: components["schemas"]["ApiKey"];
+            /** @description The plaintext key, only ever returned once at creation */
+            key: string;
+        };
+        CreateSubscriptionRequest: {
+            /** @description Refill once the wallet holds less than this, in base units */
+            min_balance: string;
+            /** @description In base units, at most what a GitHub user can claim at once */
+            refill_amount: string;
+            token_address: string;
+            wallet_address: string;
+        };
+        CreateWebhookRequest: {
+            events: string[];
+            /** @description An https URL */
+            url: string;
+        };
+        CreateWebhookResponse: {
+            /** @description Secret the payloads are signed with, only ever returned once at creation */
+            secret: string;
+            webhook: components["schemas"]["Webhook"];
+        };
+        DeliveryStatus: "PENDING" | "DELIVERED" | "FAILED";
+        /**
+         * @description Multipart body of `POST /deploy/erc20`, only described for the docs since
+         * the handler reads the parts itself
+         */
+        DeployErc20Form: {
+            /** @description [`TokenDeployRequestData`] as JSON */
+            data: string;
+            /** @description The logo, a PNG, JPEG, WebP or SVG image */
+            file: string;
+        };
+        DeployErc20Response: {
+            /** @description Follow the deploy through `/v1/deploy/jobs/:id` */
+            job_id: string;
+        };
+        DeployJob: {
+            allocation_tx_hashes: string[];
+            contract_address?: string | null;
+            created_at: string;
+            deploy_tx_hash?: string | null;
+            error?: string | null;
+            error_code?: string | null;
+            id: string;
+            step: components["schemas"]["DeployStep"];
+            symbol: string;
+            updated_at: string;
+        };
+        /** @description Progress of a token deployment */
+        DeployStep: "QUEUED" | "UPLOADING_LOGO" | "DEPLOYING" | "ALLOCATING_SUPPLY" | "REGISTERED" | "FAILED";
+        /** @description What triggered a token transfer */
+        DripKind: "CLAIM" | "SCHEDULED" | "API_KEY" | "DEPLOY";
+        DripResponse: {
+            amount: string;
+            magnification: number;
+            tx_hash: string;
+        };
+        ErrorResponse: {
+            /** @description Stable machine-readable error code, e.g. `INSUFFICIENT_FAUCET_BALANCE` */
+            code?: string | null;
+            message: string;
+            next_access?: string | null;
+        };
+        ExecutorResponse: {
+            data?: null | components["schemas"]["DripResponse"];
+            error?: null | components["schemas"]["ErrorResponse"];
+            status: components["schemas"]["ResponseStatus"];
+        };
+        GithubLoginStartResponse: {
+            /** @description Where to send the user to sign in on GitHub */
+            authorize_url: string;
+            /**
+             * @description Keep until GitHub redirects back and send it along with the code, it
+             * ties the redirect to this login and holds its PKCE verifier
+             */
+            state_token: string;
+        };
+        HealthCheckResponse: {
+            status: string;
+        };
+        LogoutRequest: {
+            /** @description End every session of the user instead of only this one */
+            everywhere?: boolean;
+            refresh_token: string;
+        };
+        LogoutResponse: {
+            /** @description Number of sessions ended, 0 for an unknown or already ended session */
+            revoked_sessions: number;
+        };
+        /** @description An executor queue, as named in `/v1/admin/queues/:queue/...` */
+        QueueName: "withdraw" | "withdraw_batch" | "bulk_drip" | "scheduled" | "deploy";
+        QueueState: {
+            /** @description Jobs waiting to be picked up */
+            length: number;
+            paused: boolean;
+            queue: components["schemas"]["QueueName"];
+        };
+        RefreshRequest: {
+            refresh_token: string;
+        };
+        ResponseStatus: "Success" | "Error";
+        /** @description Envelope of every JSON answer, `data` on success and `error` otherwise */
+        Response_ApiKey: {
+            data?: {
+                drips_per_day: number;
+                id: string;
+                key_prefix: string;
+                name: string;
+                revoked: boolean;
+                user_id: string;
+            };
+            error?: {
+                drips_per_day: number;
+                id: string;
+                key_prefix: string;
+                name: string;
+                revoked: boolean;
+                user_id: string;
+            };
+            status: components["schemas"]["ResponseStatus"];
+        };
+        /** @description Envelope of every JSON answer, `data` on success and `error` otherwise */
+        Response_CreateApiKeyResponse: {
+            data?: {
+                api_key: components["schemas"]["ApiKey"];
+                /** @description The plaintext key, only ever returned once at creation */
+                key: string;
+            };
+            error?: {
+                api_key: components["schemas"]["ApiKey"];
+                /** @description The plaintext key, only ever returned once at creation */
+                key: string;
+            };
+            status: components["schemas"]["ResponseStatus"];
+        };
+        /** @description Envelope of every JSON answer, `data` on success and `error` otherwise */
+        Response_CreateWebhookResponse: {
+            data?: {
+                /** @description Secret the payloads are signed with, only ever returned once at creation */
+                secret: string;
+                webhook: components["schemas"]["Webhook"];
+            };
+            error?: {
+                /** @description Secret the payloads are signed with, only ever returned once at creation */
+                secret: string;
+                webhook: components["schemas"]["Webhook"];
+            };
+            status: components["schemas"]["ResponseStatus"];
+        };
+        /** @description Envelope of every JSON answer, `data` on success and `error` otherwise */
+        Response_DeployErc20Response: {
+            data?: {
+                /** @description Follow the deploy through `/v1/deploy/jobs/:id` */
+                job_id: string;
+            };
+            error?: {
+                /** @description Follow the deploy through `/v1/deploy/jobs/:id` */
+                job_id: string;
+            };
+            status: components["schemas"]["ResponseStatus"];
+        };
+        /** @description Envelope of every JSON answer, `data` on success and `error` otherwise */
+        Response_DeployJob: {
+            data?: {
+                allocation_tx_hashes: string[];
+                contract_address?: string | null;
+                created_at: string;
+                deploy_tx_hash?: string | null;
+                error?: string | null;
+                error_code?: string | null;
+                id: string;
+                step: components["schemas"]["DeployStep"];
+                symbol: string;
+                updated_at: string;
+            };
+            error?: {
+                allocation_tx_hashes: string[];
+                contract_address?: string | null;
+                created_at: string;
+                deploy_tx_hash?: string | null;
+                error?: string | null;
+                error_code?: string | null;
+                id: string;
+                step: components["schemas"]["DeployStep"];
+                symbol: string;
+                updated_at: string;
+            };
+            status: components["schemas"]["ResponseStatus"];
+        };
+        /** @description Envelope of every JSON answer, `data` on success and `error` otherwise */
+        Response_DripResponse: {
+            data?: {
+                amount: string;
+                magnification: number;
+                tx_hash: string;
+            };
+            error?: {
+                amount: string;
+                magnification: number;
+                tx_hash: string;
+            };
+            status: components["schemas"]["ResponseStatus"];
+        };
+        /** @description Envelope of every JSON answer, `data` on success and `error` otherwise */
+        Response_ErrorResponse: {
+            data?: {
+                /** @description Stable machine-readable error code, e.g. `INSUFFICIENT_FAUCET_BALANCE` */
+                code?: string | null;
+                message: string;
+                next_access?: string | null;
+            };
+            error?: {
+                /** @description Stable machine-readable error code, e.g. `INSUFFICIENT_FAUCET_BALANCE` */
+                code?: string | null;
+                message: string;
+                next_access?: string | null;
+            };
+            status: components["schemas"]["ResponseStatus"];
+        };
+        /** @description Envelope of every JSON answer, `data` on success and `error` otherwise */
+        Response_GithubLoginStartResponse: {
+            data?: {
+                /** @description Where to send the user to sign in on GitHub */
+                authorize_url: string;
+                /**
+                 * @description Keep until GitHub redirects back and send it along with the code, it
+                 * ties the redirect to this login and holds its PKCE verifier
+                 */
+                state_token: string;
+            };
+            error?: {
+                /** @description Where to send the user to sign in on GitHub */
+                authorize_url: string;
+                /**
+                 * @description Keep until GitHub redirects back and send it along with the code, it
+                 * ties the redirect to this login and holds its PKCE verifier
+                 */
+                state_token: string;
+            };
+            status: components["schemas"]["ResponseStatus"];
+        };
+        /** @description Envelope of every JSON answer, `data` on success and `error` otherwise */
+        Response_HealthCheckResponse: {
+            data?: {
+                status: string;
+            };
+            error?: {
+                status: string;
+            };
+            status: components["schemas"]["ResponseStatus"];
+        };
+        /** @description Envelope of every JSON answer, `data` on success and `error` otherwise */
+        Response_LogoutResponse: {
+            data?: {
+                /** @description Number of sessions ended, 0 for an unknown or already ended session */
+                revoked_sessions: number;
+            };
+            error?: {
+                /** @description Number of sessions ended, 0 for an unknown or already ended session */
+                revoked_sessions: number;
+            };
+            status: components["schemas"]["ResponseStatus"];
+        };
+        /** @description Envelope of every JSON answer, `data` on success and `error` otherwise */
+        Response_SessionTokensResponse: {
+            data?: {
+                /** @description Seconds until `token` expires */
+                expires_in: number;
+                /** @description Trades for new tokens on `POST /auth/refresh`, works once */
+                refresh_token: string;
+                /** @description Access token to send as `Authorization: Bearer <token>`, short lived */
+                token: string;
+            };
+            error?: {
+                /** @description Seconds until `token` expires */
+                expires_in: number;
+                /** @description Trades for new tokens on `POST /auth/refresh`, works once */
+                refresh_token: string;
+                /** @description Access token to send as `Authorization: Bearer <token>`, short lived */
+                token: string;
+            };
+            status: components["schemas"]["ResponseStatus"];
+        };
+        /** @description Envelope of every JSON answer, `data` on success and `error` otherwise */
+        Response_String: {
+            data?: string;
+            error?: string;
+            status: components["schemas"]["ResponseStatus"];
+        };
+        /** @description Envelope of every JSON answer, `data` on success and `error` otherwise */
+        Response_TokenPause: {
+            /** @description Circuit breaker state of a token, tripped when its outflow exceeds a cap */
+            data?: {
+                paused: boolean;
+                paused_at?: string | null;
+                reason?: string | null;
+                /** @description Outflow before this no longer counts towards the caps */
+                resumed_at?: string | null;
+                resumed_by?: string | null;
+                token_address: string;
+            };
+            /** @description Circuit breaker state of a token, tripped when its outflow exceeds a cap */
+            error?: {
+                paused: boolean;
+                paused_at?: string | null;
+                reason?: string | null;
+                /** @description Outflow before this no longer counts towards the caps */
+                resumed_at?: string | null;
+                resumed_by?: string | null;
+                token_address: string;
+            };
+            status: components["schemas"]["ResponseStatus"];
+        };
+        /** @description Envelope of every JSON answer, `data` on success and `error` otherwise */
+        Response_TuningConfig: {
+            /**
+             * @description Drip parameters that can be changed at runtime, from the `[tuning]`
+             * section of the config file
+             */
+            data?: {
+                /**
+                 * @description Same over a day
+                 * @default 5000
+                 */
+                daily_outflow_cap_drips?: number;
+                /**
+                 * @description Pause a token once more max size drips than this went out in an
+                 * hour, 0 disables the cap
+                 * @default 500
+                 */
+                hourly_outflow_cap_drips?: number;
+                /**
+                 * @description Warn once fewer max size drips than this are left
+                 * @default 100
+                 */
+                low_balance_drips?: number;
+                /** @default 10 */
+                magnification_garden_user?: number;
+                /**
+                 * @description Either GitHub authenticated or Garden user
+                 * @default 10
+                 */
+                magnification_github_auth?: number;
+                /**
+                 * @description Neither authenticated nor Garden user
+                 * @default 1
+                 */
+                magnification_no_auth?: number;
+                /**
+                 * @description Tokens claimable in one `/withdraw/batch` request
+                 * @default 5
+                 */
+                max_batch_tokens?: number;
+                /**
+                 * @description Per owner, across all their wallets
+                 * @default 10
+                 */
+                max_scheduled_refills_per_day?: number;
+                /**
+                 * @description Share of a deployed token's supply dripped at once
+                 * @default 1000000000
+                 */
+                withdraw_limit_denominator?: number;
+            };
+            /**
+             * @description Drip parameters that can be changed at runtime, from the `[tuning]`
+             * section of the config file
+             */
+            error?: {
+                /**
+                 * @description Same over a day
+                 * @default 5000
+                 */
+                daily_outflow_cap_drips?: number;
+                /**
+                 * @description Pause a token once more max size drips than this went out in an
+                 * hour, 0 disables the cap
+                 * @default 500
+                 */
+                hourly_outflow_cap_drips?: number;
+                /**
+                 * @description Warn once fewer max size drips than this are left
+                 * @default 100
+                 */
+                low_balance_drips?: number;
+                /** @default 10 */
+                magnification_garden_user?: number;
+                /**
+                 * @description Either GitHub authenticated or Garden user
+                 * @default 10
+                 */
+                magnification_github_auth?: number;
+                /**
+                 * @description Neither authenticated nor Garden user
+                 * @default 1
+                 */
+                magnification_no_auth?: number;
+                /**
+                 * @description Tokens claimable in one `/withdraw/batch` request
+                 * @default 5
+                 */
+                max_batch_tokens?: number;
+                /**
+                 * @description Per owner, across all their wallets
+                 * @default 10
+                 */
+                max_scheduled_refills_per_day?: number;
+                /**
+                 * @description Share of a deployed token's supply dripped at once
+                 * @default 1000000000
+                 */
+                withdraw_limit_denominator?: number;
+            };
+            status: components["schemas"]["ResponseStatus"];
+        };
+        /** @description Envelope of every JSON answer, `data` on success and `error` otherwise */
+        Response_TurnstileResponse: {
+            data?: {
+                success: boolean;
+            };
+            error?: {
+                success: boolean;
+            };
+            status: components["schemas"]["ResponseStatus"];
+        };
+        /** @description Envelope of every JSON answer, `data` on success and `error` otherwise */
+        Response_User: {
+            data?: {
+                access_token: string;
+                avatar_url: string;
+                email?: string | null;
+                github_id: string;
+                id: string;
+                username: string;
+            };
+            error?: {
+                access_token: string;
+                avatar_url: string;
+                email?: string | null;
+                github_id: string;
+                id: string;
+                username: string;
+            };
+            status: components["schemas"]["ResponseStatus"];
+        };
+        /** @description Envelope of every JSON answer, `data` on success and `error` otherwise */
+        Response_Vec_ApiKey: {
+            data?: {
+                drips_per_day: number;
+                id: string;
+                key_prefix: string;
+                name: string;
+                revoked: boolean;
+                user_id: string;
+            }[];
+            error?: {
+                drips_per_day: number;
+                id: string;
+                key_prefix: string;
+                name: string;
+                revoked: boolean;
+                user_id: string;
+            }[];
+            status: components["schemas"]["ResponseStatus"];
+        };
+        /** @description Envelope of every JSON answer, `data` on success and `error` otherwise */
+        Response_Vec_BatchTransferResult: {
+            data?: ((components["schemas"]["ExecutorResponse"]) & ({
+                token_address: string;
+            }))[];
+            error?: ((components["schemas"]["ExecutorResponse"]) & ({
+                token_address: string;
+            }))[];
+            status: components["schemas"]["ResponseStatus"];
+        };
+        /** @description Envelope of every JSON answer, `data` on success and `error` otherwise */
+        Response_Vec_BulkDripResult: {
+            data?: ((components["schemas"]["ExecutorResponse"]) & ({
+                to: string;
+                token_address: string;
+            }))[];
+            error?: ((components["schemas"]["ExecutorResponse"]) & ({
+                to: string;
+                token_address: string;
+            }))[];
+            status: components["schemas"]["ResponseStatus"];
+        };
+        /** @description Envelope of every JSON answer, `data` on success and `error` otherwise */
+        Response_Vec_QueueState: {
+            data?: {
+                /** @description Jobs waiting to be picked up */
+                length: number;
+                paused: boolean;
+                queue: components["schemas"]["QueueName"];
+            }[];
+            error?: {
+                /** @description Jobs waiting to be picked up */
+                length: number;
+                paused: boolean;
+                queue: components["schemas"]["QueueName"];
+            }[];
+            status: components["schemas"]["ResponseStatus"];
+        };
+        /** @description Envelope of every JSON answer, `data` on success and `error` otherwise */
+        Response_Vec_Token: {
+            data?: {
+                address: string;
+                chain_id: number;
+                created_by: string;
+                decimals: number;
+                /** @description Square PNG rendition of the logo, NULL for tokens deployed before it existed */
+                logo_thumbnail_url?: string | null;
+                logo_url: string;
+                name: string;
+                symbol: string;
+                token_type: components["schemas"]["TokenType"];
+                /**
+                 * @description When set, drips top the recipient up to this balance (in base units)
+                 * instead of sending a fixed `withdraw_limit`
+                 */
+                top_up_target?: string | null;
+                withdraw_limit: string;
+            }[];
+            error?: {
+                address: string;
+                chain_id: number;
+                created_by: string;
+                decimals: number;
+                /** @description Square PNG rendition of the logo, NULL for tokens deployed before it existed */
+                logo_thumbnail_url?: string | null;
+                logo_url: string;
+                name: string;
+                symbol: string;
+                token_type: components["schemas"]["TokenType"];
+                /**
+                 * @description When set, drips top the recipient up to this balance (in base units)
+                 * instead of sending a fixed `withdraw_limit`
+                 */
+                top_up_target?: string | null;
+                withdraw_limit: string;
+            }[];
+            status: components["schemas"]["ResponseStatus"];
+        };
+        /** @description Envelope of every JSON answer, `data` on success and `error` otherwise */
+        Response_Vec_TokenPause: {
+            data?: {
+                paused: boolean;
+                paused_at?: string | null;
+                reason?: string | null;
+                /** @description Outflow before this no longer counts towards the caps */
+                resumed_at?: string | null;
+                resumed_by?: string | null;
+                token_address: string;
+            }[];
+            error?: {
+                paused: boolean;
+                paused_at?: string | null;
+                reason?: string | null;
+                /** @description Outflow before this no longer counts towards the caps */
+                resumed_at?: string | null;
+                resumed_by?: string | null;
+                token_address: string;
+            }[];
+            status: components["schemas"]["ResponseStatus"];
+        };
+        /** @description Envelope of every JSON answer, `data` on success and `error` otherwise */
+        Response_Vec_TokenTransfer: {
+            data?: {
+                amount: string;
+                chain_id: number;
+                from_address: string;
+                kind: components["schemas"]["DripKind"];
+                to_address: string;
+                token_address: string;
+                token_type: components["schemas"]["TokenType"];
+                tx_hash: string;
+            }[];
+            error?: {
+                amount: string;
+                chain_id: number;
+                from_address: string;
+                kind: components["schemas"]["DripKind"];
+                to_address: string;
+                token_address: string;
+                token_type: components["schemas"]["TokenType"];
+                tx_hash: string;
+            }[];
+            status: components["schemas"]["ResponseStatus"];
+        };
+        /** @description Envelope of every JSON answer, `data` on success and `error` otherwise */
+        Response_Vec_WalletSubscription: {
+            data?: {
+                enabled: boolean;
+                id: string;
+                last_checked_at?: string | null;
+                last_refill_at?: string | null;
+                min_balance: string;
+                refill_amount: string;
+                token_address: string;
+                user_id: string;
+                wallet_address: string;
+            }[];
+            error?: {
+                enabled: boolean;
+                id: string;
+                last_checked_at?: string | null;
+                last_refill_at?: string | null;
+                min_balance: string;
+                refill_amount: string;
+                token_address: string;
+                user_id: string;
+                wallet_address: string;
+            }[];
+            status: components["schemas"]["ResponseStatus"];
+        };
+        /** @description Envelope of every JSON answer, `data` on success and `error` otherwise */
+        Response_Vec_Webhook: {
+            data?: {
+                created_at: string;
+                created_by: string;
+                events: string[];
+                id: string;
+                /** @description None for a webhook of `created_by` notified about their own deploys */
+                token_address?: string | null;
+                url: string;
+            }[];
+            error?: {
+                created_at: string;
+                created_by: string;
+                events: string[];
+                id: string;
+                /** @description None for a webhook of `created_by` notified about their own deploys */
+                token_address?: string | null;
+                url: string;
+            }[];
+            status: components["schemas"]["ResponseStatus"];
+        };
+        /** @description Envelope of every JSON answer, `data` on success and `error` otherwise */
+        Response_Vec_WebhookDelivery: {
+            data?: {
+                attempts: number;
+                created_at: string;
+                event_type: string;
+                id: string;
+                last_error?: string | null;
+                last_status_code?: number | null;
+                next_attempt_at: string;
+                payload: string;
+                status: components["schemas"]["DeliveryStatus"];
+                webhook_id: string;
+            }[];
+            error?: {
+                attempts: number;
+                created_at: string;
+                event_type: string;
+                id: string;
+                last_error?: string | null;
+                last_status_code?: number | null;
+                next_attempt_at: string;
+                payload: string;
+                status: components["schemas"]["DeliveryStatus"];
+                webhook_id: string;
+            }[];
+            status: components["schemas"]["ResponseStatus"];
+        };
+        /** @description Envelope of every JSON answer, `data` on success and `error` otherwise */
+        Response_WalletSubscription: {
+            /**
+             * @description A wallet that is automatically refilled once its balance drops below
+             * `min_balance`
+             */
+            data?: {
+                enabled: boolean;
+                id: string;
+                last_checked_at?: string | null;
+                last_refill_at?: string | null;
+                min_balance: string;
+                refill_amount: string;
+                token_address: string;
+                user_id: string;
+                wallet_address: string;
+            };
+            /**
+             * @description A wallet that is automatically refilled once its balance drops below
+             * `min_balance`
+             */
+            error?: {
+                enabled: boolean;
+                id: string;
+                last_checked_at?: string | null;
+                last_refill_at?: string | null;
+                min_balance: string;
+                refill_amount: string;
+                token_address: string;
+                user_id: string;
+                wallet_address: string;
+            };
+            status: components["schemas"]["ResponseStatus"];
+        };
+        /** @description Envelope of every JSON answer, `data` on success and `error` otherwise */
+        Response_Webhook: {
+            /** @description An endpoint notified about events of one token */
+            data?: {
+                created_at: string;
+                created_by: string;
+                events: string[];
+                id: string;
+                /** @description None for a webhook of `created_by` notified about their own deploys */
+                token_address?: string | null;
+                url: string;
+            };
+            /** @description An endpoint notified about events of one token */
+            error?: {
+                created_at: string;
+                created_by: string;
+                events: string[];
+                id: string;
+                /** @description None for a webhook of `created_by` notified about their own deploys */
+                token_address?: string | null;
+                url: string;
+            };
+            status: components["schemas"]["ResponseStatus"];
+        };
+        SessionTokensResponse: {
+            /** @description Seconds until `token` expires */
+            expires_in: number;
+            /** @description Trades for new tokens on `POST /auth/refresh`, works once */
+            refresh_token: string;
+            /** @description Access token to send as `Authorization: Bearer <token>`, short lived */
+            token: string;
+        };
+        Token: {
+            address: string;
+            chain_id: number;
+            created_by: string;
+            decimals: number;
+            /** @description Square PNG rendition of the logo, NULL for tokens deployed before it existed */
+            logo_thumbnail_url?: string | null;
+            logo_url: string;
+            name: string;
+            symbol: string;
+            token_type: components["schemas"]["TokenType"];
+            /**
+             * @description When set, drips top the recipient up to this balance (in base units)
+             * instead of sending a fixed `withdraw_limit`
+             */
+            top_up_target?: string | null;
+            withdraw_limit: string;
+        };
+        /** @description Circuit breaker state of a token, tripped when its outflow exceeds a cap */
+        TokenPause: {
+            paused: boolean;
+            paused_at?: string | null;
+            reason?: string | null;
+            /** @description Outflow before this no longer counts towards the caps */
+            resumed_at?: string | null;
+            resumed_by?: string | null;
+            token_address: string;
+        };
+        TokenTransfer: {
+            amount: string;
+            chain_id: number;
+            from_address: string;
+            kind: components["schemas"]["DripKind"];
+            to_address: string;
+            token_address: string;
+            token_type: components["schemas"]["TokenType"];
+            tx_hash: string;
+        };
+        TokenTransferRequest: {
+            /** @example "203.0.113.7" */
+            ip: string;
+            /** @description Ignored, the faucet decides the magnification */
+            magnification?: number | null;
+            to: string;
+            token_address: string;
+            token_type: components["schemas"]["TokenType"];
+        };
+        TokenType: "ERC20" | "NATIVE";
+        /**
+         * @description Drip parameters that can be changed at runtime, from the `[tuning]`
+         * section of the config file
+         */
+        TuningConfig: {
+            /**
+             * @description Same over a day
+             * @default 5000
+             */
+            daily_outflow_cap_drips?: number;
+            /**
+             * @description Pause a token once more max size drips than this went out in an
+             * hour, 0 disables the cap
+             * @default 500
+             */
+            hourly_outflow_cap_drips?: number;
+            /**
+             * @description Warn once fewer max size drips than this are left
+             * @default 100
+             */
+            low_balance_drips?: number;
+            /** @default 10 */
+            magnification_garden_user?: number;
+            /**
+             * @description Either GitHub authenticated or Garden user
+             * @default 10
+             */
+            magnification_github_auth?: number;
+            /**
+             * @description Neither authenticated nor Garden user
+             * @default 1
+             */
+            magnification_no_auth?: number;
+            /**
+             * @description Tokens claimable in one `/withdraw/batch` request
+             * @default 5
+             */
+            max_batch_tokens?: number;
+            /**
+             * @description Per owner, across all their wallets
+             * @default 10
+             */
+            max_scheduled_refills_per_day?: number;
+            /**
+             * @description Share of a deployed token's supply dripped at once
+             * @default 1000000000
+             */
+            withdraw_limit_denominator?: number;
+        };
+        TurnstilePayload: {
+            token: string;
+        };
+        TurnstileResponse: {
+            success: boolean;
+        };
+        UpdateSubscriptionRequest: {
+            enabled?: boolean | null;
+            min_balance?: string | null;
+            refill_amount?: string | null;
+        };
+        User: {
+            access_token: string;
+            avatar_url: string;
+            email?: string | null;
+            github_id: string;
+            id: string;
+            username: string;
+        };
+        /**
+         * @description A wallet that is automatically refilled once its balance drops below
+         * `min_balance`
+         */
+        WalletSubscription: {
+            enabled: boolean;
+            id: string;
+            last_checked_at?: string | null;
+            last_refill_at?: string | null;
+            min_balance: string;
+            refill_amount: string;
+            token_address: string;
+            user_id: string;
+            wallet_address: string;
+        };
+        /** @description An endpoint notified about events of one token */
+        Webhook: {
+            created_at: string;
+            created_by: string;
+            events: string[];
+            id: string;
+            /** @description None for a webhook of `created_by` notified about their own deploys */
+            token_address?: string | null;
+            url: string;
+        };
+        /** @description A webhook call, pending in the outbox until it is delivered or gives up */
+        WebhookDelivery: {
+            attempts: number;
+            created_at: string;
+            event_type: string;
+            id: string;
+            last_error?: string | null;
+            last_status_code?: number | null;
+            next_attempt_at: string;
+            payload: string;
+            status: components["schemas"]["DeliveryStatus"];
+            webhook_id: string;
+        };
+    };
+    responses: never;
+    parameters: never;
+    requestBodies: never;
+    headers: never;
+    pathItems: never;
+}
+export type $defs = Record<string, never>;
+export interface operations {
+    health_check: {
+        parameters: {
+            query?: never;
+            header?: never;
+            path?: never;
+            cookie?: never;
+        };
+        requestBody?: never;
+        responses: {
+            /** @description The faucet is up */
+            200: {
+                headers: {
+                    [name: string]: unknown;
+                };
+                content: {
+                    "application/json": components["schemas"]["Response_HealthCheckResponse"];
+                };
+            };
+        };
+    };
+    paused_tokens: {
+        parameters: {
+            query?: never;
+            header?: never;
+            path?: never;
+            cookie?: never;
+        };
+        requestBody?: never;
+        responses: {
+            /** @description Paused tokens */
+            200: {
+                headers: {
+                    [name: string]: unknown;
+                };
+                content: {
+                    "application/json": components["schemas"]["Response_Vec_TokenPause"];
+                };
+            };
+            /** @description Not an admin */
+            403: {
+                headers: {
+                    [name: string]: unknown;
+                };
+                content: {
+                    "application/json": components["schemas"]["Response_ErrorResponse"];
+                };
+            };
+        };
+    };
+    queues: {
+        parameters: {
+            query?: never;
+            header?: never;
+            path?: never;
+            cookie?: never;
+        };
+        requestBody?: never;
+        responses: {
+            /** @description Every queue */
+            200: {
+                headers: {
+                    [name: string]: unknown;
+                };
+                content: {
+                    "application/json": components["schemas"]["Response_Vec_QueueState"];
+                };
+            };
+            /** @description Not an admin */
+            403: {
+                headers: {
+                    [name: string]: unknown;
+                };
+                content: {
+                    "application/json": components["schemas"]["Response_ErrorResponse"];
+                };
+            };
+        };
+    };
+    pause_queue: {
+        parameters: {
+            query?: never;
+            header?: never;
+            path: {
+                queue: components["schemas"]["QueueName"];
+            };
+            cookie?: never;
+        };
+        requestBody?: never;
+        responses: {
+            /** @description Every queue after pausing */
+            200: {
+                headers: {
+                    [name: string]: unknown;
+                };
+                content: {
+                    "application/json": components["schemas"]["Response_Vec_QueueState"];
+                };
+            };
+            /** @description Not an admin */
+            403: {
+                headers: {
+                    [name: string]: unknown;
+                };
+                content: {
+                    "application/json": components["schemas"]["Response_ErrorResponse"];
+                };
+            };
+        };
+    };
+    resume_queue: {
+        parameters: {
+            query?: never;
+            header?: never;
+            path: {
+                queue: components["schemas"]["QueueName"];
+            };
+            cookie?: never;
+        };
+        requestBody?: never;
+        responses: {
+            /** @description Every queue after resuming */
+            200: {
+                headers: {
+                    [name: string]: unknown;
+                };
+                content: {
+                    "application/json": components["schemas"]["Response_Vec_QueueState"];
+                };
+            };
+            /** @description Not an admin */
+            403: {
+                headers: {
+                    [name: string]: unknown;
+                };
+                content: {
+                    "application/json": components["schemas"]["Response_ErrorResponse"];
+                };
+            };
+        };
+    };
+    reload_config: {
+        parameters: {
+            query?: never;
+            header?: never;
+            path?: never;
+            cookie?: never;
+        };
+        requestBody?: never;
+        responses: {
+            /** @description The tuning now in effect */
+            200: {
+                headers: {
+                    [name: string]: unknown;
+                };
+                content: {
+                    "application/json": components["schemas"]["Response_TuningConfig"];
+                };
+            };
+            /** @description Not an admin */
+            403: {
+                headers: {
+                    [name: string]: unknown;
+                };
+                content: {
+                    "application/json": components["schemas"]["Response_ErrorResponse"];
+                };
+            };
+            /** @description The new config is invalid, the current one is kept */
+            422: {
+                headers: {
+                    [name: string]: unknown;
+                };
+                content: {
+                    "application/json": components["schemas"]["Response_ErrorResponse"];
+                };
+            };
+        };
+    };
+    resume_token: {
+        parameters: {
+            query?: never;
+            header?: never;
+            path: {
+                /** @description Paused token */
+                address: string;
+            };
+            cookie?: never;
+        };
+        requestBody?: never;
+        responses: {
+            /** @description The lifted pause */
+            200: {
+                headers: {
+                    [name: string]: unknown;
+                };
+                content: {
+                    "application/json": components["schemas"]["Response_TokenPause"];
+                };
+            };
+            /** @description Not an admin */
+            403: {
+                headers: {
+                    [name: string]: unknown;
+                };
+                content: {
+                    "application/json": components["schemas"]["Response_ErrorResponse"];
+                };
+            };
+            /** @description The token is not paused */
+            404: {
+                headers: {
+                    [name: string]: unknown;
+                };
+                content: {
+                    "application/json": components["schemas"]["Response_ErrorResponse"];
+                };
+            };
+        };
+    };
+    api_keys: {
+        parameters: {
+            query?: never;
+            header?: never;
+            path?: never;
+            cookie?: never;
+        };
+        requestBody?: never;
+        responses: {
+            /** @description Keys of the user, without the keys themselves */
+            200: {
+                headers: {
+                    [name: string]: unknown;
+                };
+                content: {
+                    "application/json": components["schemas"]["Response_Vec_ApiKey"];
+                };
+            };
+            /** @description Not signed in with GitHub */
+            401: {
+                headers: {
+                    [name: string]: unknown;
+                };
+                content: {
+                    "application/json": components["schemas"]["Response_ErrorResponse"];
+                };
+            };
+        };
+    };
+    create_api_key: {
+        parameters: {
+            query?: never;
+            header?: never;
+            path?: never;
+            cookie?: never;
+        };
+        requestBody: {
+            content: {
+                "application/json": components["schemas"]["CreateApiKeyRequest"];
+            };
+        };
+        responses: {
+            /** @description The key, shown only this once */
+            200: {
+                headers: {
+                    [name: string]: unknown;
+                };
+                content: {
+                    "application/json": components["schemas"]["Response_CreateApiKeyResponse"];
+                };
+            };
+            /** @description Missing name, drip count or quotas */
+            400: {
+                headers: {
+                    [name: string]: unknown;
+                };
+                content: {
+                    "application/json": components["schemas"]["Response_ErrorResponse"];
+                };
+            };
+            /** @description Not an admin */
+            403: {
+                headers: {
+                    [name: string]: unknown;
+                };
+                content: {
+                    "application/json": components["schemas"]["Response_ErrorResponse"];
+                };
+            };
+            /** @description The owner does not exist */
+            404: {
+                headers: {
+                    [name: string]: unknown;
+                };
+                content: {
+                    "application/json": components["schemas"]["Response_ErrorResponse"];
+                };
+            };
+        };
+    };
+    revoke_api_key: {
+        parameters: {
+            query?: never;
+            header?: never;
+            path: {
+                id: string;
+            };
+            cookie?: never;
+        };
+        requestBody?: never;
+        responses: {
+            /** @description The revoked key */
+            200: {
+                headers: {
+                    [name: string]: unknown;
+                };
+                content: {
+                    "application/json": components["schemas"]["Response_ApiKey"];
+                };
+            };
+            /** @description Neither the owner nor an admin */
+            403: {
+                headers: {
+                    [name: string]: unknown;
+                };
+                content: {
+                    "application/json": components["schemas"]["Response_ErrorResponse"];
+                };
+            };
+            /** @description No such key */
+            404: {
+                headers: {
+                    [name: string]: unknown;
+                };
+                content: {
+                    "application/json": components["schemas"]["Response_ErrorResponse"];
+                };
+            };
+        };
+    };
+    auth: {
+        parameters: {
+            query?: never;
+            header?: never;
+            path?: never;
+            cookie?: never;
+        };
+        requestBody: {
+            content: {
+                "application/json": components["schemas"]["AuthGithubRequest"];
+            };
+        };
+        responses: {
+            /** @description Signed in */
+            200: {
+                headers: {
+                    [name: string]: unknown;
+                };
+                content: {
+                    "application/json": components["schemas"]["Response_SessionTokensResponse"];
+                };
+            };
+            /** @description No code, state or state token */
+            400: {
+                headers: {
+                    [name: string]: unknown;
+                };
+                content: {
+                    "application/json": components["schemas"]["Response_ErrorResponse"];
+                };
+            };
+            /** @description The state does not match, the login expired or GitHub rejected the code */
+            401: {
+                headers: {
+                    [name: string]: unknown;
+                };
+                content: {
+                    "application/json": components["schemas"]["Response_ErrorResponse"];
+                };
+            };
+            /** @description GitHub failed or could not be reached */
+            502: {
+                headers: {
+                    [name: string]: unknown;
+                };
+                content: {
+                    "application/json": components["schemas"]["Response_ErrorResponse"];
+                };
+            };
+            /** @description GitHub login is not configured */
+            503: {
+                headers: {
+                    [name: string]: unknown;
+                };
+                content: {
+                    "application/json": components["schemas"]["Response_ErrorResponse"];
+                };
+            };
+        };
+    };
+    github_start: {
+        parameters: {
+            query?: never;
+            header?: never;
+            path?: never;
+            cookie?: never;
+        };
+        requestBody?: never;
+        responses: {
+            /** @description Login started */
+            200: {
+                headers: {
+                    [name: string]: unknown;
+                };
+                content: {
+                    "application/json": components["schemas"]["Response_GithubLoginStartResponse"];
+                };
+            };
+            /** @description GitHub login is not configured */
+            503: {
+                headers: {
+                    [name: string]: unknown;
+                };
+                content: {
+                    "application/json": components["schemas"]["Response_ErrorResponse"];
+                };
+            };
+        };
+    };
+    logout: {
+        parameters: {
+            query?: never;
+            header?: never;
+            path?: never;
+            cookie?: never;
+        };
+        requestBody: {
+            content: {
+                "application/json": components["schemas"]["LogoutRequest"];
+            };
+        };
+        responses: {
+            /** @description Sessions ended */
+            200: {
+                headers: {
+                    [name: string]: unknown;
+                };
+                content: {
+                    "application/json": components["schemas"]["Response_LogoutResponse"];
+                };
+            };
+            /** @description No refresh token */
+            400: {
+                headers: {
+                    [name: string]: unknown;
+                };
+                content: {
+                    "application/json": components["schemas"]["Response_ErrorResponse"];
+                };
+            };
+        };
+    };
+    refresh: {
+        parameters: {
+            query?: never;
+            header?: never;
+            path?: never;
+            cookie?: never;
+        };
+        requestBody: {
+            content: {
+                "application/json": components["schemas"]["RefreshRequest"];
+            };
+        };
+        responses: {
+            /** @description New tokens */
+            200: {
+                headers: {
+                    [name: string]: unknown;
+                };
+                content: {
+                    "application/json": components["schemas"]["Response_SessionTokensResponse"];
+                };
+            };
+            /** @description No refresh token */
+            400: {
+                headers: {
+                    [name: string]: unknown;
+                };
+                content: {
+                    "application/json": components["schemas"]["Response_ErrorResponse"];
+                };
+            };
+            /** @description Unknown, expired, revoked or reused refresh token */
+            401: {
+                headers: {
+                    [name: string]: unknown;
+                };
+                content: {
+                    "application/json": components["schemas"]["Response_ErrorResponse"];
+                };
+            };
+        };
+    };
+    claims: {
+        parameters: {
+            query?: never;
+            header?: never;
+            path: {
+                /** @description Receiving wallet, in any case */
+                address: string;
+            };
+            cookie?: never;
+        };
+        requestBody?: never;
+        responses: {
+            /** @description Latest transfers to the wallet */
+            200: {
+                headers: {
+                    [name: string]: unknown;
+                };
+                content: {
+                    "application/json": components["schemas"]["Response_Vec_TokenTransfer"];
+                };
+            };
+            /** @description Invalid wallet address */
+            400: {
+                headers: {
+                    [name: string]: unknown;
+                };
+                content: {
+                    "application/json": components["schemas"]["Response_ErrorResponse"];
+                };
+            };
+        };
+    };
+    deploy_erc20: {
+        parameters: {
+            query?: never;
+            header?: {
+                /** @description Retries with the same key get the first job instead of a second deploy, signed in callers only */
+                "Idempotency-Key"?: string | null;
+            };
+            path?: never;
+            cookie?: never;
+        };
+        requestBody: {
+            content: {
+                "multipart/form-data": components["schemas"]["DeployErc20Form"];
+            };
+        };
+        responses: {
+            /** @description Deploy queued */
+            200: {
+                headers: {
+                    [name: string]: unknown;
+                };
+                content: {
+                    "application/json": components["schemas"]["Response_DeployErc20Response"];
+                };
+            };
+            /** @description Missing or invalid token data or logo */
+            400: {
+                headers: {
+                    [name: string]: unknown;
+                };
+                content: {
+                    "application/json": components["schemas"]["Response_ErrorResponse"];
+                };
+            };
+            /** @description Idempotency-Key sent without signing in */
+            401: {
+                headers: {
+                    [name: string]: unknown;
+                };
+                content: {
+                    "application/json": components["schemas"]["Response_ErrorResponse"];
+                };
+            };
+            /** @description A token with the symbol exists */
+            409: {
+                headers: {
+                    [name: string]: unknown;
+                };
+                content: {
+                    "application/json": components["schemas"]["Response_ErrorResponse"];
+                };
+            };
+            /** @description Logo too large */
+            413: {
+                headers: {
+                    [name: string]: unknown;
+                };
+                content: {
+                    "application/json": components["schemas"]["Response_ErrorResponse"];
+                };
+            };
+            /** @description Idempotency-Key reused */
+            422: {
+                headers: {
+                    [name: string]: unknown;
+                };
+                content: {
+                    "application/json": components["schemas"]["Response_ErrorResponse"];
+                };
+            };
+            /** @description Deploys disabled or shutting down */
+            503: {
+                headers: {
+                    [name: string]: unknown;
+                };
+                content: {
+                    "application/json": components["schemas"]["Response_ErrorResponse"];
+                };
+            };
+        };
+    };
+    deploy_job: {
+        parameters: {
+            query?: never;
+            header?: never;
+            path: {
+                id: string;
+            };
+            cookie?: never;
+        };
+        requestBody?: never;
+        responses: {
+            /** @description The job */
+            200: {
+                headers: {
+                    [name: string]: unknown;
+                };
+                content: {
+                    "application/json": components["schemas"]["Response_DeployJob"];
+                };
+            };
+            /** @description No such job */
+            404: {
+                headers: {
+                    [name: string]: unknown;
+                };
+                content: {
+                    "application/json": components["schemas"]["Response_ErrorResponse"];
+                };
+            };
+        };
+    };
+    deploy_job_events: {
+        parameters: {
+            query?: never;
+            header?: never;
+            path: {
+                id: string;
+            };
+            cookie?: never;
+        };
+        requestBody?: never;
+        responses: {
+            /** @description `progress` events, each a `DeployJob` as JSON */
+            200: {
+                headers: {
+                    [name: string]: unknown;
+                };
+                content: {
+                    "text/event-stream": string;
+                };
+            };
+            /** @description No such job */
+            404: {
+                headers: {
+                    [name: string]: unknown;
+                };
+                content: {
+                    "application/json": components["schemas"]["Response_ErrorResponse"];
+                };
+            };
+        };
+    };
+    bulk_drip: {
+        parameters: {
+            query?: never;
+            header?: never;
+            path?: never;
+            cookie?: never;
+        };
+        requestBody: {
+            content: {
+                "application/json": components["schemas"]["BulkDripPayload"];
+            };
+        };
+        responses: {
+            /** @description Outcome per drip */
+            200: {
+                headers: {
+                    [name: string]: unknown;
+                };
+                content: {
+                    "application/json": components["schemas"]["Response_Vec_BulkDripResult"];
+                };
+            };
+            /** @description No drips, too many, or an invalid address */
+            400: {
+                headers: {
+                    [name: string]: unknown;
+                };
+                content: {
+                    "application/json": components["schemas"]["Response_ErrorResponse"];
+                };
+            };
+            /** @description Missing or invalid API key */
+            401: {
+                headers: {
+                    [name: string]: unknown;
+                };
+                content: {
+                    "application/json": components["schemas"]["Response_ErrorResponse"];
+                };
+            };
+            /** @description Shutting down */
+            503: {
+                headers: {
+                    [name: string]: unknown;
+                };
+                content: {
+                    "application/json": components["schemas"]["Response_ErrorResponse"];
+                };
+            };
+        };
+    };
+    events: {
+        parameters: {
+            query?: {
+                /** @description Only stream events about this token */
+                token?: string;
+            };
+            header?: never;
+            path?: never;
+            cookie?: never;
+        };
+        requestBody?: never;
+        responses: {
+            /** @description One event per drip, deploy or pause, as JSON */
+            200: {
+                headers: {
+                    [name: string]: unknown;
+                };
+                content: {
+                    "text/event-stream": string;
+                };
+            };
+        };
+    };
+    events_ws: {
+        parameters: {
+            query?: {
+                /** @description Only stream events about this token */
+                token?: string;
+            };
+            header?: never;
+            path?: never;
+            cookie?: never;
+        };
+        requestBody?: never;
+        responses: {
+            /** @description Switched to a WebSocket carrying the same events as `/events` */
+            101: {
+                headers: {
+                    [name: string]: unknown;
+                };
+                content?: never;
+            };
+        };
+    };
+    subscriptions: {
+        parameters: {
+            query?: never;
+            header?: never;
+            path?: never;
+            cookie?: never;
+        };
+        requestBody?: never;
+        responses: {
+            /** @description Subscriptions of the user */
+            200: {
+                headers: {
+                    [name: string]: unknown;
+                };
+                content: {
+                    "application/json": components["schemas"]["Response_Vec_WalletSubscription"];
+                };
+            };
+            /** @description Not signed in with GitHub */
+            401: {
+                headers: {
+                    [name: string]: unknown;
+                };
+                content: {
+                    "application/json": components["schemas"]["Response_ErrorResponse"];
+                };
+            };
+        };
+    };
+    create_subscription: {
+        parameters: {
+            query?: never;
+            header?: never;
+            path?: never;
+            cookie?: never;
+        };
+        requestBody: {
+            content: {
+                "application/json": components["schemas"]["CreateSubscriptionRequest"];
+            };
+        };
+        responses: {
+            /** @description The new subscription */
+            200: {
+                headers: {
+                    [name: string]: unknown;
+                };
+                content: {
+                    "application/json": components["schemas"]["Response_WalletSubscription"];
+                };
+            };
+            /** @description Invalid address or amounts */
+            400: {
+                headers: {
+                    [name: string]: unknown;
+                };
+                content: {
+                    "application/json": components["schemas"]["Response_ErrorResponse"];
+                };
+            };
+            /** @description Not signed in with GitHub */
+            401: {
+                headers: {
+                    [name: string]: unknown;
+                };
+                content: {
+                    "application/json": components["schemas"]["Response_ErrorResponse"];
+                };
+            };
+            /** @description Unknown token */
+            404: {
+                headers: {
+                    [name: string]: unknown;
+                };
+                content: {
+                    "application/json": components["schemas"]["Response_ErrorResponse"];
+                };
+            };
+            /** @description The wallet is already subscribed to the token */
+            409: {
+                headers: {
+                    [name: string]: unknown;
+                };
+                content: {
+                    "application/json": components["schemas"]["Response_ErrorResponse"];
+                };
+            };
+        };
+    };
+    update_subscription: {
+        parameters: {
+            query?: never;
+            header?: never;
+            path: {
+                id: string;
+            };
+            cookie?: never;
+        };
+        requestBody: {
+            content: {
+                "application/json": components["schemas"]["UpdateSubscriptionRequest"];
+            };
+        };
+        responses: {
+            /** @description The updated subscription */
+            200: {
+                headers: {
+                    [name: string]: unknown;
+                };
+                content: {
+                    "application/json": components["schemas"]["Response_WalletSubscription"];
+                };
+            };
+            /** @description Nothing to update or invalid amounts */
+            400: {
+                headers: {
+                    [name: string]: unknown;
+                };
+                content: {
+                    "application/json": components["schemas"]["Response_ErrorResponse"];
+                };
+            };
+            /** @description Not signed in with GitHub */
+            401: {
+                headers: {
+                    [name: string]: unknown;
+                };
+                content: {
+                    "application/json": components["schemas"]["Response_ErrorResponse"];
+                };
+            };
+            /** @description No such subscription of the user */
+            404: {
+                headers: {
+                    [name: string]: unknown;
+                };
+                content: {
+                    "application/json": components["schemas"]["Response_ErrorResponse"];
+                };
+            };
+        };
+    };
+    delete_subscription: {
+        parameters: {
+            query?: never;
+            header?: never;
+            path: {
+                id: string;
+            };
+            cookie?: never;
+        };
+        requestBody?: never;
+        responses: {
+            /** @description The deleted subscription */
+            200: {
+                headers: {
+                    [name: string]: unknown;
+                };
+                content: {
+                    "application/json": components["schemas"]["Response_WalletSubscription"];
+                };
+            };
+            /** @description Not signed in with GitHub */
+            401: {
+                headers: {
+                    [name: string]: unknown;
+                };
+                content: {
+                    "application/json": components["schemas"]["Response_ErrorResponse"];
+                };
+            };
+            /** @description No such subscription of the user */
+            404: {
+                headers: {
+                    [name: string]: unknown;
+                };
+                content: {
+                    "application/json": components["schemas"]["Response_ErrorResponse"];
+                };
+            };
+        };
+    };
+    test_auth: {
+        parameters: {
+            query?: never;
+            header?: never;
+            path?: never;
+            cookie?: never;
+        };
+        requestBody?: never;
+        responses: {
+            /** @description Who the request is authenticated as */
+            200: {
+                headers: {
+                    [name: string]: unknown;
+                };
+                content: {
+                    "application/json": components["schemas"]["Response_String"];
+                };
+            };
+            /** @description Invalid API key */
+            401: {
+                headers: {
+                    [name: string]: unknown;
+                };
+                content: {
+                    "application/json": components["schemas"]["Response_ErrorResponse"];
+                };
+            };
+        };
+    };
+    tokens: {
+        parameters: {
+            query?: never;
+            header?: never;
+            path?: never;
+            cookie?: never;
+        };
+        requestBody?: never;
+        responses: {
+            /** @description Claimable tokens */
+            200: {
+                headers: {
+                    [name: string]: unknown;
+                };
+                content: {
+                    "application/json": components["schemas"]["Response_Vec_Token"];
+                };
+            };
+            /** @description Tokens could not be fetched */
+            500: {
+                headers: {
+                    [name: string]: unknown;
+                };
+                content: {
+                    "application/json": components["schemas"]["Response_ErrorResponse"];
+                };
+            };
+        };
+    };
+    token_webhooks: {
+        parameters: {
+            query?: never;
+            header?: never;
+            path: {
+                /** @description Token address */
+                address: string;
+            };
+            cookie?: never;
+        };
+        requestBody?: never;
+        responses: {
+            /** @description Webhooks of the token */
+            200: {
+                headers: {
+                    [name: string]: unknown;
+                };
+                content: {
+                    "application/json": components["schemas"]["Response_Vec_Webhook"];
+                };
+            };
+            /** @description Neither the token deployer nor an admin */
+            403: {
+                headers: {
+                    [name: string]: unknown;
+                };
+                content: {
+                    "application/json": components["schemas"]["Response_ErrorResponse"];
+                };
+            };
+            /** @description Unknown token */
+            404: {
+                headers: {
+                    [name: string]: unknown;
+                };
+                content: {
+                    "application/json": components["schemas"]["Response_ErrorResponse"];
+                };
+            };
+        };
+    };
+    create_webhook: {
+        parameters: {
+            query?: never;
+            header?: never;
+            path: {
+                /** @description Token address */
+                address: string;
+            };
+            cookie?: never;
+        };
+        requestBody: {
+            content: {
+                "application/json": components["schemas"]["CreateWebhookRequest"];
+            };
+        };
+        responses: {
+            /** @description The webhook with its signing secret, shown only this once */
+            200: {
+                headers: {
+                    [name: string]: unknown;
+                };
+                content: {
+                    "application/json": components["schemas"]["Response_CreateWebhookResponse"];
+                };
+            };
+            /** @description Invalid or non public URL, or invalid events */
+            400: {
+                headers: {
+                    [name: string]: unknown;
+                };
+                content: {
+                    "application/json": components["schemas"]["Response_ErrorResponse"];
+                };
+            };
+            /** @description Neither the token deployer nor an admin */
+            403: {
+                headers: {
+                    [name: string]: unknown;
+                };
+                content: {
+                    "application/json": components["schemas"]["Response_ErrorResponse"];
+                };
+            };
+            /** @description Unknown token */
+            404: {
+                headers: {
+                    [name: string]: unknown;
+                };
+                content: {
+                    "application/json": components["schemas"]["Response_ErrorResponse"];
+                };
+            };
+            /** @description The token has as many webhooks as allowed */
+            409: {
+                headers: {
+                    [name: string]: unknown;
+                };
+                content: {
+                    "application/json": components["schemas"]["Response_ErrorResponse"];
+                };
+            };
+        };
+    };
+    user: {
+        parameters: {
+            query?: never;
+            header?: never;
+            path?: never;
+            cookie?: never;
+        };
+        requestBody?: never;
+        responses: {
+            /** @description The signed in user */
+            200: {
+                headers: {
+                    [name: string]: unknown;
+                };
+                content: {
+                    "application/json": components["schemas"]["Response_User"];
+                };
+            };
+            /** @description Not signed in with GitHub */
+            401: {
+                headers: {
+                    [name: string]: unknown;
+                };
+                content: {
+                    "application/json": components["schemas"]["Response_ErrorResponse"];
+                };
+            };
+            /** @description The user was deleted */
+            404: {
+                headers: {
+                    [name: string]: unknown;
+                };
+                content: {
+                    "application/json": components["schemas"]["Response_ErrorResponse"];
+                };
+            };
+        };
+    };
+    verify_turnstile_captcha: {
+        parameters: {
+            query?: never;
+            header?: never;
+            path?: never;
+            cookie?: never;
+        };
+        requestBody: {
+            content: {
+                "application/json": components["schemas"]["TurnstilePayload"];
+            };
+        };
+        responses: {
+            /** @description Whether the captcha was solved */
+            200: {
+                headers: {
+                    [name: string]: unknown;
+                };
+                content: {
+                    "application/json": components["schemas"]["Response_TurnstileResponse"];
+                };
+            };
+            /** @description No token */
+            400: {
+                headers: {
+                    [name: string]: unknown;
+                };
+                content: {
+                    "application/json": components["schemas"]["Response_ErrorResponse"];
+                };
+            };
+        };
+    };
+    user_webhooks: {
+        parameters: {
+            query?: never;
+            header?: never;
+            path?: never;
+            cookie?: never;
+        };
+        requestBody?: never;
+        responses: {
+            /** @description The user's webhooks */
+            200: {
+                headers: {
+                    [name: string]: unknown;
+                };
+                content: {
+                    "application/json": components["schemas"]["Response_Vec_Webhook"];
+                };
+            };
+            /** @description Not signed in with GitHub */
+            401: {
+                headers: {
+                    [name: string]: unknown;
+                };
+                content: {
+                    "application/json": components["schemas"]["Response_ErrorResponse"];
+                };
+            };
+        };
+    };
+    create_user_webhook: {
+        parameters: {
+            query?: never;
+            header?: never;
+            path?: never;
+            cookie?: never;
+        };
+        requestBody: {
+            content: {
+                "application/json": components["schemas"]["CreateWebhookRequest"];
+            };
+        };
+        responses: {
+            /** @description The webhook with its signing secret, shown only this once */
+            200: {
+                headers: {
+                    [name: string]: unknown;
+                };
+                content: {
+                    "application/json": components["schemas"]["Response_CreateWebhookResponse"];
+                };
+            };
+            /** @description Invalid or non public URL, or invalid events */
+            400: {
+                headers: {
+                    [name: string]: unknown;
+                };
+                content: {
+                    "application/json": components["schemas"]["Response_ErrorResponse"];
+                };
+            };
+            /** @description Not signed in with GitHub */
+            401: {
+                headers: {
+                    [name: string]: unknown;
+                };
+                content: {
+                    "application/json": components["schemas"]["Response_ErrorResponse"];
+                };
+            };
+            /** @description The user has as many webhooks as allowed */
+            409: {
+                headers: {
+                    [name: string]: unknown;
+                };
+                content: {
+                    "application/json": components["schemas"]["Response_ErrorResponse"];
+                };
+            };
+        };
+    };
+    delete_webhook: {
+        parameters: {
+            query?: never;
+            header?: never;
+            path: {
+                id: string;
+            };
+            cookie?: never;
+        };
+        requestBody?: never;
+        responses: {
+            /** @description The deleted webhook */
+            200: {
+                headers: {
+                    [name: string]: unknown;
+                };
+                content: {
+                    "application/json": components["schemas"]["Response_Webhook"];
+                };
+            };
+            /** @description Neither the token deployer, the webhook's creator nor an admin */
+            403: {
+                headers: {
+                    [name: string]: unknown;
+                };
+                content: {
+                    "application/json": components["schemas"]["Response_ErrorResponse"];
+                };
+            };
+            /** @description No such webhook */
+            404: {
+                headers: {
+                    [name: string]: unknown;
+                };
+                content: {
+                    "application/json": components["schemas"]["Response_ErrorResponse"];
+                };
+            };
+        };
+    };
+    webhook_deliveries: {
+        parameters: {
+            query?: never;
+            header?: never;
+            path: {
+                id: string;
+            };
+            cookie?: never;
+        };
+        requestBody?: never;
+        responses: {
+            /** @description Latest deliveries, newest first */
+            200: {
+                headers: {
+                    [name: string]: unknown;
+                };
+                content: {
+                    "application/json": components["schemas"]["Response_Vec_WebhookDelivery"];
+                };
+            };
+            /** @description Neither the token deployer, the webhook's creator nor an admin */
+            403: {
+                headers: {
+                    [name: string]: unknown;
+                };
+                content: {
+                    "application/json": components["schemas"]["Response_ErrorResponse"];
+                };
+            };
+            /** @description No such webhook */
+            404: {
+                headers: {
+                    [name: string]: unknown;
+                };
+                content: {
+                    "application/json": components["schemas"]["Response_ErrorResponse"];
+                };
+            };
+        };
+    };
+    withdraw: {
+        parameters: {
+            query?: never;
+            header?: {
+                /** @description Retries with the same key get the first outcome instead of a second drip, signed in callers only */
+                "Idempotency-Key"?: string | null;
+            };
+            path?: never;
+            cookie?: never;
+        };
+        requestBody: {
+            content: {
+                "application/json": components["schemas"]["TokenTransferRequest"];
+            };
+        };
+        responses: {
+            /** @description Drip sent */
+            200: {
+                headers: {
+                    [name: string]: unknown;
+                };
+                content: {
+                    "application/json": components["schemas"]["Response_DripResponse"];
+                };
+            };
+            /** @description Malformed request or address */
+            400: {
+                headers: {
+                    [name: string]: unknown;
+                };
+                content: {
+                    "application/json": components["schemas"]["Response_ErrorResponse"];
+                };
+            };
+            /** @description Idempotency-Key sent without signing in */
+            401: {
+                headers: {
+                    [name: string]: unknown;
+                };
+                content: {
+                    "application/json": components["schemas"]["Response_ErrorResponse"];
+                };
+            };
+            /** @description A request with the same Idempotency-Key is in progress */
+            409: {
+                headers: {
+                    [name: string]: unknown;
+                };
+                content: {
+                    "application/json": components["schemas"]["Response_ErrorResponse"];
+                };
+            };
+            /** @description Idempotency-Key reused or token not claimable */
+            422: {
+                headers: {
+                    [name: string]: unknown;
+                };
+                content: {
+                    "application/json": components["schemas"]["Response_ErrorResponse"];
+                };
+            };
+            /** @description Claimed too recently */
+            429: {
+                headers: {
+                    [name: string]: unknown;
+                };
+                content: {
+                    "application/json": components["schemas"]["Response_ErrorResponse"];
+                };
+            };
+            /** @description Faucet out of funds, paused or shutting down */
+            503: {
+                headers: {
+                    [name: string]: unknown;
+                };
+                content: {
+                    "application/json": components["schemas"]["Response_ErrorResponse"];
+                };
+            };
+        };
+    };
+    withdraw_batch: {
+        parameters: {
+            query?: never;
+            header?: never;
+            path?: never;
+            cookie?: never;
+        };
+        requestBody: {
+            content: {
+                "application/json": components["schemas"]["BatchTransferRequest"];
+            };
+        };
+        responses: {
+            /** @description Outcome per token */
+            200: {
+                headers: {
+                    [name: string]: unknown;
+                };
+                content: {
+                    "application/json": components["schemas"]["Response_Vec_BatchTransferResult"];
+                };
+            };
+            /** @description Malformed request or too many tokens */
+            400: {
+                headers: {
+                    [name: string]: unknown;
+                };
+                content: {
+                    "application/json": components["schemas"]["Response_ErrorResponse"];
+                };
+            };
+            /** @description Shutting down */
+            503: {
+                headers: {
+                    [name: string]: unknown;
+                };
+                content: {
+                    "application/json": components["schemas"]["Response_ErrorResponse"];
+                };
+            };
+        };
+    };
+}
@@ -2,6 +2,7 @@ import clsx, { ClassValue } from "clsx";
 import { twMerge } from "tailwind-merge";
 import { API } from "../constants/api";
 import {
+  DeployJob,
   DeployResponse,
   ResponseStatus,
   TransferResponse,
@@ -62,12 +63,14 @@ async function handleQuenchTokens(
       }),
     });
 
-    if (!response.ok) {
+    // errors are answered with a JSON body as well, carrying their `code`
+    const data: TransferResponse | null = await response
+      .json()
+      .catch(() => null);
+    if (!response.ok && !data?.error) {
       throw new Error(`HTTP error! status: ${response.status}`);
     }
 
-    const data: TransferResponse = await response.json();
-
     if (data) {
       const tock = performance.now();
       if (tock - tick < 500) {
@@ -123,7 +126,12 @@ async function deployToken(
 
     if (!response.ok) {
       // setCursorState({ cursor: true, loader: false, dancer: false });
-      throw new Error(`HTTP error! status: ${response.status}`);
+      const body: TransferResponse | null = await response
+        .json()
+        .catch(() => null);
+      throw new Error(
+        body?.error?.message ?? `HTTP error! status: ${response.status}`
+      );
     }
 
     const data: DeployResponse = await response.json();
@@ -149,6 +157,33 @@ async function deployToken(
   }
 }
 
+/**
+ * Follows a deploy job until it is registered or failed, calling `onProgress`
+ * on every step.
+ */
+function followDeployJob(
+  jobId: string,
+  onProgress: (job: DeployJob) => void
+): Promise<DeployJob> {
+  return new Promise((resolve, reject) => {
+    const events = new EventSource(API().deployJobEvents(jobId));
+
+    events.addEventListener("progress", (event) => {
+      const job: DeployJob = JSON.parse((event as MessageEvent).data);
+      onProgress(job);
+      if (job.step === "REGISTERED" || job.step === "FAILED") {
+        events.close();
+        resolve(job);
+      }
+    });
+
+    events.onerror = () => {
+      events.close();
+      reject(new Error("Lost connection while following the deployment"));
+    };
+  });
+}
+
 function convertToLocalTime(dateString: string): string {
   const date = new Date(dateString);
   return date.toLocaleString(undefined, {
@@ -168,5 +203,6 @@ export {
   handleQuenchTokens,
   getCode,
   deployToken,
+  followDeployJob,
   convertToLocalTime,
 };